@@ -0,0 +1,74 @@
+//! Benchmarks for the hot paths run after every action, when the updates sent to clients are generated.
+//!
+//! Run with `cargo bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use hitler_server_rs::game::{Game, GameOptions};
+use hitler_server_rs::session::GameUpdate;
+
+/// The number of players in the benchmarked game, which is the most the game allows.
+const NUM_PLAYERS: usize = 16;
+
+/// Creates a game in which every player has just been asked to vote on a government.
+fn voting_game() -> Game {
+    let opts = GameOptions { communists: true, ..GameOptions::default() };
+    let names = (0..NUM_PLAYERS).map(|i| format!("PLAYER{}", i + 1)).collect::<Vec<_>>();
+    let mut game = Game::new(opts, &names, 0).unwrap();
+    // End the night round, and then nominate a chancellor
+    for _ in 0..2 {
+        for player in 0..NUM_PLAYERS {
+            if let Some(action) = game.legal_actions(player).into_iter().next() {
+                game.apply(Some(player), action).unwrap();
+            }
+        }
+    }
+    game
+}
+
+fn updates(c: &mut Criterion) {
+    let game = voting_game();
+
+    c.bench_function("get_board_update", |b| b.iter(|| black_box(&game).get_board_update()));
+
+    c.bench_function("get_player_prompt/16 players", |b| {
+        b.iter(|| {
+            for player in 0..NUM_PLAYERS {
+                black_box(black_box(&game).get_player_prompt(player));
+            }
+        })
+    });
+
+    c.bench_function("get_player_update/16 players", |b| {
+        b.iter(|| {
+            for player in 0..NUM_PLAYERS {
+                black_box(black_box(&game).get_player_update(player));
+            }
+        })
+    });
+
+    c.bench_function("get_player_updates/16 players", |b| {
+        b.iter(|| black_box(&game).get_player_updates())
+    });
+
+    let update = GameUpdate {
+        players: game.get_public_players(),
+        board_update: Some(game.get_board_update()),
+        state_hash: Some(game.state_hash()),
+        ..GameUpdate::default()
+    };
+    c.bench_function("serialize GameUpdate", |b| {
+        b.iter(|| serde_json::to_vec(black_box(&update)).unwrap())
+    });
+
+    let player_updates = game.get_player_updates();
+    c.bench_function("serialize PlayerUpdate/16 players", |b| {
+        b.iter(|| {
+            for update in black_box(&player_updates) {
+                black_box(serde_json::to_vec(update).unwrap());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, updates);
+criterion_main!(benches);
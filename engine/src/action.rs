@@ -0,0 +1,431 @@
+use super::eligible::EligiblePlayers;
+use super::executive_power::ExecutiveAction;
+use super::player::Role;
+use super::{AssassinationState, Game, GameState, LegislativeSessionTurn, Phase, VetoStatus};
+use crate::error::GameError;
+use serde::{Deserialize, Serialize};
+
+/// An action which a player or the board can perform on the game.
+/// Players are referred to by their index, rather than by name.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(tag = "type")]
+pub enum Action {
+    /// Confirms the player is ready to end the night round.
+    EndNightRound,
+    /// Acknowledges that the player has looked at their role card during the night round.
+    ViewRole,
+    /// Confirms the player is ready to move on from the card reveal.
+    EndCardReveal,
+    /// Confirms the player has seen the result of an executive action.
+    EndExecutiveAction,
+    /// The president acknowledges having seen the policies revealed by a policy peak, which records what they saw.
+    AcknowledgePolicyPeak,
+    /// Nominates, votes for, investigates, executes, or otherwise chooses a player.
+    ChoosePlayer {
+        player: usize,
+    },
+    CastVote {
+        vote: bool,
+    },
+    /// Abstains from voting on the proposed government.
+    Abstain,
+    /// Discards the policy card at the given index.
+    Discard {
+        index: usize,
+    },
+    VetoAgenda,
+    AcceptVeto,
+    RejectVeto,
+    StartAssassination,
+    EndCongress,
+    HijackElection,
+    /// Publicly reveals the party of the player the president investigated.
+    RevealInvestigation,
+    /// The capitalist bribes the president to cancel or redirect their power.
+    OfferBribe,
+    DeclineBribe,
+    /// The bribed president cancels their power.
+    CancelPower,
+    /// The bribed president uses their power as they originally intended.
+    RejectBribe,
+    /// Ends the voting phase once every vote has been revealed.
+    EndVoting,
+    /// Moves on once the board has shown the election tracker advance.
+    EndElectionFailed,
+    EndLegislativeSession,
+    EndAssassination,
+    EndCommunistStart,
+    EndCommunistEnd,
+    /// Ends the monarchist's opportunity to hijack a special election.
+    StartSpecialElection,
+    /// Reverts the player's last action.
+    Undo,
+    /// Resolves the current phase as if its timer ran out.
+    Timeout,
+    /// Closes the undo window for the last action.
+    ExpireUndo,
+    /// Moves the game on from a phase which is stuck waiting on players who are no longer responding.
+    ForceAdvance,
+}
+
+impl Game {
+    /// Performs an action on behalf of the given player, or the board if `player` is `None`.
+    /// Every successful action is recorded in the game's log.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub fn apply(&mut self, player: Option<usize>, action: Action) -> Result<(), GameError> {
+        if player.is_some_and(|player| player >= self.num_players()) {
+            return Err(GameError::InvalidPlayerIndex);
+        }
+        if player.is_some_and(|player| !self.players[player].alive && !self.may_act_while_dead(player)) {
+            return Err(GameError::DeadPlayer);
+        }
+        if player.is_none() && self.game_over() {
+            // There is nothing left for the board to move on once the game is over
+            return Err(self.wrong_phase(&Phase::IN_PLAY));
+        }
+        let phase = self.view_phase();
+        let undo_player = self.undo_player();
+        // This clones the whole game, log included, before every action, which is why it is only done on request
+        let snapshot = cfg!(feature = "strict-invariants").then(|| self.clone());
+        self.dispatch(player, action)?;
+        if self.opts.boardless {
+            self.stand_in_for_board();
+        }
+        if let Err(violation) = self.check_invariants() {
+            tracing::error!(?player, ?action, "Action broke the game's invariants");
+            if let Some(snapshot) = snapshot {
+                *self = snapshot;
+                return Err(GameError::InconsistentState(violation));
+            }
+        }
+        self.record_action(player, action);
+
+        // Confirmations and ballots only change the view of the player who made them, unless the phase moved on
+        let private = matches!(
+            action,
+            Action::EndNightRound
+                | Action::ViewRole
+                | Action::CastVote { .. }
+                | Action::Abstain
+                | Action::EndCardReveal
+        );
+        match player {
+            Some(player) if private && !self.opts.boardless && self.view_phase() == phase => {
+                for seat in [Some(player), undo_player, self.undo_player()].into_iter().flatten() {
+                    self.mark_seat_changed(seat);
+                }
+            }
+            _ => self.mark_all_seats_changed(),
+        }
+        Ok(())
+    }
+
+    /// Performs the board's actions for a game played without a board.
+    /// Each is performed once no player has anything left to do, so the players have all confirmed what the
+    /// board would have shown them. Without a board to hold it open, the monarchist cannot hijack a special election,
+    /// which is why the monarchist cannot be included in a boardless game.
+    fn stand_in_for_board(&mut self) {
+        while !self.game_over() && !self.awaiting_players() {
+            let Some(action) = self.board_actions().first().copied() else {
+                break;
+            };
+            self.dispatch(None, action).expect("board action is legal");
+        }
+    }
+
+    /// Resolves the current phase with safe defaults, for when it is stuck waiting on a player who has vanished.
+    /// A timed phase is resolved as if its timer ran out; otherwise every remaining confirmation is made, missing
+    /// votes count as "nein", pending bribes and vetoes are turned down, and then the board moves the game on.
+    /// Choices which would shape the game, such as whom to investigate or execute, are never made for a player.
+    fn force_advance(&mut self) -> Result<(), GameError> {
+        if self.timed_phase().is_some() {
+            return self.resolve_timeout();
+        }
+
+        use Action::*;
+        const DEFAULTS: [Action; 7] = [
+            EndNightRound,
+            EndCardReveal,
+            EndExecutiveAction,
+            EndCongress,
+            DeclineBribe,
+            RejectBribe,
+            RejectVeto,
+        ];
+        let phase = self.view_phase();
+        let mut advanced = false;
+        for player in 0..self.num_players() {
+            // Once a player's default moves the game on, the next phase is left for the next forced advance
+            if self.view_phase() != phase {
+                break;
+            }
+            let actions = self.legal_actions(player);
+            // A policy peak is ended without the president's acknowledgment, as no one can give it for them,
+            // and so what they saw is not recorded
+            if actions.contains(&AcknowledgePolicyPeak) {
+                self.finish_executive_action(Some(player))?;
+                advanced = true;
+            } else if let Some(action) = DEFAULTS.into_iter().find(|action| actions.contains(action)) {
+                self.dispatch(Some(player), action)?;
+                advanced = true;
+            }
+        }
+        if self.view_phase() == phase {
+            if let Some(action) = self.board_actions().first().copied() {
+                self.dispatch(None, action)?;
+                advanced = true;
+            }
+        }
+        match advanced {
+            true => Ok(()),
+            false => Err(GameError::InvalidAction),
+        }
+    }
+
+    /// Returns whether the given dead player may still act, which is only to carry out an assassination they had
+    /// activated before they were executed, under the house rule which allows it.
+    fn may_act_while_dead(&self, player: usize) -> bool {
+        matches!(self.state, GameState::Assassination { anarchist, cancelled: false, .. } if anarchist == player)
+    }
+
+    /// Returns whether any player has something left to do, other than hijack a special election.
+    fn awaiting_players(&self) -> bool {
+        (0..self.num_players()).any(|player| {
+            self.legal_actions(player)
+                .iter()
+                .any(|action| !matches!(action, Action::HijackElection | Action::Undo))
+        })
+    }
+
+    fn dispatch(&mut self, player: Option<usize>, action: Action) -> Result<(), GameError> {
+        use Action::*;
+
+        match (player, action) {
+            (Some(player), EndNightRound) => self.end_night_round(player),
+            (Some(player), ViewRole) => self.view_role(player),
+            (player, EndCardReveal) => self.end_card_reveal(player),
+            (player, EndExecutiveAction) => self.end_executive_action(player),
+            (Some(player), AcknowledgePolicyPeak) => self.acknowledge_policy_peak(player),
+            (Some(player), ChoosePlayer { player: other }) => match other < self.num_players() {
+                true => self.choose_player(player, other),
+                false => Err(GameError::InvalidPlayerIndex),
+            },
+            (Some(player), CastVote { vote }) => self.cast_vote(player, vote),
+            (Some(player), Abstain) => self.abstain(player),
+            (Some(player), Discard { index }) => self.discard_policy(player, index),
+            (Some(player), VetoAgenda | AcceptVeto) => self.veto_agenda(player),
+            (Some(player), RejectVeto) => self.reject_veto(player),
+            (Some(player), StartAssassination) => self.start_assassination(player),
+            (Some(player), EndCongress) => self.end_congress(player),
+            (Some(player), HijackElection) => self.hijack_special_election(player),
+            (Some(player), RevealInvestigation) => self.reveal_investigation(player),
+            (Some(player), OfferBribe) => self.offer_bribe(player, true),
+            (Some(player), DeclineBribe) => self.offer_bribe(player, false),
+            (Some(player), CancelPower) => self.respond_to_bribe(player, true),
+            (Some(player), RejectBribe) => self.respond_to_bribe(player, false),
+            (None, EndVoting) => self.end_voting(),
+            (None, EndElectionFailed) => self.end_election_failed(),
+            (None, EndLegislativeSession) => self.end_legislative_session(),
+            (None, EndAssassination) => self.end_assassination(),
+            (None, EndCommunistStart) => self.end_communist_start(),
+            (None, EndCommunistEnd) => self.end_communist_end(),
+            (None, StartSpecialElection) => self.start_special_election(),
+            (Some(player), Undo) => self.undo(player),
+            (None, Timeout) => self.resolve_timeout(),
+            (None, ForceAdvance) => self.force_advance(),
+            (None, ExpireUndo) => {
+                self.clear_undo();
+                Ok(())
+            }
+            _ => Err(GameError::InvalidAction),
+        }
+    }
+
+    /// Gets every action the board may currently perform to move the game on, worked out from the state of the game.
+    pub fn board_actions(&self) -> Vec<Action> {
+        use Action::*;
+
+        match &self.state {
+            GameState::Election { chancellor: Some(_), votes, .. } if votes.outcome().is_some() => vec![EndVoting],
+            GameState::MonarchistElection {
+                monarchist_chancellor: Some(_),
+                president_chancellor: Some(_),
+                votes,
+                ..
+            } if votes.outcome().is_some() => vec![EndVoting],
+            GameState::ElectionFailed { .. } => vec![EndElectionFailed],
+            GameState::LegislativeSession {
+                turn: LegislativeSessionTurn::VetoApproved, ..
+            } => {
+                vec![EndLegislativeSession]
+            }
+            GameState::CardReveal { board_ready: false, .. } => vec![EndCardReveal],
+            GameState::ActionReveal {
+                action: ExecutiveAction::SpecialElection | ExecutiveAction::Execution | ExecutiveAction::FiveYearPlan,
+                ..
+            } => vec![EndExecutiveAction],
+            GameState::Assassination { chosen_player, cancelled, .. } if chosen_player.is_some() || *cancelled => {
+                vec![EndAssassination]
+            }
+            GameState::CommunistStart { .. } => vec![EndCommunistStart],
+            GameState::CommunistEnd { .. } => vec![EndCommunistEnd],
+            GameState::PromptMonarchist { .. } => vec![StartSpecialElection],
+            _ => vec![],
+        }
+    }
+
+    /// Gets every action the given player may currently perform, worked out from the state of the game.
+    pub fn legal_actions(&self, player_idx: usize) -> Vec<Action> {
+        use Action::*;
+
+        let player = &self.players[player_idx];
+        // An executed anarchist may still carry out their assassination, under the house rule
+        let assassin = matches!(
+            self.state,
+            GameState::Assassination { anarchist, cancelled: false, .. } if anarchist == player_idx
+        );
+        if !player.alive && !assassin {
+            return vec![];
+        }
+        let choose = |eligible: &EligiblePlayers| {
+            (0..self.num_players())
+                .filter(|idx| eligible.includes(*idx))
+                .map(|player| ChoosePlayer { player })
+                .collect::<Vec<_>>()
+        };
+
+        match &self.state {
+            GameState::Night { confirmations, roles_viewed } => match confirmations.has_confirmed(player_idx) {
+                true => vec![],
+                false if roles_viewed[player_idx] => vec![EndNightRound],
+                false => vec![EndNightRound, ViewRole],
+            },
+
+            GameState::Election {
+                president,
+                chancellor: None,
+                eligible_chancellors,
+                ..
+            } if player_idx == *president => choose(eligible_chancellors),
+            GameState::Election {
+                president,
+                chancellor: Some(chancellor),
+                votes,
+                ..
+            } if self.may_vote(votes.has_cast(player_idx), votes.outcome().is_some()) => {
+                let mut actions = vec![CastVote { vote: true }, CastVote { vote: false }];
+                if self.opts.house_rules.abstentions && player_idx != *president && player_idx != *chancellor {
+                    actions.push(Abstain);
+                }
+                actions
+            }
+
+            GameState::MonarchistElection {
+                monarchist,
+                last_president,
+                monarchist_chancellor,
+                president_chancellor,
+                eligible_chancellors,
+                votes,
+            } => match (monarchist_chancellor, president_chancellor) {
+                (None, _) if player_idx == *monarchist => choose(eligible_chancellors),
+                (Some(_), None) if player_idx == *last_president => choose(eligible_chancellors),
+                (Some(first), Some(second)) if self.may_vote(votes.has_cast(player_idx), votes.outcome().is_some()) => {
+                    vec![ChoosePlayer { player: *first }, ChoosePlayer { player: *second }]
+                }
+                _ => vec![],
+            },
+
+            GameState::LegislativeSession { president, chancellor, turn } => match turn {
+                LegislativeSessionTurn::President { cards } if player_idx == *president => {
+                    (0..cards.len()).map(|index| Discard { index }).collect()
+                }
+                LegislativeSessionTurn::Chancellor { cards, veto } if player_idx == *chancellor => {
+                    let mut actions = (0..cards.len()).map(|index| Discard { index }).collect::<Vec<_>>();
+                    if *veto == VetoStatus::CanVeto {
+                        actions.push(VetoAgenda);
+                    }
+                    actions
+                }
+                LegislativeSessionTurn::VetoRequested { .. } if player_idx == *president => {
+                    vec![AcceptVeto, RejectVeto]
+                }
+                _ => vec![],
+            },
+
+            GameState::CardReveal { confirmations, board_ready: true, .. }
+                if !confirmations.has_confirmed(player_idx) =>
+            {
+                match self.assassination == AssassinationState::Unused && player.role == Role::Anarchist {
+                    true => vec![EndCardReveal, StartAssassination],
+                    false => vec![EndCardReveal],
+                }
+            }
+
+            GameState::PromptMonarchist { monarchist, hijacked: false, .. } if player_idx == *monarchist => {
+                vec![HijackElection]
+            }
+
+            GameState::ChoosePlayer { can_select, can_be_selected, .. } if can_select.includes(player_idx) => {
+                choose(can_be_selected)
+            }
+
+            GameState::Congress if player.role == Role::Communist => vec![EndCongress],
+
+            GameState::BuggingVote { communists, votes, .. }
+                if communists.includes(player_idx) && !votes.has_cast(player_idx) =>
+            {
+                choose(communists)
+            }
+
+            GameState::ActionReveal { action, confirmations, chosen_player }
+                if !confirmations.has_confirmed(player_idx) =>
+            {
+                use ExecutiveAction::*;
+
+                let president = self.last_government.map(|government| government.president);
+                match action {
+                    InvestigatePlayer if president == Some(player_idx) => {
+                        let chosen = chosen_player.map(|idx| &self.players[idx]);
+                        match self.opts.house_rules.public_investigations
+                            && chosen.is_some_and(|chosen| chosen.confirmed_party.is_none())
+                        {
+                            true => vec![EndExecutiveAction, RevealInvestigation],
+                            false => vec![EndExecutiveAction],
+                        }
+                    }
+                    PolicyPeak if president == Some(player_idx) => vec![AcknowledgePolicyPeak],
+                    Bugging if player.role == Role::Communist => vec![EndExecutiveAction],
+                    Radicalisation | Congress | Confession => vec![EndExecutiveAction],
+                    _ => vec![],
+                }
+            }
+
+            GameState::Assassination { chosen_player: None, cancelled: false, .. }
+                if player.role == Role::Anarchist =>
+            {
+                choose(&self.eligible_players().exclude(player_idx).make())
+            }
+
+            GameState::Bribe {
+                president, capitalist, chosen_player, offered, ..
+            } => match *offered {
+                false if player_idx == *capitalist => vec![OfferBribe, DeclineBribe],
+                true if player_idx == *president => {
+                    let mut actions = choose(
+                        &self
+                            .eligible_players()
+                            .exclude(*president)
+                            .exclude(*chosen_player)
+                            .make(),
+                    );
+                    actions.extend([CancelPower, RejectBribe]);
+                    actions
+                }
+                _ => vec![],
+            },
+
+            _ => vec![],
+        }
+    }
+}
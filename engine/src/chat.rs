@@ -0,0 +1,45 @@
+use super::{player::Role, Game};
+use serde::{Deserialize, Serialize};
+
+/// The audience a chat message is addressed to.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub enum ChatScope {
+    /// Visible to every player and the board.
+    All,
+    /// Announcements made by the board, visible to everyone.
+    Board,
+    /// Private channel for the fascists who know each other's identities.
+    Fascists,
+    /// Private channel for the ordinary communists.
+    Communists,
+}
+
+impl Game {
+    /// Returns whether the given player may read and post messages in a faction chat scope.
+    /// Only players who already know each other's identities share a channel,
+    /// so the chat never leaks information the night round would not have revealed.
+    pub fn can_chat(&self, player: usize, scope: ChatScope) -> bool {
+        let Some(role) = self.players.get(player).map(|p| p.role) else {
+            return false;
+        };
+        match scope {
+            ChatScope::All => true,
+            ChatScope::Board => false,
+            ChatScope::Fascists => match role {
+                Role::Fascist => true,
+                Role::Hitler => self.players.iter().filter(|p| p.role == Role::Fascist).count() < 2,
+                _ => false,
+            },
+            // Radicalised players are never told who the other communists are
+            ChatScope::Communists => role == Role::Communist && !self.players[player].radicalised,
+        }
+    }
+
+    /// Gets the names of the players who may read messages in the given faction chat scope.
+    pub fn chat_members(&self, scope: ChatScope) -> Vec<String> {
+        (0..self.num_players())
+            .filter(|idx| self.can_chat(*idx, scope))
+            .map(|idx| self.players[idx].name.clone())
+            .collect()
+    }
+}
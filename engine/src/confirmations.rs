@@ -2,6 +2,16 @@ use serde::{Deserialize, Serialize};
 
 use super::MAX_PLAYERS;
 
+/// How many players have confirmed, out of the number needed for the game to proceed.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct Progress {
+    pub confirmed: usize,
+    pub required: usize,
+    /// The players who have yet to confirm.
+    #[serde(default)]
+    pub waiting: Vec<usize>,
+}
+
 /// Tracks the acknowledgement status of each player,
 /// such that game play can only proceed once all players have elected to move on.
 #[derive(Clone, Copy, Serialize, Deserialize, Debug)]
@@ -29,6 +39,16 @@ impl Confirmations {
         self.can_proceed()
     }
 
+    /// Gets how many players have confirmed, out of the number needed to proceed,
+    /// given which of the players are expected to confirm.
+    pub fn progress(&self, expected: impl Iterator<Item = usize>) -> Progress {
+        Progress {
+            confirmed: self.state.iter().filter(|c| **c).count(),
+            required: self.num_players,
+            waiting: expected.filter(|idx| !self.has_confirmed(*idx)).collect(),
+        }
+    }
+
     /// Returns `true` iff the game can now proceed.
     pub fn can_proceed(&self) -> bool {
         if std::env::var("QUICK_MODE").is_ok() {
@@ -0,0 +1,158 @@
+use super::{board::Board, party::Party};
+use rand::prelude::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::iter::repeat_n;
+
+/// The cards which the Five-Year Plan shuffles into the draw pile.
+pub const FIVE_YEAR_PLAN: [Party; 3] = [Party::Communist, Party::Communist, Party::Liberal];
+
+/// The number of each kind of policy which has not yet been enacted, as the whole table can count from the board.
+/// These cards may be in the draw pile, the discard pile or a government's hand, so they are not the odds of the
+/// next draw: a discarded card cannot be drawn until the piles are next reshuffled.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct DeckOdds {
+    pub liberal: usize,
+    pub fascist: usize,
+    pub communist: Option<usize>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Deck {
+    /// Total number of liberal cards in the deck, discard pile and game board
+    liberal: usize,
+    /// Total number of fascist cards in the deck, discard pile and game board
+    fascist: usize,
+    /// Total number of communist cards in the deck, discard pile and game board
+    communist: usize,
+    /// The current draw deck
+    deck: Vec<Party>,
+    /// The discard pile, or `None` for a game saved before it was tracked, until the discard pile is next shuffled in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    discard: Option<Vec<Party>>,
+}
+
+impl Deck {
+    pub fn new(communists: bool) -> Self {
+        let (liberal, fascist, communist) = match communists {
+            false => (6, 11, 0),
+            true => (6, 14, 8),
+        };
+        Self {
+            liberal,
+            fascist,
+            communist,
+            deck: vec![],
+            discard: Some(vec![]),
+        }
+    }
+
+    /// Shuffles the discard pile into the deck, if there are fewer than three cards in the draw deck.
+    pub fn check_shuffle(&mut self, board: &Board, rng: &mut impl Rng) {
+        if self.deck.len() < 3 {
+            self.shuffle(board, rng);
+        }
+    }
+
+    /// Shuffles the discard pile into the deck.
+    pub fn shuffle(&mut self, board: &Board, rng: &mut impl Rng) {
+        let liberal = self.liberal - board.liberal_cards;
+        let fascist = self.fascist - board.fascist_cards;
+        let communist = self.communist - board.communist_cards;
+
+        self.deck.clear();
+        self.discard = Some(vec![]);
+        self.deck.extend(repeat_n(Party::Liberal, liberal));
+        self.deck.extend(repeat_n(Party::Fascist, fascist));
+        self.deck.extend(repeat_n(Party::Communist, communist));
+        self.deck.shuffle(rng);
+    }
+
+    /// Shuffles two communist cards and one liberal card into the deck.
+    pub fn five_year_plan(&mut self, rng: &mut impl Rng) {
+        for card in FIVE_YEAR_PLAN {
+            match card {
+                Party::Liberal => self.liberal += 1,
+                Party::Fascist => self.fascist += 1,
+                Party::Communist => self.communist += 1,
+            }
+            self.deck.push(card);
+        }
+        self.deck.shuffle(rng);
+    }
+
+    /// Draws the top card from the deck.
+    pub fn draw_one(&mut self) -> Party {
+        self.deck.pop().unwrap()
+    }
+
+    /// Draws the top three cards from the deck.
+    pub fn draw_three(&mut self) -> [Party; 3] {
+        let mut cards = [
+            self.deck.pop().unwrap(),
+            self.deck.pop().unwrap(),
+            self.deck.pop().unwrap(),
+        ];
+        cards.reverse();
+        cards
+    }
+
+    /// Puts a card on the discard pile.
+    pub fn discard(&mut self, card: Party) {
+        if let Some(discard) = &mut self.discard {
+            discard.push(card);
+        }
+    }
+
+    /// Rearranges the draw pile so that the given cards are drawn first, in order.
+    /// Any card which is not in the draw pile is skipped.
+    pub fn stack(&mut self, cards: &[Party]) {
+        let mut top = self.deck.len();
+        for card in cards {
+            let Some(index) = self.deck[..top].iter().rposition(|c| c == card) else {
+                continue;
+            };
+            top -= 1;
+            self.deck.swap(index, top);
+        }
+    }
+
+    /// The number of cards in the draw pile.
+    pub fn count(&self) -> usize {
+        self.deck.len()
+    }
+
+    /// The number of cards of the given party in the draw pile.
+    pub fn count_party(&self, party: Party) -> usize {
+        self.deck.iter().filter(|card| **card == party).count()
+    }
+
+    /// The number of cards of the given party in the discard pile, if it is known.
+    pub fn count_discarded(&self, party: Party) -> Option<usize> {
+        let discard = self.discard.as_ref()?;
+        Some(discard.iter().filter(|card| **card == party).count())
+    }
+
+    /// The total number of cards of the given party in the game, wherever they are.
+    pub fn total(&self, party: Party) -> usize {
+        match party {
+            Party::Liberal => self.liberal,
+            Party::Fascist => self.fascist,
+            Party::Communist => self.communist,
+        }
+    }
+
+    /// The publicly known counts of the policies yet to be enacted, given the cards on the board.
+    pub fn odds(&self, board: &Board, communists: bool) -> DeckOdds {
+        DeckOdds {
+            liberal: self.liberal - board.liberal_cards,
+            fascist: self.fascist - board.fascist_cards,
+            communist: communists.then(|| self.communist - board.communist_cards),
+        }
+    }
+
+    /// Peeks at the top three cards in the draw pile.
+    pub fn peek_three(&self) -> [Party; 3] {
+        self.deck[self.deck.len() - 3..].try_into().unwrap()
+    }
+}
@@ -0,0 +1,136 @@
+use super::{executive_power::ExecutiveAction, party::Party, player::Role, Game, MAX_PLAYERS};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct EligiblePlayers {
+    eligible: [bool; MAX_PLAYERS],
+}
+
+impl EligiblePlayers {
+    pub fn only_one(player: usize) -> Self {
+        Self {
+            eligible: core::array::from_fn(|i| i == player),
+        }
+    }
+
+    pub fn only(players: &[usize]) -> Self {
+        Self {
+            eligible: core::array::from_fn(|i| players.contains(&i)),
+        }
+    }
+
+    pub fn exclude(&mut self, player: usize) {
+        self.eligible[player] = false;
+    }
+
+    pub fn includes(&self, player: usize) -> bool {
+        self.eligible[player]
+    }
+
+    pub fn names(&self, game: &Game) -> Vec<String> {
+        game.players
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.includes(*i))
+            .map(|(_, p)| p.name.clone())
+            .collect()
+    }
+}
+
+pub struct EligiblePlayersBuilder<'a> {
+    game: &'a Game,
+    eligible: [bool; MAX_PLAYERS],
+}
+
+impl Game {
+    pub fn eligible_players(&self) -> EligiblePlayersBuilder<'_> {
+        EligiblePlayersBuilder {
+            game: self,
+            eligible: core::array::from_fn(|i| self.players.get(i).map(|p| p.alive).unwrap_or(false)),
+        }
+    }
+
+    /// Gets the players who may be chosen as the target of an executive action played after the last government.
+    /// This is the only place these rules live, so that the players offered in a prompt are always the players
+    /// who may actually be chosen:
+    ///
+    /// - Dead players can never be chosen.
+    /// - The president can't investigate, execute or call a special election on themselves.
+    /// - A player can't be investigated twice, unless the house rules allow repeat investigations.
+    /// - Term limits don't apply to a special election, so the last chancellor may be chosen.
+    /// - The chancellor must choose between themselves and the president for a confession.
+    /// - The communists can't bug or radicalise one of their own, nor radicalise a player who has been
+    ///   investigated or whom they have already tried to radicalise.
+    /// - The policy peak and five year plan have no target.
+    pub fn action_targets(&self, action: ExecutiveAction) -> EligiblePlayers {
+        use ExecutiveAction::*;
+
+        let Some(government) = self.last_government else {
+            return EligiblePlayers::only(&[]);
+        };
+        let president = government.president;
+        match action {
+            InvestigatePlayer => self.eligible_players().can_be_investigated().exclude(president).make(),
+            SpecialElection | Execution => self.eligible_players().exclude(president).make(),
+            Confession => self.eligible_players().only(&[president, government.chancellor]).make(),
+            Bugging => self.eligible_players().not_communist().make(),
+            Radicalisation | Congress => self.eligible_players().can_radicalise().make(),
+            PolicyPeak | FiveYearPlan => EligiblePlayers::only(&[]),
+        }
+    }
+}
+
+impl<'a> EligiblePlayersBuilder<'a> {
+    pub fn exclude(mut self, player: usize) -> Self {
+        self.eligible[player] = false;
+        self
+    }
+
+    pub fn only(mut self, players: &[usize]) -> Self {
+        for (idx, eligible) in self.eligible.iter_mut().enumerate() {
+            *eligible &= players.contains(&idx);
+        }
+        self
+    }
+
+    pub fn not_communist(mut self) -> Self {
+        for (idx, player) in self.game.players.iter().enumerate() {
+            self.eligible[idx] &= player.party() != Party::Communist;
+        }
+        self
+    }
+
+    pub fn ordinary_communist(mut self) -> Self {
+        for (idx, player) in self.game.players.iter().enumerate() {
+            self.eligible[idx] &= player.role == Role::Communist;
+        }
+        self
+    }
+
+    pub fn can_radicalise(mut self) -> Self {
+        for (idx, player) in self.game.players.iter().enumerate() {
+            self.eligible[idx] &= !player.tried_to_radicalise;
+        }
+        self.not_communist().not_investigated()
+    }
+
+    pub fn not_investigated(mut self) -> Self {
+        for (idx, player) in self.game.players.iter().enumerate() {
+            self.eligible[idx] &= !player.investigated;
+        }
+        self
+    }
+
+    /// Excludes players who have already been investigated, unless the house rules allow repeat investigations.
+    pub fn can_be_investigated(self) -> Self {
+        if self.game.opts.house_rules.repeat_investigations {
+            self
+        } else {
+            self.not_investigated()
+        }
+    }
+
+    pub fn make(self) -> EligiblePlayers {
+        EligiblePlayers { eligible: self.eligible }
+    }
+}
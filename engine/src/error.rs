@@ -0,0 +1,141 @@
+use crate::{InvariantViolation, OptionsProblem, Phase};
+use thiserror::Error;
+
+/// The result of attempting to perform an invalid operation on a [Game] or [Session].
+#[derive(Error, Debug)]
+pub enum GameError {
+    #[error("invalid combination of game options")]
+    InvalidGameOptions,
+    #[error("{}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    OptionsValidation(Vec<OptionsProblem>),
+    #[error("game does not exist")]
+    GameNotFound,
+    #[error("the server is hosting as many games as it can")]
+    ServerFull,
+    #[error("you already have {max} open lobbies")]
+    TooManyLobbies { max: usize },
+    #[error("too few players in the game")]
+    TooFewPlayers,
+    #[error("too many players in the game")]
+    TooManyPlayers,
+    #[error("no player exists with the given name")]
+    PlayerNotFound,
+    #[error("another player already has this name")]
+    NameTaken,
+    #[error("names must be between 1 and {max} characters")]
+    InvalidNameLength { max: usize },
+    #[error("names may only contain letters, digits, single spaces and the characters - _ ' .")]
+    InvalidNameCharacters,
+    #[error("this name is not allowed")]
+    NameNotAllowed,
+    #[error("the game has already been started")]
+    AlreadyStarted,
+    #[error("cannot join a game in progress")]
+    CannotJoinStartedGame,
+    #[error("this player cannot be chosen for this action")]
+    InvalidPlayerChoice,
+    #[error("invalid player index")]
+    InvalidPlayerIndex,
+    #[error("this action cannot be performed during this phase of the game")]
+    InvalidAction,
+    #[error("this action cannot be performed during the {actual:?} phase of the game")]
+    WrongPhase { expected: Vec<Phase>, actual: Phase },
+    #[error("it is not your turn to do this")]
+    NotYourTurn,
+    #[error("you are not able to do this")]
+    NotEligible,
+    #[error("you have already done this")]
+    AlreadyConfirmed,
+    #[error("dead players cannot act")]
+    DeadPlayer,
+    #[error("a veto cannot be proposed yet")]
+    VetoLocked,
+    #[error("you have already voted")]
+    AlreadyVoted,
+    #[error("an invalid card was chosen")]
+    InvalidCard,
+    #[error("chat messages must be between 1 and 500 characters")]
+    InvalidChatMessage,
+    #[error("you cannot post to this chat")]
+    ChatNotPermitted,
+    #[error("this action must be confirmed")]
+    ConfirmationRequired,
+    #[error("it is too late to undo this action")]
+    UndoExpired,
+    #[error("the action could not be saved")]
+    PersistenceFailed,
+    #[error("the board key is missing or incorrect")]
+    InvalidBoardKey,
+    #[error("the player token is missing, expired or does not match")]
+    InvalidToken,
+    #[error("the admin token is missing or incorrect")]
+    InvalidAdminToken,
+    #[error("the save code is damaged or was not issued by this server")]
+    InvalidSaveCode,
+    #[error("save codes are not enabled on this server")]
+    SaveCodesDisabled,
+    #[error("another connection is already playing as this player")]
+    SeatTaken,
+    #[error("the action would leave the game in an inconsistent state: {0}")]
+    InconsistentState(InvariantViolation),
+    #[error("replay diverged from the log at action {index}")]
+    ReplayDiverged { index: usize },
+    #[error("push notifications are not enabled on this server")]
+    NotificationsDisabled,
+    #[error("notifications cannot be delivered to this target")]
+    InvalidPushTarget,
+    #[error("game is hosted by another server")]
+    HostedElsewhere { address: String },
+    #[error("the server cannot reach its storage")]
+    StoreUnavailable,
+}
+
+impl GameError {
+    /// A stable name for the kind of error, so that clients can tell users why their request was rejected.
+    pub fn code(&self) -> &'static str {
+        match self {
+            GameError::InvalidGameOptions => "invalid_game_options",
+            GameError::OptionsValidation(_) => "options_validation",
+            GameError::GameNotFound => "game_not_found",
+            GameError::ServerFull => "server_full",
+            GameError::TooManyLobbies { .. } => "too_many_lobbies",
+            GameError::TooFewPlayers => "too_few_players",
+            GameError::TooManyPlayers => "too_many_players",
+            GameError::PlayerNotFound => "player_not_found",
+            GameError::NameTaken => "name_taken",
+            GameError::InvalidNameLength { .. } => "invalid_name_length",
+            GameError::InvalidNameCharacters => "invalid_name_characters",
+            GameError::NameNotAllowed => "name_not_allowed",
+            GameError::AlreadyStarted => "already_started",
+            GameError::CannotJoinStartedGame => "cannot_join_started_game",
+            GameError::InvalidPlayerChoice => "invalid_player_choice",
+            GameError::InvalidPlayerIndex => "invalid_player_index",
+            GameError::InvalidAction => "invalid_action",
+            GameError::WrongPhase { .. } => "wrong_phase",
+            GameError::NotYourTurn => "not_your_turn",
+            GameError::NotEligible => "not_eligible",
+            GameError::AlreadyConfirmed => "already_confirmed",
+            GameError::DeadPlayer => "dead_player",
+            GameError::VetoLocked => "veto_locked",
+            GameError::AlreadyVoted => "already_voted",
+            GameError::InvalidCard => "invalid_card",
+            GameError::InvalidChatMessage => "invalid_chat_message",
+            GameError::ChatNotPermitted => "chat_not_permitted",
+            GameError::ConfirmationRequired => "confirmation_required",
+            GameError::UndoExpired => "undo_expired",
+            GameError::PersistenceFailed => "persistence_failed",
+            GameError::InvalidBoardKey => "invalid_board_key",
+            GameError::InvalidToken => "invalid_token",
+            GameError::InvalidAdminToken => "invalid_admin_token",
+            GameError::InvalidSaveCode => "invalid_save_code",
+            GameError::SaveCodesDisabled => "save_codes_disabled",
+            GameError::SeatTaken => "seat_taken",
+            GameError::InconsistentState(_) => "inconsistent_state",
+            GameError::ReplayDiverged { .. } => "replay_diverged",
+            GameError::NotificationsDisabled => "notifications_disabled",
+            GameError::InvalidPushTarget => "invalid_push_target",
+            GameError::HostedElsewhere { .. } => "hosted_elsewhere",
+            GameError::StoreUnavailable => "store_unavailable",
+        }
+    }
+}
@@ -0,0 +1,425 @@
+use super::player::{InvestigationResult, Knowledge, KnowledgeSource, PolicyPeakRecord, Role};
+use super::{Game, GameState, NextPresident, Phase};
+use crate::transitions::transition;
+use crate::{confirmations::Confirmations, eligible::EligiblePlayers, error::GameError, government::Government};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub enum ExecutiveAction {
+    /// The president must investigate a player's loyalty.
+    InvestigatePlayer,
+    /// The president must call a special election.
+    SpecialElection,
+    /// The president must peek at the top three cards on the deck.
+    PolicyPeak,
+    /// The president must execute a player.
+    Execution,
+    /// The communists learn the party membership of one player.
+    Bugging,
+    /// The communists attempt to convert one player to the communist team.
+    Radicalisation,
+    /// 2 communist and 1 liberal policy are shuffled into the deck.
+    FiveYearPlan,
+    /// The new communist learns who their allies are, or another radicalisation is attempted.
+    Congress,
+    /// The president or chancellor reveals their party membership.
+    Confession,
+}
+
+impl std::fmt::Display for ExecutiveAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ExecutiveAction::InvestigatePlayer => "investigate",
+            ExecutiveAction::SpecialElection => "specialElection",
+            ExecutiveAction::PolicyPeak => "policyPeak",
+            ExecutiveAction::Execution => "execution",
+            ExecutiveAction::Bugging => "bugging",
+            ExecutiveAction::Radicalisation => "radicalisation",
+            ExecutiveAction::FiveYearPlan => "fiveYearPlan",
+            ExecutiveAction::Congress => "congress",
+            ExecutiveAction::Confession => "confession",
+        })
+    }
+}
+
+impl Game {
+    /// Begins an executive action.
+    pub fn start_executive_action(&mut self, action: ExecutiveAction) {
+        use ExecutiveAction::*;
+
+        // There must have been a last government for an executive power to be played
+        let Government { president, chancellor } = self.last_government.unwrap();
+
+        match action {
+            InvestigatePlayer => {
+                transition!(self, &[Phase::CardReveal] => ChoosePlayer {
+                    action,
+                    can_select: EligiblePlayers::only_one(president),
+                    can_be_selected: self.action_targets(action),
+                });
+            }
+            SpecialElection => {
+                // Note: We still want to "wait for the monarchist" even if they're dead,
+                // so as to not reveal any information to other players.
+                let monarchist = self.players.iter().position(|p| p.role == Role::Monarchist);
+                if let Some(monarchist) = monarchist {
+                    transition!(self, &[Phase::CardReveal] => PromptMonarchist {
+                        monarchist,
+                        last_president: president,
+                        hijacked: false,
+                    });
+                } else {
+                    transition!(self, &[Phase::CardReveal] => ChoosePlayer {
+                        action,
+                        can_select: EligiblePlayers::only_one(president),
+                        can_be_selected: self.action_targets(action),
+                    });
+                }
+            }
+            Execution => {
+                transition!(self, &[Phase::CardReveal] => ChoosePlayer {
+                    action,
+                    can_select: EligiblePlayers::only_one(president),
+                    can_be_selected: self.action_targets(action),
+                });
+            }
+            PolicyPeak | FiveYearPlan => {
+                // The new cards go into the deck straight away, so the board can show the new draw pile
+                if action == FiveYearPlan {
+                    self.deck.five_year_plan(&mut self.rng);
+                }
+                transition!(self, &[Phase::CardReveal] => ActionReveal {
+                    action,
+                    chosen_player: None,
+                    confirmations: Confirmations::new(self.num_players_alive()),
+                });
+            }
+            Bugging | Radicalisation | Congress => {
+                transition!(self, &[Phase::CardReveal] => CommunistStart { action });
+            }
+            Confession => {
+                transition!(self, &[Phase::CardReveal] => ChoosePlayer {
+                    action,
+                    can_select: EligiblePlayers::only_one(chancellor),
+                    can_be_selected: self.action_targets(action),
+                });
+            }
+        }
+    }
+
+    /// Called when the board has finished entering a "communist session".
+    pub fn end_communist_start(&mut self) -> Result<(), GameError> {
+        use ExecutiveAction::*;
+
+        let GameState::CommunistStart { action } = self.state else {
+            return Err(self.wrong_phase(&[Phase::CommunistStart]));
+        };
+
+        // If radicalisation succeeded, there's no second attempt during congress
+        if action == Congress && self.radicalised {
+            transition!(self, &[Phase::CommunistStart] => Congress);
+            return Ok(());
+        }
+
+        let can_select = self.eligible_players().ordinary_communist().make();
+
+        let can_be_selected = self.action_targets(action);
+
+        transition!(self, &[Phase::CommunistStart] => ChoosePlayer { action, can_select, can_be_selected });
+        Ok(())
+    }
+
+    /// Called when a player is ready to end the congress session.
+    pub fn end_congress(&mut self, player: usize) -> Result<(), GameError> {
+        let GameState::Congress = &self.state else {
+            return Err(self.wrong_phase(&[Phase::Congress]));
+        };
+        if self.players.get(player).map(|p| p.role) != Some(Role::Communist) {
+            return Err(GameError::NotEligible);
+        }
+        transition!(self, &[Phase::Congress] => CommunistEnd {
+            action: ExecutiveAction::Congress,
+            chosen_player: None,
+        });
+        Ok(())
+    }
+
+    /// Called when the monarchist elects to hijack a special election.
+    pub fn hijack_special_election(&mut self, player: usize) -> Result<(), GameError> {
+        let GameState::PromptMonarchist { monarchist, hijacked, .. } = &mut self.state else {
+            return Err(self.wrong_phase(&[Phase::PromptMonarchist]));
+        };
+
+        if player != *monarchist {
+            return Err(GameError::NotYourTurn);
+        }
+        if !self.players[player].alive {
+            return Err(GameError::DeadPlayer);
+        }
+
+        *hijacked = true;
+        Ok(())
+    }
+
+    /// Gets the capitalist who may bribe the given president to cancel or redirect the power they have just used,
+    /// if bribes are enabled and the capitalist has not already made one.
+    pub(super) fn can_bribe(&self, action: ExecutiveAction, president: usize) -> Option<usize> {
+        if !self.opts.house_rules.capitalist_bribe || self.bribed || action != ExecutiveAction::Execution {
+            return None;
+        }
+        let capitalist = self
+            .players
+            .iter()
+            .position(|p| p.alive && p.role == Role::Capitalist)?;
+        (capitalist != president).then_some(capitalist)
+    }
+
+    /// Called when the capitalist decides whether to bribe the president.
+    pub fn offer_bribe(&mut self, player: usize, offer: bool) -> Result<(), GameError> {
+        let GameState::Bribe {
+            action,
+            capitalist,
+            chosen_player,
+            offered: false,
+            ..
+        } = self.state
+        else {
+            return Err(self.bribe_phase_error());
+        };
+        if player != capitalist {
+            return Err(GameError::NotYourTurn);
+        }
+        if offer {
+            self.bribed = true;
+            let GameState::Bribe { offered, .. } = &mut self.state else {
+                unreachable!()
+            };
+            *offered = true;
+        } else {
+            self.reveal_action(action, chosen_player);
+        }
+        Ok(())
+    }
+
+    /// Called when the president responds to the capitalist's bribe, either by cancelling their power or refusing.
+    /// The president may instead accept the bribe by choosing a different player.
+    pub fn respond_to_bribe(&mut self, player: usize, cancel: bool) -> Result<(), GameError> {
+        let GameState::Bribe {
+            action,
+            president,
+            chosen_player,
+            offered: true,
+            ..
+        } = self.state
+        else {
+            return Err(self.bribe_phase_error());
+        };
+        if player != president {
+            return Err(GameError::NotYourTurn);
+        }
+        match cancel {
+            true => self.start_round(),
+            false => self.reveal_action(action, chosen_player),
+        }
+        Ok(())
+    }
+
+    /// Moves on to revealing the outcome of an executive action which targets the given player.
+    /// If Hitler is chosen for execution and the monarchist is protecting them, the monarchist is executed instead.
+    pub(super) fn reveal_action(&mut self, action: ExecutiveAction, mut chosen_player: usize) {
+        if action == ExecutiveAction::Execution && self.opts.house_rules.monarchist_protection {
+            let monarchist = self.players.iter().position(|p| p.alive && p.role == Role::Monarchist);
+            if let (Some(monarchist), Role::Hitler) = (monarchist, self.players[chosen_player].role) {
+                chosen_player = monarchist;
+            }
+        }
+        transition!(self, &[Phase::ChoosePlayer, Phase::Bribe] => ActionReveal {
+            action,
+            chosen_player: Some(chosen_player),
+            confirmations: Confirmations::new(self.num_players_alive()),
+        });
+    }
+
+    /// Called when the president publicly reveals the party of the player they investigated.
+    pub fn reveal_investigation(&mut self, player: usize) -> Result<(), GameError> {
+        let GameState::ActionReveal {
+            action: ExecutiveAction::InvestigatePlayer,
+            chosen_player: Some(chosen_player),
+            ..
+        } = self.state
+        else {
+            return Err(GameError::InvalidAction);
+        };
+        if !self.opts.house_rules.public_investigations {
+            return Err(GameError::NotEligible);
+        }
+        if self.last_government.map(|g| g.president) != Some(player) {
+            return Err(GameError::NotYourTurn);
+        }
+        let chosen = &mut self.players[chosen_player];
+        if chosen.confirmed_party.is_some() {
+            return Err(GameError::AlreadyConfirmed);
+        }
+        chosen.confirmed_party = Some(chosen.party());
+        Ok(())
+    }
+
+    /// Called when the board has finished presenting the special election screen
+    pub fn start_special_election(&mut self) -> Result<(), GameError> {
+        let GameState::PromptMonarchist { monarchist, last_president, hijacked } = self.state else {
+            return Err(self.wrong_phase(&[Phase::PromptMonarchist]));
+        };
+
+        if hijacked {
+            self.next_president = Some(NextPresident::Monarchist { monarchist, last_president });
+            self.start_round();
+        } else {
+            transition!(self, &[Phase::PromptMonarchist] => ChoosePlayer {
+                action: ExecutiveAction::SpecialElection,
+                can_select: EligiblePlayers::only_one(last_president),
+                can_be_selected: self.action_targets(ExecutiveAction::SpecialElection),
+            });
+        }
+        Ok(())
+    }
+
+    /// Called when the board has finished leaving a "communist session".
+    pub fn end_communist_end(&mut self) -> Result<(), GameError> {
+        use ExecutiveAction::*;
+
+        let GameState::CommunistEnd { action, chosen_player } = self.state else {
+            return Err(self.wrong_phase(&[Phase::CommunistEnd]));
+        };
+
+        match action {
+            Bugging => {
+                self.start_round();
+            }
+            Radicalisation | Congress => {
+                if let Some(player_idx) = chosen_player {
+                    let player = &mut self.players[player_idx];
+                    self.radicalised = player.radicalise();
+                }
+                if action == Congress {
+                    self.reveal_communists();
+                }
+                transition!(self, &[Phase::CommunistEnd] => ActionReveal {
+                    action,
+                    chosen_player,
+                    confirmations: Confirmations::new(self.num_players_alive()),
+                });
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    /// Records that the communists have learned each other's identities during a congress,
+    /// so that they stay known after the congress has ended.
+    fn reveal_communists(&mut self) {
+        let communists = (0..self.num_players())
+            .filter(|idx| self.players[*idx].alive && self.players[*idx].role == Role::Communist)
+            .collect::<Vec<_>>();
+        for &player in &communists {
+            for &target in communists.iter().filter(|target| **target != player) {
+                let result = InvestigationResult::Role(Role::Communist);
+                let (source, round) = (KnowledgeSource::Congress, self.round);
+                self.players[player].others[target] = result;
+                self.players[player]
+                    .knowledge
+                    .push(Knowledge { target, result, source, round });
+            }
+        }
+    }
+
+    /// Called when the president acknowledges the policies they saw with a policy peak,
+    /// which ends the policy peak and records what they saw in case it is disputed.
+    pub fn acknowledge_policy_peak(&mut self, player: usize) -> Result<(), GameError> {
+        let GameState::ActionReveal { action, .. } = &self.state else {
+            return Err(self.wrong_phase(&[Phase::ActionReveal]));
+        };
+        if *action != ExecutiveAction::PolicyPeak {
+            return Err(GameError::InvalidAction);
+        }
+        let record = PolicyPeakRecord {
+            cards: self.deck.peek_three(),
+            round: self.round,
+        };
+        self.finish_executive_action(Some(player))?;
+        self.players[player].policy_peaks.push(record);
+        Ok(())
+    }
+
+    /// Called when the board has finished presenting the executive action.
+    /// A policy peak can only be ended by the president acknowledging it, so that what they saw is recorded.
+    pub fn end_executive_action(&mut self, player: Option<usize>) -> Result<(), GameError> {
+        if let GameState::ActionReveal { action: ExecutiveAction::PolicyPeak, .. } = self.state {
+            return Err(GameError::InvalidAction);
+        }
+        self.finish_executive_action(player)
+    }
+
+    /// Ends the executive action on behalf of the given player, or of the board, whichever action it is.
+    pub(crate) fn finish_executive_action(&mut self, player: Option<usize>) -> Result<(), GameError> {
+        use ExecutiveAction::*;
+
+        let GameState::ActionReveal { action, chosen_player, confirmations } = &mut self.state else {
+            return Err(self.wrong_phase(&[Phase::ActionReveal]));
+        };
+
+        match action {
+            // Only the president may end these actions
+            InvestigatePlayer | PolicyPeak => {
+                let president = self.last_government.unwrap().president;
+                if player != Some(president) {
+                    return Err(GameError::NotYourTurn);
+                }
+            }
+            // Only the board may end these actions
+            SpecialElection | Execution | FiveYearPlan => {
+                if player.is_some() {
+                    return Err(GameError::InvalidAction);
+                }
+            }
+            // These actions are ended once all players are ready
+            Bugging | Radicalisation | Congress | Confession => {
+                let Some(player) = player else {
+                    return Err(GameError::InvalidAction);
+                };
+                confirmations.confirm(player);
+                if !confirmations.can_proceed() {
+                    return Ok(());
+                }
+            }
+        };
+
+        match action {
+            InvestigatePlayer => {
+                self.start_round();
+            }
+            SpecialElection => {
+                let player = chosen_player.unwrap();
+                self.next_president = Some(NextPresident::Normal { player, fallback: None });
+                self.start_round();
+            }
+            Execution => {
+                let player = &mut self.players[chosen_player.unwrap()];
+                player.alive = false;
+                player.not_hitler = player.role != Role::Hitler;
+
+                if self.check_game_over() {
+                    return Ok(());
+                }
+
+                self.start_round();
+            }
+            Bugging => {
+                transition!(self, &[Phase::ActionReveal] => CommunistEnd { action: Bugging, chosen_player: None });
+            }
+            _ => {
+                self.start_round();
+            }
+        }
+        Ok(())
+    }
+}
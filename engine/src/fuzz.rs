@@ -0,0 +1,321 @@
+#![cfg(test)]
+
+//! Property-based tests which play random sequences of legal actions,
+//! checking that the game's invariants hold after every step.
+
+use super::{Action, ChaosRule, Game, GameOptions, HouseRules, PlayerPrompt};
+use proptest::prelude::*;
+
+/// The actions which only the board can perform, each of which moves the game on once the board has finished
+/// presenting the current phase.
+const BOARD_ACTIONS: [Action; 9] = [
+    Action::EndVoting,
+    Action::EndElectionFailed,
+    Action::EndCardReveal,
+    Action::EndExecutiveAction,
+    Action::EndLegislativeSession,
+    Action::EndAssassination,
+    Action::EndCommunistStart,
+    Action::EndCommunistEnd,
+    Action::StartSpecialElection,
+];
+
+/// A single step of a randomly played game: an action performed by a player, or by the board if there is no player.
+#[derive(Clone, Debug)]
+struct Step(Option<usize>, Action);
+
+impl Step {
+    fn apply(&self, game: &mut Game) -> Result<(), crate::error::GameError> {
+        game.apply(self.0, self.1)
+    }
+}
+
+/// Gets every action which can currently be performed on the game.
+/// Player actions are the legal actions, which must succeed, whereas other board actions are found by trial and error.
+fn candidate_actions(game: &Game) -> Vec<(Step, bool)> {
+    let mut actions = vec![];
+    for player in 0..game.num_players() {
+        for action in game.legal_actions(player) {
+            actions.push((Step(Some(player), action), true));
+        }
+        let update = game.get_player_update(player);
+        if update.can_undo {
+            actions.push((Step(Some(player), Action::Undo), true));
+        }
+    }
+    if game.timed_phase().is_some() {
+        actions.push((Step(None, Action::Timeout), true));
+    }
+    for action in game.board_actions() {
+        actions.push((Step(None, action), true));
+    }
+    for action in BOARD_ACTIONS {
+        if !game.board_actions().contains(&action) && game.clone().apply(None, action).is_ok() {
+            actions.push((Step(None, action), false));
+        }
+    }
+    actions
+}
+
+/// Checks the invariants which must hold in every state of the game, and that every view of it can be produced.
+/// The legal actions must also be those which respond to the player's prompt.
+fn check_invariants(game: &Game) {
+    assert_eq!(game.invariant_violations(), vec![]);
+    game.get_board_update();
+    for player in 0..game.num_players() {
+        let update = game.get_player_update(player);
+        let expected = update
+            .prompt
+            .as_ref()
+            .map_or(vec![], |prompt| prompt_actions(game, prompt));
+        assert_eq!(update.legal_actions, expected, "legal actions of player {}", player);
+    }
+}
+
+/// Gets the actions which respond to the given prompt, as a reference for the legal actions,
+/// which are worked out from the game state instead.
+fn prompt_actions(game: &Game, prompt: &PlayerPrompt) -> Vec<Action> {
+    use Action::*;
+
+    match *prompt {
+        PlayerPrompt::Night { role_viewed } => match role_viewed {
+            true => vec![EndNightRound],
+            false => vec![EndNightRound, ViewRole],
+        },
+        PlayerPrompt::ChoosePlayer { ref options, .. } => options
+            .iter()
+            .filter_map(|name| game.find_player(name).ok())
+            .map(|player| ChoosePlayer { player })
+            .collect(),
+        PlayerPrompt::Vote { can_abstain } => match can_abstain {
+            true => vec![CastVote { vote: true }, CastVote { vote: false }, Abstain],
+            false => vec![CastVote { vote: true }, CastVote { vote: false }],
+        },
+        PlayerPrompt::HijackElection => vec![HijackElection],
+        PlayerPrompt::PresidentDiscard { ref cards } => (0..cards.len()).map(|index| Discard { index }).collect(),
+        PlayerPrompt::ChancellorDiscard { ref cards, can_veto } => {
+            let mut actions = (0..cards.len()).map(|index| Discard { index }).collect::<Vec<_>>();
+            if can_veto {
+                actions.push(VetoAgenda);
+            }
+            actions
+        }
+        PlayerPrompt::ApproveVeto => vec![AcceptVeto, RejectVeto],
+        PlayerPrompt::StartElection { can_assassinate } => match can_assassinate {
+            true => vec![EndCardReveal, StartAssassination],
+            false => vec![EndCardReveal],
+        },
+        PlayerPrompt::EndCongress { .. } => vec![EndCongress],
+        PlayerPrompt::InvestigatePlayer { can_reveal, .. } => match can_reveal {
+            true => vec![EndExecutiveAction, RevealInvestigation],
+            false => vec![EndExecutiveAction],
+        },
+        PlayerPrompt::PolicyPeak { .. } => vec![AcknowledgePolicyPeak],
+        PlayerPrompt::Radicalisation { .. }
+        | PlayerPrompt::BuggingWithheld { .. }
+        | PlayerPrompt::Confession { .. } => vec![EndExecutiveAction],
+        PlayerPrompt::OfferBribe { .. } => vec![OfferBribe, DeclineBribe],
+        PlayerPrompt::ConsiderBribe { ref options, .. } => {
+            let mut actions = options
+                .iter()
+                .filter_map(|name| game.find_player(name).ok())
+                .map(|player| ChoosePlayer { player })
+                .collect::<Vec<_>>();
+            actions.extend([CancelPower, RejectBribe]);
+            actions
+        }
+        PlayerPrompt::Dead | PlayerPrompt::GameOver { .. } => vec![],
+    }
+}
+
+/// Checks that the seats the game reports as unchanged really do have the same view as last time,
+/// so that their cached updates can safely be reused.
+fn check_changed_seats(game: &mut Game, views: &mut Vec<serde_json::Value>) -> Result<(), TestCaseError> {
+    let changed = game.take_changed_seats();
+    let current = (0..game.num_players())
+        .map(|player| serde_json::to_value(game.get_player_update(player)).unwrap())
+        .collect::<Vec<_>>();
+    if let Some(changed) = changed {
+        for (player, (last, view)) in views.iter().zip(&current).enumerate() {
+            prop_assert!(
+                changed[player] || last == view,
+                "seat {} changed without being marked",
+                player
+            );
+        }
+    }
+    *views = current;
+    Ok(())
+}
+
+/// Every kind of action, named as in its `Debug` output.
+const ACTION_KINDS: [&str; 31] = [
+    "EndNightRound",
+    "ViewRole",
+    "EndCardReveal",
+    "EndExecutiveAction",
+    "AcknowledgePolicyPeak",
+    "ChoosePlayer",
+    "CastVote",
+    "Abstain",
+    "Discard",
+    "VetoAgenda",
+    "AcceptVeto",
+    "RejectVeto",
+    "StartAssassination",
+    "EndCongress",
+    "HijackElection",
+    "RevealInvestigation",
+    "OfferBribe",
+    "DeclineBribe",
+    "CancelPower",
+    "RejectBribe",
+    "EndVoting",
+    "EndElectionFailed",
+    "EndLegislativeSession",
+    "EndAssassination",
+    "EndCommunistStart",
+    "EndCommunistEnd",
+    "StartSpecialElection",
+    "Undo",
+    "Timeout",
+    "ExpireUndo",
+    "ForceAdvance",
+];
+
+/// Plays the same games on every run, checking after each action that the updates reused for the seats which were
+/// not marked as changed are the same as freshly generated ones, and that every kind of action was checked.
+#[test]
+fn reused_updates_match_fresh_ones_after_every_kind_of_action() {
+    let mut performed = std::collections::HashSet::new();
+    let mut choice = 0x2545_f491_4f6c_dd1d_u64;
+    for seed in 0..60u64 {
+        let bits = |bit: u64| (seed >> bit) & 1 == 1;
+        let opts = GameOptions {
+            communists: bits(0),
+            monarchist: bits(1),
+            anarchist: bits(2),
+            capitalist: true,
+            centrists: bits(3),
+            undo_window: Some(10),
+            house_rules: HouseRules {
+                public_investigations: true,
+                capitalist_bribe: true,
+                monarchist_protection: bits(4),
+                private_bugging: bits(5),
+                abstentions: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let names = (0..5 + seed as usize % 6)
+            .map(|i| format!("P{}", i))
+            .collect::<Vec<_>>();
+        let Ok(mut game) = Game::new(opts, &names, seed) else {
+            continue;
+        };
+        let mut views = vec![];
+        check_changed_seats(&mut game, &mut views).unwrap();
+
+        for _ in 0..400 {
+            if game.game_over() {
+                break;
+            }
+            // A xorshift generator picks the actions, so that every run plays the same games
+            choice ^= choice << 13;
+            choice ^= choice >> 7;
+            choice ^= choice << 17;
+
+            // Actions which cut a phase short are only tried now and then, so that most games are played out
+            let cut_short = [Action::Timeout, Action::ExpireUndo, Action::ForceAdvance];
+            let mut actions = candidate_actions(&game);
+            actions.retain(|(step, _)| !cut_short.contains(&step.1));
+            if choice.is_multiple_of(8) {
+                for action in cut_short {
+                    if game.clone().apply(None, action).is_ok() {
+                        actions.push((Step(None, action), true));
+                    }
+                }
+            }
+            if actions.is_empty() {
+                continue;
+            }
+            let (step, _) = &actions[(choice >> 8) as usize % actions.len()];
+            if step.apply(&mut game).is_ok() {
+                let kind = format!("{:?}", step.1);
+                performed.insert(kind.split([' ', '{']).next().unwrap().to_string());
+                check_changed_seats(&mut game, &mut views).unwrap();
+            }
+        }
+    }
+    let missing = ACTION_KINDS
+        .iter()
+        .filter(|kind| !performed.contains(**kind))
+        .collect::<Vec<_>>();
+    assert!(missing.is_empty(), "never performed {:?}", missing);
+}
+
+fn game_options() -> impl Strategy<Value = GameOptions> {
+    let chaos_rules = prop_oneof![
+        Just(ChaosRule::NoPower),
+        Just(ChaosRule::Reshuffle),
+        Just(ChaosRule::GrantsPower)
+    ];
+    (any::<[bool; 5]>(), any::<[bool; 10]>(), chaos_rules).prop_map(|(roles, rules, chaos_rule)| GameOptions {
+        communists: roles[0],
+        monarchist: roles[1],
+        anarchist: roles[2],
+        capitalist: roles[3],
+        centrists: roles[4],
+        undo_window: rules[0].then_some(10),
+        house_rules: HouseRules {
+            public_investigations: rules[1],
+            capitalist_bribe: rules[2],
+            monarchist_protection: rules[3],
+            private_bugging: rules[4],
+            secret_ballots: rules[6],
+            abstentions: rules[7],
+            posthumous_assassination: rules[8],
+            repeat_investigations: rules[9],
+            ..Default::default()
+        },
+        boardless: rules[5],
+        chaos_rule,
+        ..Default::default()
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(200))]
+
+    #[test]
+    fn random_legal_actions_preserve_invariants(
+        opts in game_options(),
+        num_players in 5usize..=16,
+        seed in any::<u64>(),
+        choices in prop::collection::vec(any::<prop::sample::Index>(), 400),
+    ) {
+        let names = (0..num_players).map(|i| format!("P{}", i)).collect::<Vec<_>>();
+        let Ok(mut game) = Game::new(opts, &names, seed) else {
+            return Ok(());
+        };
+        check_invariants(&game);
+        let mut views = vec![];
+        check_changed_seats(&mut game, &mut views)?;
+
+        for choice in choices {
+            if game.game_over() {
+                break;
+            }
+            let actions = candidate_actions(&game);
+            if actions.is_empty() {
+                break;
+            }
+            let (action, legal) = choice.get(&actions);
+            let result = action.apply(&mut game);
+            prop_assert!(result.is_ok() || !legal, "legal action {:?} failed: {:?}", action, result);
+            check_invariants(&game);
+            check_changed_seats(&mut game, &mut views)?;
+        }
+    }
+}
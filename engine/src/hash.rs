@@ -0,0 +1,86 @@
+use super::{
+    AssassinationState, Board, BoardUpdate, Deck, Game, GameOptions, GameState, Government, LogEntry, NextPresident,
+    Player, UndoSnapshot, WinCondition,
+};
+use serde::Serialize;
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// The fields of a game which define its state, borrowed so they can be hashed without copying the game.
+/// The log is left empty, as it grows with every action and each entry already records the hash before it;
+/// the fields are otherwise serialized exactly as the game itself is, so hashes match those already logged.
+#[derive(Serialize)]
+struct HashedState<'a> {
+    opts: &'a GameOptions,
+    players: &'a [Player],
+    board: &'a Board,
+    deck: &'a Deck,
+    state: &'a GameState,
+    presidential_turn: usize,
+    next_president: &'a Option<NextPresident>,
+    election_tracker: usize,
+    last_government: &'a Option<Government>,
+    radicalised: bool,
+    assassination: &'a AssassinationState,
+    rng: &'a rand_chacha::ChaCha8Rng,
+    undo: &'a Option<UndoSnapshot>,
+    round: usize,
+    bribed: bool,
+    chaos_policies: usize,
+    secondary_conditions: &'a [WinCondition],
+    seed: u64,
+    log: [LogEntry; 0],
+}
+
+impl Game {
+    /// Computes a hash of the game state, other than the log, which is stable across server restarts and versions,
+    /// such that two games with the same hash can be assumed to be identical.
+    /// It takes time in proportion to the size of the state, but not to the number of actions performed.
+    pub fn state_hash(&self) -> u64 {
+        let state = HashedState {
+            opts: &self.opts,
+            players: &self.players,
+            board: &self.board,
+            deck: &self.deck,
+            state: &self.state,
+            presidential_turn: self.presidential_turn,
+            next_president: &self.next_president,
+            election_tracker: self.election_tracker,
+            last_government: &self.last_government,
+            radicalised: self.radicalised,
+            assassination: &self.assassination,
+            rng: &self.rng,
+            undo: &self.undo,
+            round: self.round,
+            bribed: self.bribed,
+            chaos_policies: self.chaos_policies,
+            secondary_conditions: &self.secondary_conditions,
+            seed: self.seed,
+            log: [],
+        };
+        let bytes = serde_json::to_vec(&state).expect("game state is serializable");
+        fnv1a(&bytes)
+    }
+
+    /// Computes the hash of the whole game, log included, which is how older servers computed the state hash.
+    /// It is only needed to check the journals they wrote.
+    pub fn legacy_state_hash(&self) -> u64 {
+        let bytes = serde_json::to_vec(self).expect("game state is serializable");
+        fnv1a(&bytes)
+    }
+
+    /// Computes a hash of the public game state, being the given board update (minus its own hash) and the players.
+    pub(super) fn public_hash(&self, update: &BoardUpdate) -> u64 {
+        let update = BoardUpdate { state_hash: 0, ..update.clone() };
+        let bytes = serde_json::to_vec(&(update, self.get_public_players())).expect("game state is serializable");
+        fnv1a(&bytes)
+    }
+}
+
+/// The 64-bit FNV-1a hash of the given bytes.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(FNV_OFFSET, |hash, byte| (hash ^ *byte as u64).wrapping_mul(FNV_PRIME))
+}
@@ -0,0 +1,99 @@
+//! The invariants which must hold in every state of the game. They are checked after every action in debug builds,
+//! or in any build with the `invariants` feature, so that a bug is noticed before it silently corrupts a session.
+
+use super::{Game, GameState, LegislativeSessionTurn, Party};
+use thiserror::Error;
+
+/// Whether the invariants are checked after every action.
+const CHECK_INVARIANTS: bool = cfg!(any(debug_assertions, feature = "invariants"));
+
+/// A way in which the state of a game is inconsistent.
+#[derive(Error, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InvariantViolation {
+    #[error("player {0} is in government but dead")]
+    DeadInGovernment(usize),
+    #[error("there are too many {0} cards")]
+    TooManyCards(Party),
+    #[error("there are too few {0} cards")]
+    TooFewCards(Party),
+    #[error("no players are alive")]
+    NoPlayersAlive,
+}
+
+impl Game {
+    /// Gets every invariant which the current state of the game breaks.
+    pub fn invariant_violations(&self) -> Vec<InvariantViolation> {
+        let mut violations = vec![];
+
+        // Everyone in government must be alive
+        let government = match &self.state {
+            GameState::Election { president, chancellor, .. } => vec![Some(*president), *chancellor],
+            GameState::LegislativeSession { president, chancellor, .. } => vec![Some(*president), Some(*chancellor)],
+            GameState::MonarchistElection {
+                monarchist,
+                monarchist_chancellor,
+                president_chancellor,
+                ..
+            } => vec![Some(*monarchist), *monarchist_chancellor, *president_chancellor],
+            _ => vec![],
+        };
+        violations.extend(
+            government
+                .into_iter()
+                .flatten()
+                .filter(|player| !self.players[*player].alive)
+                .map(InvariantViolation::DeadInGovernment),
+        );
+
+        // No cards are created or destroyed: each is in the draw pile, the discard pile, a government's hand,
+        // being revealed or on the board
+        let in_hand = match &self.state {
+            GameState::LegislativeSession { turn, .. } => match turn {
+                LegislativeSessionTurn::President { cards } => cards.to_vec(),
+                LegislativeSessionTurn::Chancellor { cards, .. } | LegislativeSessionTurn::VetoRequested { cards } => {
+                    cards.to_vec()
+                }
+                LegislativeSessionTurn::VetoApproved => vec![],
+            },
+            // The card is only put on the board once both the board and the players are done revealing it
+            GameState::CardReveal { result, .. } => vec![*result],
+            _ => vec![],
+        };
+        for party in [Party::Liberal, Party::Fascist, Party::Communist] {
+            let on_board = match party {
+                Party::Liberal => self.board.liberal_cards,
+                Party::Fascist => self.board.fascist_cards,
+                Party::Communist => self.board.communist_cards,
+            };
+            let held = in_hand.iter().filter(|card| **card == party).count();
+            // Games saved before the discard pile was tracked can only be checked for extra cards until it is shuffled in
+            let discarded = self.deck.count_discarded(party);
+            let count = self.deck.count_party(party) + discarded.unwrap_or(0) + on_board + held;
+            if count > self.deck.total(party) {
+                violations.push(InvariantViolation::TooManyCards(party));
+            } else if discarded.is_some() && count < self.deck.total(party) && !self.game_over() {
+                // A government which is still holding its policies when Hitler is elected chancellor never plays them
+                violations.push(InvariantViolation::TooFewCards(party));
+            }
+        }
+
+        if self.num_players_alive() == 0 {
+            violations.push(InvariantViolation::NoPlayersAlive);
+        }
+        violations
+    }
+
+    /// Checks the invariants after an action, if they are being checked, logging the first which is broken.
+    pub(crate) fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        if !CHECK_INVARIANTS {
+            return Ok(());
+        }
+        match self.invariant_violations().first() {
+            Some(violation) => {
+                tracing::error!(%violation, "Game is in an inconsistent state");
+                Err(*violation)
+            }
+            None => Ok(()),
+        }
+    }
+}
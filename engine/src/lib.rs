@@ -0,0 +1,1306 @@
+//! The rules of Secret Hitler XL, as a game engine with no dependence on the server,
+//! so that it can also be embedded in other projects.
+
+pub use self::action::Action;
+use self::board::Board;
+pub use self::chat::ChatScope;
+pub use self::confirmations::Progress;
+use self::deck::Deck;
+pub use self::deck::DeckOdds;
+use self::eligible::EligiblePlayers;
+use self::executive_power::ExecutiveAction;
+pub use self::invariants::InvariantViolation;
+pub use self::locale::{Language, Message};
+pub use self::options::{
+    AsyncOptions, ChaosRule, GameOptions, HouseRule, HouseRules, InvestigationReveals, OptionsProblem, PresetInfo,
+    RolePreset,
+};
+pub use self::party::Party;
+use self::player::{assign_roles, Knowledge, Player};
+pub use self::player::{InvestigationResult, KnowledgeSource, PlayerDistribution, PolicyPeakRecord, Role};
+pub use self::replay::{LogEntry, LoggedAction, Substitution};
+pub use self::scenario::{Scenario, ScenarioInfo};
+pub use self::timers::TimedPhase;
+use self::transitions::transition;
+pub use self::transitions::Phase;
+use self::undo::UndoSnapshot;
+pub use self::update::*;
+use self::votes::{CandidateVotes, MonarchistVotes, Votes};
+use self::{confirmations::Confirmations, government::Government};
+use crate::adjacent::players_are_adjacent;
+use crate::error::GameError;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+mod action;
+mod adjacent;
+mod board;
+mod chat;
+mod confirmations;
+mod deck;
+mod eligible;
+pub mod error;
+mod executive_power;
+mod fuzz;
+mod government;
+mod hash;
+mod invariants;
+mod locale;
+mod options;
+mod party;
+mod player;
+mod replay;
+mod scenario;
+mod test;
+mod timers;
+mod transitions;
+mod undo;
+mod update;
+mod votes;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+pub const MAX_PLAYERS: usize = 16;
+
+/// A game of Secret Hitler.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Game {
+    opts: GameOptions,
+    players: Vec<Player>,
+    board: Board,
+    deck: Deck,
+    state: GameState,
+    presidential_turn: usize,
+    next_president: Option<NextPresident>,
+    election_tracker: usize,
+    last_government: Option<Government>,
+    radicalised: bool,
+    assassination: AssassinationState,
+    rng: rand_chacha::ChaCha8Rng,
+    #[serde(default)]
+    undo: Option<UndoSnapshot>,
+    #[serde(default)]
+    round: usize,
+    #[serde(default)]
+    bribed: bool,
+    /// The number of policies enacted because the election tracker ran out.
+    #[serde(default)]
+    chaos_policies: usize,
+    /// Any win conditions which were met at the same time as the outcome of the game, but took lower priority.
+    #[serde(default)]
+    secondary_conditions: Vec<WinCondition>,
+    #[serde(default)]
+    seed: u64,
+    /// Every action performed on the game, and every substitution made, in order.
+    #[serde(default)]
+    log: Vec<LogEntry>,
+    /// The seats whose view of the game has changed since they were last taken, or `None` if every seat's may have.
+    #[serde(skip)]
+    changed_seats: Option<[bool; MAX_PLAYERS]>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+enum NextPresident {
+    Normal {
+        player: usize,
+        /// Why this player is president instead of the one who was chosen, if that player died in the meantime.
+        #[serde(default)]
+        fallback: Option<PresidencyFallback>,
+    },
+    Monarchist {
+        monarchist: usize,
+        last_president: usize,
+    },
+}
+
+/// Represents the current phase in the game loop.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+enum GameState {
+    Night {
+        confirmations: Confirmations,
+        /// Which players have acknowledged looking at their role card.
+        #[serde(default)]
+        roles_viewed: [bool; MAX_PLAYERS],
+    },
+    Election {
+        president: usize,
+        chancellor: Option<usize>,
+        eligible_chancellors: EligiblePlayers,
+        votes: Votes,
+        #[serde(default)]
+        fallback: Option<PresidencyFallback>,
+    },
+    MonarchistElection {
+        /// The player who is the monarchist, and therefore the next president
+        monarchist: usize,
+        /// The last president who unlocked the special election power
+        last_president: usize,
+        /// The monarchist's choice for chancellor
+        monarchist_chancellor: Option<usize>,
+        /// The last president's choice for chancellor
+        president_chancellor: Option<usize>,
+        /// Players eligible to be selected as chancellor
+        eligible_chancellors: EligiblePlayers,
+        /// Players' votes for the chancellor; the monarchist breaks ties
+        votes: MonarchistVotes,
+    },
+    LegislativeSession {
+        president: usize,
+        chancellor: usize,
+        turn: LegislativeSessionTurn,
+    },
+    /// An election failed, advancing the election tracker; the board confirms before the next round begins.
+    ElectionFailed {
+        tracker: usize,
+    },
+    CardReveal {
+        result: Party,
+        chaos: bool,
+        confirmations: Confirmations,
+        board_ready: bool,
+    },
+    CommunistStart {
+        action: ExecutiveAction,
+    },
+    PromptMonarchist {
+        monarchist: usize,
+        last_president: usize,
+        hijacked: bool,
+    },
+    ChoosePlayer {
+        action: ExecutiveAction,
+        can_select: EligiblePlayers,
+        can_be_selected: EligiblePlayers,
+    },
+    Congress,
+    /// The communists are electing which of them will be told the party of the player they bugged.
+    BuggingVote {
+        chosen_player: usize,
+        /// The communists who may vote, and be voted for
+        communists: EligiblePlayers,
+        /// Each communist's choice of recipient; the communist who chose whom to bug breaks ties
+        votes: CandidateVotes,
+    },
+    CommunistEnd {
+        action: ExecutiveAction,
+        chosen_player: Option<usize>,
+    },
+    ActionReveal {
+        action: ExecutiveAction,
+        chosen_player: Option<usize>,
+        confirmations: Confirmations,
+    },
+    Assassination {
+        anarchist: usize,
+        chosen_player: Option<usize>,
+        /// Whether the anarchist was executed before they could carry out the assassination, so it will not happen.
+        #[serde(default)]
+        cancelled: bool,
+    },
+    /// The capitalist may bribe the president to cancel or redirect the power they have just used.
+    Bribe {
+        action: ExecutiveAction,
+        president: usize,
+        capitalist: usize,
+        chosen_player: usize,
+        /// Whether the capitalist has offered the bribe, such that the president must respond to it.
+        offered: bool,
+    },
+    GameOver(WinCondition),
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+enum LegislativeSessionTurn {
+    /// President must discard a card.
+    President { cards: [Party; 3] },
+    /// Chancellor must discard a card.
+    Chancellor { cards: [Party; 2], veto: VetoStatus },
+    /// Chancellor has called for a veto.
+    VetoRequested { cards: [Party; 2] },
+    /// President has approved the veto.
+    VetoApproved,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+enum VetoStatus {
+    CannotVeto,
+    CanVeto,
+    VetoDenied,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+enum AssassinationState {
+    Unused,
+    Activated { anarchist: usize },
+    Completed,
+}
+
+/// The order in which win conditions take precedence when several are met at once.
+/// An election or execution ends the game the moment it happens, so it takes precedence over the policy tracks,
+/// which in turn take precedence over the anarchist's chaos victory.
+const WIN_PRIORITY: [WinCondition; 7] = [
+    WinCondition::HitlerChancellor,
+    WinCondition::HitlerExecuted,
+    WinCondition::CapitalistExecuted,
+    WinCondition::LiberalPolicyTrack,
+    WinCondition::FascistPolicyTrack,
+    WinCondition::CommunistPolicyTrack,
+    WinCondition::AnarchistChaos,
+];
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub enum WinCondition {
+    /// The liberals completed their policy track.
+    LiberalPolicyTrack,
+    /// The fascists completed their policy track.
+    FascistPolicyTrack,
+    /// The communists completed their policy track.
+    CommunistPolicyTrack,
+    /// Hitler was elected chancellor
+    HitlerChancellor,
+    /// Hitler was executed
+    HitlerExecuted,
+    /// The Capitalist was executed
+    CapitalistExecuted,
+    /// Enough policies were enacted through chaos for the Anarchist to win alone
+    AnarchistChaos,
+}
+
+/// How the end of the game turned out for a particular player.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub enum WinStatus {
+    /// The player's side won the game.
+    Won,
+    /// The player's side lost the game.
+    Lost,
+    /// The player's party had no stake in how the game was decided, and they lived to see it end.
+    SurvivedNeutral,
+}
+
+impl std::fmt::Display for WinCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            WinCondition::LiberalPolicyTrack => "LiberalPolicyTrack",
+            WinCondition::FascistPolicyTrack => "FascistPolicyTrack",
+            WinCondition::CommunistPolicyTrack => "CommunistPolicyTrack",
+            WinCondition::HitlerChancellor => "HitlerChancellor",
+            WinCondition::HitlerExecuted => "HitlerExecuted",
+            WinCondition::CapitalistExecuted => "CapitalistExecuted",
+            WinCondition::AnarchistChaos => "AnarchistChaos",
+        })
+    }
+}
+
+impl Game {
+    /// Creates a new game of Secret Hitler.
+    pub fn new(opts: GameOptions, player_names: &[String], seed: u64) -> Result<Self, GameError> {
+        let opts = opts.resolve_preset();
+        let num_players = player_names.len();
+
+        // Generate the players and their roles
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
+        let roles = assign_roles(opts.player_distribution(num_players)?, &mut rng);
+        let mut players = player_names
+            .iter()
+            .zip(roles)
+            .map(|(name, role)| Player::new(name.into(), role))
+            .collect::<Vec<_>>();
+
+        // Reveal certain player roles/parties to other players
+        Self::reveal_roles(&mut players);
+
+        // Create the board; shuffle the deck
+        let board = Board::new(num_players);
+        let mut deck = Deck::new(opts.communists);
+        deck.shuffle(&board, &mut rng);
+
+        let mut game = Game {
+            opts,
+            players,
+            board,
+            deck,
+            state: GameState::Night {
+                roles_viewed: [false; MAX_PLAYERS],
+                confirmations: Confirmations::new(num_players),
+            },
+            presidential_turn: rng.gen_range(0..num_players),
+            next_president: None,
+            election_tracker: 0,
+            last_government: None,
+            radicalised: false,
+            assassination: AssassinationState::Unused,
+            rng,
+            undo: None,
+            round: 0,
+            bribed: false,
+            chaos_policies: 0,
+            secondary_conditions: vec![],
+            seed,
+            log: vec![],
+            changed_seats: None,
+        };
+
+        // Set the scene for a scenario
+        if let Some(scenario) = opts.scenario {
+            scenario.open(&mut game);
+        }
+        Ok(game)
+    }
+
+    /// Gets the game options.
+    pub fn options(&self) -> GameOptions {
+        self.opts
+    }
+
+    fn reveal_roles(players: &mut [Player]) {
+        use Role::*;
+        let fascists = players.iter().filter(|p| p.role == Fascist).count();
+
+        for i in 0..players.len() {
+            for j in 0..players.len() {
+                let (p1, p2) = (&players[i], &players[j]);
+                let adjacent = players_are_adjacent(i, j, players.len());
+                let result = match (p1.role, p2.role) {
+                    // Everyone knows their own role, but exclude it from the list
+                    _ if i == j => InvestigationResult::Unknown,
+                    // Ordinary fascists know all the fascists' identities
+                    (Fascist, Fascist | Hitler | Monarchist) => InvestigationResult::Role(p2.role),
+                    // In smaller games, Hitler knows who the other fascist is
+                    (Hitler, Fascist) if fascists < 2 => InvestigationResult::Role(p2.role),
+                    // Ordinary communists know all the communists' identities
+                    (Communist, Communist | Anarchist) => InvestigationResult::Role(p2.role),
+                    // The centrists know each other
+                    (Centrist, Centrist) => InvestigationResult::Role(p2.role),
+                    // The capitalist knows the party of the players either side of them
+                    (Capitalist, _) if adjacent => InvestigationResult::Party(p2.party()),
+                    _ => InvestigationResult::Unknown,
+                };
+                players[i].others[j] = result;
+                if result != InvestigationResult::Unknown {
+                    let source = KnowledgeSource::InitialReveal;
+                    players[i]
+                        .knowledge
+                        .push(Knowledge { target: j, result, source, round: 0 });
+                }
+            }
+        }
+    }
+
+    /// Gets the player names.
+    pub fn player_names(&self) -> impl Iterator<Item = &'_ str> {
+        self.players.iter().map(|p| &p.name[..])
+    }
+
+    /// Finds a player with the given name.
+    pub fn find_player(&self, name: &str) -> Result<usize, GameError> {
+        self.players
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or(GameError::PlayerNotFound)
+    }
+
+    /// Called when a player is ready to end the night round.
+    pub fn end_night_round(&mut self, player: usize) -> Result<(), GameError> {
+        self.check_player_index(player)?;
+        let GameState::Night { confirmations, .. } = &mut self.state else {
+            return Err(self.wrong_phase(&[Phase::Night]));
+        };
+        let can_proceed = confirmations.confirm(player);
+        if can_proceed {
+            self.start_round();
+        }
+        Ok(())
+    }
+
+    /// Called when a player acknowledges that they have looked at their role card during the night round.
+    pub fn view_role(&mut self, player: usize) -> Result<(), GameError> {
+        self.check_player_index(player)?;
+        let GameState::Night { confirmations, roles_viewed } = &mut self.state else {
+            return Err(self.wrong_phase(&[Phase::Night]));
+        };
+        if roles_viewed[player] || confirmations.has_confirmed(player) {
+            return Err(GameError::AlreadyConfirmed);
+        }
+        roles_viewed[player] = true;
+        Ok(())
+    }
+
+    /// Called when a player is ready to end the card reveal.
+    pub fn end_card_reveal(&mut self, player: Option<usize>) -> Result<(), GameError> {
+        let GameState::CardReveal { result, chaos, confirmations, board_ready } = &mut self.state else {
+            return Err(self.wrong_phase(&[Phase::CardReveal]));
+        };
+
+        if let Some(player) = player {
+            confirmations.confirm(player);
+        } else {
+            *board_ready = true;
+        }
+
+        // Skip player confirmations if the game is over
+        let players_ready = confirmations.can_proceed() || self.board.is_winning_card(*result);
+        if !players_ready || !*board_ready {
+            return Ok(());
+        }
+
+        // Play the card
+        let (result, chaos) = (*result, *chaos);
+        self.board.play_card(result);
+        if self.check_game_over() {
+            return Ok(());
+        }
+        match (chaos, self.opts.chaos_rule) {
+            (true, ChaosRule::Reshuffle) => self.deck.shuffle(&self.board, &mut self.rng),
+            _ => self.check_deck(),
+        }
+        let empowered = !chaos || (self.opts.chaos_rule == ChaosRule::GrantsPower && self.last_government.is_some());
+        if let (true, Some(action)) = (empowered, self.board.get_executive_power(result)) {
+            self.start_executive_action(action);
+        } else {
+            self.start_round();
+        }
+        Ok(())
+    }
+
+    /// Ends the legislative session.
+    pub fn end_legislative_session(&mut self) -> Result<(), GameError> {
+        let GameState::LegislativeSession { turn, .. } = &mut self.state else {
+            return Err(self.wrong_phase(&[Phase::LegislativeSession]));
+        };
+        let LegislativeSessionTurn::VetoApproved = turn else {
+            return Err(GameError::InvalidAction);
+        };
+        self.check_deck();
+        self.fail_election();
+        Ok(())
+    }
+
+    /// Called when the board has finished showing the election tracker advance.
+    pub fn end_election_failed(&mut self) -> Result<(), GameError> {
+        let GameState::ElectionFailed { .. } = self.state else {
+            return Err(self.wrong_phase(&[Phase::ElectionFailed]));
+        };
+        self.start_round();
+        Ok(())
+    }
+
+    /// Called when a player casts their vote.
+    pub fn cast_vote(&mut self, player: usize, vote: bool) -> Result<(), GameError> {
+        self.record_ballot(player, Some(vote))
+    }
+
+    /// Called when a player abstains from voting.
+    pub fn abstain(&mut self, player: usize) -> Result<(), GameError> {
+        self.record_ballot(player, None)
+    }
+
+    /// Records a player's vote, or their abstention if `vote` is `None`.
+    fn record_ballot(&mut self, player: usize, vote: Option<bool>) -> Result<(), GameError> {
+        self.check_player_index(player)?;
+        let GameState::Election { president, chancellor, votes, .. } = &self.state else {
+            return Err(self.wrong_phase(&[Phase::Election]));
+        };
+        let Some(chancellor) = *chancellor else {
+            return Err(GameError::InvalidAction);
+        };
+        if votes.outcome().is_some() {
+            return Err(GameError::InvalidAction);
+        }
+        let in_government = player == *president || player == chancellor;
+        if vote.is_none() && (!self.opts.house_rules.abstentions || in_government) {
+            return Err(GameError::NotEligible);
+        }
+        let allow_change = self.opts.allow_vote_change;
+        if votes.has_cast(player) && !allow_change {
+            return Err(GameError::AlreadyVoted);
+        }
+        self.record_undo(player);
+        let GameState::Election { votes, .. } = &mut self.state else {
+            unreachable!()
+        };
+        match vote {
+            Some(vote) => votes.vote(player, vote, allow_change)?,
+            None => votes.abstain(player, allow_change)?,
+        }
+        if votes.outcome().is_some() {
+            self.clear_undo();
+        }
+        Ok(())
+    }
+
+    /// Called when a player chooses another player.
+    pub fn choose_player(&mut self, player: usize, other: usize) -> Result<(), GameError> {
+        self.check_player_index(player)?;
+        self.check_player_index(other)?;
+
+        match &mut self.state {
+            GameState::Election {
+                president, chancellor, eligible_chancellors, ..
+            } => {
+                if player != *president {
+                    return Err(GameError::NotYourTurn);
+                }
+                if chancellor.is_some() {
+                    return Err(GameError::AlreadyConfirmed);
+                }
+                if !eligible_chancellors.includes(other) {
+                    return Err(GameError::InvalidPlayerChoice);
+                }
+                self.record_undo(player);
+                let GameState::Election { chancellor, .. } = &mut self.state else {
+                    unreachable!()
+                };
+                *chancellor = Some(other);
+                Ok(())
+            }
+            GameState::ChoosePlayer { action, can_select, can_be_selected } => {
+                use ExecutiveAction::*;
+                if !can_select.includes(player) {
+                    return Err(GameError::NotYourTurn);
+                }
+                if !can_be_selected.includes(other) {
+                    return Err(GameError::InvalidPlayerChoice);
+                }
+                let action = *action;
+                match action {
+                    InvestigatePlayer => self.investigate(player, other),
+                    Confession => self.confess(other),
+                    Bugging if self.opts.house_rules.private_bugging => {
+                        transition!(self, &[Phase::ChoosePlayer] => BuggingVote {
+                            chosen_player: other,
+                            communists: self.eligible_players().ordinary_communist().make(),
+                            votes: CandidateVotes::new(self.num_communists_left(), player),
+                        });
+                        return Ok(());
+                    }
+                    Bugging => {
+                        for communist in 0..self.num_players() {
+                            if self.players[communist].alive && self.players[communist].role == Role::Communist {
+                                self.learn_party(communist, other, KnowledgeSource::Bugging);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                if let Some(capitalist) = self.can_bribe(action, player) {
+                    transition!(self, &[Phase::ChoosePlayer] => Bribe {
+                        action,
+                        president: player,
+                        capitalist,
+                        chosen_player: other,
+                        offered: false,
+                    });
+                    return Ok(());
+                }
+                match action {
+                    InvestigatePlayer | SpecialElection | Execution | Confession => {
+                        self.reveal_action(action, other);
+                    }
+                    Bugging => {
+                        transition!(self, &[Phase::ChoosePlayer] => ActionReveal {
+                            action,
+                            chosen_player: Some(other),
+                            confirmations: Confirmations::new(self.num_communists_left()),
+                        });
+                    }
+                    Radicalisation | Congress => {
+                        transition!(self, &[Phase::ChoosePlayer] => CommunistEnd { action, chosen_player: Some(other) });
+                    }
+                    _ => unreachable!(),
+                }
+                Ok(())
+            }
+            GameState::BuggingVote { chosen_player, communists, votes } => {
+                if !communists.includes(player) {
+                    return Err(GameError::NotEligible);
+                }
+                if votes.has_cast(player) {
+                    return Err(GameError::AlreadyVoted);
+                }
+                if !communists.includes(other) {
+                    return Err(GameError::InvalidPlayerChoice);
+                }
+                votes.vote(player, other);
+                let chosen_player = *chosen_player;
+                if let Some(recipient) = votes.outcome() {
+                    self.learn_party(recipient, chosen_player, KnowledgeSource::Bugging);
+                    transition!(self, &[Phase::BuggingVote] => ActionReveal {
+                        action: ExecutiveAction::Bugging,
+                        chosen_player: Some(chosen_player),
+                        confirmations: Confirmations::new(self.num_communists_left()),
+                    });
+                }
+                Ok(())
+            }
+            GameState::MonarchistElection {
+                monarchist,
+                last_president,
+                monarchist_chancellor,
+                president_chancellor,
+                eligible_chancellors,
+                votes,
+            } => {
+                let Some(mon_chan) = *monarchist_chancellor else {
+                    if player != *monarchist {
+                        return Err(GameError::NotYourTurn);
+                    }
+                    if !eligible_chancellors.includes(other) {
+                        return Err(GameError::InvalidPlayerChoice);
+                    }
+                    *monarchist_chancellor = Some(other);
+                    eligible_chancellors.exclude(other);
+                    return Ok(());
+                };
+
+                let Some(pres_chan) = *president_chancellor else {
+                    if player != *last_president {
+                        return Err(GameError::NotYourTurn);
+                    }
+                    if !eligible_chancellors.includes(other) {
+                        return Err(GameError::InvalidPlayerChoice);
+                    }
+                    *president_chancellor = Some(other);
+                    eligible_chancellors.exclude(other);
+                    return Ok(());
+                };
+
+                if votes.outcome().is_some() {
+                    return Err(GameError::InvalidAction);
+                }
+
+                votes.vote(
+                    player,
+                    if other == mon_chan {
+                        true
+                    } else if other == pres_chan {
+                        false
+                    } else {
+                        return Err(GameError::InvalidPlayerChoice);
+                    },
+                    self.opts.allow_vote_change,
+                )
+            }
+            GameState::Assassination { anarchist, chosen_player, cancelled } => {
+                if player != *anarchist {
+                    return Err(GameError::NotYourTurn);
+                }
+                if chosen_player.is_some() {
+                    return Err(GameError::AlreadyConfirmed);
+                }
+                if *cancelled {
+                    return Err(GameError::InvalidAction);
+                }
+                if !self.players[other].alive || player == other {
+                    return Err(GameError::InvalidPlayerChoice);
+                }
+                *chosen_player = Some(other);
+                Ok(())
+            }
+            GameState::Bribe {
+                action,
+                president,
+                chosen_player,
+                offered: true,
+                ..
+            } => {
+                if player != *president {
+                    return Err(GameError::NotYourTurn);
+                }
+                let (action, chosen_player) = (*action, *chosen_player);
+                if !self.action_targets(action).includes(other) || other == chosen_player {
+                    return Err(GameError::InvalidPlayerChoice);
+                }
+                self.reveal_action(action, other);
+                Ok(())
+            }
+            _ => Err(self.wrong_phase(&[
+                Phase::Election,
+                Phase::MonarchistElection,
+                Phase::ChoosePlayer,
+                Phase::BuggingVote,
+                Phase::Assassination,
+                Phase::Bribe,
+            ])),
+        }
+    }
+
+    /// Called when the board has finished revealing the election result.
+    pub fn end_voting(&mut self) -> Result<(), GameError> {
+        match &self.state {
+            GameState::Election { president, chancellor, votes, .. } => {
+                let Some(chancellor) = chancellor else {
+                    return Err(GameError::InvalidAction);
+                };
+                let Some(passed) = votes.outcome() else {
+                    return Err(GameError::InvalidAction);
+                };
+                let government = Government {
+                    president: *president,
+                    chancellor: *chancellor,
+                };
+                if passed {
+                    self.start_legislative_session(government);
+                    self.check_game_over();
+                } else {
+                    self.fail_election();
+                }
+                Ok(())
+            }
+            GameState::MonarchistElection {
+                monarchist,
+                monarchist_chancellor,
+                president_chancellor,
+                votes,
+                ..
+            } => {
+                let (Some(c1), Some(c2)) = (*monarchist_chancellor, *president_chancellor) else {
+                    return Err(GameError::InvalidAction);
+                };
+                let Some(outcome) = votes.outcome() else {
+                    return Err(GameError::InvalidAction);
+                };
+                self.start_legislative_session(Government {
+                    president: *monarchist,
+                    chancellor: if outcome { c1 } else { c2 },
+                });
+                self.check_game_over();
+                Ok(())
+            }
+            _ => Err(self.wrong_phase(&[Phase::Election, Phase::MonarchistElection])),
+        }
+    }
+
+    /// Called when a player discards a policy from their hand.
+    pub fn discard_policy(&mut self, player: usize, card_idx: usize) -> Result<(), GameError> {
+        use LegislativeSessionTurn::*;
+
+        self.check_player_index(player)?;
+
+        let GameState::LegislativeSession { president, chancellor, turn } = &mut self.state else {
+            return Err(self.wrong_phase(&[Phase::LegislativeSession]));
+        };
+
+        match turn {
+            President { cards } if player == *president => {
+                let (discarded, cards) = match card_idx {
+                    0 => (cards[0], [cards[1], cards[2]]),
+                    1 => (cards[1], [cards[0], cards[2]]),
+                    2 => (cards[2], [cards[0], cards[1]]),
+                    _ => return Err(GameError::InvalidCard),
+                };
+                let veto = if self.board.veto_unlocked() {
+                    VetoStatus::CanVeto
+                } else {
+                    VetoStatus::CannotVeto
+                };
+                let GameState::LegislativeSession { turn, .. } = &mut self.state else {
+                    unreachable!();
+                };
+                *turn = Chancellor { cards, veto };
+                self.deck.discard(discarded);
+            }
+            Chancellor { cards, .. } if player == *chancellor => {
+                let (discarded, card) = match card_idx {
+                    0 => (cards[0], cards[1]),
+                    1 => (cards[1], cards[0]),
+                    _ => return Err(GameError::InvalidCard),
+                };
+                self.deck.discard(discarded);
+                self.play_card(card, false);
+            }
+            _ => return Err(GameError::NotYourTurn),
+        }
+
+        Ok(())
+    }
+
+    /// Called when the chancellor proposes a veto, or the president consents to a proposed veto.
+    pub fn veto_agenda(&mut self, player: usize) -> Result<(), GameError> {
+        use LegislativeSessionTurn::*;
+
+        self.check_player_index(player)?;
+
+        let GameState::LegislativeSession { president, chancellor, turn } = &mut self.state else {
+            return Err(self.wrong_phase(&[Phase::LegislativeSession]));
+        };
+
+        match turn {
+            Chancellor { cards, veto } => {
+                if player != *chancellor {
+                    return Err(GameError::NotYourTurn);
+                }
+                if *veto != VetoStatus::CanVeto {
+                    return Err(GameError::VetoLocked);
+                }
+                *turn = VetoRequested { cards: *cards };
+                Ok(())
+            }
+            VetoRequested { cards } => {
+                if player == *president {
+                    let cards = *cards;
+                    *turn = VetoApproved;
+                    for card in cards {
+                        self.deck.discard(card);
+                    }
+                    Ok(())
+                } else {
+                    Err(GameError::NotYourTurn)
+                }
+            }
+            _ => Err(GameError::InvalidAction),
+        }
+    }
+
+    /// Called when the president rejects a proposed veto.
+    pub fn reject_veto(&mut self, player: usize) -> Result<(), GameError> {
+        self.check_player_index(player)?;
+
+        let GameState::LegislativeSession { president, turn, .. } = &mut self.state else {
+            return Err(self.wrong_phase(&[Phase::LegislativeSession]));
+        };
+
+        let LegislativeSessionTurn::VetoRequested { cards } = turn else {
+            return Err(GameError::InvalidAction);
+        };
+
+        if player != *president {
+            return Err(GameError::NotYourTurn);
+        }
+
+        *turn = LegislativeSessionTurn::Chancellor { cards: *cards, veto: VetoStatus::VetoDenied };
+
+        Ok(())
+    }
+
+    /// Called when the anarchist wishes to execute a player.
+    pub fn start_assassination(&mut self, player_idx: usize) -> Result<(), GameError> {
+        let GameState::CardReveal { .. } = &self.state else {
+            return Err(self.wrong_phase(&[Phase::CardReveal]));
+        };
+        let Some(player) = self.players.get(player_idx) else {
+            return Err(GameError::InvalidPlayerIndex);
+        };
+        if !player.alive {
+            return Err(GameError::DeadPlayer);
+        }
+        if player.role != Role::Anarchist {
+            return Err(GameError::NotEligible);
+        }
+        if !matches!(self.assassination, AssassinationState::Unused) {
+            return Err(GameError::AlreadyConfirmed);
+        }
+
+        self.assassination = AssassinationState::Activated { anarchist: player_idx };
+
+        self.end_card_reveal(Some(player_idx))
+    }
+
+    /// Called when the board has finished revealing the assassination.
+    pub fn end_assassination(&mut self) -> Result<(), GameError> {
+        let GameState::Assassination { chosen_player, cancelled, .. } = &self.state else {
+            return Err(self.wrong_phase(&[Phase::Assassination]));
+        };
+        if *cancelled {
+            self.assassination = AssassinationState::Completed;
+            self.start_round();
+            return Ok(());
+        }
+        if chosen_player.is_none() {
+            return Err(GameError::InvalidAction);
+        }
+
+        let player = &mut self.players[chosen_player.unwrap()];
+        player.alive = false;
+        player.not_hitler = player.role != Role::Hitler;
+
+        self.assassination = AssassinationState::Completed;
+
+        if self.check_game_over() {
+            return Ok(());
+        }
+
+        self.start_round();
+        Ok(())
+    }
+
+    /// Returns true if the game is over.
+    pub fn game_over(&self) -> bool {
+        matches!(self.state, GameState::GameOver(_))
+    }
+
+    /// Ends the game with the given outcome, for tests which need a finished game.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn force_outcome(&mut self, outcome: WinCondition) {
+        self.state = GameState::GameOver(outcome);
+        self.mark_all_seats_changed();
+    }
+
+    /// Returns the game outcome.
+    pub fn outcome(&self) -> Option<WinCondition> {
+        if let GameState::GameOver(outcome) = &self.state {
+            Some(*outcome)
+        } else {
+            None
+        }
+    }
+
+    /// Gets the win conditions which were met alongside the outcome of the game, but took lower priority.
+    pub fn secondary_conditions(&self) -> &[WinCondition] {
+        &self.secondary_conditions
+    }
+
+    /// Returns whether a particular player has won.
+    pub fn player_has_won(&self, player: usize) -> bool {
+        let GameState::GameOver(outcome) = self.state else {
+            return false;
+        };
+        let player = &self.players[player];
+        match outcome {
+            WinCondition::LiberalPolicyTrack => player.party() == Party::Liberal,
+            WinCondition::FascistPolicyTrack => player.party() == Party::Fascist,
+            WinCondition::CommunistPolicyTrack => player.party() == Party::Communist,
+            WinCondition::HitlerExecuted => player.party() != Party::Fascist,
+            WinCondition::HitlerChancellor => player.party() == Party::Fascist,
+            WinCondition::CapitalistExecuted => player.party() == Party::Communist,
+            WinCondition::AnarchistChaos => player.role == Role::Anarchist,
+        }
+    }
+
+    /// Gets how the game turned out for a particular player, or `None` if the game is not over.
+    /// The communists have no stake in a contest between the liberals and fascists,
+    /// so those who survive to see one decided are neither winners nor losers.
+    pub fn outcome_for_player(&self, player: usize) -> Option<WinStatus> {
+        let outcome = self.outcome()?;
+        if self.player_has_won(player) {
+            return Some(WinStatus::Won);
+        }
+        let player = &self.players[player];
+        let contest = matches!(
+            outcome,
+            WinCondition::LiberalPolicyTrack | WinCondition::FascistPolicyTrack | WinCondition::HitlerChancellor
+        );
+        if contest && player.alive && player.party() == Party::Communist {
+            Some(WinStatus::SurvivedNeutral)
+        } else {
+            Some(WinStatus::Lost)
+        }
+    }
+
+    /// Advances the election tracker, pausing so the board can show it before the next round,
+    /// which is decided by a chaos policy if the tracker has reached three.
+    fn fail_election(&mut self) {
+        self.clear_undo();
+        self.election_tracker += 1;
+        transition!(self, &[Phase::Election, Phase::MonarchistElection, Phase::LegislativeSession] => ElectionFailed { tracker: self.election_tracker });
+    }
+
+    fn start_round(&mut self) {
+        self.clear_undo();
+
+        if self.election_tracker == 3 {
+            let card = self.deck.draw_one();
+            // The last government keeps its term limits if it is to wield the power of the chaos policy
+            if self.opts.chaos_rule != ChaosRule::GrantsPower {
+                self.last_government = None;
+            }
+            self.play_card(card, true);
+            return;
+        }
+
+        if let AssassinationState::Activated { anarchist } = self.assassination {
+            // An anarchist executed since activating the assassination only carries it out under the house rule
+            let cancelled = !self.players[anarchist].alive && !self.opts.house_rules.posthumous_assassination;
+            transition!(self, Phase::ROUND_ENDS => Assassination { anarchist, chosen_player: None, cancelled });
+            return;
+        }
+
+        let next_president = self.take_next_president();
+        self.round += 1;
+        match next_president {
+            NextPresident::Normal { player, fallback } => transition!(self, Phase::ROUND_ENDS => Election {
+                president: player,
+                chancellor: None,
+                eligible_chancellors: self.eligble_chancellors(player),
+                votes: Votes::new(self.num_players_alive()),
+                fallback,
+            }),
+            NextPresident::Monarchist { monarchist, last_president } => {
+                transition!(self, Phase::ROUND_ENDS => MonarchistElection {
+                    monarchist,
+                    last_president,
+                    monarchist_chancellor: None,
+                    president_chancellor: None,
+                    eligible_chancellors: self.eligble_chancellors(monarchist),
+                    votes: MonarchistVotes::new(self.num_players_alive(), monarchist),
+                })
+            }
+        }
+    }
+
+    /// Decides who is president of the next round, checking the players chosen by a special election are still alive.
+    /// If the chosen president or the monarchist who hijacked the election has died, the presidency passes in the usual
+    /// rotation; if only the president whose election was hijacked has died, the monarchist is president unopposed.
+    fn take_next_president(&mut self) -> NextPresident {
+        let alive = |player: usize| self.players[player].alive;
+        let fallback = match self.next_president.take() {
+            None => None,
+            Some(NextPresident::Normal { player, .. }) if !alive(player) => {
+                Some(PresidencyFallback::NomineeDied { nominee: player })
+            }
+            Some(NextPresident::Monarchist { monarchist, .. }) if !alive(monarchist) => {
+                Some(PresidencyFallback::MonarchistDied { monarchist })
+            }
+            Some(NextPresident::Monarchist { monarchist, last_president }) if !alive(last_president) => {
+                return NextPresident::Normal {
+                    player: monarchist,
+                    fallback: Some(PresidencyFallback::PresidentDied { president: last_president }),
+                };
+            }
+            Some(next) => return next,
+        };
+        self.presidential_turn = self.next_player(self.presidential_turn);
+        NextPresident::Normal { player: self.presidential_turn, fallback }
+    }
+
+    fn start_legislative_session(&mut self, government: Government) {
+        let cards = self.deck.draw_three();
+        transition!(self, &[Phase::Election, Phase::MonarchistElection] => LegislativeSession {
+            president: government.president,
+            chancellor: government.chancellor,
+            turn: LegislativeSessionTurn::President { cards },
+        });
+        self.last_government = Some(government);
+    }
+
+    fn play_card(&mut self, card: Party, chaos: bool) {
+        transition!(self, Phase::POLICY_PLAYS => CardReveal {
+            result: card,
+            chaos,
+            confirmations: Confirmations::new(self.num_players_alive()),
+            board_ready: false,
+        });
+        self.election_tracker = 0;
+        if chaos {
+            self.chaos_policies += 1;
+        }
+    }
+
+    fn check_deck(&mut self) {
+        self.deck.check_shuffle(&self.board, &mut self.rng);
+    }
+
+    fn check_game_over(&mut self) -> bool {
+        let mut conditions = vec![];
+
+        // Check for legislative victory
+        if let Some(party) = self.board.check_tracks() {
+            conditions.push(match party {
+                Party::Liberal => WinCondition::LiberalPolicyTrack,
+                Party::Fascist => WinCondition::FascistPolicyTrack,
+                Party::Communist => WinCondition::CommunistPolicyTrack,
+            });
+        }
+
+        // Check whether Hitler was elected chancellor
+        if self.board.fascist_cards >= 3 {
+            if let GameState::LegislativeSession { chancellor, .. } = &self.state {
+                let player = &mut self.players[*chancellor];
+                if player.role == Role::Hitler {
+                    conditions.push(WinCondition::HitlerChancellor);
+                } else {
+                    player.not_hitler = true;
+                }
+            }
+        }
+
+        // Check whether Hitler has been executed
+        if self.players.iter().any(|p| p.role == Role::Hitler && !p.alive) {
+            conditions.push(WinCondition::HitlerExecuted);
+        }
+
+        // Check whether the Capitalist has been executed
+        if self.capitalist().map(|p| p.alive) == Some(false) {
+            conditions.push(WinCondition::CapitalistExecuted);
+        }
+
+        // Check whether the Anarchist has sown enough chaos
+        let anarchist_alive = self.players.iter().any(|p| p.alive && p.role == Role::Anarchist);
+        let chaos_target = self.opts.house_rules.anarchist_chaos_win.map(|n| n as usize);
+        if anarchist_alive && chaos_target.is_some_and(|n| self.chaos_policies >= n) {
+            conditions.push(WinCondition::AnarchistChaos);
+        }
+
+        // If several conditions were met at once, the one with the highest priority decides the game
+        conditions.sort_by_key(|condition| WIN_PRIORITY.iter().position(|c| c == condition));
+        let Some((outcome, secondary)) = conditions.split_first() else {
+            return false;
+        };
+        transition!(self, Phase::GAME_ENDS => GameOver(*outcome));
+        self.secondary_conditions = secondary.to_vec();
+        true
+    }
+
+    /// Gets the number of players in the game.
+    pub fn num_players(&self) -> usize {
+        self.players.len()
+    }
+
+    /// Gets the role of each player, in seating order.
+    pub fn roles(&self) -> impl Iterator<Item = Role> + '_ {
+        self.players.iter().map(|p| p.role)
+    }
+
+    /// Gets the names of the players who have been executed.
+    pub fn executed_players(&self) -> impl Iterator<Item = &'_ str> {
+        self.players.iter().filter(|p| !p.alive).map(|p| p.name.as_str())
+    }
+
+    /// Gets the number of policies of the given party which have been enacted.
+    pub fn policies_enacted(&self, party: Party) -> usize {
+        match party {
+            Party::Liberal => self.board.liberal_cards,
+            Party::Fascist => self.board.fascist_cards,
+            Party::Communist => self.board.communist_cards,
+        }
+    }
+
+    /// Gets the number of players in the game that are alive.
+    pub fn num_players_alive(&self) -> usize {
+        self.players.iter().filter(|p| p.alive).count()
+    }
+
+    /// Gets the number of ordinary communists that are alive.
+    pub fn num_communists_left(&self) -> usize {
+        self.players
+            .iter()
+            .filter(|p| p.alive && p.role == Role::Communist)
+            .count()
+    }
+
+    /// Records that a player has learned the party of another player.
+    fn learn_party(&mut self, player: usize, target: usize, source: KnowledgeSource) {
+        let result = InvestigationResult::Party(self.players[target].party());
+        let round = self.round;
+        self.players[player]
+            .knowledge
+            .push(Knowledge { target, result, source, round });
+    }
+
+    /// Records what the president learned by investigating a player, which is either their party or their role
+    /// depending on the game's options. Something already known about the player is never made less specific.
+    fn investigate(&mut self, player: usize, target: usize) {
+        let investigated = &self.players[target];
+        let result = match self.opts.investigation_reveals {
+            InvestigationReveals::Party => InvestigationResult::Party(investigated.party()),
+            InvestigationReveals::Role => InvestigationResult::Role(investigated.role),
+        };
+        self.learn(player, target, result, KnowledgeSource::Investigation);
+        self.players[target].investigated = true;
+    }
+
+    /// Records that every other player has seen the party of the player chosen to confess.
+    fn confess(&mut self, target: usize) {
+        let result = InvestigationResult::Party(self.players[target].party());
+        for player in (0..self.num_players()).filter(|player| *player != target) {
+            self.learn(player, target, result, KnowledgeSource::Confession);
+        }
+    }
+
+    /// Records something a player has learned about another player in their knowledge log, and remembers it as what
+    /// they know of that player unless they already knew something more specific.
+    fn learn(&mut self, player: usize, target: usize, result: InvestigationResult, source: KnowledgeSource) {
+        let known = &mut self.players[player].others[target];
+        if !matches!(
+            (*known, result),
+            (InvestigationResult::Role(_), InvestigationResult::Party(_))
+        ) {
+            *known = result;
+        }
+        let round = self.round;
+        self.players[player]
+            .knowledge
+            .push(Knowledge { target, result, source, round });
+    }
+
+    /// Returns `Ok` if the given player index is valid, and an `Err` otherwise.
+    fn check_player_index(&self, player: usize) -> Result<(), GameError> {
+        if player < self.num_players() {
+            Ok(())
+        } else {
+            Err(GameError::InvalidPlayerIndex)
+        }
+    }
+
+    /// Gets the order in which alive players will hold the presidency, starting with the president of the current round,
+    /// if one is underway, followed by any president chosen by a special election, then the usual rotation.
+    /// A player who was specially elected may appear twice, as they keep their usual turn.
+    fn rotation(&self) -> Vec<usize> {
+        let current = match self.state {
+            GameState::Election { president, .. } | GameState::LegislativeSession { president, .. } => Some(president),
+            GameState::MonarchistElection { monarchist, .. } => Some(monarchist),
+            _ => None,
+        };
+        let queued = self.next_president.as_ref().map(|next| match *next {
+            NextPresident::Normal { player, .. } => player,
+            NextPresident::Monarchist { monarchist, .. } => monarchist,
+        });
+        let usual =
+            std::iter::successors(Some(self.presidential_turn), |&player| Some(self.next_player(player))).skip(1);
+        current
+            .into_iter()
+            .chain(queued.filter(|&player| self.players[player].alive))
+            .chain(usual)
+            .filter(|&player| self.players[player].alive)
+            .take(self.num_players_alive())
+            .collect()
+    }
+
+    /// Finds the next alive player.
+    fn next_player(&self, player: usize) -> usize {
+        (player + 1..self.num_players())
+            .chain(0..player)
+            .find(|idx| self.players[*idx].alive)
+            .unwrap()
+    }
+
+    /// Gets the player who is the Capitalist.
+    fn capitalist(&self) -> Option<&Player> {
+        self.players.iter().find(|player| player.role == Role::Capitalist)
+    }
+
+    /// Determines which players are eligble to be chancellor.
+    fn eligble_chancellors(&self, president: usize) -> EligiblePlayers {
+        let mut result = self.eligible_players().exclude(president);
+
+        if let Some(government) = self.last_government {
+            result = result.exclude(government.chancellor);
+            if self.num_players_alive() > 5 {
+                result = result.exclude(government.president);
+            }
+        }
+
+        result.make()
+    }
+
+    /// Explains why each player who is not among the eligible chancellors cannot be nominated by the given president.
+    fn ineligibility_reasons(&self, president: usize, eligible: &EligiblePlayers) -> Vec<(usize, IneligibilityReason)> {
+        let last_government = self.last_government;
+        (0..self.num_players())
+            .filter(|idx| !eligible.includes(*idx))
+            .map(|idx| {
+                let reason = if !self.players[idx].alive {
+                    IneligibilityReason::Dead
+                } else if idx == president {
+                    IneligibilityReason::President
+                } else if last_government.is_some_and(|g| g.chancellor == idx) {
+                    IneligibilityReason::LastChancellor
+                } else if last_government.is_some_and(|g| g.president == idx) {
+                    IneligibilityReason::LastPresident
+                } else {
+                    IneligibilityReason::AlreadyNominated
+                };
+                (idx, reason)
+            })
+            .collect()
+    }
+}
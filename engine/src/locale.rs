@@ -0,0 +1,241 @@
+use super::update::{
+    BoardPrompt, ChoosePlayerKind, CommunistSessionPhase, LegislativePhase, PlayerPrompt, RadicalisationResult,
+};
+use super::{Game, WinCondition};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// A language which the server can produce pre-translated text in.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug, Default)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+    Spanish,
+}
+
+/// A localizable message, identified by a key which the frontend looks up in its string catalogue,
+/// along with the values to substitute into the translated string.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct Message {
+    pub key: String,
+    pub params: Map<String, Value>,
+}
+
+impl Message {
+    fn new(key: &str) -> Self {
+        Self { key: key.to_string(), params: Map::new() }
+    }
+
+    /// Adds a parameter to the message.
+    fn with(mut self, name: &str, value: impl Serialize) -> Self {
+        let value = serde_json::to_value(value).unwrap_or(Value::Null);
+        self.params.insert(name.to_string(), value);
+        self
+    }
+}
+
+impl WinCondition {
+    /// Describes how the game was won, in the given language.
+    pub fn describe(&self, language: Language) -> &'static str {
+        use Language::*;
+        use WinCondition::*;
+
+        match (self, language) {
+            (LiberalPolicyTrack, English) => "The liberals completed their policy track",
+            (LiberalPolicyTrack, German) => "Die Liberalen haben ihre Gesetzesleiste vervollständigt",
+            (LiberalPolicyTrack, Spanish) => "Los liberales completaron su tablero de políticas",
+            (FascistPolicyTrack, English) => "The fascists completed their policy track",
+            (FascistPolicyTrack, German) => "Die Faschisten haben ihre Gesetzesleiste vervollständigt",
+            (FascistPolicyTrack, Spanish) => "Los fascistas completaron su tablero de políticas",
+            (CommunistPolicyTrack, English) => "The communists completed their policy track",
+            (CommunistPolicyTrack, German) => "Die Kommunisten haben ihre Gesetzesleiste vervollständigt",
+            (CommunistPolicyTrack, Spanish) => "Los comunistas completaron su tablero de políticas",
+            (HitlerChancellor, English) => "Hitler was elected chancellor",
+            (HitlerChancellor, German) => "Hitler wurde zum Kanzler gewählt",
+            (HitlerChancellor, Spanish) => "Hitler fue elegido canciller",
+            (HitlerExecuted, English) => "Hitler was executed",
+            (HitlerExecuted, German) => "Hitler wurde hingerichtet",
+            (HitlerExecuted, Spanish) => "Hitler fue ejecutado",
+            (CapitalistExecuted, English) => "The capitalist was executed",
+            (CapitalistExecuted, German) => "Der Kapitalist wurde hingerichtet",
+            (CapitalistExecuted, Spanish) => "El capitalista fue ejecutado",
+            (AnarchistChaos, English) => "The anarchist sowed enough chaos to win alone",
+            (AnarchistChaos, German) => "Der Anarchist hat genug Chaos gestiftet, um allein zu gewinnen",
+            (AnarchistChaos, Spanish) => "El anarquista sembró suficiente caos para ganar solo",
+        }
+    }
+}
+
+impl Game {
+    /// Gets the localizable message describing a board prompt.
+    /// Player indices are resolved to names, so the frontend needn't do so.
+    pub fn board_message(&self, prompt: &BoardPrompt) -> Message {
+        let name = |idx: &usize| self.players.get(*idx).map(|p| p.name.as_str());
+        let language = self.opts.language;
+
+        match prompt {
+            BoardPrompt::Night { progress, roles_viewed } => Message::new("board.night")
+                .with("confirmed", progress.confirmed)
+                .with("required", progress.required)
+                .with("roles_viewed", roles_viewed.confirmed),
+            BoardPrompt::Election { president, chancellor, outcome, .. } => {
+                let key = match (chancellor, outcome) {
+                    (None, _) => "board.election.nominating",
+                    (Some(_), None) => "board.election.voting",
+                    (Some(_), Some(true)) => "board.election.passed",
+                    (Some(_), Some(false)) => "board.election.failed",
+                };
+                Message::new(key)
+                    .with("president", name(president))
+                    .with("chancellor", chancellor.as_ref().and_then(name))
+            }
+            BoardPrompt::SpecialElection { hijacked_by, chosen_player, .. } => {
+                let key = match hijacked_by {
+                    Some(_) => "board.special_election.hijacked",
+                    None => "board.special_election",
+                };
+                Message::new(key)
+                    .with("hijacked_by", hijacked_by.as_ref().and_then(name))
+                    .with("player", chosen_player.as_ref().and_then(name))
+            }
+            BoardPrompt::MonarchistElection { monarchist, president, outcome, .. } => {
+                let key = match outcome {
+                    None => "board.monarchist_election",
+                    Some(true) => "board.monarchist_election.passed",
+                    Some(false) => "board.monarchist_election.failed",
+                };
+                Message::new(key)
+                    .with("monarchist", name(monarchist))
+                    .with("president", name(president))
+            }
+            BoardPrompt::LegislativeSession {
+                president, chancellor, phase, veto_available, ..
+            } => {
+                let key = match phase {
+                    LegislativePhase::President => "board.legislative_session.president",
+                    LegislativePhase::Chancellor => "board.legislative_session.chancellor",
+                    LegislativePhase::VetoRequested => "board.legislative_session.veto_requested",
+                    LegislativePhase::VetoApproved => "board.legislative_session.veto_approved",
+                    LegislativePhase::VetoRejected => "board.legislative_session.veto_rejected",
+                };
+                Message::new(key)
+                    .with("president", name(president))
+                    .with("chancellor", name(chancellor))
+                    .with("veto_available", veto_available)
+            }
+            BoardPrompt::CardReveal { result, chaos, .. } => {
+                let key = match chaos {
+                    true => "board.card_reveal.chaos",
+                    false => "board.card_reveal",
+                };
+                Message::new(key).with("party", result)
+            }
+            BoardPrompt::InvestigatePlayer { chosen_player } => {
+                Message::new("board.investigate").with("player", chosen_player.as_ref().and_then(name))
+            }
+            BoardPrompt::ElectionFailed { tracker, chaos } => Message::new("board.election_failed")
+                .with("tracker", tracker)
+                .with("chaos", chaos),
+            BoardPrompt::PolicyPeak => Message::new("board.policy_peak"),
+            BoardPrompt::Execution { chosen_player } => {
+                Message::new("board.execution").with("player", chosen_player.as_ref().and_then(name))
+            }
+            BoardPrompt::CommunistSession { action, phase, .. } => {
+                let key = match phase {
+                    CommunistSessionPhase::Entering => "board.communist_session.entering",
+                    CommunistSessionPhase::InProgress => "board.communist_session.in_progress",
+                    CommunistSessionPhase::Leaving => "board.communist_session.leaving",
+                    CommunistSessionPhase::Reveal => "board.communist_session.reveal",
+                };
+                Message::new(key).with("action", action.to_string())
+            }
+            BoardPrompt::FiveYearPlan { cards_added } => {
+                Message::new("board.five_year_plan").with("cards_added", cards_added)
+            }
+            BoardPrompt::Confession { chosen_player, party, .. } => Message::new("board.confession")
+                .with("player", chosen_player.as_ref().and_then(name))
+                .with("party", party),
+            BoardPrompt::Assassination { anarchist, chosen_player } => Message::new("board.assassination")
+                .with("anarchist", name(anarchist))
+                .with("player", chosen_player.as_ref().and_then(name)),
+            BoardPrompt::AssassinationCancelled { anarchist } => {
+                Message::new("board.assassination.cancelled").with("anarchist", name(anarchist))
+            }
+            BoardPrompt::Bribe { capitalist, chosen_player, offered } => Message::new(match offered {
+                true => "board.bribe.offered",
+                false => "board.bribe",
+            })
+            .with("capitalist", name(capitalist))
+            .with("player", name(chosen_player)),
+            BoardPrompt::GameOver { outcome } => Message::new("board.game_over")
+                .with("outcome", outcome)
+                .with("text", outcome.describe(language)),
+        }
+    }
+
+    /// Gets the localizable message describing a player prompt.
+    pub fn player_message(&self, prompt: &PlayerPrompt) -> Message {
+        let language = self.opts.language;
+
+        match prompt {
+            PlayerPrompt::Night { role_viewed } => Message::new("player.night").with("role_viewed", role_viewed),
+            PlayerPrompt::ChoosePlayer { kind, .. } => Message::new(match kind {
+                ChoosePlayerKind::NominateChancellor => "player.choose.nominate_chancellor",
+                ChoosePlayerKind::NominatePresident => "player.choose.nominate_president",
+                ChoosePlayerKind::MonarchistFirstChancellor => "player.choose.monarchist_first_chancellor",
+                ChoosePlayerKind::MonarchistSecondChancellor => "player.choose.monarchist_second_chancellor",
+                ChoosePlayerKind::VoteChancellor => "player.choose.vote_chancellor",
+                ChoosePlayerKind::Investigate => "player.choose.investigate",
+                ChoosePlayerKind::Execute => "player.choose.execute",
+                ChoosePlayerKind::Radicalise => "player.choose.radicalise",
+                ChoosePlayerKind::Confession => "player.choose.confession",
+                ChoosePlayerKind::BuggingRecipient => "player.choose.bugging_recipient",
+            }),
+            PlayerPrompt::Vote { can_abstain } => Message::new("player.vote").with("can_abstain", can_abstain),
+            PlayerPrompt::HijackElection => Message::new("player.hijack_election"),
+            PlayerPrompt::PresidentDiscard { .. } => Message::new("player.president_discard"),
+            PlayerPrompt::ChancellorDiscard { can_veto, .. } => {
+                Message::new("player.chancellor_discard").with("can_veto", can_veto)
+            }
+            PlayerPrompt::ApproveVeto => Message::new("player.approve_veto"),
+            PlayerPrompt::StartElection { can_assassinate } => {
+                Message::new("player.start_election").with("can_assassinate", can_assassinate)
+            }
+            PlayerPrompt::EndCongress { communists } => {
+                Message::new("player.end_congress").with("communists", communists)
+            }
+            PlayerPrompt::InvestigatePlayer { name, party, role, can_reveal } => Message::new("player.investigate")
+                .with("player", name)
+                .with("party", party)
+                .with("role", role)
+                .with("can_reveal", can_reveal),
+            PlayerPrompt::PolicyPeak { .. } => Message::new("player.policy_peak"),
+            PlayerPrompt::Radicalisation { result } => Message::new(match result {
+                RadicalisationResult::NoAttempt => "player.radicalisation.no_attempt",
+                RadicalisationResult::Fail => "player.radicalisation.fail",
+                RadicalisationResult::Success => "player.radicalisation.success",
+                RadicalisationResult::Unchanged => "player.radicalisation.unchanged",
+                RadicalisationResult::Radicalised => "player.radicalisation.radicalised",
+            }),
+            PlayerPrompt::OfferBribe { action, player } => Message::new("player.offer_bribe")
+                .with("action", action.to_string())
+                .with("player", player),
+            PlayerPrompt::ConsiderBribe { action, player, .. } => Message::new("player.consider_bribe")
+                .with("action", action.to_string())
+                .with("player", player),
+            PlayerPrompt::BuggingWithheld { name, recipient } => Message::new("player.bugging_withheld")
+                .with("player", name)
+                .with("recipient", recipient),
+            PlayerPrompt::Confession { name, party } => Message::new("player.confession")
+                .with("player", name)
+                .with("party", party),
+            PlayerPrompt::Dead => Message::new("player.dead"),
+            PlayerPrompt::GameOver { outcome, won, status } => Message::new("player.game_over")
+                .with("outcome", outcome)
+                .with("won", won)
+                .with("status", status)
+                .with("text", outcome.describe(language)),
+        }
+    }
+}
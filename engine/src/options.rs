@@ -0,0 +1,408 @@
+use super::{player::PlayerDistribution, Language, Role, Scenario, TimedPhase};
+use crate::error::GameError;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Options for customising the game of Secret Hitler or Secret Hitler XL.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug, Default)]
+pub struct GameOptions {
+    /// Whether to include the communists in the game.
+    pub communists: bool,
+    /// Whether to include the monarchist (fascist team).
+    pub monarchist: bool,
+    /// Whether to include the anarchist (communist team).
+    pub anarchist: bool,
+    /// Whether to include the capitalist (liberal team).
+    pub capitalist: bool,
+    /// Whether to include the centrists (liberal team).
+    pub centrists: bool,
+    /// Whether to keep the chat log in the archive once the game is over.
+    #[serde(default)]
+    pub archive_chat: bool,
+    /// Time limits for the timed phases of the game.
+    #[serde(default)]
+    pub timers: PhaseTimers,
+    /// Time, in seconds, during which a player may undo a nomination or vote, or `None` to disallow undoing.
+    #[serde(default)]
+    pub undo_window: Option<u32>,
+    /// Whether players may change their vote until the last vote is cast.
+    #[serde(default)]
+    pub allow_vote_change: bool,
+    /// The language of the pre-translated text sent to clients.
+    #[serde(default)]
+    pub language: Language,
+    /// Time, in seconds, the game may sit idle before it is purged, overriding the server's setting.
+    /// Useful for long-running asynchronous games.
+    #[serde(default)]
+    pub idle_timeout: Option<u32>,
+    /// Settings for playing the game asynchronously, over hours or days, or `None` for a game played in one sitting.
+    /// An asynchronous game is never purged for being idle, and its players are reminded when it is waiting on them.
+    #[serde(default)]
+    pub asynchronous: Option<AsyncOptions>,
+    /// Whether every player shares the board's device, taking turns to see their private prompts.
+    #[serde(default)]
+    pub hotseat: bool,
+    /// Whether the lobby is listed publicly, so that strangers can find and join the game.
+    #[serde(default)]
+    pub public: bool,
+    /// Whether the game is played without a board client, such that the game performs the board's actions itself
+    /// and every player is shown what the board would display.
+    #[serde(default)]
+    pub boardless: bool,
+    /// What a president learns about the player they investigate.
+    #[serde(default)]
+    pub investigation_reveals: InvestigationReveals,
+    /// Whether presidents are reminded of the policies they saw with each policy peak, alongside what they have
+    /// learned about the other players. A policy peak which the board forces onwards is not recorded, as the
+    /// president never acknowledged it.
+    #[serde(default)]
+    pub remember_policy_peaks: bool,
+    /// Whether the board is shown how many of each policy have yet to be enacted.
+    #[serde(default)]
+    pub show_deck_odds: bool,
+    /// What happens when a policy is enacted because three elections failed in a row.
+    #[serde(default)]
+    pub chaos_rule: ChaosRule,
+    /// The optional rules in play.
+    #[serde(flatten)]
+    pub house_rules: HouseRules,
+    /// A named set of roles, which takes precedence over the individual role toggles above.
+    #[serde(default)]
+    pub preset: Option<RolePreset>,
+    /// A fixed setup to play, which takes precedence over the roles and house rules above.
+    #[serde(default)]
+    pub scenario: Option<Scenario>,
+}
+
+/// The optional rules which change how the game is played, as shown to players so they know what is in play.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug, Default)]
+pub struct HouseRules {
+    /// Whether a president may publicly reveal the party of the player they investigated.
+    #[serde(default)]
+    pub public_investigations: bool,
+    /// The number of policies which must be enacted through chaos for the anarchist to win alone,
+    /// or `None` if the anarchist cannot win this way.
+    #[serde(default)]
+    pub anarchist_chaos_win: Option<u32>,
+    /// Whether the monarchist is executed in Hitler's place if Hitler is chosen for execution.
+    #[serde(default)]
+    pub monarchist_protection: bool,
+    /// Whether the capitalist may, once per game, bribe the president to cancel or redirect an execution.
+    #[serde(default)]
+    pub capitalist_bribe: bool,
+    /// Whether the communists elect one of their number to be told the result of bugging,
+    /// rather than it being shown to all of them.
+    #[serde(default)]
+    pub private_bugging: bool,
+    /// Whether votes are kept hidden until every player has voted, with the board only showing who has voted.
+    #[serde(default)]
+    pub secret_ballots: bool,
+    /// Whether players outside the proposed government may abstain from voting on it, for a more casual game.
+    #[serde(default)]
+    pub abstentions: bool,
+    /// Whether an anarchist who is executed before their assassination resolves still carries it out,
+    /// rather than it being cancelled.
+    #[serde(default)]
+    pub posthumous_assassination: bool,
+    /// Whether a president may investigate a player who has already been investigated,
+    /// which the official rules forbid.
+    #[serde(default)]
+    pub repeat_investigations: bool,
+}
+
+/// A house rule which only makes sense alongside a particular role.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub enum HouseRule {
+    AnarchistChaosWin,
+    MonarchistProtection,
+    CapitalistBribe,
+    PrivateBugging,
+    PosthumousAssassination,
+}
+
+impl std::fmt::Display for HouseRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            HouseRule::AnarchistChaosWin => "anarchist chaos win",
+            HouseRule::MonarchistProtection => "monarchist protection",
+            HouseRule::CapitalistBribe => "capitalist bribe",
+            HouseRule::PrivateBugging => "private bugging",
+            HouseRule::PosthumousAssassination => "posthumous assassination",
+        })
+    }
+}
+
+/// A named combination of roles, so that players needn't choose each role individually.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub enum RolePreset {
+    /// The original game: liberals, fascists and Hitler.
+    Classic,
+    /// Secret Hitler XL with the communists and every special role.
+    XlFull,
+    /// Secret Hitler XL with the communists and their anarchist, but no other special roles.
+    CommunistsOnly,
+    /// Secret Hitler XL with the communists, but without any special roles.
+    NoSpecialRoles,
+}
+
+/// A role preset, along with the number of players it can be played with.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct PresetInfo {
+    pub preset: RolePreset,
+    pub min_players: usize,
+    pub max_players: usize,
+}
+
+impl RolePreset {
+    pub const ALL: [RolePreset; 4] = [
+        RolePreset::Classic,
+        RolePreset::XlFull,
+        RolePreset::CommunistsOnly,
+        RolePreset::NoSpecialRoles,
+    ];
+
+    /// Gets every preset, along with the number of players each can be played with.
+    pub fn catalogue() -> Vec<PresetInfo> {
+        Self::ALL
+            .iter()
+            .filter_map(|preset| {
+                let opts = GameOptions { preset: Some(*preset), ..Default::default() };
+                Some(PresetInfo {
+                    preset: *preset,
+                    min_players: opts.min_players()?,
+                    max_players: opts.max_players()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Sets the role toggles of the given options to match this preset.
+    fn apply(self, opts: &mut GameOptions) {
+        let (communists, specials, anarchist) = match self {
+            RolePreset::Classic => (false, false, false),
+            RolePreset::XlFull => (true, true, true),
+            RolePreset::CommunistsOnly => (true, false, true),
+            RolePreset::NoSpecialRoles => (true, false, false),
+        };
+        opts.communists = communists;
+        opts.monarchist = specials;
+        opts.anarchist = anarchist;
+        opts.capitalist = specials;
+        opts.centrists = specials;
+    }
+}
+
+/// A reason why a combination of game options cannot be played with a given number of players.
+#[derive(Clone, Copy, Error, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(tag = "type")]
+pub enum OptionsProblem {
+    #[error("the anarchist cannot be included without the communists")]
+    AnarchistRequiresCommunists,
+    #[error("there are too many special roles for any number of players")]
+    TooManySpecialRoles,
+    #[error("at least {min_players} players are needed")]
+    TooFewPlayers { min_players: usize },
+    #[error("at most {max_players} players can play")]
+    TooManyPlayers { max_players: usize },
+    #[error("the {role} role needs at least {min_players} players")]
+    RoleNeedsMorePlayers { role: Role, min_players: usize },
+    #[error("the {rule} rule needs the {role} role")]
+    RuleNeedsRole { rule: HouseRule, role: Role },
+    #[error("the {role} role cannot be played without a board")]
+    RoleNeedsBoard { role: Role },
+}
+
+/// What an investigation reveals about the investigated player.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug, Default)]
+pub enum InvestigationReveals {
+    /// The player's party membership, as on the standard loyalty cards.
+    #[default]
+    Party,
+    /// The player's secret role.
+    Role,
+}
+
+/// What happens when the election tracker runs out and the top policy of the deck is enacted.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug, Default)]
+pub enum ChaosRule {
+    /// The policy grants no executive power and term limits are forgotten, as in the official rules.
+    #[default]
+    NoPower,
+    /// As in the official rules, but the discard pile is also shuffled back into the deck.
+    Reshuffle,
+    /// The policy grants its executive power to the last elected government, whose term limits still apply.
+    /// No power is granted if a government has yet to be elected.
+    GrantsPower,
+}
+
+/// Settings for a game whose players take their turns whenever they can, rather than all at once.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug, Default)]
+pub struct AsyncOptions {
+    /// Hours a player has to act in each timed phase which has no time limit of its own, or `None` for no deadline.
+    #[serde(default)]
+    pub turn_deadline_hours: Option<u32>,
+}
+
+/// Time limits, in seconds, after which a phase of the game is resolved automatically.
+/// A limit of `None` means the phase is untimed.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug, Default)]
+pub struct PhaseTimers {
+    /// Time for the president to nominate a chancellor.
+    pub nominate: Option<u32>,
+    /// Time for the players to vote on a government.
+    pub vote: Option<u32>,
+    /// Time for the president or chancellor to discard a policy, or respond to a veto.
+    pub legislate: Option<u32>,
+    /// Delay before the players are moved into or out of a communist session without waiting for the board,
+    /// so that games without a board device can progress.
+    #[serde(default)]
+    pub communist_session: Option<u32>,
+}
+
+impl GameOptions {
+    /// Gets the player distribution for this configuration for the given number of players.
+    /// Returns a `GameError` if the combination of settings and player count is not valid.
+    pub fn player_distribution(&self, num_players: usize) -> Result<PlayerDistribution, GameError> {
+        PlayerDistribution::new(&self.resolve_preset(), num_players)
+    }
+
+    /// Gets these options with the role toggles set to match the preset or scenario, if there is one.
+    pub fn resolve_preset(mut self) -> Self {
+        if let Some(preset) = self.preset {
+            preset.apply(&mut self);
+        }
+        if let Some(scenario) = self.scenario {
+            scenario.apply(&mut self);
+        }
+        self
+    }
+
+    /// Gets the time limit for the given phase of the game, in seconds, if there is one.
+    /// A phase without a time limit of its own is given the turn deadline of an asynchronous game.
+    pub fn time_limit(&self, phase: TimedPhase) -> Option<u32> {
+        let limit = match phase {
+            TimedPhase::Nomination { .. } | TimedPhase::MonarchistNomination { .. } => self.timers.nominate,
+            TimedPhase::Vote { .. } | TimedPhase::MonarchistVote { .. } => self.timers.vote,
+            TimedPhase::PresidentDiscard { .. }
+            | TimedPhase::ChancellorDiscard { .. }
+            | TimedPhase::VetoResponse { .. } => self.timers.legislate,
+            TimedPhase::CommunistStart | TimedPhase::CommunistEnd => self.timers.communist_session,
+        };
+        let turn_deadline = self.asynchronous.and_then(|opts| opts.turn_deadline_hours);
+        limit.or(turn_deadline.map(|hours| hours.saturating_mul(3600)))
+    }
+
+    /// Returns the minimum number of players for this configuration, or `None` if the configuration is not valid.
+    pub fn min_players(&self) -> Option<usize> {
+        (0..20).find(|num_players| self.player_distribution(*num_players).is_ok())
+    }
+
+    /// Returns the maxmimum number of players for this configuration, or `None` if the configuration is not valid.
+    pub fn max_players(&self) -> Option<usize> {
+        (0..20).rfind(|num_players| self.player_distribution(*num_players).is_ok())
+    }
+
+    /// Checks that these options can be played with the given number of players.
+    pub fn validate(&self, num_players: usize) -> Result<(), GameError> {
+        let problems = self.problems(num_players);
+        match problems.is_empty() {
+            true => Ok(()),
+            false => Err(GameError::OptionsValidation(problems)),
+        }
+    }
+
+    /// Lists the reasons these options cannot be played with the given number of players, if there are any.
+    /// When there are too few players, the special roles which need more players are listed as well.
+    pub fn problems(&self, num_players: usize) -> Vec<OptionsProblem> {
+        let opts = self.resolve_preset();
+        if opts.anarchist && !opts.communists {
+            return vec![OptionsProblem::AnarchistRequiresCommunists];
+        }
+        let rule_problems = opts.rule_problems();
+        if !rule_problems.is_empty() {
+            return rule_problems;
+        }
+        if opts.boardless && opts.monarchist {
+            // Only the board holds open the monarchist's chance to hijack a special election
+            return vec![OptionsProblem::RoleNeedsBoard { role: Role::Monarchist }];
+        }
+        let (Some(min_players), Some(max_players)) = (opts.min_players(), opts.max_players()) else {
+            return vec![OptionsProblem::TooManySpecialRoles];
+        };
+        if num_players > max_players {
+            return vec![OptionsProblem::TooManyPlayers { max_players }];
+        }
+        if num_players >= min_players {
+            return vec![];
+        }
+
+        let roles = [
+            (opts.monarchist, Role::Monarchist),
+            (opts.anarchist, Role::Anarchist),
+            (opts.capitalist, Role::Capitalist),
+            (opts.centrists, Role::Centrist),
+        ];
+        let role_problems = roles
+            .into_iter()
+            .filter(|(included, _)| *included)
+            .filter_map(|(_, role)| {
+                let min_players = opts.with_only_role(role).min_players()?;
+                (num_players < min_players).then_some(OptionsProblem::RoleNeedsMorePlayers { role, min_players })
+            });
+        std::iter::once(OptionsProblem::TooFewPlayers { min_players })
+            .chain(role_problems)
+            .collect()
+    }
+
+    /// Lists the house rules which are enabled without the role they depend on.
+    fn rule_problems(&self) -> Vec<OptionsProblem> {
+        let rules = &self.house_rules;
+        [
+            (
+                rules.anarchist_chaos_win.is_some() && !self.anarchist,
+                HouseRule::AnarchistChaosWin,
+                Role::Anarchist,
+            ),
+            (
+                rules.monarchist_protection && !self.monarchist,
+                HouseRule::MonarchistProtection,
+                Role::Monarchist,
+            ),
+            (
+                rules.capitalist_bribe && !self.capitalist,
+                HouseRule::CapitalistBribe,
+                Role::Capitalist,
+            ),
+            (
+                rules.private_bugging && !self.communists,
+                HouseRule::PrivateBugging,
+                Role::Communist,
+            ),
+            (
+                rules.posthumous_assassination && !self.anarchist,
+                HouseRule::PosthumousAssassination,
+                Role::Anarchist,
+            ),
+        ]
+        .into_iter()
+        .filter(|(conflict, _, _)| *conflict)
+        .map(|(_, rule, role)| OptionsProblem::RuleNeedsRole { rule, role })
+        .collect()
+    }
+
+    /// Gets the options with the same parties as these, but only the given special role.
+    fn with_only_role(&self, role: Role) -> Self {
+        let mut opts = GameOptions {
+            communists: self.communists,
+            ..Default::default()
+        };
+        match role {
+            Role::Monarchist => opts.monarchist = true,
+            Role::Anarchist => opts.anarchist = true,
+            Role::Capitalist => opts.capitalist = true,
+            Role::Centrist => opts.centrists = true,
+            _ => {}
+        }
+        opts
+    }
+}
@@ -8,13 +8,12 @@ pub enum Party {
     Communist,
 }
 
-impl ToString for Party {
-    fn to_string(&self) -> String {
-        match self {
+impl std::fmt::Display for Party {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
             Party::Liberal => "Liberal",
             Party::Fascist => "Fascist",
             Party::Communist => "Communist",
-        }
-        .to_string()
+        })
     }
 }
@@ -2,7 +2,7 @@ use super::{party::Party, GameOptions, MAX_PLAYERS};
 use crate::error::GameError;
 use rand::prelude::SliceRandom;
 use serde::{Deserialize, Serialize};
-use std::iter::repeat;
+use std::iter::repeat_n;
 
 /// A game player.
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -14,6 +14,51 @@ pub struct Player {
     pub not_hitler: bool,
     pub investigated: bool,
     pub tried_to_radicalise: bool,
+    /// Everything the player has learned about the other players, in the order it was learned.
+    #[serde(default)]
+    pub knowledge: Vec<Knowledge>,
+    /// The player's party, if it has been publicly revealed by the president who investigated them.
+    #[serde(default)]
+    pub confirmed_party: Option<Party>,
+    /// Whether the player became a communist through radicalisation, rather than being dealt the role.
+    #[serde(default)]
+    pub radicalised: bool,
+    /// The policies the player saw with each policy peak they acknowledged as president.
+    #[serde(default)]
+    pub policy_peaks: Vec<PolicyPeakRecord>,
+}
+
+/// The policies a president acknowledged seeing with a policy peak, from the top of the deck down.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct PolicyPeakRecord {
+    pub cards: [Party; 3],
+    /// The round in which the policy peak happened.
+    pub round: usize,
+}
+
+/// Something a player has learned about another player's allegiance.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct Knowledge {
+    pub target: usize,
+    pub result: InvestigationResult,
+    pub source: KnowledgeSource,
+    /// The round in which it was learned, where the night round is round 0.
+    pub round: usize,
+}
+
+/// How a player came to learn something about another player.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub enum KnowledgeSource {
+    /// Revealed during the night round, owing to the player's role.
+    InitialReveal,
+    /// Learned by investigating the player as president.
+    Investigation,
+    /// Learned by the communists bugging the player.
+    Bugging,
+    /// Learned by the communists meeting during a congress.
+    Congress,
+    /// Revealed to the whole table by a confession.
+    Confession,
 }
 
 #[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
@@ -28,9 +73,9 @@ pub enum Role {
     Centrist,
 }
 
-impl ToString for Role {
-    fn to_string(&self) -> String {
-        match self {
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
             Role::Liberal => "Liberal",
             Role::Fascist => "Fascist",
             Role::Communist => "Communist",
@@ -39,8 +84,7 @@ impl ToString for Role {
             Role::Anarchist => "Anarchist",
             Role::Capitalist => "Capitalist",
             Role::Centrist => "Centrist",
-        }
-        .to_string()
+        })
     }
 }
 
@@ -51,6 +95,22 @@ pub enum InvestigationResult {
     Role(Role),
 }
 
+impl Role {
+    /// Gets the party that players with this role belong to.
+    pub fn party(&self) -> Party {
+        match self {
+            Role::Liberal => Party::Liberal,
+            Role::Fascist => Party::Fascist,
+            Role::Communist => Party::Communist,
+            Role::Hitler => Party::Fascist,
+            Role::Monarchist => Party::Fascist,
+            Role::Anarchist => Party::Communist,
+            Role::Capitalist => Party::Liberal,
+            Role::Centrist => Party::Liberal,
+        }
+    }
+}
+
 impl Player {
     pub fn new(name: String, role: Role) -> Self {
         Self {
@@ -61,26 +121,22 @@ impl Player {
             not_hitler: false,
             investigated: false,
             tried_to_radicalise: false,
+            knowledge: vec![],
+            confirmed_party: None,
+            radicalised: false,
+            policy_peaks: vec![],
         }
     }
 
     pub fn party(&self) -> Party {
-        match self.role {
-            Role::Liberal => Party::Liberal,
-            Role::Fascist => Party::Fascist,
-            Role::Communist => Party::Communist,
-            Role::Hitler => Party::Fascist,
-            Role::Monarchist => Party::Fascist,
-            Role::Anarchist => Party::Communist,
-            Role::Capitalist => Party::Liberal,
-            Role::Centrist => Party::Liberal,
-        }
+        self.role.party()
     }
 
     pub fn radicalise(&mut self) -> bool {
         self.tried_to_radicalise = true;
         if matches!(self.role, Role::Liberal | Role::Centrist) {
             self.role = Role::Communist;
+            self.radicalised = true;
             true
         } else {
             false
@@ -88,7 +144,8 @@ impl Player {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+/// The number of players given each role, for a particular combination of game options and player count.
+#[derive(Clone, Copy, Serialize, Debug)]
 pub struct PlayerDistribution {
     pub num_players: usize,
     pub liberals: usize,
@@ -171,9 +228,9 @@ impl PlayerDistribution {
 pub fn assign_roles(distr: PlayerDistribution, rng: &mut impl rand::Rng) -> Vec<Role> {
     let mut roles = Vec::with_capacity(distr.num_players);
 
-    roles.extend(repeat(Role::Fascist).take(distr.fascists));
-    roles.extend(repeat(Role::Communist).take(distr.communists));
-    roles.extend(repeat(Role::Liberal).take(distr.liberals));
+    roles.extend(repeat_n(Role::Fascist, distr.fascists));
+    roles.extend(repeat_n(Role::Communist, distr.communists));
+    roles.extend(repeat_n(Role::Liberal, distr.liberals));
 
     if distr.hitler {
         roles.push(Role::Hitler);
@@ -201,8 +258,8 @@ pub fn assign_roles(distr: PlayerDistribution, rng: &mut impl rand::Rng) -> Vec<
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::game::player::Role;
-    use crate::game::GameOptions;
+    use crate::player::Role;
+    use crate::GameOptions;
 
     #[test]
     fn role_assignment_10players() {
@@ -213,6 +270,7 @@ mod test {
             capitalist: true,
             centrists: true,
             monarchist: false,
+            ..Default::default()
         };
         let distr = PlayerDistribution::new(&opts, 10).unwrap();
         println!("{:?}", &distr);
@@ -0,0 +1,100 @@
+use super::{Action, Game, GameOptions};
+use crate::error::GameError;
+use serde::{Deserialize, Serialize};
+
+/// An action recorded in a game's log.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct LoggedAction {
+    /// The player who performed the action, or `None` for the board.
+    pub player: Option<usize>,
+    pub action: Action,
+    /// The hash of the game state after the action was performed, excluding the log itself.
+    pub state_hash: u64,
+}
+
+/// A player who was replaced partway through the game, with the newcomer taking over their seat.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct Substitution {
+    pub player: usize,
+    pub previous_name: String,
+    pub name: String,
+}
+
+/// An entry in a game's log, which records everything that happened to the game in order.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(untagged)]
+pub enum LogEntry {
+    Action(LoggedAction),
+    Substitution(Substitution),
+}
+
+impl Game {
+    /// Reconstructs a game by making each entry of its log in turn on a newly created game,
+    /// checking that the resulting state matches the logged hash after every action.
+    pub fn replay(opts: GameOptions, player_names: &[String], seed: u64, log: &[LogEntry]) -> Result<Game, GameError> {
+        let mut game = Game::new(opts, player_names, seed)?;
+        for (index, entry) in log.iter().enumerate() {
+            let result = match entry {
+                LogEntry::Action(logged) => game.apply(logged.player, logged.action),
+                LogEntry::Substitution(sub) => game.substitute(sub.player, &sub.name),
+            };
+            if result.is_err() || game.log[index] != *entry {
+                return Err(GameError::ReplayDiverged { index });
+            }
+        }
+        Ok(game)
+    }
+
+    /// Gets the seed the game was created with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Gets every entry of the game's log, in order.
+    pub fn log(&self) -> &[LogEntry] {
+        &self.log
+    }
+
+    /// Gets every action which has been performed on the game, in order.
+    pub fn actions(&self) -> impl Iterator<Item = &LoggedAction> {
+        self.log.iter().filter_map(|entry| match entry {
+            LogEntry::Action(logged) => Some(logged),
+            LogEntry::Substitution(_) => None,
+        })
+    }
+
+    /// Gets every substitution made during the game, in order.
+    pub fn substitutions(&self) -> impl Iterator<Item = &Substitution> {
+        self.log.iter().filter_map(|entry| match entry {
+            LogEntry::Substitution(sub) => Some(sub),
+            LogEntry::Action(_) => None,
+        })
+    }
+
+    /// Hands a player's seat over to a newcomer with the given name, who keeps the player's role and knowledge.
+    /// The last action can no longer be undone, as doing so would restore the previous name.
+    pub fn substitute(&mut self, player: usize, name: &str) -> Result<(), GameError> {
+        if player >= self.num_players() {
+            return Err(GameError::InvalidPlayerIndex);
+        }
+        if self.find_player(name).is_ok() {
+            return Err(GameError::NameTaken);
+        }
+        let previous_name = std::mem::replace(&mut self.players[player].name, name.to_string());
+        self.mark_all_seats_changed();
+        self.log.push(LogEntry::Substitution(Substitution {
+            player,
+            previous_name,
+            name: name.to_string(),
+        }));
+        self.undo = None;
+        Ok(())
+    }
+
+    /// Appends a successfully performed action to the log.
+    pub(super) fn record_action(&mut self, player: Option<usize>, action: Action) {
+        let state_hash = self.state_hash();
+        self.log
+            .push(LogEntry::Action(LoggedAction { player, action, state_hash }));
+    }
+}
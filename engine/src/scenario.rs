@@ -0,0 +1,105 @@
+use super::{party::Party, Game, GameOptions, HouseRules};
+use serde::{Deserialize, Serialize};
+
+/// A fixed setup which different groups can play so as to compare how their games turned out.
+/// Every game of a scenario with the same number of players deals the same roles and the same deck.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub enum Scenario {
+    /// The original game, where the liberals get off to a flying start.
+    LiberalSpring,
+    /// The original game, where the fascists hold the first three policies.
+    IronFist,
+    /// Secret Hitler XL with the communists, where the first president is the first player to join.
+    RedDawn,
+}
+
+/// A scenario, as listed to players choosing one to play.
+#[derive(Clone, Copy, Serialize, Debug)]
+pub struct ScenarioInfo {
+    pub scenario: Scenario,
+    pub description: &'static str,
+    pub min_players: usize,
+    pub max_players: usize,
+}
+
+/// Something which happens to a scenario's game before anyone has acted.
+enum Opening {
+    /// The given cards are drawn first, in order.
+    StackDeck(&'static [Party]),
+    /// The given player is the first president.
+    FirstPresident(usize),
+}
+
+impl Scenario {
+    pub const ALL: [Scenario; 3] = [Scenario::LiberalSpring, Scenario::IronFist, Scenario::RedDawn];
+
+    /// Gets every scenario, along with the number of players each can be played with.
+    pub fn catalogue() -> Vec<ScenarioInfo> {
+        Self::ALL.iter().filter_map(|scenario| scenario.info()).collect()
+    }
+
+    /// Gets the scenario of the day, which changes at midnight UTC.
+    pub fn daily(date: chrono::NaiveDate) -> Scenario {
+        let days = date.signed_duration_since(chrono::NaiveDate::default()).num_days();
+        Self::ALL[days.rem_euclid(Self::ALL.len() as i64) as usize]
+    }
+
+    /// Describes the scenario, along with the number of players it can be played with.
+    pub fn info(self) -> Option<ScenarioInfo> {
+        let opts = GameOptions { scenario: Some(self), ..Default::default() };
+        Some(ScenarioInfo {
+            scenario: self,
+            description: self.description(),
+            min_players: opts.min_players()?,
+            max_players: opts.max_players()?,
+        })
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Scenario::LiberalSpring => "The liberals draw the first three policies.",
+            Scenario::IronFist => "The fascists draw the first three policies.",
+            Scenario::RedDawn => "The communists are in play, and the first player to join is president.",
+        }
+    }
+
+    /// The seed the scenario's games are dealt with.
+    pub fn seed(self) -> u64 {
+        match self {
+            Scenario::LiberalSpring => 0x5eed_0001,
+            Scenario::IronFist => 0x5eed_0002,
+            Scenario::RedDawn => 0x5eed_0003,
+        }
+    }
+
+    /// Sets the roles and house rules of the given options to match this scenario.
+    pub(super) fn apply(self, opts: &mut GameOptions) {
+        let communists = matches!(self, Scenario::RedDawn);
+        opts.preset = None;
+        opts.communists = communists;
+        opts.monarchist = false;
+        opts.anarchist = communists;
+        opts.capitalist = false;
+        opts.centrists = false;
+        opts.house_rules = HouseRules::default();
+    }
+
+    fn openings(self) -> &'static [Opening] {
+        use Party::*;
+        match self {
+            Scenario::LiberalSpring => &[Opening::StackDeck(&[Liberal, Liberal, Liberal])],
+            Scenario::IronFist => &[Opening::StackDeck(&[Fascist, Fascist, Fascist])],
+            Scenario::RedDawn => &[Opening::FirstPresident(0)],
+        }
+    }
+
+    /// Plays out the scenario's openings on a newly created game.
+    pub(super) fn open(self, game: &mut Game) {
+        for opening in self.openings() {
+            match opening {
+                Opening::StackDeck(cards) => game.deck.stack(cards),
+                Opening::FirstPresident(player) => game.presidential_turn = player % game.num_players(),
+            }
+        }
+    }
+}
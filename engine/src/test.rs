@@ -0,0 +1,1753 @@
+#![cfg(test)]
+#![allow(clippy::bool_assert_comparison)]
+
+use super::confirmations::Confirmations;
+use super::eligible::EligiblePlayers;
+use super::executive_power::ExecutiveAction;
+use super::player::InvestigationResult;
+use super::player::Player;
+use super::player::Role;
+use super::votes::MonarchistVotes;
+use super::BoardPrompt;
+use super::GameState;
+use super::NextPresident;
+use super::Party::*;
+use super::PlayerPrompt;
+use crate::deck::Deck;
+use crate::error::GameError;
+use crate::government::Government;
+use crate::Action;
+use crate::AsyncOptions;
+use crate::ChaosRule;
+use crate::ChatScope;
+use crate::ChoosePlayerKind;
+use crate::DeckOdds;
+use crate::Game;
+use crate::GameOptions;
+use crate::HouseRule;
+use crate::HouseRules;
+use crate::IneligibilityReason;
+use crate::InvariantViolation;
+use crate::InvestigationReveals;
+use crate::KnowledgeSource;
+use crate::Language;
+use crate::LogEntry;
+use crate::OptionsProblem;
+use crate::Phase;
+use crate::PresidencyFallback;
+use crate::RolePreset;
+use crate::Scenario;
+use crate::TimedPhase;
+use crate::WinCondition;
+use crate::WinStatus;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+#[test]
+fn can_create_game() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let opts = GameOptions::default();
+    let game = Game::new(opts, &players, 0).unwrap();
+    assert!(matches!(game.state, GameState::Night { .. }));
+}
+
+#[test]
+fn legal_actions_follow_the_game() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    assert_eq!(game.legal_actions(0), vec![Action::EndNightRound, Action::ViewRole]);
+    for player in 0..5 {
+        game.end_night_round(player).unwrap();
+    }
+
+    let president = game.presidential_turn;
+    let nominations = game.legal_actions(president);
+    assert_eq!(nominations.len(), 4);
+    assert_eq!(nominations.contains(&Action::ChoosePlayer { player: president }), false);
+    assert_eq!(game.legal_actions((president + 1) % 5), vec![]);
+}
+
+#[test]
+fn replay_reconstructs_game() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let opts = GameOptions { undo_window: Some(10), ..Default::default() };
+    let mut game = Game::new(opts, &players, 7).unwrap();
+    for player in 0..5 {
+        game.apply(Some(player), Action::EndNightRound).unwrap();
+    }
+    let president = game.presidential_turn;
+    let chancellor = (president + 1) % 5;
+    game.apply(Some(president), Action::ChoosePlayer { player: chancellor })
+        .unwrap();
+    game.apply(Some(president), Action::Undo).unwrap();
+    game.apply(Some(president), Action::ChoosePlayer { player: chancellor })
+        .unwrap();
+    for player in 0..5 {
+        game.apply(Some(player), Action::CastVote { vote: true }).unwrap();
+    }
+
+    let replayed = Game::replay(opts, &players, game.seed(), game.log()).unwrap();
+    assert_eq!(replayed.state_hash(), game.state_hash());
+
+    let mut log = game.log().to_vec();
+    let LogEntry::Action(logged) = &mut log[7] else {
+        panic!("Expected an action");
+    };
+    logged.action = Action::CastVote { vote: false };
+    assert!(matches!(
+        Game::replay(opts, &players, game.seed(), &log),
+        Err(GameError::ReplayDiverged { index: 7 })
+    ));
+    assert!(matches!(
+        Game::replay(opts, &players, 8, game.log()),
+        Err(GameError::ReplayDiverged { .. })
+    ));
+}
+
+#[test]
+fn state_hash_covers_everything_but_the_log() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    for player in 0..5 {
+        game.apply(Some(player), Action::EndNightRound).unwrap();
+    }
+    assert_eq!(game.log().len(), 5);
+
+    // The hash must be that of the whole game with its log emptied, so no field is left out of it
+    let mut without_log = game.clone();
+    without_log.log.clear();
+    let expected = crate::hash::fnv1a(&serde_json::to_vec(&without_log).unwrap());
+    assert_eq!(game.state_hash(), expected);
+    assert_eq!(game.actions().nth(4).unwrap().state_hash, expected);
+}
+
+#[test]
+fn board_update_hash_tracks_public_state() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    let hash = game.get_board_update().state_hash;
+    assert_eq!(game.get_board_update().state_hash, hash);
+    for player in 0..5 {
+        game.end_night_round(player).unwrap();
+    }
+    assert_ne!(game.get_board_update().state_hash, hash);
+}
+
+#[test]
+fn apply_checks_who_is_acting() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    assert_eq!(game.apply(Some(5), Action::EndNightRound).is_err(), true);
+    assert_eq!(game.apply(None, Action::EndNightRound).is_err(), true);
+    for player in 0..5 {
+        game.apply(Some(player), Action::EndNightRound).unwrap();
+    }
+
+    let president = game.presidential_turn;
+    let chancellor = (president + 1) % 5;
+    assert_eq!(
+        game.apply(None, Action::ChoosePlayer { player: chancellor }).is_err(),
+        true
+    );
+    assert_eq!(
+        game.apply(Some(president), Action::ChoosePlayer { player: 9 }).is_err(),
+        true
+    );
+    game.apply(Some(president), Action::ChoosePlayer { player: chancellor })
+        .unwrap();
+    assert_eq!(game.apply(Some(president), Action::EndVoting).is_err(), true);
+}
+
+#[test]
+fn prompts_are_localized() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let opts = GameOptions {
+        language: Language::German,
+        ..Default::default()
+    };
+    let mut game = Game::new(opts, &players, 0).unwrap();
+    for player in 0..5 {
+        game.end_night_round(player).unwrap();
+    }
+
+    let message = game.get_board_update().message.unwrap();
+    assert_eq!(message.key, "board.election.nominating");
+    assert_eq!(message.params["president"], game.players[game.presidential_turn].name);
+    assert_eq!(
+        WinCondition::HitlerExecuted.describe(opts.language),
+        "Hitler wurde hingerichtet"
+    );
+}
+
+#[test]
+fn knowledge_log_includes_initial_reveal() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    let fascist = game.players.iter().position(|p| p.role == Role::Fascist).unwrap();
+    let hitler = game.players.iter().position(|p| p.role == Role::Hitler).unwrap();
+
+    let log = game.get_player_update(fascist).knowledge_log;
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].player, game.players[hitler].name);
+    assert_eq!(log[0].source, KnowledgeSource::InitialReveal);
+    assert_eq!(log[0].round, 0);
+}
+
+#[test]
+fn presets_set_roles() {
+    let opts = GameOptions {
+        monarchist: true,
+        preset: Some(RolePreset::Classic),
+        ..Default::default()
+    };
+    assert_eq!(opts.min_players(), Some(5));
+    assert_eq!(opts.max_players(), Some(10));
+
+    let players = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L"].map(|s| s.into());
+    let opts = GameOptions {
+        preset: Some(RolePreset::XlFull),
+        ..Default::default()
+    };
+    let game = Game::new(opts, &players, 0).unwrap();
+    assert_eq!(game.options().communists, true);
+    assert_eq!(game.options().centrists, true);
+    assert_eq!(RolePreset::catalogue().len(), RolePreset::ALL.len());
+}
+
+#[test]
+fn congress_reveals_original_communists() {
+    let players = ["A", "B", "C", "D", "E", "F", "G", "H"].map(|s| s.into());
+    let opts = GameOptions { communists: true, ..Default::default() };
+    let mut game = Game::new(opts, &players, 0).unwrap();
+    let recruit = game.players.iter().position(|p| p.role == Role::Liberal).unwrap();
+    let original = game.players.iter().position(|p| p.role == Role::Communist).unwrap();
+    assert_eq!(game.players[recruit].radicalise(), true);
+    game.state = GameState::Congress;
+
+    let Some(PlayerPrompt::EndCongress { communists }) = game.get_player_update(recruit).prompt else {
+        panic!("expected the congress prompt");
+    };
+    assert_eq!(communists, vec![game.players[original].name.clone()]);
+
+    game.end_congress(recruit).unwrap();
+    game.end_communist_end().unwrap();
+    assert_eq!(
+        game.players[recruit].others[original],
+        InvestigationResult::Role(Role::Communist)
+    );
+}
+
+#[test]
+fn private_bugging_tells_one_communist() {
+    let players = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K"].map(|s| s.into());
+    let opts = GameOptions {
+        communists: true,
+        house_rules: HouseRules { private_bugging: true, ..Default::default() },
+        ..Default::default()
+    };
+    let mut game = Game::new(opts, &players, 0).unwrap();
+    game.last_government = Some(Government { president: 0, chancellor: 1 });
+    let communists = (0..11)
+        .filter(|i| game.players[*i].role == Role::Communist)
+        .collect::<Vec<_>>();
+    let target = game.players.iter().position(|p| p.role == Role::Liberal).unwrap();
+    assert_eq!(communists.len() >= 2, true);
+
+    game.start_executive_action(ExecutiveAction::Bugging);
+    game.end_communist_start().unwrap();
+    game.choose_player(communists[0], target).unwrap();
+    for &communist in &communists {
+        game.choose_player(communist, communists[1]).unwrap();
+    }
+    assert!(matches!(game.state, GameState::ActionReveal { .. }));
+
+    let prompt = game.get_player_update(communists[1]).prompt;
+    assert!(matches!(
+        prompt,
+        Some(PlayerPrompt::InvestigatePlayer { party: Liberal, .. })
+    ));
+    let prompt = game.get_player_update(communists[0]).prompt;
+    assert!(matches!(prompt, Some(PlayerPrompt::BuggingWithheld { .. })));
+    assert_eq!(
+        game.get_player_update(communists[0]).knowledge_log.len(),
+        communists.len() - 1
+    );
+}
+
+#[test]
+fn five_year_plan_adds_cards() {
+    let players = ["A", "B", "C", "D", "E", "F", "G", "H"].map(|s| s.into());
+    let opts = GameOptions { communists: true, ..Default::default() };
+    let mut game = Game::new(opts, &players, 0).unwrap();
+    game.last_government = Some(Government { president: 0, chancellor: 1 });
+    let draw_pile = game.deck.count();
+    let communists = game.deck.total(Communist);
+
+    game.start_executive_action(ExecutiveAction::FiveYearPlan);
+    let update = game.get_board_update();
+    assert_eq!(update.draw_pile, draw_pile + 3);
+    assert!(matches!(
+        update.prompt,
+        Some(BoardPrompt::FiveYearPlan { cards_added: 3 })
+    ));
+    assert_eq!(game.deck.total(Communist), communists + 2);
+
+    game.end_executive_action(None).unwrap();
+    assert_eq!(game.deck.count(), draw_pile + 3);
+    assert!(matches!(game.state, GameState::Election { .. }));
+}
+
+#[test]
+fn liberal_track_victory() {
+    let mut game = Game {
+        opts: GameOptions::default(),
+        board: super::board::Board {
+            num_players: 5,
+            liberal_cards: 4,
+            fascist_cards: 0,
+            communist_cards: 0,
+        },
+        deck: Deck::new(false),
+        election_tracker: 0,
+        last_government: None,
+        players: vec![
+            Player::new("ALEX".to_string(), Role::Liberal),
+            Player::new("BOB".to_string(), Role::Liberal),
+            Player::new("CHARLIE".to_string(), Role::Liberal),
+            Player::new("DAVID".to_string(), Role::Liberal),
+            Player::new("ED".to_string(), Role::Liberal),
+        ],
+        presidential_turn: 0,
+        next_president: None,
+        rng: ChaCha8Rng::seed_from_u64(0),
+        state: GameState::CardReveal {
+            result: Liberal,
+            chaos: false,
+            confirmations: Confirmations::new(5),
+            board_ready: false,
+        },
+        radicalised: false,
+        assassination: crate::AssassinationState::Unused,
+        undo: None,
+        round: 0,
+        bribed: false,
+        chaos_policies: 0,
+        secondary_conditions: vec![],
+        seed: 0,
+        log: vec![],
+        changed_seats: None,
+    };
+
+    game.end_card_reveal(None).unwrap();
+
+    assert!(game.game_over());
+    assert!(matches!(
+        game.state,
+        GameState::GameOver(WinCondition::LiberalPolicyTrack)
+    ));
+}
+
+#[test]
+fn fascist_track_victory() {
+    let mut game = Game {
+        opts: GameOptions::default(),
+        board: super::board::Board {
+            num_players: 5,
+            liberal_cards: 0,
+            fascist_cards: 5,
+            communist_cards: 0,
+        },
+        deck: Deck::new(false),
+        election_tracker: 0,
+        last_government: None,
+        players: vec![
+            Player::new("ALEX".to_string(), Role::Liberal),
+            Player::new("BOB".to_string(), Role::Liberal),
+            Player::new("CHARLIE".to_string(), Role::Liberal),
+            Player::new("DAVID".to_string(), Role::Liberal),
+            Player::new("ED".to_string(), Role::Liberal),
+        ],
+        presidential_turn: 0,
+        next_president: None,
+        rng: ChaCha8Rng::seed_from_u64(0),
+        state: GameState::CardReveal {
+            result: Fascist,
+            chaos: false,
+            confirmations: Confirmations::new(5),
+            board_ready: false,
+        },
+        radicalised: false,
+        assassination: crate::AssassinationState::Unused,
+        undo: None,
+        round: 0,
+        bribed: false,
+        chaos_policies: 0,
+        secondary_conditions: vec![],
+        seed: 0,
+        log: vec![],
+        changed_seats: None,
+    };
+
+    game.end_card_reveal(None).unwrap();
+
+    assert!(game.game_over());
+    assert!(matches!(
+        game.state,
+        GameState::GameOver(WinCondition::FascistPolicyTrack)
+    ));
+}
+
+/// The roles of a six-player game with an anarchist in the second seat.
+const ANARCHIST_ROLES: [Role; 6] = [
+    Role::Liberal,
+    Role::Anarchist,
+    Role::Liberal,
+    Role::Fascist,
+    Role::Hitler,
+    Role::Liberal,
+];
+
+/// Creates a game in which the players have the given roles and the given policies have been enacted, with every other
+/// card in the draw pile. The first player holds the presidency; each test puts the game into the state it needs.
+fn scenario(opts: GameOptions, roles: &[Role], liberal_cards: usize, fascist_cards: usize) -> Game {
+    let names = ["ALEX", "BOB", "CHARLIE", "DAVID", "ED", "FRED"][..roles.len()]
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    let mut game = Game::new(GameOptions::default(), &names, 0).unwrap();
+    game.players = names
+        .into_iter()
+        .zip(roles)
+        .map(|(name, role)| Player::new(name, *role))
+        .collect();
+    game.board.liberal_cards = liberal_cards;
+    game.board.fascist_cards = fascist_cards;
+    game.deck = Deck::new(opts.communists);
+    game.deck.shuffle(&game.board, &mut game.rng);
+    game.opts = opts;
+    game.presidential_turn = 0;
+    game
+}
+
+/// Draws a policy from the deck and puts the game into its reveal, as though it were enacted by the chaos rule.
+fn reveal_chaos_policy(game: &mut Game, result: super::Party) {
+    game.deck.stack(&[result]);
+    game.chaos_policies += 1;
+    game.state = GameState::CardReveal {
+        result: game.deck.draw_one(),
+        chaos: true,
+        confirmations: Confirmations::new(game.num_players_alive()),
+        board_ready: false,
+    };
+}
+
+#[test]
+fn president_reveals_investigation() {
+    let house_rules = HouseRules {
+        public_investigations: true,
+        ..Default::default()
+    };
+    let opts = GameOptions { house_rules, ..Default::default() };
+    let roles = [Role::Liberal, Role::Liberal, Role::Liberal, Role::Fascist, Role::Hitler];
+    let mut game = scenario(opts, &roles, 0, 2);
+    game.last_government = Some(Government { president: 0, chancellor: 3 });
+    game.state = GameState::ActionReveal {
+        action: ExecutiveAction::InvestigatePlayer,
+        chosen_player: Some(3),
+        confirmations: Confirmations::new(5),
+    };
+
+    assert_eq!(game.legal_actions(0).contains(&Action::RevealInvestigation), true);
+    assert_eq!(game.apply(Some(3), Action::RevealInvestigation).is_err(), true);
+    game.apply(Some(0), Action::RevealInvestigation).unwrap();
+    assert_eq!(game.get_public_players()[3].confirmed_party, Some(Fascist));
+    assert_eq!(game.legal_actions(0), vec![Action::EndExecutiveAction]);
+}
+
+#[test]
+fn capitalist_bribes_president() {
+    let house_rules = HouseRules { capitalist_bribe: true, ..Default::default() };
+    let opts = GameOptions {
+        capitalist: true,
+        house_rules,
+        ..Default::default()
+    };
+    let roles = [
+        Role::Fascist,
+        Role::Capitalist,
+        Role::Liberal,
+        Role::Liberal,
+        Role::Hitler,
+    ];
+    let mut game = scenario(opts, &roles, 0, 4);
+    game.last_government = Some(Government { president: 0, chancellor: 3 });
+    game.state = GameState::ChoosePlayer {
+        action: ExecutiveAction::Execution,
+        can_select: EligiblePlayers::only_one(0),
+        can_be_selected: EligiblePlayers::only(&[1, 2, 3, 4]),
+    };
+
+    game.choose_player(0, 1).unwrap();
+    assert_eq!(game.legal_actions(1), vec![Action::OfferBribe, Action::DeclineBribe]);
+    game.apply(Some(1), Action::OfferBribe).unwrap();
+    assert_eq!(game.apply(Some(0), Action::ChoosePlayer { player: 1 }).is_err(), true);
+    game.apply(Some(0), Action::ChoosePlayer { player: 2 }).unwrap();
+    assert!(matches!(
+        game.state,
+        GameState::ActionReveal { chosen_player: Some(2), .. }
+    ));
+    assert_eq!(game.can_bribe(ExecutiveAction::Execution, 0), None);
+}
+
+#[test]
+fn monarchist_protects_hitler() {
+    let house_rules = HouseRules {
+        monarchist_protection: true,
+        ..Default::default()
+    };
+    let opts = GameOptions {
+        monarchist: true,
+        house_rules,
+        ..Default::default()
+    };
+    let roles = [
+        Role::Liberal,
+        Role::Monarchist,
+        Role::Liberal,
+        Role::Liberal,
+        Role::Hitler,
+    ];
+    let mut game = scenario(opts, &roles, 0, 4);
+    game.last_government = Some(Government { president: 0, chancellor: 3 });
+    game.state = GameState::ChoosePlayer {
+        action: ExecutiveAction::Execution,
+        can_select: EligiblePlayers::only_one(0),
+        can_be_selected: EligiblePlayers::only(&[1, 2, 3, 4]),
+    };
+
+    game.choose_player(0, 4).unwrap();
+    game.end_executive_action(None).unwrap();
+    assert_eq!(game.players[1].alive, false);
+    assert_eq!(game.players[4].alive, true);
+    assert_eq!(game.game_over(), false);
+
+    // Without the monarchist, Hitler's execution ends the game and the monarchist loses with the fascists
+    game.state = GameState::ChoosePlayer {
+        action: ExecutiveAction::Execution,
+        can_select: EligiblePlayers::only_one(0),
+        can_be_selected: EligiblePlayers::only(&[2, 3, 4]),
+    };
+    game.choose_player(0, 4).unwrap();
+    game.end_executive_action(None).unwrap();
+    assert!(matches!(game.state, GameState::GameOver(WinCondition::HitlerExecuted)));
+    assert_eq!(game.player_has_won(0), true);
+    assert_eq!(game.player_has_won(1), false);
+}
+
+#[test]
+fn anarchist_chaos_victory() {
+    let house_rules = HouseRules {
+        anarchist_chaos_win: Some(2),
+        ..Default::default()
+    };
+    let opts = GameOptions {
+        communists: true,
+        anarchist: true,
+        house_rules,
+        ..Default::default()
+    };
+    let mut game = scenario(opts, &ANARCHIST_ROLES, 1, 1);
+    game.chaos_policies = 1;
+    reveal_chaos_policy(&mut game, Liberal);
+
+    for player in 0..6 {
+        game.end_card_reveal(Some(player)).unwrap();
+    }
+    game.end_card_reveal(None).unwrap();
+    assert!(matches!(game.state, GameState::GameOver(WinCondition::AnarchistChaos)));
+    assert_eq!(game.player_has_won(1), true);
+    assert_eq!(game.player_has_won(0), false);
+}
+
+#[test]
+fn simultaneous_wins_use_priority() {
+    let house_rules = HouseRules {
+        anarchist_chaos_win: Some(2),
+        ..Default::default()
+    };
+    let opts = GameOptions {
+        communists: true,
+        anarchist: true,
+        house_rules,
+        ..Default::default()
+    };
+    let mut game = scenario(opts, &ANARCHIST_ROLES, 4, 1);
+    game.chaos_policies = 1;
+    reveal_chaos_policy(&mut game, Liberal);
+
+    for player in 0..6 {
+        game.end_card_reveal(Some(player)).unwrap();
+    }
+    game.end_card_reveal(None).unwrap();
+    assert!(matches!(
+        game.state,
+        GameState::GameOver(WinCondition::LiberalPolicyTrack)
+    ));
+    assert_eq!(game.secondary_conditions(), &[WinCondition::AnarchistChaos]);
+}
+
+#[test]
+fn outcome_for_each_player() {
+    let opts = GameOptions {
+        communists: true,
+        anarchist: true,
+        ..Default::default()
+    };
+    let mut game = scenario(opts, &ANARCHIST_ROLES, 4, 1);
+    reveal_chaos_policy(&mut game, Liberal);
+
+    for player in 0..6 {
+        game.end_card_reveal(Some(player)).unwrap();
+    }
+    game.end_card_reveal(None).unwrap();
+    assert_eq!(game.outcome_for_player(0), Some(WinStatus::Won));
+    assert_eq!(game.outcome_for_player(1), Some(WinStatus::SurvivedNeutral));
+    assert_eq!(game.outcome_for_player(3), Some(WinStatus::Lost));
+}
+
+#[test]
+fn eligible_chancellors_5players() {
+    let mut game = Game {
+        opts: GameOptions::default(),
+        board: super::board::Board {
+            num_players: 5,
+            liberal_cards: 0,
+            fascist_cards: 0,
+            communist_cards: 0,
+        },
+        deck: Deck::new(false),
+        election_tracker: 0,
+        last_government: Some(Government { president: 0, chancellor: 3 }),
+        players: vec![
+            Player::new("ALEX".to_string(), Role::Liberal),
+            Player::new("BOB".to_string(), Role::Liberal),
+            Player::new("CHARLIE".to_string(), Role::Liberal),
+            Player::new("DAVID".to_string(), Role::Fascist),
+            Player::new("ED".to_string(), Role::Hitler),
+        ],
+        presidential_turn: 0,
+        next_president: None,
+        rng: ChaCha8Rng::seed_from_u64(0),
+        state: GameState::CardReveal {
+            result: Fascist,
+            chaos: false,
+            confirmations: Confirmations::new(5),
+            board_ready: false,
+        },
+        radicalised: false,
+        assassination: crate::AssassinationState::Unused,
+        undo: None,
+        round: 0,
+        bribed: false,
+        chaos_policies: 0,
+        secondary_conditions: vec![],
+        seed: 0,
+        log: vec![],
+        changed_seats: None,
+    };
+
+    for i in 0..5 {
+        game.end_card_reveal(Some(i)).unwrap();
+    }
+    game.end_card_reveal(None).unwrap();
+
+    let GameState::Election {
+        president,
+        chancellor,
+        eligible_chancellors,
+        votes,
+        fallback,
+    } = game.state
+    else {
+        panic!("Expected an election");
+    };
+
+    assert_eq!(president, 1);
+    assert_eq!(chancellor, None);
+    assert_eq!(eligible_chancellors.includes(0), true);
+    assert_eq!(eligible_chancellors.includes(1), false);
+    assert_eq!(eligible_chancellors.includes(2), true);
+    assert_eq!(eligible_chancellors.includes(3), false);
+    assert_eq!(eligible_chancellors.includes(4), true);
+    assert_eq!(votes.outcome(), None);
+    assert_eq!(fallback, None);
+}
+
+#[test]
+fn faction_chat_membership() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed", "Fred"].map(|s| s.into());
+    let opts = GameOptions { communists: true, ..Default::default() };
+    let game = Game::new(opts, &players, 0).unwrap();
+
+    for (idx, player) in game.players.iter().enumerate() {
+        assert_eq!(game.can_chat(idx, ChatScope::All), true);
+        assert_eq!(game.can_chat(idx, ChatScope::Board), false);
+        assert_eq!(
+            game.can_chat(idx, ChatScope::Communists),
+            player.role == Role::Communist
+        );
+        // With only one ordinary fascist, Hitler knows who they are
+        assert_eq!(
+            game.can_chat(idx, ChatScope::Fascists),
+            matches!(player.role, Role::Fascist | Role::Hitler)
+        );
+    }
+    assert_eq!(game.chat_members(ChatScope::Fascists).len(), 2);
+}
+
+#[test]
+fn radicalised_players_are_kept_out_of_the_communist_chat() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed", "Fred"].map(|s| s.into());
+    let opts = GameOptions { communists: true, ..Default::default() };
+    let mut game = Game::new(opts, &players, 0).unwrap();
+    let communists = game.chat_members(ChatScope::Communists);
+
+    let idx = game.players.iter().position(|p| p.role == Role::Liberal).unwrap();
+    assert!(game.players[idx].radicalise());
+    assert_eq!(game.players[idx].role, Role::Communist);
+    assert_eq!(game.can_chat(idx, ChatScope::Communists), false);
+    assert_eq!(game.chat_members(ChatScope::Communists), communists);
+}
+
+#[test]
+fn nomination_timeout_fails_election() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    for i in 0..5 {
+        game.end_night_round(i).unwrap();
+    }
+
+    let Some(TimedPhase::Nomination { president }) = game.timed_phase() else {
+        panic!("Expected a nomination");
+    };
+    game.resolve_timeout().unwrap();
+
+    assert_eq!(game.election_tracker, 1);
+    assert!(matches!(
+        game.get_board_prompt(),
+        BoardPrompt::ElectionFailed { tracker: 1, chaos: false }
+    ));
+    assert_eq!(game.timed_phase(), None);
+    game.apply(None, Action::EndElectionFailed).unwrap();
+    let Some(TimedPhase::Nomination { president: next }) = game.timed_phase() else {
+        panic!("Expected a nomination");
+    };
+    assert_eq!(next, (president + 1) % 5);
+
+    game.election_tracker = 2;
+    game.resolve_timeout().unwrap();
+    assert!(matches!(
+        game.get_board_prompt(),
+        BoardPrompt::ElectionFailed { tracker: 3, chaos: true }
+    ));
+    game.apply(None, Action::EndElectionFailed).unwrap();
+    assert!(matches!(game.state, GameState::CardReveal { chaos: true, .. }));
+}
+
+#[test]
+fn monarchist_election_is_timed() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    let election = GameState::MonarchistElection {
+        monarchist: 2,
+        last_president: 0,
+        monarchist_chancellor: None,
+        president_chancellor: None,
+        eligible_chancellors: EligiblePlayers::only(&[1, 3, 4]),
+        votes: MonarchistVotes::new(5, 2),
+    };
+    game.state = election.clone();
+    assert_eq!(
+        game.timed_phase(),
+        Some(TimedPhase::MonarchistNomination { nominator: 2 })
+    );
+    game.choose_player(2, 1).unwrap();
+    assert_eq!(
+        game.timed_phase(),
+        Some(TimedPhase::MonarchistNomination { nominator: 0 })
+    );
+    game.resolve_timeout().unwrap();
+    assert_eq!(game.election_tracker, 1);
+    assert!(matches!(game.state, GameState::ElectionFailed { tracker: 1 }));
+
+    // Missing votes go with the monarchist's selection, as a tie would
+    game.state = election;
+    game.choose_player(2, 1).unwrap();
+    game.choose_player(0, 3).unwrap();
+    game.choose_player(0, 3).unwrap();
+    game.choose_player(1, 3).unwrap();
+    assert_eq!(game.timed_phase(), Some(TimedPhase::MonarchistVote { monarchist: 2 }));
+    game.resolve_timeout().unwrap();
+    assert_eq!(game.timed_phase(), None);
+    let GameState::MonarchistElection { votes, .. } = &game.state else {
+        panic!("Expected the monarchist election");
+    };
+    assert_eq!(votes.outcome(), Some(true));
+}
+
+#[test]
+fn communist_session_progresses_on_timeout() {
+    let players = ["A", "B", "C", "D", "E", "F", "G", "H"].map(|s| s.into());
+    let opts = GameOptions { communists: true, ..Default::default() };
+    let mut game = Game::new(opts, &players, 0).unwrap();
+    game.last_government = Some(Government { president: 0, chancellor: 1 });
+
+    game.start_executive_action(ExecutiveAction::Radicalisation);
+    assert_eq!(game.timed_phase(), Some(TimedPhase::CommunistStart));
+    game.resolve_timeout().unwrap();
+    assert!(matches!(game.state, GameState::ChoosePlayer { .. }));
+
+    game.state = GameState::CommunistEnd {
+        action: ExecutiveAction::Radicalisation,
+        chosen_player: None,
+    };
+    assert_eq!(game.timed_phase(), Some(TimedPhase::CommunistEnd));
+    game.resolve_timeout().unwrap();
+    assert!(matches!(game.state, GameState::ActionReveal { .. }));
+}
+
+#[test]
+fn boardless_game_moves_on_without_board() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let opts = GameOptions { boardless: true, ..Default::default() };
+    let mut game = Game::new(opts, &players, 0).unwrap();
+    for i in 0..5 {
+        game.apply(Some(i), Action::EndNightRound).unwrap();
+    }
+    let Some(TimedPhase::Nomination { president }) = game.timed_phase() else {
+        panic!("Expected a nomination");
+    };
+    let chancellor = (president + 1) % 5;
+    game.apply(Some(president), Action::ChoosePlayer { player: chancellor })
+        .unwrap();
+    for i in 0..5 {
+        game.apply(Some(i), Action::CastVote { vote: true }).unwrap();
+    }
+
+    assert!(matches!(game.state, GameState::LegislativeSession { .. }));
+    assert_eq!(game.board_actions(), vec![]);
+    assert!(matches!(
+        game.get_player_update(0).board_prompt,
+        Some(BoardPrompt::LegislativeSession { veto_available: false, cards: 3, .. })
+    ));
+}
+
+#[test]
+fn undo_nomination_and_vote() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let opts = GameOptions { undo_window: Some(10), ..Default::default() };
+    let mut game = Game::new(opts, &players, 0).unwrap();
+    for i in 0..5 {
+        game.end_night_round(i).unwrap();
+    }
+    let Some(TimedPhase::Nomination { president }) = game.timed_phase() else {
+        panic!("Expected a nomination");
+    };
+    let chancellor = (president + 1) % 5;
+
+    game.choose_player(president, chancellor).unwrap();
+    assert_eq!(game.undo_player(), Some(president));
+    game.undo(president).unwrap();
+    assert!(matches!(game.timed_phase(), Some(TimedPhase::Nomination { .. })));
+
+    game.choose_player(president, chancellor).unwrap();
+    game.cast_vote(0, true).unwrap();
+    assert!(game.undo(president).is_err());
+    game.undo(0).unwrap();
+    assert_eq!(game.undo_player(), None);
+
+    // The final vote decides the election, so can't be undone
+    for i in 0..5 {
+        game.cast_vote(i, true).unwrap();
+    }
+    assert_eq!(game.undo_player(), None);
+}
+
+#[test]
+fn board_actions_are_rejected_once_the_game_is_over() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    game.force_outcome(WinCondition::LiberalPolicyTrack);
+    assert_eq!(game.board_actions(), vec![]);
+    for action in [
+        Action::EndCardReveal,
+        Action::Timeout,
+        Action::ExpireUndo,
+        Action::ForceAdvance,
+    ] {
+        assert!(matches!(
+            game.apply(None, action),
+            Err(GameError::WrongPhase { actual: Phase::GameOver, .. })
+        ));
+    }
+}
+
+#[test]
+fn president_discard_cannot_be_undone() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let opts = GameOptions { undo_window: Some(10), ..Default::default() };
+    let mut game = Game::new(opts, &players, 0).unwrap();
+    for i in 0..5 {
+        game.end_night_round(i).unwrap();
+    }
+    let Some(TimedPhase::Nomination { president }) = game.timed_phase() else {
+        panic!("Expected a nomination");
+    };
+    let chancellor = (president + 1) % 5;
+    game.choose_player(president, chancellor).unwrap();
+    for i in 0..5 {
+        game.cast_vote(i, true).unwrap();
+    }
+    game.end_voting().unwrap();
+
+    // The chancellor is shown the remaining cards as soon as the president discards, so it cannot be taken back
+    game.discard_policy(president, 0).unwrap();
+    assert_eq!(game.undo_player(), None);
+    assert!(game.undo(president).is_err());
+    assert!(matches!(game.timed_phase(), Some(TimedPhase::ChancellorDiscard { .. })));
+}
+
+#[test]
+fn options_problems_are_listed() {
+    let opts = GameOptions { anarchist: true, ..Default::default() };
+    assert_eq!(opts.problems(8), vec![OptionsProblem::AnarchistRequiresCommunists]);
+
+    let opts = GameOptions {
+        preset: Some(RolePreset::XlFull),
+        ..Default::default()
+    };
+    assert_eq!(
+        opts.problems(8),
+        vec![
+            OptionsProblem::TooFewPlayers { min_players: 9 },
+            OptionsProblem::RoleNeedsMorePlayers { role: Role::Anarchist, min_players: 9 }
+        ]
+    );
+    assert_eq!(
+        opts.problems(17),
+        vec![OptionsProblem::TooManyPlayers { max_players: 16 }]
+    );
+    assert!(opts.validate(9).is_ok());
+    assert!(matches!(opts.validate(6), Err(GameError::OptionsValidation(_))));
+}
+
+#[test]
+fn substitution_keeps_role_and_replays() {
+    let opts = GameOptions::default();
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(opts, &players, 3).unwrap();
+    for player in 0..5 {
+        game.apply(Some(player), Action::EndNightRound).unwrap();
+    }
+    let role = game.players[2].role;
+    assert!(matches!(game.substitute(2, "Bob"), Err(GameError::NameTaken)));
+    game.substitute(2, "Chris").unwrap();
+    assert_eq!(game.find_player("Chris").unwrap(), 2);
+    assert_eq!(game.players[2].role, role);
+    assert_eq!(game.substitutions().next().unwrap().previous_name, "Charlie");
+    assert!(matches!(game.log().last(), Some(LogEntry::Substitution(_))));
+
+    // A substitution made after the last action is replayed too
+    let replayed = Game::replay(opts, &players, game.seed(), game.log()).unwrap();
+    assert_eq!(replayed.find_player("Chris").unwrap(), 2);
+    assert_eq!(replayed.state_hash(), game.state_hash());
+
+    let president = game.presidential_turn;
+    let chancellor = (president + 1) % 5;
+    game.apply(Some(president), Action::ChoosePlayer { player: chancellor })
+        .unwrap();
+    let replayed = Game::replay(opts, &players, game.seed(), game.log()).unwrap();
+    assert_eq!(replayed.state_hash(), game.state_hash());
+}
+
+#[test]
+fn boardless_games_cannot_include_the_monarchist() {
+    let opts = GameOptions {
+        boardless: true,
+        monarchist: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        opts.problems(7),
+        vec![OptionsProblem::RoleNeedsBoard { role: Role::Monarchist }]
+    );
+    assert!(GameOptions { boardless: false, ..opts }.validate(7).is_ok());
+}
+
+#[test]
+fn house_rules_need_their_roles() {
+    let house_rules = HouseRules { capitalist_bribe: true, ..Default::default() };
+    let opts = GameOptions { house_rules, ..Default::default() };
+    assert_eq!(
+        opts.problems(5),
+        vec![OptionsProblem::RuleNeedsRole {
+            rule: HouseRule::CapitalistBribe,
+            role: Role::Capitalist
+        }]
+    );
+    assert!(GameOptions { capitalist: true, ..opts }.validate(6).is_ok());
+
+    let json = serde_json::to_value(opts).unwrap();
+    assert_eq!(json["capitalist_bribe"], true);
+    assert_eq!(serde_json::from_value::<GameOptions>(json).unwrap(), opts);
+}
+
+#[test]
+fn scenarios_deal_identical_games() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let opts = GameOptions {
+        scenario: Some(Scenario::IronFist),
+        communists: true,
+        ..Default::default()
+    };
+    let seed = Scenario::IronFist.seed();
+    let game = Game::new(opts, &players, seed).unwrap();
+    assert_eq!(game.state_hash(), Game::new(opts, &players, seed).unwrap().state_hash());
+    assert_eq!(game.options().communists, false);
+    assert_eq!(game.deck.peek_three(), [Fascist, Fascist, Fascist]);
+
+    let opts = GameOptions {
+        scenario: Some(Scenario::RedDawn),
+        ..Default::default()
+    };
+    let players = [
+        "Alex", "Bob", "Charlie", "David", "Ed", "Fred", "George", "Harry", "Isaac",
+    ]
+    .map(|s| s.into());
+    let game = Game::new(opts, &players, Scenario::RedDawn.seed()).unwrap();
+    assert_eq!(game.presidential_turn, 0);
+    assert_eq!(Scenario::catalogue().len(), Scenario::ALL.len());
+}
+
+#[test]
+fn rotation_starts_from_the_current_president() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    for player in 0..5 {
+        game.end_night_round(player).unwrap();
+    }
+    let president = game.presidential_turn;
+    let expected: Vec<_> = (0..5).map(|n| (president + n) % 5).collect();
+    assert_eq!(game.get_board_update().rotation, expected);
+
+    let special = (president + 3) % 5;
+    game.next_president = Some(NextPresident::Normal { player: special, fallback: None });
+    game.players[(president + 2) % 5].alive = false;
+    let rotation = game.get_board_update().rotation;
+    assert_eq!(rotation, vec![president, special, (president + 1) % 5, special]);
+}
+
+#[test]
+fn secret_ballots_hide_votes_until_decided() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let house_rules = HouseRules { secret_ballots: true, ..Default::default() };
+    let mut game = Game::new(GameOptions { house_rules, ..Default::default() }, &players, 0).unwrap();
+    for player in 0..5 {
+        game.end_night_round(player).unwrap();
+    }
+    let president = game.presidential_turn;
+    game.choose_player(president, (president + 1) % 5).unwrap();
+    game.cast_vote(0, true).unwrap();
+    let BoardPrompt::Election { votes, voted: Some(voted), .. } = game.get_board_prompt() else {
+        panic!("Expected hidden votes");
+    };
+    assert!(votes.iter().all(Option::is_none));
+    assert_eq!(&voted[..2], [true, false]);
+
+    for player in 1..5 {
+        game.cast_vote(player, false).unwrap();
+    }
+    let BoardPrompt::Election { votes, voted: None, .. } = game.get_board_prompt() else {
+        panic!("Expected revealed votes");
+    };
+    assert_eq!(&votes[..2], [Some(true), Some(false)]);
+}
+
+#[test]
+fn votes_can_only_be_changed_when_allowed() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    for allow_vote_change in [false, true] {
+        let mut game = Game::new(GameOptions { allow_vote_change, ..Default::default() }, &players, 0).unwrap();
+        for player in 0..5 {
+            game.end_night_round(player).unwrap();
+        }
+        let president = game.presidential_turn;
+        game.choose_player(president, (president + 1) % 5).unwrap();
+        game.cast_vote(0, false).unwrap();
+
+        let result = game.cast_vote(0, true);
+        assert_eq!(result.is_ok(), allow_vote_change);
+        assert_eq!(matches!(result, Err(GameError::AlreadyVoted)), !allow_vote_change);
+        for player in 1..5 {
+            game.cast_vote(player, false).unwrap();
+        }
+        let BoardPrompt::Election { votes, .. } = game.get_board_prompt() else {
+            panic!("Expected an election");
+        };
+        assert_eq!(votes[0], Some(allow_vote_change));
+        assert!(matches!(game.cast_vote(0, true), Err(GameError::InvalidAction)));
+    }
+}
+
+#[test]
+fn players_outside_government_may_abstain() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let house_rules = HouseRules { abstentions: true, ..Default::default() };
+    let mut game = Game::new(GameOptions { house_rules, ..Default::default() }, &players, 0).unwrap();
+    for player in 0..5 {
+        game.end_night_round(player).unwrap();
+    }
+    let president = game.presidential_turn;
+    let chancellor = (president + 1) % 5;
+    game.choose_player(president, chancellor).unwrap();
+    assert!(game.abstain(president).is_err());
+    assert!(!game.legal_actions(chancellor).contains(&Action::Abstain));
+
+    let others: Vec<_> = (0..5).filter(|p| *p != president && *p != chancellor).collect();
+    assert!(game.legal_actions(others[0]).contains(&Action::Abstain));
+    for player in &others {
+        game.apply(Some(*player), Action::Abstain).unwrap();
+    }
+    game.cast_vote(president, true).unwrap();
+    game.cast_vote(chancellor, false).unwrap();
+    let BoardPrompt::Election { outcome, abstained, .. } = game.get_board_prompt() else {
+        panic!("Expected an election");
+    };
+    assert_eq!(outcome, Some(false));
+    assert_eq!(abstained, others);
+}
+
+#[test]
+fn monarchist_tiebreak_is_reported() {
+    let mut votes = MonarchistVotes::new(4, 0);
+    for (player, vote) in [(0, false), (1, false), (2, true)] {
+        votes.vote(player, vote, false).unwrap();
+    }
+    assert_eq!(votes.decided_by_tiebreak(), false);
+    votes.vote(3, true, false).unwrap();
+    assert_eq!(votes.tally(), [2, 2]);
+    assert_eq!(votes.outcome(), Some(false));
+    assert_eq!(votes.decided_by_tiebreak(), true);
+}
+
+#[test]
+fn night_round_reports_progress() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    game.end_night_round(0).unwrap();
+    game.end_night_round(3).unwrap();
+    let BoardPrompt::Night { progress, .. } = game.get_board_prompt() else {
+        panic!("Expected the night round");
+    };
+    assert_eq!((progress.confirmed, progress.required), (2, 5));
+    assert_eq!(progress.waiting, vec![1, 2, 4]);
+}
+
+#[test]
+fn player_updates_match_individual_updates() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let opts = GameOptions { boardless: true, ..Default::default() };
+    let mut game = Game::new(opts, &players, 0).unwrap();
+    game.apply(Some(0), Action::EndNightRound).unwrap();
+
+    let updates = game.get_player_updates();
+    assert_eq!(updates.len(), 5);
+    for (player, update) in updates.iter().enumerate() {
+        let expected = game.get_player_update(player);
+        assert_eq!(
+            serde_json::to_value(update).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+        assert_eq!(update.legal_actions, game.legal_actions(player));
+    }
+}
+
+#[test]
+fn night_round_tracks_who_has_viewed_their_role() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    game.apply(Some(1), Action::ViewRole).unwrap();
+    assert!(matches!(
+        game.apply(Some(1), Action::ViewRole),
+        Err(GameError::AlreadyConfirmed)
+    ));
+    assert!(matches!(
+        game.get_player_prompt(1),
+        Some(PlayerPrompt::Night { role_viewed: true })
+    ));
+    assert_eq!(game.legal_actions(1), vec![Action::EndNightRound]);
+
+    // Confirming the night round is separate from viewing one's role
+    game.apply(Some(2), Action::EndNightRound).unwrap();
+    let BoardPrompt::Night { progress, roles_viewed } = game.get_board_prompt() else {
+        panic!("Expected the night round");
+    };
+    assert_eq!(progress.waiting, vec![0, 1, 3, 4]);
+    assert_eq!((roles_viewed.confirmed, roles_viewed.required), (1, 5));
+    assert_eq!(roles_viewed.waiting, vec![0, 2, 3, 4]);
+}
+
+#[test]
+fn board_can_force_a_stuck_phase_to_advance() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    assert!(matches!(
+        game.apply(Some(0), Action::ForceAdvance),
+        Err(GameError::InvalidAction)
+    ));
+
+    // One player never confirms the night round, so it is confirmed on their behalf
+    for player in 0..4 {
+        game.apply(Some(player), Action::EndNightRound).unwrap();
+    }
+    game.apply(None, Action::ForceAdvance).unwrap();
+    let Some(TimedPhase::Nomination { president }) = game.timed_phase() else {
+        panic!("Expected a nomination");
+    };
+
+    // A nomination which never comes fails the election, as if its timer ran out, and the board then moves on
+    game.apply(None, Action::ForceAdvance).unwrap();
+    assert!(matches!(game.state, GameState::ElectionFailed { .. }));
+    game.apply(None, Action::ForceAdvance).unwrap();
+    let Some(TimedPhase::Nomination { president: next }) = game.timed_phase() else {
+        panic!("Expected a nomination");
+    };
+    assert_ne!(president, next);
+    assert_eq!(
+        game.actions().last().map(|logged| logged.action),
+        Some(Action::ForceAdvance)
+    );
+}
+
+/// Creates a game in which the president has just executed the anarchist, who had already activated their assassination.
+fn anarchist_executed_mid_assassination(posthumous_assassination: bool) -> Game {
+    let house_rules = HouseRules {
+        posthumous_assassination,
+        ..Default::default()
+    };
+    let opts = GameOptions {
+        anarchist: true,
+        house_rules,
+        ..Default::default()
+    };
+    let mut game = scenario(opts, &ANARCHIST_ROLES, 0, 4);
+    game.last_government = Some(Government { president: 0, chancellor: 3 });
+    game.state = GameState::ActionReveal {
+        action: ExecutiveAction::Execution,
+        chosen_player: Some(1),
+        confirmations: Confirmations::new(6),
+    };
+    game.assassination = crate::AssassinationState::Activated { anarchist: 1 };
+    game
+}
+
+#[test]
+fn executing_the_anarchist_cancels_their_assassination() {
+    let mut game = anarchist_executed_mid_assassination(false);
+    game.apply(None, Action::EndExecutiveAction).unwrap();
+    assert!(!game.players[1].alive);
+    assert!(matches!(
+        game.get_board_prompt(),
+        BoardPrompt::AssassinationCancelled { anarchist: 1 }
+    ));
+    assert!(matches!(game.get_player_prompt(1), Some(PlayerPrompt::Dead)));
+    assert!(game.apply(Some(1), Action::ChoosePlayer { player: 0 }).is_err());
+
+    game.apply(None, Action::EndAssassination).unwrap();
+    assert!(matches!(game.state, GameState::Election { president: 2, .. }));
+    assert_eq!(game.num_players_alive(), 5);
+}
+
+#[test]
+fn executed_anarchist_may_still_assassinate_under_house_rule() {
+    let mut game = anarchist_executed_mid_assassination(true);
+    game.apply(None, Action::EndExecutiveAction).unwrap();
+    assert!(matches!(
+        game.get_board_prompt(),
+        BoardPrompt::Assassination { anarchist: 1, chosen_player: None }
+    ));
+    assert!(matches!(
+        game.get_player_prompt(1),
+        Some(PlayerPrompt::ChoosePlayer { kind: ChoosePlayerKind::Execute, .. })
+    ));
+
+    game.apply(Some(1), Action::ChoosePlayer { player: 0 }).unwrap();
+    game.apply(None, Action::EndAssassination).unwrap();
+    assert!(!game.players[0].alive);
+    assert!(matches!(game.get_player_prompt(1), Some(PlayerPrompt::Dead)));
+    assert!(matches!(game.state, GameState::Election { president: 2, .. }));
+}
+
+#[test]
+fn special_election_falls_back_when_its_president_is_assassinated() {
+    let cases = [
+        (
+            NextPresident::Normal { player: 2, fallback: None },
+            1,
+            PresidencyFallback::NomineeDied { nominee: 2 },
+        ),
+        (
+            NextPresident::Monarchist { monarchist: 2, last_president: 0 },
+            1,
+            PresidencyFallback::MonarchistDied { monarchist: 2 },
+        ),
+        (
+            NextPresident::Monarchist { monarchist: 5, last_president: 2 },
+            5,
+            PresidencyFallback::PresidentDied { president: 2 },
+        ),
+    ];
+    for (next_president, expected_president, expected_fallback) in cases {
+        // The anarchist assassinates the player chosen for the special election before it begins
+        let mut game = anarchist_executed_mid_assassination(false);
+        game.players[1].alive = true;
+        game.state = GameState::Assassination {
+            anarchist: 1,
+            chosen_player: Some(2),
+            cancelled: false,
+        };
+        game.next_president = Some(next_president);
+        game.apply(None, Action::EndAssassination).unwrap();
+
+        let BoardPrompt::Election { president, fallback, .. } = game.get_board_prompt() else {
+            panic!("Expected an election");
+        };
+        assert_eq!(president, expected_president);
+        assert_eq!(fallback, Some(expected_fallback));
+    }
+}
+
+/// Draws a policy from the deck and puts the game into its reveal, from which an executive action may begin.
+fn reveal_policy(game: &mut Game, result: super::Party) {
+    game.deck.stack(&[result]);
+    game.state = GameState::CardReveal {
+        result: game.deck.draw_one(),
+        chaos: false,
+        confirmations: Confirmations::new(game.num_players_alive()),
+        board_ready: true,
+    };
+}
+
+#[test]
+fn invariant_violations_are_detected() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    for player in 0..5 {
+        game.apply(Some(player), Action::EndNightRound).unwrap();
+    }
+    assert_eq!(game.invariant_violations(), vec![]);
+
+    let GameState::Election { president, .. } = game.state else {
+        panic!("Expected an election");
+    };
+    game.players[president].alive = false;
+    assert_eq!(
+        game.invariant_violations(),
+        vec![InvariantViolation::DeadInGovernment(president)]
+    );
+    game.players[president].alive = true;
+
+    let card = game.deck.draw_one();
+    assert_eq!(game.invariant_violations(), vec![InvariantViolation::TooFewCards(card)]);
+    game.deck.discard(card);
+    assert_eq!(game.invariant_violations(), vec![]);
+    game.deck.discard(card);
+    assert_eq!(
+        game.invariant_violations(),
+        vec![InvariantViolation::TooManyCards(card)]
+    );
+}
+
+#[test]
+fn rejected_actions_say_why() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    assert!(matches!(
+        game.apply(None, Action::EndVoting),
+        Err(GameError::WrongPhase { actual: Phase::Night, .. })
+    ));
+    for player in 0..5 {
+        game.apply(Some(player), Action::EndNightRound).unwrap();
+    }
+
+    let GameState::Election { president, .. } = game.state else {
+        panic!("Expected an election");
+    };
+    let other = (president + 1) % 5;
+    assert!(matches!(
+        game.apply(Some(other), Action::ChoosePlayer { player: president }),
+        Err(GameError::NotYourTurn)
+    ));
+
+    game.players[other].alive = false;
+    let err = game.apply(Some(other), Action::EndCardReveal).unwrap_err();
+    assert!(matches!(err, GameError::DeadPlayer));
+    assert_eq!(err.code(), "dead_player");
+}
+
+#[test]
+fn nomination_explains_who_is_ineligible() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed", "Fred", "Greg"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    let president = game.next_player(game.presidential_turn);
+    let [last_president, last_chancellor, dead] = [1, 2, 3].map(|n| (president + n) % 7);
+    game.last_government = Some(Government {
+        president: last_president,
+        chancellor: last_chancellor,
+    });
+    game.players[dead].alive = false;
+    for player in 0..7 {
+        game.end_night_round(player).unwrap();
+    }
+
+    let Some(PlayerPrompt::ChoosePlayer { options, ineligibility_reasons, .. }) = game.get_player_prompt(president)
+    else {
+        panic!("Expected a nomination");
+    };
+    assert_eq!(options.len(), 3);
+    let mut reasons = ineligibility_reasons;
+    reasons.sort_by_key(|(player, _)| *player);
+    let mut expected = vec![
+        (president, IneligibilityReason::President),
+        (last_president, IneligibilityReason::LastPresident),
+        (last_chancellor, IneligibilityReason::LastChancellor),
+        (dead, IneligibilityReason::Dead),
+    ];
+    expected.sort_by_key(|(player, _)| *player);
+    assert_eq!(reasons, expected);
+}
+
+#[test]
+fn overlay_shows_the_elected_government() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    for player in 0..5 {
+        game.end_night_round(player).unwrap();
+    }
+    let president = game.presidential_turn;
+    let chancellor = (president + 1) % 5;
+    game.choose_player(president, chancellor).unwrap();
+    let overlay = game.get_overlay_update();
+    assert_eq!(overlay.phase, Phase::Election);
+    assert_eq!(overlay.government, None);
+
+    for player in 0..5 {
+        game.cast_vote(player, true).unwrap();
+    }
+    game.end_voting().unwrap();
+    let overlay = game.get_overlay_update();
+    assert_eq!(overlay.phase, Phase::LegislativeSession);
+    assert_eq!(overlay.liberal_cards, 0);
+    assert_eq!(overlay.communist_cards, None);
+    let names = (players[president].clone(), players[chancellor].clone());
+    assert_eq!(overlay.government, Some(names));
+}
+
+#[test]
+fn policy_peak_is_acknowledged_and_remembered() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let opts = GameOptions {
+        remember_policy_peaks: true,
+        ..Default::default()
+    };
+    let mut game = Game::new(opts, &players, 0).unwrap();
+    game.last_government = Some(Government { president: 0, chancellor: 1 });
+    reveal_policy(&mut game, Fascist);
+    game.start_executive_action(ExecutiveAction::PolicyPeak);
+
+    let cards = game.deck.peek_three();
+    assert_eq!(game.legal_actions(0), vec![Action::AcknowledgePolicyPeak]);
+    assert!(matches!(
+        game.apply(Some(1), Action::AcknowledgePolicyPeak),
+        Err(GameError::NotYourTurn)
+    ));
+    // The president cannot skip the acknowledgment, which would leave what they saw unrecorded
+    assert!(matches!(
+        game.apply(Some(0), Action::EndExecutiveAction),
+        Err(GameError::InvalidAction)
+    ));
+    game.apply(Some(0), Action::AcknowledgePolicyPeak).unwrap();
+    assert_eq!(game.actions().last().unwrap().action, Action::AcknowledgePolicyPeak);
+    assert!(matches!(game.state, GameState::Election { .. }));
+
+    let peaks = game.get_player_update(0).policy_peaks;
+    assert_eq!(peaks.len(), 1);
+    assert_eq!(peaks[0].cards, cards);
+    assert!(game.get_player_update(1).policy_peaks.is_empty());
+}
+
+#[test]
+fn forced_policy_peaks_are_not_remembered() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let opts = GameOptions {
+        remember_policy_peaks: true,
+        ..Default::default()
+    };
+    let mut game = Game::new(opts, &players, 0).unwrap();
+    game.last_government = Some(Government { president: 0, chancellor: 1 });
+    reveal_policy(&mut game, Fascist);
+    game.start_executive_action(ExecutiveAction::PolicyPeak);
+
+    game.apply(None, Action::ForceAdvance).unwrap();
+    assert!(matches!(game.state, GameState::Election { .. }));
+    assert!(game.get_player_update(0).policy_peaks.is_empty());
+}
+
+#[test]
+fn forcing_a_card_reveal_does_not_force_the_policy_peak_it_grants() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    game.last_government = Some(Government { president: 4, chancellor: 1 });
+    game.board.fascist_cards = 2;
+    reveal_policy(&mut game, Fascist);
+    for player in [0, 1, 2, 4] {
+        game.end_card_reveal(Some(player)).unwrap();
+    }
+
+    // The last player to confirm the card sits before the president, who is then left to see the peak
+    game.apply(None, Action::ForceAdvance).unwrap();
+    assert!(matches!(
+        game.state,
+        GameState::ActionReveal { action: ExecutiveAction::PolicyPeak, .. }
+    ));
+    assert!(game.legal_actions(4).contains(&Action::AcknowledgePolicyPeak));
+}
+
+#[test]
+fn investigations_may_reveal_roles() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    for reveals in [InvestigationReveals::Party, InvestigationReveals::Role] {
+        let opts = GameOptions {
+            investigation_reveals: reveals,
+            ..Default::default()
+        };
+        let mut game = Game::new(opts, &players, 0).unwrap();
+        // A liberal president knows nothing of Hitler beforehand
+        let president = (0..5).find(|p| game.players[*p].role == Role::Liberal).unwrap();
+        let target = (0..5).find(|p| game.players[*p].role == Role::Hitler).unwrap();
+        let chancellor = (0..5).find(|p| ![president, target].contains(p)).unwrap();
+        game.last_government = Some(Government { president, chancellor });
+        reveal_policy(&mut game, Fascist);
+        game.start_executive_action(ExecutiveAction::InvestigatePlayer);
+        game.choose_player(president, target).unwrap();
+
+        let expected = match reveals {
+            InvestigationReveals::Party => InvestigationResult::Party(Fascist),
+            InvestigationReveals::Role => InvestigationResult::Role(Role::Hitler),
+        };
+        let update = game.get_player_update(president);
+        assert_eq!(update.others[target], expected);
+        assert_eq!(update.knowledge_log.last().unwrap().result, expected);
+        let Some(PlayerPrompt::InvestigatePlayer { party, role, .. }) = update.prompt else {
+            panic!("Expected the investigation result");
+        };
+        assert_eq!(party, Fascist);
+        assert_eq!(role, (reveals == InvestigationReveals::Role).then_some(Role::Hitler));
+    }
+}
+
+#[test]
+fn investigated_players_are_marked_and_excluded() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    for repeat_investigations in [false, true] {
+        let house_rules = HouseRules { repeat_investigations, ..Default::default() };
+        let opts = GameOptions { house_rules, ..Default::default() };
+        let mut game = Game::new(opts, &players, 0).unwrap();
+        game.last_government = Some(Government { president: 0, chancellor: 1 });
+        reveal_policy(&mut game, Fascist);
+        game.start_executive_action(ExecutiveAction::InvestigatePlayer);
+        game.choose_player(0, 2).unwrap();
+        assert!(game.get_public_players()[2].investigated);
+        assert!(!game.get_public_players()[3].investigated);
+
+        game.last_government = Some(Government { president: 1, chancellor: 0 });
+        reveal_policy(&mut game, Fascist);
+        game.start_executive_action(ExecutiveAction::InvestigatePlayer);
+        let GameState::ChoosePlayer { can_be_selected, .. } = &game.state else {
+            panic!("Expected a player to be chosen");
+        };
+        assert_eq!(can_be_selected.includes(2), repeat_investigations);
+        assert!(can_be_selected.includes(3));
+    }
+}
+
+#[test]
+fn action_targets_follow_the_eligibility_rules() {
+    use ExecutiveAction::*;
+    use Role::{Communist, Fascist, Hitler, Liberal};
+
+    let players = ["A", "B", "C", "D", "E", "F", "G", "H"].map(|s| s.into());
+    let roles = [Liberal, Liberal, Fascist, Liberal, Liberal, Communist, Hitler, Liberal];
+    let opts = GameOptions { communists: true, ..Default::default() };
+    let mut game = Game::new(opts, &players, 0).unwrap();
+    for (player, role) in game.players.iter_mut().zip(roles) {
+        player.role = role;
+    }
+    game.players[3].investigated = true;
+    game.players[4].alive = false;
+    game.players[7].tried_to_radicalise = true;
+    game.last_government = Some(Government { president: 0, chancellor: 1 });
+
+    let cases: &[(ExecutiveAction, bool, &[usize])] = &[
+        (InvestigatePlayer, false, &[1, 2, 5, 6, 7]),
+        (InvestigatePlayer, true, &[1, 2, 3, 5, 6, 7]),
+        (SpecialElection, false, &[1, 2, 3, 5, 6, 7]),
+        (Execution, false, &[1, 2, 3, 5, 6, 7]),
+        (Confession, false, &[0, 1]),
+        (Bugging, false, &[0, 1, 2, 3, 6, 7]),
+        (Radicalisation, false, &[0, 1, 2, 6]),
+        (Congress, false, &[0, 1, 2, 6]),
+        (PolicyPeak, false, &[]),
+        (FiveYearPlan, false, &[]),
+    ];
+    for &(action, repeat_investigations, expected) in cases {
+        game.opts.house_rules.repeat_investigations = repeat_investigations;
+        let targets = game.action_targets(action);
+        let targets = (0..8).filter(|p| targets.includes(*p)).collect::<Vec<_>>();
+        assert_eq!(targets, expected, "{:?}", action);
+    }
+
+    // The prompt offers the same players that may be chosen
+    game.opts.house_rules.repeat_investigations = false;
+    reveal_policy(&mut game, super::Party::Fascist);
+    game.start_executive_action(SpecialElection);
+    let Some(PlayerPrompt::ChoosePlayer { options, .. }) = game.get_player_update(0).prompt else {
+        panic!("Expected the president to choose a player");
+    };
+    assert_eq!(options, ["B", "C", "D", "F", "G", "H"]);
+    assert!(matches!(game.choose_player(0, 0), Err(GameError::InvalidPlayerChoice)));
+    assert!(matches!(game.choose_player(0, 4), Err(GameError::InvalidPlayerChoice)));
+    game.choose_player(0, 1).unwrap();
+}
+
+#[test]
+fn confession_is_revealed_to_the_whole_table() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let mut game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    game.last_government = Some(Government { president: 0, chancellor: 1 });
+    reveal_policy(&mut game, Fascist);
+    game.start_executive_action(ExecutiveAction::Confession);
+    game.choose_player(1, 0).unwrap();
+
+    let party = game.players[0].party();
+    let Some(BoardPrompt::Confession {
+        chosen_player: Some(0),
+        party: Some(shown),
+        progress: Some(progress),
+    }) = game.get_board_update().prompt
+    else {
+        panic!("Expected the confession to be revealed");
+    };
+    assert_eq!(shown, party);
+    assert_eq!((progress.confirmed, progress.required), (0, 5));
+    for player in 1..5 {
+        let update = game.get_player_update(player);
+        let entry = update.knowledge_log.last().unwrap();
+        assert_eq!(entry.source, KnowledgeSource::Confession);
+        assert_eq!(
+            (entry.player.as_str(), entry.result),
+            ("Alex", InvestigationResult::Party(party))
+        );
+        assert!(matches!(update.prompt, Some(PlayerPrompt::Confession { .. })));
+    }
+
+    // Every player must be ready before the game moves on
+    assert!(game.end_executive_action(None).is_err());
+    for player in 0..5 {
+        assert_eq!(game.state.phase(), Phase::ActionReveal);
+        game.end_executive_action(Some(player)).unwrap();
+    }
+    assert_ne!(game.state.phase(), Phase::ActionReveal);
+}
+
+#[test]
+fn chaos_rule_decides_the_power_of_a_chaos_policy() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let government = Government { president: 0, chancellor: 1 };
+    let cases = [
+        (ChaosRule::NoPower, Some(government), Phase::Election, false, 16),
+        (ChaosRule::Reshuffle, Some(government), Phase::Election, false, 14),
+        (ChaosRule::GrantsPower, Some(government), Phase::ActionReveal, true, 16),
+        (ChaosRule::GrantsPower, None, Phase::Election, false, 16),
+    ];
+    for (chaos_rule, last_government, phase, term_limited, deck) in cases {
+        let opts = GameOptions { chaos_rule, ..Default::default() };
+        let mut game = Game::new(opts, &players, 0).unwrap();
+        game.board.fascist_cards = 2;
+        game.deck.stack(&[Fascist]);
+        game.last_government = last_government;
+        game.election_tracker = 3;
+        game.state = GameState::ElectionFailed { tracker: 3 };
+        game.end_election_failed().unwrap();
+        game.end_card_reveal(None).unwrap();
+        for player in 0..5 {
+            game.end_card_reveal(Some(player)).unwrap();
+        }
+
+        assert_eq!(game.board.fascist_cards, 3);
+        assert_eq!(game.state.phase(), phase, "{:?}", chaos_rule);
+        assert_eq!(game.last_government.is_some(), term_limited, "{:?}", chaos_rule);
+        assert_eq!(game.deck.count(), deck, "{:?}", chaos_rule);
+    }
+}
+
+#[test]
+fn asynchronous_games_have_turn_deadlines() {
+    let president = 0;
+    let asynchronous = AsyncOptions { turn_deadline_hours: Some(24) };
+    let mut opts = GameOptions {
+        asynchronous: Some(asynchronous),
+        ..Default::default()
+    };
+    assert_eq!(opts.time_limit(TimedPhase::Nomination { president }), Some(24 * 3600));
+    opts.timers.nominate = Some(60);
+    assert_eq!(opts.time_limit(TimedPhase::Nomination { president }), Some(60));
+    opts.asynchronous = None;
+    assert_eq!(opts.time_limit(TimedPhase::Vote { president, chancellor: 1 }), None);
+}
+
+#[test]
+fn board_shows_deck_odds_when_enabled() {
+    let players = ["Alex", "Bob", "Charlie", "David", "Ed"].map(|s| s.into());
+    let game = Game::new(GameOptions::default(), &players, 0).unwrap();
+    assert_eq!(game.get_board_update().deck_odds, None);
+
+    let opts = GameOptions { show_deck_odds: true, ..Default::default() };
+    let mut game = Game::new(opts, &players, 0).unwrap();
+    let odds = DeckOdds { liberal: 6, fascist: 11, communist: None };
+    assert_eq!(game.get_board_update().deck_odds, Some(odds));
+
+    game.board.play_card(Liberal);
+    let odds = DeckOdds { liberal: 5, fascist: 11, communist: None };
+    assert_eq!(game.get_board_update().deck_odds, Some(odds));
+}
+
+#[test]
+fn undeclared_transitions_are_rejected() {
+    use Phase::*;
+
+    // The transition! macro refuses to compile a change of phase which the table does not declare
+    assert!(Night.may_become(Election));
+    assert!(!Night.may_become(GameOver));
+    assert!(!Congress.may_become(Election));
+    assert!(!BuggingVote.may_become(CommunistEnd));
+    for phase in Phase::IN_PLAY {
+        assert!(!GameOver.may_become(phase), "the game resumed as {:?}", phase);
+    }
+
+    // A change of phase from several phases is rejected if any one of them may not make it
+    assert!(Phase::all_may_become(&[Night, ElectionFailed], Election));
+    assert!(!Phase::all_may_become(&[Night, Congress], Election));
+    // This is checked while compiling, as the macro checks it
+    const { assert!(!Phase::all_may_become(Phase::ROUND_ENDS, Congress)) };
+}
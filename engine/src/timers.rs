@@ -0,0 +1,125 @@
+use super::{Game, GameState, LegislativeSessionTurn};
+use crate::error::GameError;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A phase of the game which can be resolved automatically once its timer runs out.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(tag = "type")]
+pub enum TimedPhase {
+    /// The president is nominating a chancellor.
+    Nomination { president: usize },
+    /// The players are voting on a government.
+    Vote { president: usize, chancellor: usize },
+    /// The monarchist, and then the last president, must each choose a candidate for chancellor.
+    MonarchistNomination { nominator: usize },
+    /// The players are voting between the two candidates for chancellor in a monarchist election.
+    MonarchistVote { monarchist: usize },
+    /// The president must discard a policy.
+    PresidentDiscard { president: usize },
+    /// The chancellor must discard a policy.
+    ChancellorDiscard { chancellor: usize },
+    /// The president must respond to the chancellor's veto request.
+    VetoResponse { president: usize },
+    /// The communists are being woken up for a communist session.
+    CommunistStart,
+    /// The communists are being put back to sleep after a communist session.
+    CommunistEnd,
+}
+
+impl Game {
+    /// Gets the current phase of the game if it is one which can be timed.
+    pub fn timed_phase(&self) -> Option<TimedPhase> {
+        match &self.state {
+            GameState::Election { president, chancellor, votes, .. } => match chancellor {
+                None => Some(TimedPhase::Nomination { president: *president }),
+                Some(chancellor) => votes.outcome().is_none().then_some(TimedPhase::Vote {
+                    president: *president,
+                    chancellor: *chancellor,
+                }),
+            },
+            GameState::MonarchistElection {
+                monarchist,
+                last_president,
+                monarchist_chancellor,
+                president_chancellor,
+                votes,
+                ..
+            } => match (monarchist_chancellor, president_chancellor) {
+                (None, _) => Some(TimedPhase::MonarchistNomination { nominator: *monarchist }),
+                (Some(_), None) => Some(TimedPhase::MonarchistNomination { nominator: *last_president }),
+                (Some(_), Some(_)) => votes
+                    .outcome()
+                    .is_none()
+                    .then_some(TimedPhase::MonarchistVote { monarchist: *monarchist }),
+            },
+            GameState::LegislativeSession { president, chancellor, turn } => match turn {
+                LegislativeSessionTurn::President { .. } => {
+                    Some(TimedPhase::PresidentDiscard { president: *president })
+                }
+                LegislativeSessionTurn::Chancellor { .. } => {
+                    Some(TimedPhase::ChancellorDiscard { chancellor: *chancellor })
+                }
+                LegislativeSessionTurn::VetoRequested { .. } => {
+                    Some(TimedPhase::VetoResponse { president: *president })
+                }
+                LegislativeSessionTurn::VetoApproved => None,
+            },
+            GameState::CommunistStart { .. } => Some(TimedPhase::CommunistStart),
+            GameState::CommunistEnd { .. } => Some(TimedPhase::CommunistEnd),
+            _ => None,
+        }
+    }
+
+    /// Resolves the current phase of the game after its timer has run out:
+    /// a missing nomination counts as a failed election, missing votes count as "nein",
+    /// missing votes in a monarchist election go with the monarchist, as a tie would,
+    /// a random policy is discarded, a pending veto is rejected,
+    /// and the communists are moved into or out of their session as if the board had done so.
+    pub fn resolve_timeout(&mut self) -> Result<(), GameError> {
+        match self.timed_phase().ok_or(GameError::InvalidAction)? {
+            TimedPhase::Nomination { .. } => {
+                self.fail_election();
+                Ok(())
+            }
+            TimedPhase::Vote { .. } => {
+                let GameState::Election { votes, .. } = &mut self.state else {
+                    return Err(GameError::InvalidAction);
+                };
+                for (idx, player) in self.players.iter().enumerate() {
+                    if player.alive && !votes.has_cast(idx) {
+                        votes.vote(idx, false, false)?;
+                    }
+                }
+                Ok(())
+            }
+            TimedPhase::MonarchistNomination { .. } => {
+                self.fail_election();
+                Ok(())
+            }
+            TimedPhase::MonarchistVote { monarchist } => {
+                let GameState::MonarchistElection { votes, .. } = &mut self.state else {
+                    return Err(GameError::InvalidAction);
+                };
+                let vote = votes.vote_of(monarchist).unwrap_or(true);
+                for (idx, player) in self.players.iter().enumerate() {
+                    if player.alive && !votes.has_cast(idx) {
+                        votes.vote(idx, vote, false)?;
+                    }
+                }
+                Ok(())
+            }
+            TimedPhase::PresidentDiscard { president } => {
+                let idx = self.rng.gen_range(0..3);
+                self.discard_policy(president, idx)
+            }
+            TimedPhase::ChancellorDiscard { chancellor } => {
+                let idx = self.rng.gen_range(0..2);
+                self.discard_policy(chancellor, idx)
+            }
+            TimedPhase::VetoResponse { president } => self.reject_veto(president),
+            TimedPhase::CommunistStart => self.end_communist_start(),
+            TimedPhase::CommunistEnd => self.end_communist_end(),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! The phases of the game and which of them may follow each other, so that every change of phase is checked
+//! against a single table rather than relying on each action to move the game somewhere sensible.
+//! Changes of phase are made with the [transition!] macro, which checks them against the table at compile time.
+
+use super::{Game, GameState};
+use crate::error::GameError;
+use serde::{Deserialize, Serialize};
+
+/// A phase of the game, which is a game state without any of its data.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub enum Phase {
+    Night,
+    Election,
+    MonarchistElection,
+    LegislativeSession,
+    ElectionFailed,
+    CardReveal,
+    CommunistStart,
+    PromptMonarchist,
+    ChoosePlayer,
+    Congress,
+    BuggingVote,
+    CommunistEnd,
+    ActionReveal,
+    Assassination,
+    Bribe,
+    GameOver,
+}
+
+impl Phase {
+    /// Every phase in which the game is still being played.
+    pub(crate) const IN_PLAY: [Phase; 15] = [
+        Phase::Night,
+        Phase::Election,
+        Phase::MonarchistElection,
+        Phase::LegislativeSession,
+        Phase::ElectionFailed,
+        Phase::CardReveal,
+        Phase::CommunistStart,
+        Phase::PromptMonarchist,
+        Phase::ChoosePlayer,
+        Phase::Congress,
+        Phase::BuggingVote,
+        Phase::CommunistEnd,
+        Phase::ActionReveal,
+        Phase::Assassination,
+        Phase::Bribe,
+    ];
+
+    /// The phases in which a round may end, so that the next one may be started.
+    pub(crate) const ROUND_ENDS: &'static [Phase] = &[
+        Phase::Night,
+        Phase::ElectionFailed,
+        Phase::CardReveal,
+        Phase::PromptMonarchist,
+        Phase::CommunistEnd,
+        Phase::ActionReveal,
+        Phase::Assassination,
+        Phase::Bribe,
+    ];
+
+    /// The phases in which a policy may be played, being those in which a round may end,
+    /// as the election tracker may force a chaos policy, and the legislative session.
+    pub(crate) const POLICY_PLAYS: &'static [Phase] = &[
+        Phase::Night,
+        Phase::ElectionFailed,
+        Phase::CardReveal,
+        Phase::PromptMonarchist,
+        Phase::CommunistEnd,
+        Phase::ActionReveal,
+        Phase::Assassination,
+        Phase::Bribe,
+        Phase::LegislativeSession,
+    ];
+
+    /// The phases in which the game may be won.
+    pub(crate) const GAME_ENDS: &'static [Phase] = &[
+        Phase::LegislativeSession,
+        Phase::CardReveal,
+        Phase::ActionReveal,
+        Phase::Assassination,
+    ];
+
+    /// Gets the phases the game may move on to from this one, other than staying in the same phase.
+    const fn successors(self) -> &'static [Phase] {
+        use Phase::*;
+
+        // A new round begins with an election, unless an assassination is pending or the election tracker is full
+        match self {
+            Night | ElectionFailed => &[Election, MonarchistElection, Assassination, CardReveal],
+            Election => &[LegislativeSession, ElectionFailed],
+            MonarchistElection => &[LegislativeSession, ElectionFailed],
+            LegislativeSession => &[CardReveal, ElectionFailed, GameOver],
+            CardReveal => &[
+                Election,
+                MonarchistElection,
+                Assassination,
+                ChoosePlayer,
+                PromptMonarchist,
+                ActionReveal,
+                CommunistStart,
+                GameOver,
+            ],
+            CommunistStart => &[ChoosePlayer, Congress],
+            PromptMonarchist => &[Election, MonarchistElection, Assassination, CardReveal, ChoosePlayer],
+            ChoosePlayer => &[BuggingVote, Bribe, ActionReveal, CommunistEnd],
+            Congress => &[CommunistEnd],
+            BuggingVote => &[ActionReveal],
+            CommunistEnd => &[Election, MonarchistElection, Assassination, CardReveal, ActionReveal],
+            ActionReveal => &[
+                Election,
+                MonarchistElection,
+                Assassination,
+                CardReveal,
+                CommunistEnd,
+                GameOver,
+            ],
+            Assassination => &[Election, MonarchistElection, CardReveal, GameOver],
+            Bribe => &[Election, MonarchistElection, Assassination, CardReveal, ActionReveal],
+            GameOver => &[],
+        }
+    }
+
+    /// Returns whether the game may move from this phase to the given one.
+    pub(crate) const fn may_become(self, next: Phase) -> bool {
+        // Phases are compared by discriminant, as `==` cannot be used in a const fn
+        if self as u8 == next as u8 {
+            return true;
+        }
+        let successors = self.successors();
+        let mut idx = 0;
+        while idx < successors.len() {
+            if successors[idx] as u8 == next as u8 {
+                return true;
+            }
+            idx += 1;
+        }
+        false
+    }
+
+    /// Returns whether the game may move from each of the given phases to the given one.
+    pub(crate) const fn all_may_become(from: &[Phase], next: Phase) -> bool {
+        let mut idx = 0;
+        while idx < from.len() {
+            if !from[idx].may_become(next) {
+                return false;
+            }
+            idx += 1;
+        }
+        true
+    }
+}
+
+/// Moves the game into a new state, from one of the given phases, as in
+/// `transition!(self, &[Phase::Congress] => CommunistEnd { action, chosen_player: None })`.
+/// Fails to compile unless the table allows the game to move from every given phase to that of the new state.
+macro_rules! transition {
+    ($game:expr, $from:expr => $state:ident $($fields:tt)?) => {{
+        const _: () = assert!(
+            $crate::transitions::Phase::all_may_become($from, $crate::transitions::Phase::$state),
+            concat!("the game cannot move to ", stringify!($state), " from every phase given"),
+        );
+        $game.transition($from, $crate::GameState::$state $($fields)?)
+    }};
+}
+pub(crate) use transition;
+
+impl GameState {
+    /// Gets the phase of the game this state belongs to.
+    pub(crate) fn phase(&self) -> Phase {
+        match self {
+            GameState::Night { .. } => Phase::Night,
+            GameState::Election { .. } => Phase::Election,
+            GameState::MonarchistElection { .. } => Phase::MonarchistElection,
+            GameState::LegislativeSession { .. } => Phase::LegislativeSession,
+            GameState::ElectionFailed { .. } => Phase::ElectionFailed,
+            GameState::CardReveal { .. } => Phase::CardReveal,
+            GameState::CommunistStart { .. } => Phase::CommunistStart,
+            GameState::PromptMonarchist { .. } => Phase::PromptMonarchist,
+            GameState::ChoosePlayer { .. } => Phase::ChoosePlayer,
+            GameState::Congress => Phase::Congress,
+            GameState::BuggingVote { .. } => Phase::BuggingVote,
+            GameState::CommunistEnd { .. } => Phase::CommunistEnd,
+            GameState::ActionReveal { .. } => Phase::ActionReveal,
+            GameState::Assassination { .. } => Phase::Assassination,
+            GameState::Bribe { .. } => Phase::Bribe,
+            GameState::GameOver(_) => Phase::GameOver,
+        }
+    }
+}
+
+impl Game {
+    /// Moves the game into the given state, expecting it to be in one of the given phases.
+    /// Use the [transition!] macro rather than calling this directly, so the change of phase is checked.
+    /// Undoing an action and forcing the outcome of the game are the only ways to change state without it.
+    pub(crate) fn transition(&mut self, from: &[Phase], state: GameState) {
+        let phase = self.state.phase();
+        if !from.contains(&phase) {
+            tracing::error!(?phase, expected = ?from, to = ?state.phase(), "Game changed phase from an unexpected one");
+        }
+        self.state = state;
+    }
+
+    /// Gets the phase the game is currently in.
+    pub fn phase(&self) -> Phase {
+        self.state.phase()
+    }
+
+    /// The error for an action which can only be performed during the given phases.
+    pub(crate) fn wrong_phase(&self, expected: &[Phase]) -> GameError {
+        GameError::WrongPhase {
+            expected: expected.to_vec(),
+            actual: self.phase(),
+        }
+    }
+
+    /// The error for responding to a bribe out of turn, or outside of a bribe.
+    pub(crate) fn bribe_phase_error(&self) -> GameError {
+        match self.phase() {
+            Phase::Bribe => GameError::NotYourTurn,
+            _ => self.wrong_phase(&[Phase::Bribe]),
+        }
+    }
+}
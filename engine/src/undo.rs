@@ -0,0 +1,52 @@
+use super::{Game, GameState};
+use crate::error::GameError;
+use serde::{Deserialize, Serialize};
+
+/// The game state prior to the most recent undoable action.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct UndoSnapshot {
+    /// The player who performed the action.
+    player: usize,
+    /// The game state before the action was performed.
+    state: GameState,
+}
+
+impl Game {
+    /// Records the current game state so that the given player's next action can be undone.
+    /// Only nominations and votes which don't decide the election are undoable, as every other action reveals
+    /// information to at least one player. Even the president's discard shows the chancellor their cards.
+    pub(super) fn record_undo(&mut self, player: usize) {
+        self.undo = self
+            .opts
+            .undo_window
+            .is_some()
+            .then(|| UndoSnapshot { player, state: self.state.clone() });
+    }
+
+    /// Gets the player who is able to undo their last action, if there is one.
+    pub fn undo_player(&self) -> Option<usize> {
+        let snapshot = self.undo.as_ref()?;
+        let undoable = match (&self.state, &snapshot.state) {
+            (GameState::Election { president, votes, .. }, GameState::Election { president: prev_president, .. }) => {
+                president == prev_president && votes.outcome().is_none()
+            }
+            _ => false,
+        };
+        undoable.then_some(snapshot.player)
+    }
+
+    /// Reverts the last action of the given player.
+    pub fn undo(&mut self, player: usize) -> Result<(), GameError> {
+        if self.undo_player() != Some(player) {
+            return Err(GameError::InvalidAction);
+        }
+        let snapshot = self.undo.take().unwrap();
+        self.state = snapshot.state;
+        Ok(())
+    }
+
+    /// Discards the undo snapshot, such that the last action can no longer be undone.
+    pub fn clear_undo(&mut self) {
+        self.undo = None;
+    }
+}
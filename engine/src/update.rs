@@ -0,0 +1,960 @@
+use super::confirmations::{Confirmations, Progress};
+use super::deck::{DeckOdds, FIVE_YEAR_PLAN};
+use super::locale::Message;
+use super::player::{InvestigationResult, KnowledgeSource, PolicyPeakRecord};
+use super::Action;
+use super::{government::Government, party::Party, Game, GameState, WinCondition, WinStatus, MAX_PLAYERS};
+use crate::{
+    executive_power::ExecutiveAction, player::Role, AssassinationState, InvestigationReveals, LegislativeSessionTurn,
+    Phase, VetoStatus,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::mem::Discriminant;
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct BoardUpdate {
+    pub election_tracker: usize,
+    pub liberal_cards: usize,
+    pub fascist_cards: usize,
+    pub communist_cards: Option<usize>,
+    pub draw_pile: usize,
+    /// How many of each policy have yet to be enacted, if the game shows them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deck_odds: Option<DeckOdds>,
+    pub presidential_turn: usize,
+    /// The alive players in the order they will hold the presidency, starting with the current president.
+    pub rotation: Vec<usize>,
+    pub last_government: Option<Government>,
+    pub prompt: Option<BoardPrompt>,
+    /// The localizable description of the prompt.
+    pub message: Option<Message>,
+    /// A hash of the public game state, which clients can use to detect that they are out of sync.
+    pub state_hash: u64,
+}
+
+/// What a connection to a game is shown of it.
+#[derive(Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum View {
+    /// A player's own view, including their private prompts.
+    Player,
+    /// The shared board, showing everything public.
+    #[default]
+    Board,
+    /// A stream overlay, which shows only the headline figures so that it rarely changes.
+    Overlay,
+}
+
+/// The minimal, public summary of a game in progress, for stream overlays.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct OverlayUpdate {
+    pub phase: Phase,
+    pub liberal_cards: usize,
+    pub fascist_cards: usize,
+    pub communist_cards: Option<usize>,
+    pub election_tracker: usize,
+    /// The names of the president and chancellor of the sitting government, or else the last elected one.
+    pub government: Option<(String, String)>,
+}
+
+/// What a stream overlay is shown of a game at each stage of its lifecycle.
+/// Only a game in progress shows more than its stage, so the overlay does not change as players come and go.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OverlayState {
+    #[default]
+    Lobby,
+    Overlay(OverlayUpdate),
+    Ended,
+    Cancelled,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PlayerUpdate {
+    pub name: String,
+    pub role: Role,
+    pub others: Vec<InvestigationResult>,
+    pub prompt: Option<PlayerPrompt>,
+    /// The localizable description of the prompt.
+    pub message: Option<Message>,
+    /// The actions the player may currently perform.
+    pub legal_actions: Vec<Action>,
+    pub can_undo: bool,
+    /// Everything the player has learned about the other players, in the order it was learned.
+    pub knowledge_log: Vec<KnowledgeEntry>,
+    /// What the board would be displaying, in a game played without a board.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub board_prompt: Option<BoardPrompt>,
+    /// The policies the player saw with each policy peak, if the game reminds presidents of them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policy_peaks: Vec<PolicyPeakRecord>,
+}
+
+/// Something a player has learned about another player, and how they learned it.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct KnowledgeEntry {
+    pub player: String,
+    pub result: InvestigationResult,
+    pub source: KnowledgeSource,
+    pub round: usize,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PublicPlayer {
+    pub name: String,
+    pub alive: bool,
+    pub not_hitler: bool,
+    /// The player's party, if the president who investigated them revealed it publicly.
+    pub confirmed_party: Option<Party>,
+    /// Whether the player has been investigated, and so cannot be investigated again under the official rules.
+    #[serde(default)]
+    pub investigated: bool,
+    /// Whether the player has a live connection to the server, which is maintained by the session.
+    pub connected: bool,
+    /// When the player was last heard from, which is maintained by the session.
+    pub last_seen: Option<DateTime<Utc>>,
+    /// Whether another connection is waiting to take over the player's seat, which is maintained by the session.
+    #[serde(default)]
+    pub claim_pending: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum BoardPrompt {
+    Night {
+        progress: Progress,
+        /// How many players have acknowledged looking at their role card.
+        roles_viewed: Progress,
+    },
+    Election {
+        president: usize,
+        chancellor: Option<usize>,
+        votes: Vec<Option<bool>>,
+        /// Whether each player has voted, while the votes are hidden by secret ballots.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        voted: Option<Vec<bool>>,
+        /// The players who abstained, once the votes are shown.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        abstained: Vec<usize>,
+        outcome: Option<bool>,
+        /// Why the president is not the player who was chosen for this round, if they died beforehand.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fallback: Option<PresidencyFallback>,
+    },
+    SpecialElection {
+        can_hijack: bool,
+        hijacked_by: Option<usize>,
+        chosen_player: Option<usize>,
+    },
+    MonarchistElection {
+        monarchist: usize,
+        president: usize,
+        monarchist_chancellor: Option<usize>,
+        president_chancellor: Option<usize>,
+        votes: Vec<Option<bool>>,
+        /// Whether each player has voted, while the votes are hidden by secret ballots.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        voted: Option<Vec<bool>>,
+        /// The number of votes for the monarchist's chancellor and the president's, unless hidden by secret ballots.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tally: Option<[usize; 2]>,
+        outcome: Option<bool>,
+        /// Whether the votes were tied, so the outcome was decided by the monarchist's vote.
+        #[serde(default)]
+        tiebreak: bool,
+    },
+    LegislativeSession {
+        president: usize,
+        chancellor: usize,
+        phase: LegislativePhase,
+        /// Whether the chancellor may yet call for a veto during this session.
+        veto_available: bool,
+        /// The number of policies the government has yet to discard or enact.
+        cards: usize,
+    },
+    /// The election tracker has advanced. If `chaos` is set, it has reached three and the top policy will be enacted.
+    ElectionFailed {
+        tracker: usize,
+        chaos: bool,
+    },
+    CardReveal {
+        result: Party,
+        chaos: bool,
+        can_end: bool,
+        /// How many players are ready to move on, once the board has finished the reveal.
+        progress: Progress,
+    },
+    InvestigatePlayer {
+        chosen_player: Option<usize>,
+    },
+    PolicyPeak,
+    Execution {
+        chosen_player: Option<usize>,
+    },
+    CommunistSession {
+        action: ExecutiveAction,
+        phase: CommunistSessionPhase,
+        /// How many players are ready to move on, while the outcome is being revealed.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        progress: Option<Progress>,
+    },
+    FiveYearPlan {
+        /// The number of cards shuffled into the draw pile.
+        cards_added: usize,
+    },
+    Confession {
+        chosen_player: Option<usize>,
+        party: Option<Party>,
+        /// How many players are ready to move on, once the chosen player's party has been revealed.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        progress: Option<Progress>,
+    },
+    Assassination {
+        anarchist: usize,
+        chosen_player: Option<usize>,
+    },
+    /// The anarchist was executed before they could carry out their assassination, so it has been cancelled.
+    AssassinationCancelled {
+        anarchist: usize,
+    },
+    Bribe {
+        capitalist: usize,
+        chosen_player: usize,
+        offered: bool,
+    },
+    GameOver {
+        outcome: WinCondition,
+    },
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum PlayerPrompt {
+    Night {
+        /// Whether the player has acknowledged looking at their role card.
+        #[serde(default)]
+        role_viewed: bool,
+    },
+    ChoosePlayer {
+        kind: ChoosePlayerKind,
+        options: Vec<String>,
+        /// Why each player who cannot be nominated as chancellor is ineligible, when choosing a chancellor.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        ineligibility_reasons: Vec<(usize, IneligibilityReason)>,
+    },
+    Vote {
+        /// Whether the player may abstain rather than vote.
+        can_abstain: bool,
+    },
+    HijackElection,
+    PresidentDiscard {
+        cards: [Party; 3],
+    },
+    ChancellorDiscard {
+        cards: [Party; 2],
+        can_veto: bool,
+    },
+    ApproveVeto,
+    StartElection {
+        can_assassinate: bool,
+    },
+    EndCongress {
+        /// The communists who were dealt their role, rather than being radicalised, excluding the recipient.
+        communists: Vec<String>,
+    },
+    InvestigatePlayer {
+        name: String,
+        party: Party,
+        /// The player's role, if the investigation revealed it rather than just their party.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        role: Option<Role>,
+        /// Whether the player may publicly reveal the party they learned.
+        can_reveal: bool,
+    },
+    PolicyPeak {
+        cards: [Party; 3],
+    },
+    Radicalisation {
+        result: RadicalisationResult,
+    },
+    /// The capitalist may bribe the president to cancel or redirect their power against the given player.
+    OfferBribe {
+        action: ExecutiveAction,
+        player: String,
+    },
+    /// The president has been bribed, and may cancel their power, choose another player, or refuse the bribe.
+    ConsiderBribe {
+        action: ExecutiveAction,
+        player: String,
+        options: Vec<String>,
+    },
+    /// The communists bugged a player, but another communist was elected to be told their party.
+    BuggingWithheld {
+        name: String,
+        recipient: Option<String>,
+    },
+    /// A player's party has been revealed to the whole table by a confession.
+    Confession {
+        name: String,
+        party: Party,
+    },
+    Dead,
+    GameOver {
+        outcome: WinCondition,
+        won: bool,
+        status: WinStatus,
+    },
+}
+
+/// Why a player cannot be nominated as chancellor.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub enum IneligibilityReason {
+    Dead,
+    /// The player is the president, who cannot also be chancellor.
+    President,
+    /// The player was chancellor in the last elected government.
+    LastChancellor,
+    /// The player was president in the last elected government, which only counts while more than five players are alive.
+    LastPresident,
+    /// The player has already been nominated by the monarchist, so cannot also be the rival nominee.
+    AlreadyNominated,
+}
+
+/// Why the president of a round is not the player who was chosen for it, because that player died beforehand,
+/// such as by the anarchist's assassination while the special election was being revealed.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(tag = "type")]
+pub enum PresidencyFallback {
+    /// The player chosen in the special election died, so the presidency passed in the usual rotation instead.
+    NomineeDied { nominee: usize },
+    /// The monarchist who hijacked the special election died, so the presidency passed in the usual rotation instead.
+    MonarchistDied { monarchist: usize },
+    /// The president whose special election the monarchist hijacked died, so the monarchist is president unopposed.
+    PresidentDied { president: usize },
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub enum ChoosePlayerKind {
+    /// The player is selecting a chancellor nominee
+    NominateChancellor,
+    /// The player is selecting the next presidential nominee
+    NominatePresident,
+    /// The player is selecting the first chancellor in a monarchist election
+    MonarchistFirstChancellor,
+    /// The player is selecting the second chancellor in a monarchist election
+    MonarchistSecondChancellor,
+    /// The player is voting on a chancellor
+    VoteChancellor,
+    /// The player is choosing another player to investigate their party membership
+    Investigate,
+    /// The player is choosing another player to execute
+    Execute,
+    /// The player is choosing another player to attempt to convert to communism
+    Radicalise,
+    /// The player is choosing which player must reveal their party membership to all
+    Confession,
+    /// The player is voting on which communist will be told the result of bugging
+    BuggingRecipient,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub enum LegislativePhase {
+    President,
+    Chancellor,
+    VetoRequested,
+    VetoApproved,
+    VetoRejected,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub enum CommunistSessionPhase {
+    Entering,
+    InProgress,
+    Leaving,
+    Reveal,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub enum RadicalisationResult {
+    /// The player is a communist, but no radicalisation was attempted
+    NoAttempt,
+    /// The player is a communist, and the radicalisation failed
+    Fail,
+    /// The player is a communist, and the radicalisation failed
+    Success,
+    /// The player is not a communist, and their role has not changed
+    Unchanged,
+    /// The player was not a communist, but has been radicalised
+    Radicalised,
+}
+
+impl Game {
+    pub fn get_board_update(&self) -> BoardUpdate {
+        let prompt = self.get_board_prompt();
+        let message = self.board_message(&prompt);
+        let mut update = BoardUpdate {
+            election_tracker: self.election_tracker,
+            liberal_cards: self.board.liberal_cards,
+            fascist_cards: self.board.fascist_cards,
+            communist_cards: self.opts.communists.then_some(self.board.communist_cards),
+            draw_pile: self.deck.count(),
+            deck_odds: self
+                .opts
+                .show_deck_odds
+                .then(|| self.deck.odds(&self.board, self.opts.communists)),
+            presidential_turn: self.presidential_turn,
+            rotation: self.rotation(),
+            last_government: self.last_government,
+            prompt: Some(prompt),
+            message: Some(message),
+            state_hash: 0,
+        };
+        update.state_hash = self.public_hash(&update);
+        update
+    }
+
+    pub fn get_overlay_update(&self) -> OverlayUpdate {
+        OverlayUpdate {
+            phase: self.phase(),
+            liberal_cards: self.board.liberal_cards,
+            fascist_cards: self.board.fascist_cards,
+            communist_cards: self.opts.communists.then_some(self.board.communist_cards),
+            election_tracker: self.election_tracker,
+            government: self.last_government.map(|government| {
+                let name = |player: usize| self.players[player].name.clone();
+                (name(government.president), name(government.chancellor))
+            }),
+        }
+    }
+
+    /// Gets what a stream overlay is shown of the game in progress.
+    pub fn get_overlay_state(&self) -> OverlayState {
+        OverlayState::Overlay(self.get_overlay_update())
+    }
+
+    pub fn get_player_update(&self, player_idx: usize) -> PlayerUpdate {
+        let board_prompt = self.opts.boardless.then(|| self.get_board_prompt());
+        self.player_update(player_idx, board_prompt.as_ref())
+    }
+
+    /// Gets the updates for every player at once, which is cheaper than getting each in turn
+    /// as the work which all of them share is only done once.
+    pub fn get_player_updates(&self) -> Vec<PlayerUpdate> {
+        let board_prompt = self.opts.boardless.then(|| self.get_board_prompt());
+        (0..self.num_players())
+            .map(|player| self.player_update(player, board_prompt.as_ref()))
+            .collect()
+    }
+
+    /// Gets a player's update, given the board's prompt if the game is being played without a board.
+    fn player_update(&self, player_idx: usize, board_prompt: Option<&BoardPrompt>) -> PlayerUpdate {
+        let player = &self.players[player_idx];
+        let prompt = self.get_player_prompt(player_idx);
+        PlayerUpdate {
+            name: player.name.clone(),
+            role: player.role,
+            others: player.others[..self.num_players()].to_vec(),
+            message: prompt.as_ref().map(|prompt| self.player_message(prompt)),
+            legal_actions: self.legal_actions(player_idx),
+            prompt,
+            can_undo: self.undo_player() == Some(player_idx),
+            knowledge_log: player
+                .knowledge
+                .iter()
+                .map(|knowledge| KnowledgeEntry {
+                    player: self.players[knowledge.target].name.clone(),
+                    result: knowledge.result,
+                    source: knowledge.source,
+                    round: knowledge.round,
+                })
+                .collect(),
+            board_prompt: board_prompt.cloned(),
+            policy_peaks: match self.opts.remember_policy_peaks {
+                true => player.policy_peaks.clone(),
+                false => vec![],
+            },
+        }
+    }
+
+    /// Gets which seats' updates have changed since this was last called, and starts tracking changes afresh.
+    /// Returns `None` if every seat's update may have changed, such as when the game was first created or loaded.
+    pub fn take_changed_seats(&mut self) -> Option<[bool; MAX_PLAYERS]> {
+        self.changed_seats.replace([false; MAX_PLAYERS])
+    }
+
+    pub(crate) fn mark_seat_changed(&mut self, player: usize) {
+        if let Some(seats) = &mut self.changed_seats {
+            seats[player] = true;
+        }
+    }
+
+    pub(crate) fn mark_all_seats_changed(&mut self) {
+        self.changed_seats = None;
+    }
+
+    /// The phase of the game as far as players' views are concerned: an action which leaves it unchanged
+    /// can only have affected the views of the players directly involved.
+    pub(crate) fn view_phase(&self) -> (Discriminant<GameState>, bool) {
+        let decided = match &self.state {
+            GameState::Election { votes, .. } => votes.outcome().is_some(),
+            GameState::MonarchistElection { votes, .. } => votes.outcome().is_some(),
+            _ => false,
+        };
+        (std::mem::discriminant(&self.state), decided)
+    }
+
+    pub fn get_public_players(&self) -> Vec<PublicPlayer> {
+        self.players
+            .iter()
+            .map(|player| PublicPlayer {
+                name: player.name.clone(),
+                alive: player.alive,
+                not_hitler: player.not_hitler,
+                confirmed_party: player.confirmed_party,
+                investigated: player.investigated,
+                connected: false,
+                last_seen: None,
+                claim_pending: false,
+            })
+            .collect()
+    }
+
+    pub fn get_board_prompt(&self) -> BoardPrompt {
+        use GameState::*;
+
+        match &self.state {
+            Night { confirmations, roles_viewed } => BoardPrompt::Night {
+                progress: self.confirmation_progress(confirmations),
+                roles_viewed: self.roles_viewed_progress(roles_viewed),
+            },
+
+            Election { president, chancellor, votes, fallback, .. } => {
+                let (shown, voted) = votes.shown(self.opts.house_rules.secret_ballots);
+                BoardPrompt::Election {
+                    fallback: *fallback,
+                    president: *president,
+                    chancellor: *chancellor,
+                    votes: shown,
+                    abstained: match voted {
+                        Some(_) => vec![],
+                        None => votes.abstentions(),
+                    },
+                    voted,
+                    outcome: votes.outcome(),
+                }
+            }
+
+            MonarchistElection {
+                monarchist,
+                last_president: president,
+                monarchist_chancellor,
+                president_chancellor,
+                votes,
+                ..
+            } => {
+                let (shown, voted) = votes.shown(self.opts.house_rules.secret_ballots);
+                BoardPrompt::MonarchistElection {
+                    monarchist: *monarchist,
+                    president: *president,
+                    monarchist_chancellor: *monarchist_chancellor,
+                    president_chancellor: *president_chancellor,
+                    votes: shown,
+                    tally: voted.is_none().then(|| votes.tally()),
+                    voted,
+                    outcome: votes.outcome(),
+                    tiebreak: votes.decided_by_tiebreak(),
+                }
+            }
+
+            LegislativeSession { president, chancellor, turn } => {
+                let (phase, veto_available, cards) = match turn {
+                    LegislativeSessionTurn::President { .. } => {
+                        (LegislativePhase::President, self.board.veto_unlocked(), 3)
+                    }
+                    LegislativeSessionTurn::Chancellor { veto, .. } => match veto {
+                        VetoStatus::CanVeto => (LegislativePhase::Chancellor, true, 2),
+                        VetoStatus::CannotVeto => (LegislativePhase::Chancellor, false, 2),
+                        VetoStatus::VetoDenied => (LegislativePhase::VetoRejected, false, 2),
+                    },
+                    LegislativeSessionTurn::VetoRequested { .. } => (LegislativePhase::VetoRequested, false, 2),
+                    LegislativeSessionTurn::VetoApproved => (LegislativePhase::VetoApproved, false, 0),
+                };
+                BoardPrompt::LegislativeSession {
+                    president: *president,
+                    chancellor: *chancellor,
+                    phase,
+                    veto_available,
+                    cards,
+                }
+            }
+
+            ElectionFailed { tracker } => BoardPrompt::ElectionFailed { tracker: *tracker, chaos: *tracker == 3 },
+
+            CardReveal { result, chaos, confirmations, board_ready } => BoardPrompt::CardReveal {
+                result: *result,
+                chaos: *chaos,
+                can_end: !*board_ready,
+                progress: self.confirmation_progress(confirmations),
+            },
+
+            CommunistStart { action } => BoardPrompt::CommunistSession {
+                action: *action,
+                phase: CommunistSessionPhase::Entering,
+                progress: None,
+            },
+
+            PromptMonarchist { monarchist, hijacked, .. } => BoardPrompt::SpecialElection {
+                can_hijack: !hijacked,
+                hijacked_by: hijacked.then_some(*monarchist),
+                chosen_player: None,
+            },
+
+            ChoosePlayer { action, .. } => match action {
+                ExecutiveAction::InvestigatePlayer => BoardPrompt::InvestigatePlayer { chosen_player: None },
+                ExecutiveAction::SpecialElection => BoardPrompt::SpecialElection {
+                    can_hijack: false,
+                    hijacked_by: None,
+                    chosen_player: None,
+                },
+                ExecutiveAction::Execution => BoardPrompt::Execution { chosen_player: None },
+                ExecutiveAction::Bugging | ExecutiveAction::Radicalisation | ExecutiveAction::Congress => {
+                    BoardPrompt::CommunistSession {
+                        action: *action,
+                        phase: CommunistSessionPhase::InProgress,
+                        progress: None,
+                    }
+                }
+                ExecutiveAction::FiveYearPlan => BoardPrompt::FiveYearPlan { cards_added: FIVE_YEAR_PLAN.len() },
+                ExecutiveAction::Confession => BoardPrompt::Confession {
+                    chosen_player: None,
+                    party: None,
+                    progress: None,
+                },
+                _ => unreachable!(),
+            },
+
+            Congress => BoardPrompt::CommunistSession {
+                action: ExecutiveAction::Congress,
+                phase: CommunistSessionPhase::InProgress,
+                progress: None,
+            },
+
+            BuggingVote { .. } => BoardPrompt::CommunistSession {
+                action: ExecutiveAction::Bugging,
+                phase: CommunistSessionPhase::InProgress,
+                progress: None,
+            },
+
+            CommunistEnd { action, .. } => BoardPrompt::CommunistSession {
+                action: *action,
+                phase: CommunistSessionPhase::Leaving,
+                progress: None,
+            },
+
+            ActionReveal { action, chosen_player, confirmations } => match action {
+                ExecutiveAction::InvestigatePlayer => BoardPrompt::InvestigatePlayer { chosen_player: *chosen_player },
+                ExecutiveAction::SpecialElection => BoardPrompt::SpecialElection {
+                    can_hijack: false,
+                    hijacked_by: None,
+                    chosen_player: *chosen_player,
+                },
+                ExecutiveAction::PolicyPeak => BoardPrompt::PolicyPeak,
+                ExecutiveAction::Execution => BoardPrompt::Execution { chosen_player: *chosen_player },
+                ExecutiveAction::Bugging | ExecutiveAction::Radicalisation | ExecutiveAction::Congress => {
+                    BoardPrompt::CommunistSession {
+                        action: *action,
+                        phase: CommunistSessionPhase::Reveal,
+                        progress: Some(self.confirmation_progress(confirmations)),
+                    }
+                }
+                ExecutiveAction::FiveYearPlan => BoardPrompt::FiveYearPlan { cards_added: FIVE_YEAR_PLAN.len() },
+                ExecutiveAction::Confession => BoardPrompt::Confession {
+                    chosen_player: *chosen_player,
+                    party: chosen_player.map(|i| self.players[i].party()),
+                    progress: Some(self.confirmation_progress(confirmations)),
+                },
+            },
+
+            Assassination { anarchist, cancelled: true, .. } => {
+                BoardPrompt::AssassinationCancelled { anarchist: *anarchist }
+            }
+            Assassination { anarchist, chosen_player, .. } => BoardPrompt::Assassination {
+                anarchist: *anarchist,
+                chosen_player: *chosen_player,
+            },
+
+            Bribe { capitalist, chosen_player, offered, .. } => BoardPrompt::Bribe {
+                capitalist: *capitalist,
+                chosen_player: *chosen_player,
+                offered: *offered,
+            },
+
+            GameOver(outcome) => BoardPrompt::GameOver { outcome: *outcome },
+        }
+    }
+
+    /// Gets the progress of the given confirmations, which every alive player is expected to give.
+    fn confirmation_progress(&self, confirmations: &Confirmations) -> Progress {
+        confirmations.progress((0..self.num_players()).filter(|idx| self.players[*idx].alive))
+    }
+
+    /// Gets how many of the players have acknowledged looking at their role card during the night round.
+    fn roles_viewed_progress(&self, roles_viewed: &[bool; MAX_PLAYERS]) -> Progress {
+        let alive = (0..self.num_players()).filter(|idx| self.players[*idx].alive);
+        Progress {
+            confirmed: alive.clone().filter(|idx| roles_viewed[*idx]).count(),
+            required: alive.clone().count(),
+            waiting: alive.filter(|idx| !roles_viewed[*idx]).collect(),
+        }
+    }
+
+    /// Returns whether a player may vote, given whether they already have and whether the outcome is decided.
+    pub(crate) fn may_vote(&self, has_cast: bool, decided: bool) -> bool {
+        !decided && (!has_cast || self.opts.allow_vote_change)
+    }
+
+    pub fn get_player_prompt(&self, player: usize) -> Option<PlayerPrompt> {
+        use GameState::*;
+
+        let player_idx = player;
+        let player = &self.players[player_idx];
+
+        // An executed anarchist may still carry out their assassination, under the house rule
+        let assassin =
+            matches!(self.state, Assassination { anarchist, cancelled: false, .. } if anarchist == player_idx);
+        if !player.alive && !self.game_over() && !assassin {
+            return Some(PlayerPrompt::Dead);
+        }
+
+        match &self.state {
+            Night { confirmations, roles_viewed } => (!confirmations.has_confirmed(player_idx))
+                .then_some(PlayerPrompt::Night { role_viewed: roles_viewed[player_idx] }),
+
+            Election {
+                president,
+                chancellor,
+                eligible_chancellors,
+                votes,
+                ..
+            } => match chancellor {
+                None => (player_idx == *president).then(|| PlayerPrompt::ChoosePlayer {
+                    kind: ChoosePlayerKind::NominateChancellor,
+                    options: eligible_chancellors.names(self),
+                    ineligibility_reasons: self.ineligibility_reasons(*president, eligible_chancellors),
+                }),
+                Some(chancellor) => self
+                    .may_vote(votes.has_cast(player_idx), votes.outcome().is_some())
+                    .then_some(PlayerPrompt::Vote {
+                        can_abstain: self.opts.house_rules.abstentions
+                            && player_idx != *president
+                            && player_idx != *chancellor,
+                    }),
+            },
+
+            MonarchistElection {
+                monarchist,
+                last_president: president,
+                monarchist_chancellor,
+                president_chancellor,
+                eligible_chancellors,
+                votes,
+            } => {
+                if monarchist_chancellor.is_none() {
+                    (player_idx == *monarchist).then(|| PlayerPrompt::ChoosePlayer {
+                        kind: ChoosePlayerKind::MonarchistFirstChancellor,
+                        options: eligible_chancellors.names(self),
+                        ineligibility_reasons: self.ineligibility_reasons(*monarchist, eligible_chancellors),
+                    })
+                } else if president_chancellor.is_none() {
+                    (player_idx == *president).then(|| PlayerPrompt::ChoosePlayer {
+                        kind: ChoosePlayerKind::MonarchistSecondChancellor,
+                        options: eligible_chancellors.names(self),
+                        ineligibility_reasons: self.ineligibility_reasons(*monarchist, eligible_chancellors),
+                    })
+                } else {
+                    let can_vote = self.may_vote(votes.has_cast(player_idx), votes.outcome().is_some());
+                    can_vote.then_some(PlayerPrompt::ChoosePlayer {
+                        kind: ChoosePlayerKind::VoteChancellor,
+                        options: [*monarchist_chancellor, *president_chancellor]
+                            .into_iter()
+                            .map(|i| self.players[i.unwrap()].name.clone())
+                            .collect(),
+                        ineligibility_reasons: vec![],
+                    })
+                }
+            }
+
+            LegislativeSession { president, chancellor, turn } => match turn {
+                LegislativeSessionTurn::President { cards } => {
+                    (player_idx == *president).then_some(PlayerPrompt::PresidentDiscard { cards: *cards })
+                }
+                LegislativeSessionTurn::Chancellor { cards, veto } => {
+                    (player_idx == *chancellor).then_some(PlayerPrompt::ChancellorDiscard {
+                        cards: *cards,
+                        can_veto: *veto == VetoStatus::CanVeto,
+                    })
+                }
+                LegislativeSessionTurn::VetoRequested { .. } => {
+                    (player_idx == *president).then_some(PlayerPrompt::ApproveVeto)
+                }
+                LegislativeSessionTurn::VetoApproved => None,
+            },
+
+            CardReveal { confirmations, board_ready, .. } => {
+                use AssassinationState::Unused;
+                let unconfirmed = *board_ready && !confirmations.has_confirmed(player_idx);
+                unconfirmed.then_some(PlayerPrompt::StartElection {
+                    can_assassinate: self.assassination == Unused && player.role == Role::Anarchist,
+                })
+            }
+
+            ElectionFailed { .. } | CommunistStart { .. } => None,
+
+            PromptMonarchist { monarchist, hijacked, .. } => {
+                (!hijacked && player_idx == *monarchist).then_some(PlayerPrompt::HijackElection)
+            }
+
+            ChoosePlayer { action, can_select, can_be_selected } => can_select.includes(player_idx).then(|| {
+                use ExecutiveAction::*;
+                let kind = match action {
+                    InvestigatePlayer | Bugging => ChoosePlayerKind::Investigate,
+                    SpecialElection => ChoosePlayerKind::NominatePresident,
+                    Execution => ChoosePlayerKind::Execute,
+                    Radicalisation | Congress => ChoosePlayerKind::Radicalise,
+                    Confession => ChoosePlayerKind::Confession,
+                    PolicyPeak | FiveYearPlan => unreachable!(),
+                };
+                PlayerPrompt::ChoosePlayer {
+                    kind,
+                    options: can_be_selected.names(self),
+                    ineligibility_reasons: vec![],
+                }
+            }),
+
+            Congress => (player.role == Role::Communist).then(|| PlayerPrompt::EndCongress {
+                communists: (self.players.iter().enumerate())
+                    .filter(|(idx, p)| *idx != player_idx && p.alive && p.role == Role::Communist && !p.radicalised)
+                    .map(|(_, p)| p.name.clone())
+                    .collect(),
+            }),
+
+            BuggingVote { communists, votes, .. } => (communists.includes(player_idx) && !votes.has_cast(player_idx))
+                .then(|| PlayerPrompt::ChoosePlayer {
+                    kind: ChoosePlayerKind::BuggingRecipient,
+                    options: communists.names(self),
+                    ineligibility_reasons: vec![],
+                }),
+
+            CommunistEnd { .. } => None,
+
+            ActionReveal { action, confirmations, chosen_player } => {
+                use ExecutiveAction::*;
+
+                if confirmations.has_confirmed(player_idx) {
+                    return None;
+                }
+
+                let government = self.last_government.unwrap();
+                match action {
+                    InvestigatePlayer => (player_idx == government.president).then(|| {
+                        let player = &self.players[chosen_player.unwrap()];
+                        PlayerPrompt::InvestigatePlayer {
+                            name: player.name.clone(),
+                            party: player.party(),
+                            role: (self.opts.investigation_reveals == InvestigationReveals::Role)
+                                .then_some(player.role),
+                            can_reveal: self.opts.house_rules.public_investigations && player.confirmed_party.is_none(),
+                        }
+                    }),
+                    PolicyPeak => (player_idx == government.president).then(|| {
+                        let cards = self.deck.peek_three();
+                        PlayerPrompt::PolicyPeak { cards }
+                    }),
+                    Bugging => (player.role == Role::Communist).then(|| {
+                        // Only the communists who were told the result of the bugging learn the player's party
+                        let target = chosen_player.unwrap();
+                        let told = |idx: &usize| {
+                            self.players[*idx].knowledge.iter().any(|k| {
+                                k.source == KnowledgeSource::Bugging && k.target == target && k.round == self.round
+                            })
+                        };
+                        let name = self.players[target].name.clone();
+                        if told(&player_idx) {
+                            let party = self.players[target].party();
+                            PlayerPrompt::InvestigatePlayer { name, party, role: None, can_reveal: false }
+                        } else {
+                            let recipient = (0..self.num_players())
+                                .find(told)
+                                .map(|idx| self.players[idx].name.clone());
+                            PlayerPrompt::BuggingWithheld { name, recipient }
+                        }
+                    }),
+                    Radicalisation | Congress => {
+                        let result = if player.role != Role::Communist {
+                            RadicalisationResult::Unchanged
+                        } else if chosen_player.is_none() {
+                            RadicalisationResult::NoAttempt
+                        } else if *chosen_player == Some(player_idx) {
+                            RadicalisationResult::Radicalised
+                        } else if self.radicalised {
+                            RadicalisationResult::Success
+                        } else {
+                            RadicalisationResult::Fail
+                        };
+                        Some(PlayerPrompt::Radicalisation { result })
+                    }
+                    Confession => {
+                        let chosen = &self.players[chosen_player.unwrap()];
+                        Some(PlayerPrompt::Confession {
+                            name: chosen.name.clone(),
+                            party: chosen.party(),
+                        })
+                    }
+                    _ => None,
+                }
+            }
+
+            Assassination { chosen_player, cancelled, .. } => {
+                let anarchist = player.role == Role::Anarchist && chosen_player.is_none() && !cancelled;
+                anarchist.then_some(PlayerPrompt::ChoosePlayer {
+                    kind: ChoosePlayerKind::Execute,
+                    options: self.eligible_players().exclude(player_idx).make().names(self),
+                    ineligibility_reasons: vec![],
+                })
+            }
+
+            Bribe {
+                action,
+                president,
+                capitalist,
+                chosen_player,
+                offered,
+            } => {
+                let target = self.players[*chosen_player].name.clone();
+                match (*offered, player_idx) {
+                    (false, idx) if idx == *capitalist => {
+                        Some(PlayerPrompt::OfferBribe { action: *action, player: target })
+                    }
+                    (true, idx) if idx == *president => Some(PlayerPrompt::ConsiderBribe {
+                        action: *action,
+                        player: target,
+                        options: self
+                            .eligible_players()
+                            .exclude(*president)
+                            .exclude(*chosen_player)
+                            .make()
+                            .names(self),
+                    }),
+                    _ => None,
+                }
+            }
+
+            GameOver(outcome) => Some(PlayerPrompt::GameOver {
+                outcome: *outcome,
+                won: self.player_has_won(player_idx),
+                status: self.outcome_for_player(player_idx).unwrap_or(WinStatus::Lost),
+            }),
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use crate::error::GameError;
+use serde::{Deserialize, Serialize};
+
+use super::MAX_PLAYERS;
+
+/// Tracks the vote of each player.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct Votes {
+    num_players: usize,
+    votes: [Option<bool>; MAX_PLAYERS],
+    /// The players who abstained, which is a third kind of ballot alongside voting for or against.
+    #[serde(default)]
+    abstained: [bool; MAX_PLAYERS],
+}
+
+impl Votes {
+    /// Creates a new `Votes`.
+    pub fn new(num_players: usize) -> Self {
+        let votes = [None; MAX_PLAYERS];
+        let abstained = [false; MAX_PLAYERS];
+        Self { num_players, votes, abstained }
+    }
+
+    /// Returns whether the given player has cast their vote, or abstained.
+    pub fn has_cast(&self, player_idx: usize) -> bool {
+        self.votes[player_idx].is_some() || self.abstained[player_idx]
+    }
+
+    /// Records the vote of a player, replacing any vote they already cast if `allow_change` is set.
+    pub fn vote(&mut self, player_idx: usize, vote: bool, allow_change: bool) -> Result<(), GameError> {
+        if self.has_cast(player_idx) && !allow_change {
+            return Err(GameError::AlreadyVoted);
+        }
+        self.votes[player_idx] = Some(vote);
+        self.abstained[player_idx] = false;
+        Ok(())
+    }
+
+    /// Records that a player abstained, replacing any vote they already cast if `allow_change` is set.
+    pub fn abstain(&mut self, player_idx: usize, allow_change: bool) -> Result<(), GameError> {
+        if self.has_cast(player_idx) && !allow_change {
+            return Err(GameError::AlreadyVoted);
+        }
+        self.votes[player_idx] = None;
+        self.abstained[player_idx] = true;
+        Ok(())
+    }
+
+    /// If all votes are counted, returns the outcome, otherwise returns `None`.
+    /// Abstentions count towards neither side, so the election passes with a majority of the votes cast,
+    /// and fails on a tie, including when every player abstains.
+    pub fn outcome(&self) -> Option<bool> {
+        let yes = self.votes.iter().filter(|v| **v == Some(true)).count();
+        let no = self.votes.iter().filter(|v| **v == Some(false)).count();
+        let abstained = self.abstained.iter().filter(|a| **a).count();
+        if std::env::var("QUICK_MODE").is_ok() {
+            (yes + no + abstained > 0).then_some(yes > no)
+        } else {
+            (yes + no + abstained >= self.num_players).then_some(yes > no)
+        }
+    }
+
+    /// Gets the players who abstained.
+    pub fn abstentions(&self) -> Vec<usize> {
+        (0..self.num_players).filter(|idx| self.abstained[*idx]).collect()
+    }
+
+    /// Gets the votes of each player as shown on the board. See [shown_votes].
+    pub fn shown(&self, secret: bool) -> (Vec<Option<bool>>, Option<Vec<bool>>) {
+        let (votes, voted) = shown_votes(&self.votes, self.outcome().is_some(), secret);
+        let voted = voted.map(|voted| voted.iter().zip(self.abstained).map(|(v, a)| *v || a).collect());
+        (votes, voted)
+    }
+}
+
+/// Tracks the vote of each player during a monarchist election.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct MonarchistVotes {
+    num_players: usize,
+    /// The index of the player who is the monarchist
+    monarchist: usize,
+    /// `true` is a vote for the monarchist's chancellor, and `false` is for the other
+    votes: [Option<bool>; MAX_PLAYERS],
+}
+
+impl MonarchistVotes {
+    /// Creates a new `MonarchistVotes`.
+    pub fn new(num_players: usize, monarchist: usize) -> Self {
+        let votes = [None; MAX_PLAYERS];
+        Self { num_players, monarchist, votes }
+    }
+
+    /// Returns whether the given player has cast their vote.
+    pub fn has_cast(&self, player_idx: usize) -> bool {
+        self.votes[player_idx].is_some()
+    }
+
+    /// Gets the vote the given player has cast, if any, where `true` signifies the monarchist's selection.
+    pub fn vote_of(&self, player_idx: usize) -> Option<bool> {
+        self.votes[player_idx]
+    }
+
+    /// Records the vote of a player, where `true` signifies the monarchist's selection,
+    /// replacing any vote they already cast if `allow_change` is set.
+    pub fn vote(&mut self, player_idx: usize, vote: bool, allow_change: bool) -> Result<(), GameError> {
+        if self.has_cast(player_idx) && !allow_change {
+            return Err(GameError::AlreadyVoted);
+        }
+        self.votes[player_idx] = Some(vote);
+        Ok(())
+    }
+
+    /// If all votes are counted, returns the outcome, otherwise returns `None`.
+    /// A result of `true` signifies the monarchist's selection has won.
+    pub fn outcome(&self) -> Option<bool> {
+        use std::cmp::Ordering::*;
+        let [yes, no] = self.tally();
+        if std::env::var("QUICK_MODE").is_ok() {
+            (yes + no > 0).then_some(yes > no)
+        } else {
+            (yes + no >= self.num_players).then(|| match yes.cmp(&no) {
+                Less => false,
+                Greater => true,
+                Equal => self.votes[self.monarchist].unwrap_or(true),
+            })
+        }
+    }
+
+    /// Gets the number of votes for the monarchist's selection, and for the other.
+    pub fn tally(&self) -> [usize; 2] {
+        let yes = self.votes.iter().filter(|v| **v == Some(true)).count();
+        let no = self.votes.iter().filter(|v| **v == Some(false)).count();
+        [yes, no]
+    }
+
+    /// Returns whether the votes were tied once all were counted, such that the monarchist's vote decided the outcome.
+    pub fn decided_by_tiebreak(&self) -> bool {
+        let [yes, no] = self.tally();
+        self.outcome().is_some() && yes == no
+    }
+
+    /// Gets the votes of each player as shown on the board. See [shown_votes].
+    pub fn shown(&self, secret: bool) -> (Vec<Option<bool>>, Option<Vec<bool>>) {
+        shown_votes(&self.votes, self.outcome().is_some(), secret)
+    }
+}
+
+/// Gets the votes as shown on the board, along with whether each player has voted if the votes themselves are hidden.
+/// With secret ballots, the votes are hidden until the outcome is decided.
+fn shown_votes(votes: &[Option<bool>], decided: bool, secret: bool) -> (Vec<Option<bool>>, Option<Vec<bool>>) {
+    match secret && !decided {
+        true => (
+            vec![None; votes.len()],
+            Some(votes.iter().map(Option::is_some).collect()),
+        ),
+        false => (votes.to_vec(), None),
+    }
+}
+
+/// Tracks the vote of each player for one of several candidates, such as the communist who is told the result of
+/// bugging.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct CandidateVotes {
+    num_players: usize,
+    /// The index of the player whose vote breaks ties
+    tiebreaker: usize,
+    /// The candidate each player has voted for
+    votes: [Option<usize>; MAX_PLAYERS],
+}
+
+impl CandidateVotes {
+    /// Creates a new `CandidateVotes`.
+    pub fn new(num_players: usize, tiebreaker: usize) -> Self {
+        let votes = [None; MAX_PLAYERS];
+        Self { num_players, tiebreaker, votes }
+    }
+
+    /// Returns whether the given player has cast their vote.
+    pub fn has_cast(&self, player_idx: usize) -> bool {
+        self.votes[player_idx].is_some()
+    }
+
+    /// Records the vote of a player for the given candidate.
+    pub fn vote(&mut self, player_idx: usize, candidate: usize) {
+        self.votes[player_idx] = Some(candidate);
+    }
+
+    /// If all votes are counted, returns the winning candidate, otherwise returns `None`.
+    /// Ties are broken by the tiebreaker's vote, or else in favour of the candidate with the lowest index.
+    pub fn outcome(&self) -> Option<usize> {
+        let cast = self.votes.iter().flatten().count();
+        let counted = match std::env::var("QUICK_MODE") {
+            Ok(_) => cast > 0,
+            Err(_) => cast >= self.num_players,
+        };
+        if !counted {
+            return None;
+        }
+
+        let mut tally = [0; MAX_PLAYERS];
+        for candidate in self.votes.iter().flatten() {
+            tally[*candidate] += 1;
+        }
+        let most = tally.iter().copied().max().unwrap_or(0);
+        match self.votes[self.tiebreaker] {
+            Some(candidate) if tally[candidate] == most => Some(candidate),
+            _ => tally.iter().position(|count| *count == most),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! JavaScript bindings, so that clients can run the same rules locally,
+//! such as to give instant feedback before the server confirms an action, or to practise offline.
+//! Values cross the boundary as the same JSON shapes the server sends.
+//!
+//! Build the module with `cargo rustc -p shxl-engine --release --features wasm --target wasm32-unknown-unknown
+//! --crate-type cdylib`, so that other builds of the engine don't also produce a dynamic library.
+
+use crate::{Action, Game, GameOptions};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// A game of Secret Hitler, as seen from JavaScript.
+#[wasm_bindgen(js_name = Game)]
+pub struct WasmGame(Game);
+
+#[wasm_bindgen(js_class = Game)]
+impl WasmGame {
+    /// Creates a new game with the given options and players, seeded so that it plays out the same as on the server.
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: JsValue, players: Vec<String>, seed: u64) -> Result<WasmGame, JsError> {
+        let options: GameOptions = serde_wasm_bindgen::from_value(options)?;
+        Ok(Self(Game::new(options, &players, seed)?))
+    }
+
+    /// Performs an action on behalf of the given player, or the board if `player` is undefined.
+    pub fn apply(&mut self, player: Option<usize>, action: JsValue) -> Result<(), JsError> {
+        let action: Action = serde_wasm_bindgen::from_value(action)?;
+        Ok(self.0.apply(player, action)?)
+    }
+
+    /// Gets every action the given player may currently perform.
+    #[wasm_bindgen(js_name = legalActions)]
+    pub fn legal_actions(&self, player: usize) -> Result<JsValue, JsError> {
+        to_js(&self.0.legal_actions(player))
+    }
+
+    /// Gets the state of the game as shown on the board.
+    #[wasm_bindgen(js_name = boardUpdate)]
+    pub fn board_update(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.get_board_update())
+    }
+
+    /// Gets the state of the game as shown to the given player.
+    #[wasm_bindgen(js_name = playerUpdate)]
+    pub fn player_update(&self, player: usize) -> Result<JsValue, JsError> {
+        to_js(&self.0.get_player_update(player))
+    }
+
+    /// Returns true if the game is over.
+    #[wasm_bindgen(js_name = gameOver)]
+    pub fn game_over(&self) -> bool {
+        self.0.game_over()
+    }
+}
+
+/// Converts a value into the plain JavaScript object its JSON form describes.
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    Ok(value.serialize(&serializer)?)
+}
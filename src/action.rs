@@ -0,0 +1,166 @@
+use crate::{
+    error::GameError,
+    game::{Action, Game},
+};
+use serde::{Deserialize, Serialize};
+
+/// An action performed by the board.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum BoardAction {
+    EndVoting,
+    EndElectionFailed,
+    EndCardReveal,
+    EndExecutiveAction,
+    EndLegislativeSession,
+    EndAssassination,
+    EndCommunistStart,
+    EndCommunistEnd,
+    StartSpecialElection,
+    /// Resolves a phase which is stuck waiting on players who are no longer responding, with safe defaults.
+    ForceAdvance,
+}
+
+/// An action performed by the player.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum PlayerAction {
+    EndNightRound,
+    ViewRole,
+    EndCardReveal,
+    EndExecutiveAction,
+    AcknowledgePolicyPeak,
+    ChoosePlayer { name: String },
+    CastVote { vote: bool },
+    Abstain,
+    Discard { index: usize },
+    VetoAgenda,
+    AcceptVeto,
+    RejectVeto,
+    StartAssassination,
+    EndCongress,
+    HijackElection,
+    RevealInvestigation,
+    OfferBribe,
+    DeclineBribe,
+    CancelPower,
+    RejectBribe,
+}
+
+/// Any action which mutates a game in progress, such that it can be journaled and replayed.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum GameAction {
+    /// An action performed by the board.
+    Board { action: BoardAction },
+    /// An action performed by a player.
+    Player { name: String, action: PlayerAction },
+    /// A player undid their last action.
+    Undo { name: String },
+    /// The timer for the current phase ran out.
+    Timeout,
+    /// The undo window for the last action closed.
+    ExpireUndo,
+    /// A newcomer took over a player's seat, with the approval of the board.
+    Substitute { name: String, new_name: String },
+}
+
+impl GameAction {
+    /// Names the kind of action, for diagnostics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            GameAction::Board { .. } => "board",
+            GameAction::Player { .. } => "player",
+            GameAction::Undo { .. } => "undo",
+            GameAction::Timeout => "timeout",
+            GameAction::ExpireUndo => "expire_undo",
+            GameAction::Substitute { .. } => "substitute",
+        }
+    }
+
+    /// Gets the name of the player performing the action, if it was performed by a player.
+    pub fn player(&self) -> Option<&str> {
+        match self {
+            GameAction::Player { name, .. } | GameAction::Undo { name } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Applies the action to the game.
+    pub fn apply(&self, game: &mut Game) -> Result<(), GameError> {
+        match self {
+            GameAction::Board { action } => action.apply(game),
+            GameAction::Player { name, action } => {
+                let player = game.find_player(name)?;
+                action.apply(game, player)
+            }
+            GameAction::Undo { name } => {
+                let player = game.find_player(name)?;
+                game.apply(Some(player), Action::Undo)
+            }
+            GameAction::Timeout => game.apply(None, Action::Timeout),
+            GameAction::ExpireUndo => game.apply(None, Action::ExpireUndo),
+            GameAction::Substitute { name, new_name } => {
+                let player = game.find_player(name)?;
+                game.substitute(player, new_name)
+            }
+        }
+    }
+}
+
+impl BoardAction {
+    /// Applies the action to the game.
+    pub fn apply(&self, game: &mut Game) -> Result<(), GameError> {
+        game.apply(None, self.to_action())
+    }
+
+    /// Converts the action into the form the game engine accepts.
+    pub fn to_action(&self) -> Action {
+        match self {
+            BoardAction::EndVoting => Action::EndVoting,
+            BoardAction::EndElectionFailed => Action::EndElectionFailed,
+            BoardAction::EndCardReveal => Action::EndCardReveal,
+            BoardAction::EndExecutiveAction => Action::EndExecutiveAction,
+            BoardAction::EndLegislativeSession => Action::EndLegislativeSession,
+            BoardAction::EndAssassination => Action::EndAssassination,
+            BoardAction::EndCommunistStart => Action::EndCommunistStart,
+            BoardAction::EndCommunistEnd => Action::EndCommunistEnd,
+            BoardAction::StartSpecialElection => Action::StartSpecialElection,
+            BoardAction::ForceAdvance => Action::ForceAdvance,
+        }
+    }
+}
+
+impl PlayerAction {
+    /// Applies the action to the game, as performed by the given player.
+    pub fn apply(&self, game: &mut Game, player: usize) -> Result<(), GameError> {
+        let action = self.to_action(game)?;
+        game.apply(Some(player), action)
+    }
+
+    /// Converts the action into the form the game engine accepts, looking up any player it names.
+    pub fn to_action(&self, game: &Game) -> Result<Action, GameError> {
+        Ok(match self {
+            PlayerAction::EndNightRound => Action::EndNightRound,
+            PlayerAction::ViewRole => Action::ViewRole,
+            PlayerAction::EndCardReveal => Action::EndCardReveal,
+            PlayerAction::EndExecutiveAction => Action::EndExecutiveAction,
+            PlayerAction::AcknowledgePolicyPeak => Action::AcknowledgePolicyPeak,
+            PlayerAction::ChoosePlayer { name } => Action::ChoosePlayer { player: game.find_player(name)? },
+            PlayerAction::CastVote { vote } => Action::CastVote { vote: *vote },
+            PlayerAction::Abstain => Action::Abstain,
+            PlayerAction::Discard { index } => Action::Discard { index: *index },
+            PlayerAction::VetoAgenda => Action::VetoAgenda,
+            PlayerAction::AcceptVeto => Action::AcceptVeto,
+            PlayerAction::RejectVeto => Action::RejectVeto,
+            PlayerAction::StartAssassination => Action::StartAssassination,
+            PlayerAction::EndCongress => Action::EndCongress,
+            PlayerAction::HijackElection => Action::HijackElection,
+            PlayerAction::RevealInvestigation => Action::RevealInvestigation,
+            PlayerAction::OfferBribe => Action::OfferBribe,
+            PlayerAction::DeclineBribe => Action::DeclineBribe,
+            PlayerAction::CancelPower => Action::CancelPower,
+            PlayerAction::RejectBribe => Action::RejectBribe,
+        })
+    }
+}
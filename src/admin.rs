@@ -0,0 +1,75 @@
+//! Structured server events, which operators can watch as they happen over the admin websocket.
+
+use crate::action::GameAction;
+use crate::config::Config;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// How many events are buffered for each admin connection before the oldest are dropped.
+pub const ADMIN_EVENT_BUFFER: usize = 256;
+
+/// Something which happened on the server.
+#[derive(Clone, Serialize, Debug)]
+pub struct AdminEvent {
+    pub ts: DateTime<Utc>,
+    /// The game the event concerns.
+    pub game_id: String,
+    #[serde(flatten)]
+    pub kind: AdminEventKind,
+}
+
+#[derive(Clone, Serialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AdminEventKind {
+    SessionCreated,
+    SessionPurged,
+    GameStarted,
+    ActionApplied { action: GameAction },
+    ActionRejected { action: GameAction, error: String },
+    Error { message: String },
+}
+
+impl AdminEvent {
+    pub fn new(game_id: &str, kind: AdminEventKind) -> Self {
+        Self {
+            ts: chrono::offset::Utc::now(),
+            game_id: game_id.to_string(),
+            kind,
+        }
+    }
+}
+
+/// Checks the token presented by a client wishing to watch the server's events.
+/// No client is authorized if the server has no admin token configured.
+pub fn is_authorized(config: &Config, token: Option<&str>) -> bool {
+    config
+        .admin_token
+        .as_deref()
+        .zip(token)
+        .is_some_and(|(expected, token)| constant_time_eq(expected.as_bytes(), token.as_bytes()))
+}
+
+/// Compares two byte strings in a time which depends only on their lengths, so that a guess at the admin token
+/// cannot be refined by timing how quickly it is rejected.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let diff = a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y));
+    std::hint::black_box(diff) == 0 && a.len() == b.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_the_exact_admin_token_is_authorized() {
+        let mut config = Config::from_env();
+        config.admin_token = None;
+        assert!(!is_authorized(&config, Some("secret")));
+
+        config.admin_token = Some("secret".into());
+        assert!(is_authorized(&config, Some("secret")));
+        for token in [None, Some(""), Some("secre"), Some("secret!"), Some("Secret")] {
+            assert!(!is_authorized(&config, token), "{:?} was authorized", token);
+        }
+    }
+}
@@ -1,16 +1,94 @@
-use crate::session::SessionManager;
-use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
-use serde::Serialize;
+use crate::admin;
+use crate::auth::{verify_token, Claims};
+use crate::error::GameError;
+use crate::game::{GameOptions, Scenario};
+use crate::notifications::PushTarget;
+use crate::session::{ArchiveQuery, Session, SessionManager};
+use crate::store::{record, QuarantinedGame};
+use crate::summary;
+use crate::ws::{self, WireFormat, X_FORWARDED_FOR};
+use axum::{
+    extract::{
+        ws::{self as axum_ws, WebSocket, WebSocketUpgrade},
+        ConnectInfo, Path, Query, Request, State,
+    },
+    http::{
+        header::{
+            ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+            ACCESS_CONTROL_MAX_AGE, AUTHORIZATION, ORIGIN, SEC_WEBSOCKET_PROTOCOL, VARY,
+        },
+        HeaderMap, HeaderValue, Method, StatusCode,
+    },
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Extension, Json, Router,
+};
+use futures_util::{future, Sink, SinkExt, Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::net::SocketAddr;
 use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+
+/// A failed request, which is answered with the given status and a JSON body describing the error.
+/// Every error the API returns takes this form, so that clients can tell users why their request failed.
+struct ApiError(StatusCode, GameError);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let ApiError(status, err) = self;
+        let mut body = json!({ "code": err.code(), "message": err.to_string() });
+        if let GameError::HostedElsewhere { address } = &err {
+            body["address"] = json!(address);
+        }
+        (status, Json(body)).into_response()
+    }
+}
 
 pub async fn make_router(manager: &'static SessionManager) -> Router {
     Router::new()
         .route("/sessions", get(get_sessions))
         .route("/pastgames", get(get_past_games))
+        .route("/pastgames/:id/summary", get(get_game_summary))
+        .route("/lobbies", get(get_lobbies))
+        .route("/distribution", post(preview_distribution))
+        .route("/import", post(import_game))
+        .route("/scenarios", get(get_scenarios))
+        .route("/scenarios/:scenario/games", post(create_scenario_game))
+        .merge(player_router(manager))
+        .route("/ws", get(connect_client))
+        .route("/ws/:game_id", get(connect_client))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .nest("/admin", admin_router(manager))
+        .layer(middleware::from_fn_with_state(manager, cors))
         .with_state(manager)
 }
 
+/// The routes which act for a particular player, whose token is checked whenever one is presented.
+fn player_router(manager: &'static SessionManager) -> Router<&'static SessionManager> {
+    Router::new()
+        .route("/games/:game_id/players/:name", get(get_player))
+        .route("/games/:game_id/players/:name/prompt", get(get_player_prompt))
+        .route(
+            "/games/:game_id/players/:name/push",
+            put(register_push).delete(unregister_push),
+        )
+        .route("/me", get(get_me))
+        .route_layer(middleware::from_fn_with_state(manager, require_token))
+}
+
+/// The routes which only operators holding the admin token may use.
+fn admin_router(manager: &'static SessionManager) -> Router<&'static SessionManager> {
+    Router::new()
+        .route("/quarantine", get(get_quarantine))
+        .route("/quarantine/:id", get(inspect_quarantined).delete(delete_quarantined))
+        .route("/logs", get(watch_events))
+        .route_layer(middleware::from_fn_with_state(manager, require_admin))
+}
+
 pub async fn listen(port: u16) -> TcpListener {
     let listener = TcpListener::bind(format!("127.0.0.1:{}", port))
         .await
@@ -19,15 +97,178 @@ pub async fn listen(port: u16) -> TcpListener {
     listener
 }
 
-async fn get_sessions(State(manager): State<&SessionManager>) -> Result<Json<impl Serialize>, StatusCode> {
+/// Adds CORS headers to responses to requests from the origins the server is configured to allow,
+/// and answers their preflight requests.
+async fn cors(State(manager): State<&SessionManager>, req: Request, next: Next) -> Response {
+    let origins = &manager.config().cors_origins;
+    let origin = (req.headers().get(ORIGIN))
+        .filter(|origin| {
+            let origin = origin.to_str().unwrap_or_default();
+            origins.iter().any(|allowed| allowed == "*" || allowed == origin)
+        })
+        .cloned();
+    let mut res = match (&origin, req.method()) {
+        (Some(_), &Method::OPTIONS) => StatusCode::NO_CONTENT.into_response(),
+        _ => next.run(req).await,
+    };
+    let headers = res.headers_mut();
+    headers.append(VARY, HeaderValue::from_static("origin"));
+    if let Some(origin) = origin {
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        headers.insert(
+            ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderValue::from_static("GET, POST, PUT, DELETE, OPTIONS"),
+        );
+        headers.insert(
+            ACCESS_CONTROL_ALLOW_HEADERS,
+            HeaderValue::from_static("authorization, content-type"),
+        );
+        headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from_static("600"));
+    }
+    res
+}
+
+/// Rejects requests with an invalid player token in their `Authorization` header, or without one if the server
+/// requires tokens, and otherwise makes the token's claims available to the handler.
+async fn require_token(
+    State(manager): State<&SessionManager>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let config = manager.config();
+    match bearer_token(req.headers()) {
+        Some(token) => {
+            let claims =
+                verify_token(config, token).map_err(|_| ApiError(StatusCode::UNAUTHORIZED, GameError::InvalidToken))?;
+            req.extensions_mut().insert(claims);
+        }
+        None if config.require_tokens => return Err(ApiError(StatusCode::UNAUTHORIZED, GameError::InvalidToken)),
+        None => {}
+    }
+    Ok(next.run(req).await)
+}
+
+/// Gets the token in a request's `Authorization` header, if it has one.
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    (headers.get(AUTHORIZATION))
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+}
+
+/// Rejects requests which lack the admin token, either in their `Authorization` header or in the `token` query
+/// parameter, as browsers cannot set headers on websocket requests.
+async fn require_admin(State(manager): State<&SessionManager>, req: Request, next: Next) -> Result<Response, ApiError> {
+    let header = req.headers().get(AUTHORIZATION).and_then(|header| header.to_str().ok());
+    let token = ws::find_admin_token(header, req.uri().query());
+    if !admin::is_authorized(manager.config(), token) {
+        return Err(ApiError(StatusCode::UNAUTHORIZED, GameError::InvalidAdminToken));
+    }
+    Ok(next.run(req).await)
+}
+
+/// Gets the game and seat which the request's token was issued for.
+async fn get_me(claims: Option<Extension<Claims>>) -> Result<Json<Claims>, ApiError> {
+    let Extension(claims) = claims.ok_or(ApiError(StatusCode::UNAUTHORIZED, GameError::InvalidToken))?;
+    Ok(Json(claims))
+}
+
+/// Upgrades a request to a websocket connection with a game client, which is then served as if it had connected
+/// to the legacy websocket port. Connecting to a particular game checks that this node hosts the game before
+/// upgrading, and the client then follows the game from the start, as its overlay would, until it joins it.
+async fn connect_client(
+    State(manager): State<&'static SessionManager>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    game_id: Option<Path<String>>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let game_id = game_id.map(|Path(game_id)| game_id);
+    if let Some(game_id) = &game_id {
+        manager.find_game(game_id).map_err(|err| match err {
+            GameError::HostedElsewhere { .. } => ApiError(StatusCode::MISDIRECTED_REQUEST, err),
+            err => ApiError(StatusCode::NOT_FOUND, err),
+        })?;
+    }
+    let forwarded_for = headers.get(X_FORWARDED_FOR).and_then(|header| header.to_str().ok());
+    let address = manager.config().client_address(peer.ip(), forwarded_for);
+    let offered = headers
+        .get(SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|header| header.to_str().ok());
+    let format = WireFormat::negotiate(offered);
+    Ok(upgrade
+        .protocols(format.protocol())
+        .on_upgrade(move |socket| ws::serve_client(tungstenite(socket), format, Some(address), game_id, manager)))
+}
+
+/// Upgrades a request to a websocket connection on which the server's events are streamed to an operator.
+async fn watch_events(State(manager): State<&'static SessionManager>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| ws::stream_events(tungstenite(socket), manager))
+}
+
+/// Adapts an upgraded websocket to exchange the same messages as connections on the legacy websocket port.
+fn tungstenite(socket: WebSocket) -> impl Stream<Item = Result<Message, axum::Error>> + Sink<Message> + Unpin {
+    socket
+        .with(|msg| {
+            future::ok::<_, axum::Error>(match msg {
+                Message::Text(text) => axum_ws::Message::Text(text),
+                Message::Binary(bytes) => axum_ws::Message::Binary(bytes),
+                Message::Ping(bytes) => axum_ws::Message::Ping(bytes),
+                Message::Pong(bytes) => axum_ws::Message::Pong(bytes),
+                Message::Close(frame) => axum_ws::Message::Close(
+                    frame.map(|frame| axum_ws::CloseFrame { code: frame.code.into(), reason: frame.reason }),
+                ),
+                Message::Frame(frame) => axum_ws::Message::Binary(frame.into_data()),
+            })
+        })
+        .map_ok(|msg| match msg {
+            axum_ws::Message::Text(text) => Message::Text(text),
+            axum_ws::Message::Binary(bytes) => Message::Binary(bytes),
+            axum_ws::Message::Ping(bytes) => Message::Ping(bytes),
+            axum_ws::Message::Pong(bytes) => Message::Pong(bytes),
+            axum_ws::Message::Close(frame) => {
+                Message::Close(frame.map(|frame| CloseFrame { code: frame.code.into(), reason: frame.reason }))
+            }
+        })
+}
+
+/// Reports that the server is running.
+async fn healthz() -> StatusCode {
+    StatusCode::OK
+}
+
+/// Reports whether the server is able to serve games, which it cannot do if its store is unavailable.
+async fn readyz(State(manager): State<&SessionManager>) -> Result<StatusCode, ApiError> {
+    match manager.check_store() {
+        Ok(()) => Ok(StatusCode::OK),
+        Err(err) => {
+            log::error!("Store is unavailable: {}", err);
+            Err(ApiError(StatusCode::SERVICE_UNAVAILABLE, GameError::StoreUnavailable))
+        }
+    }
+}
+
+async fn get_sessions(State(manager): State<&SessionManager>) -> Result<Json<impl Serialize>, ApiError> {
     Ok(Json(json!({
-        "num_sessions": manager.num_games()
+        "num_sessions": manager.num_games(),
+        "num_quarantined": manager.quarantined_games().len(),
+        "startup": manager.load_report(),
+        "creations": manager.creation_stats(),
     })))
 }
 
-async fn get_past_games(State(manager): State<&SessionManager>) -> Result<Json<impl Serialize>, StatusCode> {
-    let games: Vec<_> = manager
-        .past_games()
+async fn get_lobbies(State(manager): State<&SessionManager>) -> Json<impl Serialize> {
+    Json(json!({
+        "lobbies": manager.public_lobbies()
+    }))
+}
+
+async fn get_past_games(
+    State(manager): State<&SessionManager>,
+    Query(query): Query<ArchiveQuery>,
+) -> Result<Json<impl Serialize>, ApiError> {
+    let page = manager.past_games(&query);
+    let games: Vec<_> = page
+        .games
         .iter()
         .map(|(id, stats)| {
             let mut json = serde_json::to_value(stats).unwrap_or(json!({}));
@@ -37,6 +278,432 @@ async fn get_past_games(State(manager): State<&SessionManager>) -> Result<Json<i
         .collect();
 
     Ok(Json(json!({
-        "games": games
+        "games": games,
+        "next": page.next
     })))
 }
+
+/// A request to resume a game from a save code.
+#[derive(Deserialize)]
+struct ImportRequest {
+    code: String,
+}
+
+/// Recreates a game from a save code, returning its new ID and board key.
+async fn import_game(
+    State(manager): State<&SessionManager>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(req): Json<ImportRequest>,
+) -> Result<Json<impl Serialize>, ApiError> {
+    let forwarded_for = headers.get(X_FORWARDED_FOR).and_then(|header| header.to_str().ok());
+    let address = manager.config().client_address(peer.ip(), forwarded_for).to_string();
+    let session = manager
+        .import_game(&req.code, Some(&address))
+        .map_err(|err| match err {
+            GameError::InvalidSaveCode => ApiError(StatusCode::BAD_REQUEST, err),
+            GameError::SaveCodesDisabled => ApiError(StatusCode::NOT_IMPLEMENTED, err),
+            GameError::ServerFull | GameError::TooManyLobbies { .. } => ApiError(StatusCode::TOO_MANY_REQUESTS, err),
+            err => {
+                log::error!("Cannot import game: {}", err);
+                ApiError(StatusCode::INTERNAL_SERVER_ERROR, err)
+            }
+        })?;
+    let created = session.call(|session| {
+        json!({
+            "game_id": session.id(),
+            "board_key": session.board_key(),
+        })
+    });
+    Ok(Json(created.await))
+}
+
+/// Lists the scenarios which can be played, along with the scenario of the day.
+async fn get_scenarios() -> Json<impl Serialize> {
+    Json(json!({
+        "scenarios": Scenario::catalogue(),
+        "daily": Scenario::daily(chrono::offset::Utc::now().date_naive()),
+    }))
+}
+
+/// Creates a game of the given scenario, returning its ID and board key.
+async fn create_scenario_game(
+    State(manager): State<&SessionManager>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(scenario): Path<Scenario>,
+) -> Result<Json<impl Serialize>, ApiError> {
+    let options = GameOptions {
+        scenario: Some(scenario),
+        ..Default::default()
+    };
+    let forwarded_for = headers.get(X_FORWARDED_FOR).and_then(|header| header.to_str().ok());
+    let address = manager.config().client_address(peer.ip(), forwarded_for).to_string();
+    let session = manager.create_game(options, Some(&address)).map_err(|err| match err {
+        GameError::ServerFull | GameError::TooManyLobbies { .. } => ApiError(StatusCode::TOO_MANY_REQUESTS, err),
+        err => {
+            log::error!("Cannot create scenario game: {}", err);
+            ApiError(StatusCode::INTERNAL_SERVER_ERROR, err)
+        }
+    })?;
+    let created = session.call(|session| {
+        json!({
+            "game_id": session.id(),
+            "board_key": session.board_key(),
+        })
+    });
+    Ok(Json(created.await))
+}
+
+/// Gets a summary of an archived game, formatted for chat embeds.
+async fn get_game_summary(
+    State(manager): State<&SessionManager>,
+    Path(id): Path<u64>,
+) -> Result<Json<impl Serialize>, ApiError> {
+    let stats = manager
+        .archived_game(id)
+        .ok_or(ApiError(StatusCode::NOT_FOUND, GameError::GameNotFound))?;
+    Ok(Json(summary::summarise(id, &stats)))
+}
+
+/// Options for polling a player's state.
+#[derive(Deserialize)]
+struct PollQuery {
+    /// Whether the poll counts as the player interacting with the game, keeping the session alive.
+    #[serde(default)]
+    touch: bool,
+}
+
+/// Gets a player as the rest of the table sees them, for clients which poll rather than stay connected.
+async fn get_player(
+    State(manager): State<&SessionManager>,
+    Path((game_id, name)): Path<(String, String)>,
+    Query(query): Query<PollQuery>,
+    claims: Option<Extension<Claims>>,
+) -> Result<Json<impl Serialize>, ApiError> {
+    let session = manager
+        .find_game(&game_id)
+        .map_err(|err| ApiError(StatusCode::NOT_FOUND, err))?;
+    let require_tokens = manager.config().require_tokens;
+    let player = session.call(move |session| {
+        authorize_seat(session, &name, claims, require_tokens)?;
+        let player = session
+            .public_player(&name)
+            .map_err(|err| ApiError(StatusCode::NOT_FOUND, err))?;
+        if query.touch {
+            session.heartbeat(Some(&name));
+        }
+        Ok(player)
+    });
+    Ok(Json(player.await?))
+}
+
+/// Gets a player's current prompt, which is `null` when they have nothing to do or the game has yet to start.
+/// As the prompt is private, it is only shown to a request bearing the player's token, even if tokens are optional.
+async fn get_player_prompt(
+    State(manager): State<&SessionManager>,
+    Path((game_id, name)): Path<(String, String)>,
+    Query(query): Query<PollQuery>,
+    claims: Option<Extension<Claims>>,
+) -> Result<Json<impl Serialize>, ApiError> {
+    let session = manager
+        .find_game(&game_id)
+        .map_err(|err| ApiError(StatusCode::NOT_FOUND, err))?;
+    let update = session.call(move |session| {
+        authorize_seat(session, &name, claims, true)?;
+        let update = session
+            .player_update(&name)
+            .map_err(|err| ApiError(StatusCode::NOT_FOUND, err))?;
+        if query.touch {
+            session.heartbeat(Some(&name));
+        }
+        Ok::<_, ApiError>(update)
+    });
+    let update = update.await?;
+    Ok(Json(json!({
+        "prompt": update.as_ref().and_then(|update| update.prompt.as_ref()),
+        "message": update.as_ref().and_then(|update| update.message.as_ref()),
+        "legal_actions": update.as_ref().map_or(&[][..], |update| &update.legal_actions[..]),
+    })))
+}
+
+/// Sets where a player is notified when the game is waiting on them, which needs the same token as their prompt.
+async fn register_push(
+    State(manager): State<&SessionManager>,
+    Path((game_id, name)): Path<(String, String)>,
+    claims: Option<Extension<Claims>>,
+    Json(target): Json<PushTarget>,
+) -> Result<StatusCode, ApiError> {
+    update_push(manager, &game_id, name, claims, Some(target)).await
+}
+
+/// Stops notifying a player when the game is waiting on them.
+async fn unregister_push(
+    State(manager): State<&SessionManager>,
+    Path((game_id, name)): Path<(String, String)>,
+    claims: Option<Extension<Claims>>,
+) -> Result<StatusCode, ApiError> {
+    update_push(manager, &game_id, name, claims, None).await
+}
+
+async fn update_push(
+    manager: &SessionManager,
+    game_id: &str,
+    name: String,
+    claims: Option<Extension<Claims>>,
+    target: Option<PushTarget>,
+) -> Result<StatusCode, ApiError> {
+    let session = manager
+        .find_game(game_id)
+        .map_err(|err| ApiError(StatusCode::NOT_FOUND, err))?;
+    let registered = session.call(move |session| {
+        authorize_seat(session, &name, claims, true)?;
+        session.register_push(&name, target).map_err(|err| match err {
+            GameError::NotificationsDisabled => ApiError(StatusCode::NOT_IMPLEMENTED, err),
+            GameError::PlayerNotFound => ApiError(StatusCode::NOT_FOUND, err),
+            err => ApiError(StatusCode::BAD_REQUEST, err),
+        })
+    });
+    registered.await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Checks that a request may act for the given player, as a websocket client joining as them could.
+/// The claims are those of the token which `require_token` verified, which must be for this player's seat,
+/// and which must be presented if `require_token` is set.
+fn authorize_seat(
+    session: &Session,
+    name: &str,
+    claims: Option<Extension<Claims>>,
+    require_token: bool,
+) -> Result<(), ApiError> {
+    let claims = claims.map(|Extension(claims)| claims);
+    if require_token && claims.is_none() {
+        return Err(ApiError(StatusCode::UNAUTHORIZED, GameError::InvalidToken));
+    }
+    session
+        .authorize_player(name, claims.as_ref(), require_token)
+        .map_err(|err| ApiError(StatusCode::UNAUTHORIZED, err))
+}
+
+/// A request to preview the roles that would be dealt in a game.
+#[derive(Deserialize)]
+struct DistributionQuery {
+    options: GameOptions,
+    num_players: usize,
+}
+
+async fn preview_distribution(Json(query): Json<DistributionQuery>) -> Result<Json<impl Serialize>, ApiError> {
+    query
+        .options
+        .player_distribution(query.num_players)
+        .map(Json)
+        .map_err(|err| ApiError(StatusCode::BAD_REQUEST, err))
+}
+
+/// Lists the games which could not be loaded and were quarantined, without their contents.
+async fn get_quarantine(State(manager): State<&SessionManager>) -> Json<impl Serialize> {
+    let games = manager.quarantined_games();
+    let games: Vec<_> = games
+        .iter()
+        .map(|game| {
+            json!({
+                "id": game.id,
+                "reason": game.reason,
+                "quarantined": game.quarantined,
+                "size": game.record.len(),
+            })
+        })
+        .collect();
+    Json(json!({ "count": games.len(), "games": games }))
+}
+
+/// Gets a quarantined game, along with its contents decoded as far as possible.
+async fn inspect_quarantined(
+    State(manager): State<&SessionManager>,
+    Path(id): Path<String>,
+) -> Result<Json<impl Serialize>, ApiError> {
+    let games = manager.quarantined_games();
+    let game = games
+        .into_iter()
+        .find(|game| game.id == id)
+        .ok_or(ApiError(StatusCode::NOT_FOUND, GameError::GameNotFound))?;
+    Ok(Json(json!({
+        "id": game.id,
+        "reason": game.reason,
+        "quarantined": game.quarantined,
+        "size": game.record.len(),
+        "contents": quarantined_contents(&game),
+    })))
+}
+
+/// Decodes the record of a quarantined game, falling back to its raw bytes if it cannot be decompressed.
+fn quarantined_contents(game: &QuarantinedGame) -> String {
+    match record::decode(&game.record) {
+        Ok(decoded) => String::from_utf8_lossy(&decoded).into_owned(),
+        Err(_) => String::from_utf8_lossy(&game.record).into_owned(),
+    }
+}
+
+/// Deletes a quarantined game for good.
+async fn delete_quarantined(
+    State(manager): State<&SessionManager>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    match manager.remove_quarantined(&id) {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(ApiError(StatusCode::NOT_FOUND, GameError::GameNotFound)),
+        Err(err) => {
+            log::error!("Cannot delete quarantined game: {}: {}", id, err);
+            Err(ApiError(StatusCode::INTERNAL_SERVER_ERROR, GameError::StoreUnavailable))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::auth::issue_token;
+    use crate::config::Config;
+    use crate::store::SqliteStore;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Serves the API for a new session manager on a local port, returning the server's address and the manager.
+    async fn serve(config: Config) -> (SocketAddr, &'static SessionManager) {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let manager = Box::leak(Box::new(SessionManager::new(store, config).unwrap()));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let service = make_router(manager)
+            .await
+            .into_make_service_with_connect_info::<SocketAddr>();
+        tokio::spawn(async move { axum::serve(listener, service).await });
+        (addr, manager)
+    }
+
+    #[tokio::test]
+    async fn player_routes_check_the_players_token() {
+        let config = Config { require_tokens: true, ..Config::from_env() };
+        let (addr, manager) = serve(config.clone()).await;
+        let session = manager.create_game(GameOptions::default(), None).unwrap();
+        let game_id = session.lock().unwrap().id().to_string();
+        session.lock().unwrap().add_player("ALEX").unwrap();
+
+        let client = reqwest::Client::new();
+        let prompt = format!("http://{}/games/{}/players/ALEX/prompt", addr, game_id);
+        let status = |req: reqwest::RequestBuilder| async move { req.send().await.unwrap().status() };
+        assert_eq!(status(client.get(&prompt)).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            status(client.get(&prompt).bearer_auth("nonsense")).await,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let other_seat = issue_token(&config, &game_id, "BOB");
+        assert_eq!(
+            status(client.get(&prompt).bearer_auth(&other_seat)).await,
+            StatusCode::UNAUTHORIZED
+        );
+        let token = issue_token(&config, &game_id, "ALEX");
+        assert_eq!(status(client.get(&prompt).bearer_auth(&token)).await, StatusCode::OK);
+
+        let push = format!("http://{}/games/{}/players/ALEX/push", addr, game_id);
+        assert_eq!(status(client.delete(&push)).await, StatusCode::UNAUTHORIZED);
+        let me = format!("http://{}/me", addr);
+        assert_eq!(status(client.get(&me)).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(client.get(&me).bearer_auth(&token)).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn private_routes_need_a_token_even_when_tokens_are_optional() {
+        let config = Config { require_tokens: false, ..Config::from_env() };
+        let (addr, manager) = serve(config.clone()).await;
+        let session = manager.create_game(GameOptions::default(), None).unwrap();
+        let game_id = session.lock().unwrap().id().to_string();
+        session.lock().unwrap().add_player("ALEX").unwrap();
+
+        let client = reqwest::Client::new();
+        let status = |req: reqwest::RequestBuilder| async move { req.send().await.unwrap().status() };
+        let prompt = format!("http://{}/games/{}/players/ALEX/prompt", addr, game_id);
+        assert_eq!(status(client.get(&prompt)).await, StatusCode::UNAUTHORIZED);
+        let other_seat = issue_token(&config, &game_id, "BOB");
+        assert_eq!(
+            status(client.get(&prompt).bearer_auth(&other_seat)).await,
+            StatusCode::UNAUTHORIZED
+        );
+        let token = issue_token(&config, &game_id, "ALEX");
+        assert_eq!(status(client.get(&prompt).bearer_auth(&token)).await, StatusCode::OK);
+
+        let push = format!("http://{}/games/{}/players/ALEX/push", addr, game_id);
+        assert_eq!(status(client.delete(&push)).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn clients_connect_to_a_game_through_the_api() {
+        let (addr, manager) = serve(Config::from_env()).await;
+        let session = manager.create_game(GameOptions::default(), None).unwrap();
+        let game_id = session.lock().unwrap().id().to_string();
+
+        let url = format!("ws://{}/ws/{}", addr, game_id);
+        let (mut socket, res) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        assert_eq!(res.status().as_u16(), StatusCode::SWITCHING_PROTOCOLS.as_u16());
+        let msg = tokio::time::timeout(Duration::from_secs(5), futures_util::StreamExt::next(&mut socket))
+            .await
+            .expect("an update is sent once connected")
+            .unwrap()
+            .unwrap();
+        let msg: serde_json::Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+        assert_eq!(msg["type"], "update");
+        assert_eq!(msg["state"]["game_id"], game_id);
+
+        // Only version 13 of the protocol is spoken, and games which are not hosted here are refused
+        let client = reqwest::Client::new();
+        let handshake = |url: String, version: &'static str| {
+            client
+                .get(url)
+                .header("connection", "upgrade")
+                .header("upgrade", "websocket")
+                .header("sec-websocket-version", version)
+                .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+                .send()
+        };
+        let res = handshake(format!("http://{}/ws/{}", addr, game_id), "8").await.unwrap();
+        assert!(res.status().is_client_error());
+        let res = handshake(format!("http://{}/ws/ZZZZ", addr), "13").await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn errors_are_described_by_a_code_and_message() {
+        let config = Config {
+            require_tokens: true,
+            admin_token: Some("secret".into()),
+            ..Config::from_env()
+        };
+        let (addr, manager) = serve(config).await;
+        let session = manager.create_game(GameOptions::default(), None).unwrap();
+        let game_id = session.lock().unwrap().id().to_string();
+        session.lock().unwrap().add_player("ALEX").unwrap();
+
+        let client = reqwest::Client::new();
+        let error = |url: String| {
+            let req = client.get(url);
+            async move {
+                let res = req.send().await.unwrap();
+                let status = res.status();
+                let body: serde_json::Value = serde_json::from_str(&res.text().await.unwrap()).unwrap();
+                assert!(body["message"].is_string());
+                (status, body["code"].as_str().unwrap().to_string())
+            }
+        };
+        let prompt = format!("http://{}/games/{}/players/ALEX/prompt", addr, game_id);
+        assert_eq!(error(prompt).await, (StatusCode::UNAUTHORIZED, "invalid_token".into()));
+        let summary = format!("http://{}/pastgames/12345/summary", addr);
+        assert_eq!(error(summary).await, (StatusCode::NOT_FOUND, "game_not_found".into()));
+        let quarantine = format!("http://{}/admin/quarantine", addr);
+        assert_eq!(
+            error(quarantine).await,
+            (StatusCode::UNAUTHORIZED, "invalid_admin_token".into())
+        );
+    }
+}
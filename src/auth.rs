@@ -0,0 +1,54 @@
+//! Signed tokens which tie a client to their seat in a game,
+//! so that only the player who took a seat can reclaim it after disconnecting.
+
+use crate::config::Config;
+use crate::error::GameError;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+/// The claims carried by a player's token.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct Claims {
+    /// The game the player joined.
+    pub game_id: String,
+    /// The name the player joined under, which identifies their seat in the game.
+    pub seat: String,
+    /// When the token expires, in seconds since the Unix epoch.
+    pub exp: u64,
+}
+
+/// Issues a token for the given seat in the given game.
+pub fn issue_token(config: &Config, game_id: &str, seat: &str) -> String {
+    let claims = Claims {
+        game_id: game_id.to_string(),
+        seat: seat.to_string(),
+        exp: jsonwebtoken::get_current_timestamp() + config.token_lifetime.as_secs(),
+    };
+    let key = EncodingKey::from_secret(config.token_secret.as_bytes());
+    encode(&Header::default(), &claims, &key).expect("claims can be signed")
+}
+
+/// Checks the signature and expiry of a token, returning its claims if it is valid.
+pub fn verify_token(config: &Config, token: &str) -> Result<Claims, GameError> {
+    let key = DecodingKey::from_secret(config.token_secret.as_bytes());
+    decode::<Claims>(token, &key, &Validation::default())
+        .map(|data| data.claims)
+        .map_err(|_| GameError::InvalidToken)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tokens_round_trip() {
+        let mut config = Config::from_env();
+        config.token_secret = "secret".into();
+        let token = issue_token(&config, "ABCD", "ALEX");
+        let claims = verify_token(&config, &token).unwrap();
+        assert_eq!((claims.game_id.as_str(), claims.seat.as_str()), ("ABCD", "ALEX"));
+
+        config.token_secret = "another secret".into();
+        assert!(verify_token(&config, &token).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! Plays many concurrent sessions between bots on an ephemeral server, and reports how quickly their actions were handled,
+//! for catching performance regressions in the update path.
+//!
+//! Usage: `loadtest [--sessions N] [--players N] [--seed N]`
+
+use hitler_server_rs::action::GameAction;
+use hitler_server_rs::config::Config;
+use hitler_server_rs::game::{BoardPrompt, GameOptions};
+use hitler_server_rs::session::{SessionHandle, SessionManager, NOTIFY_WINDOW};
+use hitler_server_rs::sim::{Bot, PartisanBot, BOARD_ACTIONS};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::time::{Duration, Instant};
+
+struct Args {
+    sessions: usize,
+    players: usize,
+    seed: u64,
+}
+
+/// How each session fared.
+#[derive(Default)]
+struct Report {
+    /// How long each action took to perform, including waiting for the session's lock.
+    latencies: Vec<Duration>,
+    /// The number of players' actions which were refused, typically because the bot's prompt was stale.
+    rejected: usize,
+    /// Whether the game was played to completion.
+    finished: bool,
+}
+
+/// The number of steps after which a game is assumed to be stuck.
+const MAX_STEPS: usize = 2_000;
+
+#[tokio::main]
+async fn main() {
+    let args = parse_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1)
+    });
+    let opts = GameOptions {
+        communists: args.players > 10,
+        ..GameOptions::default()
+    };
+    if let Err(err) = opts.validate(args.players) {
+        eprintln!("{}", err);
+        std::process::exit(1)
+    }
+    let manager = SessionManager::ephemeral(Config::from_env()).unwrap_or_else(|err| {
+        eprintln!("Could not create session manager: {}", err);
+        std::process::exit(1)
+    });
+
+    let started = Instant::now();
+    let mut tasks = vec![];
+    for i in 0..args.sessions {
+        let session = manager.create_game(opts, None).expect("could not create game");
+        let rng = ChaCha8Rng::seed_from_u64(args.seed.wrapping_add(i as u64));
+        tasks.push(tokio::spawn(play(session, args.players, rng)));
+    }
+    let mut reports = vec![];
+    for task in tasks {
+        reports.push(task.await.expect("session task panicked"));
+    }
+    let elapsed = started.elapsed();
+
+    let mut latencies = reports
+        .iter()
+        .flat_map(|r| r.latencies.iter().copied())
+        .collect::<Vec<_>>();
+    latencies.sort();
+    let percentile = |p: f64| {
+        let idx = ((latencies.len() as f64 * p) as usize).min(latencies.len().saturating_sub(1));
+        latencies.get(idx).copied().unwrap_or_default()
+    };
+    println!(
+        "sessions:   {} ({} finished)",
+        args.sessions,
+        reports.iter().filter(|r| r.finished).count()
+    );
+    println!(
+        "actions:    {} ({} rejected)",
+        latencies.len(),
+        reports.iter().map(|r| r.rejected).sum::<usize>()
+    );
+    println!("elapsed:    {:.2?}", elapsed);
+    println!(
+        "throughput: {:.0} actions/s",
+        latencies.len() as f64 / elapsed.as_secs_f64()
+    );
+    println!(
+        "latency:    p50 {:.2?}, p99 {:.2?}, max {:.2?}",
+        percentile(0.5),
+        percentile(0.99),
+        percentile(1.0)
+    );
+}
+
+/// Fills a session with bots and plays its game to completion, acting on the updates their clients would receive.
+async fn play(session: SessionHandle, players: usize, mut rng: ChaCha8Rng) -> Report {
+    let mut report = Report::default();
+    let names = (0..players).map(|i| format!("BOT{}", i + 1)).collect::<Vec<_>>();
+    let (mut updates, mut seats) = {
+        let mut session = session.lock().unwrap();
+        for name in &names {
+            session.add_player(name).expect("could not add player");
+        }
+        session.start_game().expect("could not start game");
+        let seats = (0..players)
+            .map(|seat| session.subscribe_player(seat).expect("could not subscribe to seat"))
+            .collect::<Vec<_>>();
+        (session.subscribe(), seats)
+    };
+    let perform = |action: GameAction, report: &mut Report| {
+        let started = Instant::now();
+        let result = session.lock().unwrap().perform_action(action);
+        report.latencies.push(started.elapsed());
+        result.is_ok()
+    };
+
+    for _ in 0..MAX_STEPS {
+        let update = updates.borrow_and_update().clone();
+        let board = update.board_update.as_ref();
+        if board.is_some_and(|board| matches!(board.prompt, Some(BoardPrompt::GameOver { .. }))) {
+            report.finished = true;
+            break;
+        }
+        let mut acted = false;
+        for seat in &mut seats {
+            let Some(player) = seat.borrow_and_update().clone() else {
+                continue;
+            };
+            if let Some(action) = PartisanBot.act(&player, &names, &mut rng) {
+                let action = GameAction::Player { name: player.name.clone(), action };
+                let ok = perform(action, &mut report);
+                report.rejected += !ok as usize;
+                acted |= ok;
+            }
+        }
+        if !acted {
+            for action in BOARD_ACTIONS {
+                if perform(GameAction::Board { action }, &mut report) {
+                    break;
+                }
+            }
+        }
+        // Give the session a chance to send the update it deferred to coalesce them, as a connected client would see
+        tokio::time::sleep(NOTIFY_WINDOW).await;
+        session.lock().unwrap().flush_updates();
+    }
+    report
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args { sessions: 200, players: 10, seed: 0 };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--sessions" => parsed.sessions = value()?.parse().map_err(|_| "invalid number of sessions")?,
+            "--players" => parsed.players = value()?.parse().map_err(|_| "invalid player count")?,
+            "--seed" => parsed.seed = value()?.parse().map_err(|_| "invalid seed")?,
+            other => return Err(format!("unknown argument: {}", other)),
+        }
+    }
+    Ok(parsed)
+}
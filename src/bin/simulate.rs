@@ -0,0 +1,81 @@
+//! Simulates many games between bots and reports win rates, for checking the balance of the game.
+//!
+//! Usage: `simulate [--games N] [--players MIN[-MAX]] [--bot random|partisan] [--seed N]
+//! [--communists] [--monarchist] [--anarchist] [--capitalist] [--centrists]`
+
+use hitler_server_rs::game::GameOptions;
+use hitler_server_rs::sim::{play_game, Bot, PartisanBot, RandomBot, Stats};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+struct Args {
+    games: usize,
+    players: (usize, usize),
+    bot: Box<dyn Bot>,
+    seed: u64,
+    opts: GameOptions,
+}
+
+fn main() {
+    let args = parse_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1)
+    });
+    let mut rng = ChaCha8Rng::seed_from_u64(args.seed);
+
+    for num_players in args.players.0..=args.players.1 {
+        if args.opts.player_distribution(num_players).is_err() {
+            println!(
+                "== {} players: not a valid player count for these options\n",
+                num_players
+            );
+            continue;
+        }
+        let mut stats = Stats::default();
+        for _ in 0..args.games {
+            let seed = rng.next_u64();
+            match play_game(args.opts, num_players, seed, args.bot.as_ref(), &mut rng) {
+                Ok(game) => stats.record(&game),
+                Err(_) => stats.record_failure(),
+            }
+        }
+        println!("== {} players\n{}", num_players, stats);
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        games: 1000,
+        players: (5, 10),
+        bot: Box::new(RandomBot),
+        seed: 0,
+        opts: GameOptions::default(),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--games" => parsed.games = value()?.parse().map_err(|_| "invalid number of games")?,
+            "--seed" => parsed.seed = value()?.parse().map_err(|_| "invalid seed")?,
+            "--players" => {
+                let value = value()?;
+                let (min, max) = value.split_once('-').unwrap_or((&value, &value));
+                let parse = |s: &str| s.parse::<usize>().map_err(|_| "invalid player count");
+                parsed.players = (parse(min)?, parse(max)?);
+            }
+            "--bot" => {
+                parsed.bot = match value()?.as_str() {
+                    "random" => Box::new(RandomBot),
+                    "partisan" => Box::new(PartisanBot),
+                    other => return Err(format!("unknown bot: {}", other)),
+                }
+            }
+            "--communists" => parsed.opts.communists = true,
+            "--monarchist" => parsed.opts.monarchist = true,
+            "--anarchist" => parsed.opts.anarchist = true,
+            "--capitalist" => parsed.opts.capitalist = true,
+            "--centrists" => parsed.opts.centrists = true,
+            other => return Err(format!("unknown argument: {}", other)),
+        }
+    }
+    Ok(parsed)
+}
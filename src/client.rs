@@ -1,11 +1,24 @@
 use crate::{
+    action::{BoardAction, GameAction, PlayerAction},
+    auth,
     error::GameError,
-    game::{Game as GameInner, GameOptions},
-    session::{GameLifecycle, GameUpdate, SessionHandle, SessionManager},
+    game::{ChatScope, GameOptions, PlayerUpdate, RolePreset, View},
+    patch,
+    session::{ChatMessage, GameLifecycle, GameUpdate, LobbySummary, SessionHandle, SessionManager},
 };
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
 use serde_json::{json, Value};
-use tokio::sync::watch;
+use std::collections::VecDeque;
+use std::net::IpAddr;
+use std::sync::Arc;
+use tokio::sync::{broadcast, watch};
+
+/// The number of consecutive patches sent before the full game state is sent again.
+const SNAPSHOT_INTERVAL: usize = 20;
+
+/// The number of updates a client may miss before it is considered to be lagging,
+/// in which case it is sent the full game state rather than a patch.
+const MAX_SKIPPED_UPDATES: u64 = 8;
 
 /// A single game client, which could for a board or a player.
 pub struct Client<'a> {
@@ -13,39 +26,46 @@ pub struct Client<'a> {
     session: Option<SessionHandle>,
     player: Option<String>,
     game_id: Option<String>,
+    board_key: Option<String>,
     updates: Option<watch::Receiver<GameUpdate>>,
+    /// The updates of the player's own seat, once the game has started.
+    seat: Option<watch::Receiver<Option<Arc<PlayerUpdate>>>>,
+    chat: Option<broadcast::Receiver<ChatMessage>>,
+    chat_backlog: VecDeque<ChatMessage>,
+    /// Whether the client has asked to be sent the full game state again.
+    resync: bool,
+    /// Whether the client wants updates as patches against the previous state.
+    diffs: bool,
+    /// The last state sent to the client, and the number of patches sent since it was last sent in full.
+    last_sent: Option<(Value, usize)>,
+    /// The version of the last game update sent to the client.
+    last_version: u64,
+    /// The generation of the game's lifecycle in the last update sent to the client.
+    last_generation: Option<u64>,
+    /// The claim this client holds on its player's seat.
+    claim: u64,
+    /// The list of public lobbies, if the client is watching it.
+    lobbies: Option<watch::Receiver<Vec<LobbySummary>>>,
+    /// Whether the client has yet to be sent the list of public lobbies since it started watching it.
+    lobbies_pending: bool,
+    /// The address the client connected from, if it is known, which limits how many lobbies it may open.
+    address: Option<String>,
+    /// What the client is shown of the game it has joined.
+    view: View,
+    /// The version of the game at which the board asked to force it onwards, until it confirms or cancels.
+    force_advance: Option<u64>,
 }
 
-/// An action performed by the board.
-#[derive(Serialize, Deserialize)]
-#[serde(tag = "type")]
-pub enum BoardAction {
-    EndVoting,
-    EndCardReveal,
-    EndExecutiveAction,
-    EndLegislativeSession,
-    EndAssassination,
-    EndCommunistStart,
-    EndCommunistEnd,
-    StartSpecialElection,
-}
-
-/// An action performed by the player.
-#[derive(Serialize, Deserialize)]
-#[serde(tag = "type")]
-pub enum PlayerAction {
-    EndNightRound,
-    EndCardReveal,
-    EndExecutiveAction,
-    ChoosePlayer { name: String },
-    CastVote { vote: bool },
-    Discard { index: usize },
-    VetoAgenda,
-    AcceptVeto,
-    RejectVeto,
-    StartAssassination,
-    EndCongress,
-    HijackElection,
+/// Something which must be relayed to the client.
+pub enum ClientEvent {
+    /// The game state has changed.
+    Update(Value),
+    /// The game state has changed, as described by a JSON patch against the previous state.
+    Patch(Vec<Value>),
+    /// A chat message has been received.
+    Chat(ChatMessage),
+    /// The list of public lobbies has changed.
+    Lobbies(Vec<LobbySummary>),
 }
 
 impl<'a> Client<'a> {
@@ -55,163 +75,732 @@ impl<'a> Client<'a> {
             manager,
             session: None,
             game_id: None,
+            board_key: None,
             player: None,
             updates: None,
+            seat: None,
+            chat: None,
+            chat_backlog: VecDeque::new(),
+            resync: false,
+            diffs: false,
+            last_sent: None,
+            last_version: 0,
+            last_generation: None,
+            claim: 0,
+            lobbies: None,
+            lobbies_pending: false,
+            address: None,
+            view: View::Board,
+            force_advance: None,
         }
     }
 
-    /// Creates a new game session, returning its ID.
-    pub fn create_game(&mut self, options: GameOptions) -> Result<String, GameError> {
-        let session = self.manager.create_game(options)?;
-        let id = session.lock().unwrap().id().to_owned();
-        Ok(id)
+    /// Records the address the client connected from.
+    pub fn set_address(&mut self, address: IpAddr) {
+        self.address = Some(address.to_string());
+    }
+
+    /// Creates a new game session, returning its ID and board key.
+    /// If a start time is given, the game starts by itself at that time.
+    pub async fn create_game(
+        &mut self,
+        options: GameOptions,
+        start_at: Option<DateTime<Utc>>,
+    ) -> Result<(String, Option<String>), GameError> {
+        let session = self.manager.create_game(options, self.address.as_deref())?;
+        let created = session.call(move |session| {
+            if let Some(start_at) = start_at {
+                session.schedule_start(start_at)?;
+            }
+            Ok((session.id().to_owned(), session.board_key().map(str::to_string)))
+        });
+        created.await
     }
 
-    /// Joins a game as a board.
-    pub fn join_as_board(&mut self, game_id: &str) -> Result<(), GameError> {
+    /// Joins a game as a board, which requires the board key of the game.
+    pub async fn join_as_board(&mut self, game_id: &str, board_key: Option<&str>) -> Result<(), GameError> {
         let session = self.manager.find_game(game_id)?;
+        let key = board_key.map(str::to_string);
+        let subscribed = session.call(move |session| {
+            session.authorize_board(key.as_deref())?;
+            Ok((session.subscribe(), session.subscribe_chat(None)))
+        });
+        let (updates, chat) = subscribed.await?;
+        self.detach();
         self.player = None;
+        self.seat = None;
         self.game_id = Some(game_id.to_string());
-        self.updates = Some(session.lock().unwrap().subscribe());
+        self.board_key = board_key.map(str::to_string);
+        self.view = View::Board;
+        self.updates = Some(updates);
+        self.last_sent = None;
+        self.set_chat(chat);
         self.session = Some(session);
         Ok(())
     }
 
-    /// Joins a game as a player.
-    pub fn join_as_player(&mut self, game_id: &str, name: &str) -> Result<(), GameError> {
-        let session = self.manager.find_game(game_id)?;
-        {
-            let mut session = session.lock().unwrap();
-            session.add_player(name)?;
-            self.player = Some(name.to_string());
-            self.game_id = Some(game_id.to_string());
-            self.updates = Some(session.subscribe());
+    /// Follows a game without taking part in it, either as a stream overlay or as a spectator shown the board.
+    /// The overlay is only shown what is public and needs no key, but the board shows the shared device of a
+    /// hotseat game, so it needs the board key. Either is only sent updates; they keep no hold on the session,
+    /// so they cannot act on the game.
+    pub async fn join_as_overlay(
+        &mut self,
+        game_id: &str,
+        view: View,
+        board_key: Option<&str>,
+    ) -> Result<(), GameError> {
+        if view == View::Player {
+            return Err(GameError::InvalidAction);
         }
-        self.session = Some(session);
+        let session = self.manager.find_game(game_id)?;
+        let key = board_key.map(str::to_string);
+        let subscribed = session.call(move |session| {
+            if view == View::Board {
+                session.authorize_board(key.as_deref())?;
+            }
+            Ok(session.subscribe())
+        });
+        let updates = subscribed.await?;
+        self.detach();
+        self.player = None;
+        self.seat = None;
+        self.board_key = None;
+        self.chat = None;
+        self.chat_backlog.clear();
+        self.session = None;
+        self.last_sent = None;
+        self.game_id = Some(game_id.to_string());
+        self.view = view;
+        self.updates = Some(updates);
         Ok(())
     }
 
-    /// Waits until there is an update to the game state, then returns the latest state.
-    pub async fn next_state(&mut self) -> Value {
-        let Some(updates) = &mut self.updates else {
-            return std::future::pending().await;
+    /// Joins a game as a player, returning a token with which the player can later reclaim their seat.
+    pub async fn join_as_player(
+        &mut self,
+        game_id: &str,
+        name: &str,
+        token: Option<&str>,
+    ) -> Result<String, GameError> {
+        let config = self.manager.config();
+        let claims = token.map(|token| auth::verify_token(config, token)).transpose()?;
+        let session = self.manager.find_game(game_id)?;
+        self.detach();
+        self.player = None;
+        let (require_tokens, timeout, seat) = (config.require_tokens, config.connection_timeout, name.to_string());
+        let joined = session.call(move |session| {
+            session.authorize_player(&seat, claims.as_ref(), require_tokens)?;
+            session.add_player(&seat)?;
+            let claim = session.attach(&seat, timeout)?;
+            Ok((claim, session.subscribe(), session.subscribe_chat(Some(&seat))))
+        });
+        let (claim, updates, chat) = joined.await?;
+        self.claim = claim;
+        self.player = Some(name.to_string());
+        self.board_key = None;
+        self.view = View::Player;
+        self.game_id = Some(game_id.to_string());
+        self.updates = Some(updates);
+        self.seat = None;
+        self.last_sent = None;
+        self.set_chat(chat);
+        self.session = Some(session);
+        Ok(auth::issue_token(config, game_id, name))
+    }
+
+    /// Changes this player's name while the game is still in the lobby,
+    /// returning a new token for their seat as the old one names the old seat.
+    pub async fn rename(&mut self, new_name: &str) -> Result<String, GameError> {
+        let (Some(session), Some(player), Some(game_id)) = (&self.session, &self.player, &self.game_id) else {
+            return Err(GameError::InvalidAction);
+        };
+        let (player, claim, name) = (player.clone(), self.claim, new_name.to_string());
+        let renamed = session.call(move |session| {
+            session.check_claim(&player, claim)?;
+            session.rename_player(&player, &name)
+        });
+        renamed.await?;
+        let token = auth::issue_token(self.manager.config(), game_id, new_name);
+        self.player = Some(new_name.to_string());
+        Ok(token)
+    }
+
+    /// Waits until there is an update to the game state or a new chat message, then returns it.
+    pub async fn next_event(&mut self) -> ClientEvent {
+        loop {
+            if let Some(event) = self.poll_event().await {
+                return event;
+            }
+        }
+    }
+
+    /// Waits until something happens which may need relaying to the client,
+    /// returning `None` if it turns out the client need not be told.
+    async fn poll_event(&mut self) -> Option<ClientEvent> {
+        if let Some(message) = self.chat_backlog.pop_front() {
+            return Some(ClientEvent::Chat(message));
+        }
+        if std::mem::take(&mut self.resync) && self.updates.is_some() {
+            self.last_sent = None;
+            return self.state_event().await;
+        }
+        if let (true, Some(lobbies)) = (std::mem::take(&mut self.lobbies_pending), &self.lobbies) {
+            return Some(ClientEvent::Lobbies(lobbies.borrow().clone()));
+        }
+
+        let changed = async {
+            match &mut self.updates {
+                Some(updates) => updates.changed().await.ok(),
+                None => std::future::pending().await,
+            }
+        };
+        let seat_changed = async {
+            match &mut self.seat {
+                Some(seat) => match seat.changed().await {
+                    Ok(()) => (),
+                    Err(_) => std::future::pending().await,
+                },
+                None => std::future::pending().await,
+            }
+        };
+        let chat = Self::next_chat(&mut self.chat, self.player.as_deref());
+        let lobbies = async {
+            match &mut self.lobbies {
+                Some(lobbies) => match lobbies.changed().await {
+                    Ok(()) => lobbies.borrow().clone(),
+                    Err(_) => std::future::pending().await,
+                },
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            _ = changed => self.state_event().await,
+            _ = seat_changed => self.state_event().await,
+            message = chat => Some(ClientEvent::Chat(message)),
+            lobbies = lobbies => Some(ClientEvent::Lobbies(lobbies)),
+        }
+    }
+
+    /// Creates the event which brings the client up to date with the latest game state,
+    /// being a patch if the client has opted into them, or otherwise the full state.
+    /// Overlays are only sent the state when it differs from what they were last sent.
+    async fn state_event(&mut self) -> Option<ClientEvent> {
+        self.refresh_seat().await;
+        let state = self.current_state();
+        let (version, generation) = self.updates.as_ref().map_or((0, None), |updates| {
+            let update = updates.borrow();
+            (update.version, Some(update.generation))
+        });
+        self.last_generation = generation;
+        let skipped = version.saturating_sub(self.last_version + 1);
+        self.last_version = version;
+        if self.view == View::Overlay {
+            if self.last_sent.as_ref().is_some_and(|(last, _)| *last == state) {
+                return None;
+            }
+            self.last_sent = Some((state.clone(), 0));
+            return Some(ClientEvent::Update(state));
+        }
+        if !self.diffs {
+            return Some(ClientEvent::Update(state));
+        }
+        if skipped > MAX_SKIPPED_UPDATES {
+            log::debug!("Client skipped {} updates; sending full state", skipped);
+            self.last_sent = None;
+        }
+        match self.last_sent.take() {
+            Some((last, count)) if count < SNAPSHOT_INTERVAL => {
+                let ops = patch::diff(&last, &state);
+                self.last_sent = Some((state, count + 1));
+                Some(ClientEvent::Patch(ops))
+            }
+            _ => {
+                self.last_sent = Some((state.clone(), 0));
+                Some(ClientEvent::Update(state))
+            }
+        }
+    }
+
+    /// Sets whether the client should be sent patches rather than the full game state.
+    pub fn set_diffs(&mut self, enabled: bool) {
+        self.diffs = enabled;
+        self.last_sent = None;
+    }
+
+    /// Subscribes to the updates of the player's seat once the game has started,
+    /// and again whenever the game's lifecycle changes and the old subscription is closed.
+    async fn refresh_seat(&mut self) {
+        if let Some(seat) = &mut self.seat {
+            if seat.has_changed().is_ok() {
+                // Mark the update as seen, as it is about to be sent
+                seat.borrow_and_update();
+                return;
+            }
+        }
+        self.seat = None;
+        let (Some(session), Some(player)) = (&self.session, &self.player) else {
+            return;
         };
+        let player = player.clone();
+        let seat = session.call(move |session| {
+            let seat = session.seat(&player)?;
+            session.subscribe_player(seat).ok()
+        });
+        self.seat = seat.await;
+    }
 
-        updates.changed().await.ok();
+    /// Gets the latest game state, as seen by this client.
+    fn current_state(&self) -> Value {
+        let Some(updates) = &self.updates else {
+            return Value::Null;
+        };
         let update = updates.borrow();
+        if self.view == View::Overlay {
+            return json!({
+                "game_id": self.game_id,
+                "view": View::Overlay,
+                "state": update.overlay
+            });
+        }
 
         let state = match update.lifecycle {
             GameLifecycle::Lobby { can_start } => {
-                json!({ "type": "lobby", "can_start": can_start })
+                json!({
+                    "type": "lobby",
+                    "can_start": can_start,
+                    "problems": update.problems,
+                    "presets": RolePreset::catalogue(),
+                })
+            }
+            GameLifecycle::Scheduled { can_start, start_at } => {
+                json!({
+                    "type": "lobby",
+                    "can_start": can_start,
+                    "start_at": start_at,
+                    "problems": update.problems,
+                    "presets": RolePreset::catalogue(),
+                })
             }
             GameLifecycle::Playing => {
                 if let Some(name) = &self.player {
-                    let mut state = json!(update.player_updates.iter().find(|u| &u.name == name));
+                    // The seat may have been handed over to someone else, in which case there is nothing to show
+                    let seat = self.seat.as_ref().and_then(|seat| seat.borrow().clone());
+                    let mut state = json!(seat.filter(|update| &update.name == name));
                     state["type"] = "player".into();
                     state
                 } else {
                     let mut state = json!(update.board_update);
                     state["type"] = "board".into();
+                    state["hotseat"] = json!(update.hotseat);
                     state
                 }
             }
             GameLifecycle::Ended => json!({ "type": "ended" }),
+            GameLifecycle::Cancelled => json!({ "type": "cancelled" }),
         };
 
         json!({
             "game_id": self.game_id,
             "name": self.player,
+            "board_key": self.board_key,
             "players": update.players,
+            "timer": update.timer,
+            "house_rules": update.house_rules,
+            "options": update.options,
+            "started_at": update.started_at,
+            "state_hash": update.state_hash,
+            "generation": update.generation,
             "state": state
         })
     }
 
-    /// Leaves the game.
+    /// Waits until a chat message visible to the given player (or the board) arrives, then returns it.
+    async fn next_chat(chat: &mut Option<broadcast::Receiver<ChatMessage>>, player: Option<&str>) -> ChatMessage {
+        loop {
+            let Some(receiver) = chat else {
+                return std::future::pending().await;
+            };
+            match receiver.recv().await {
+                Ok(message) if message.visible_to(player) => return message,
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => *chat = None,
+            }
+        }
+    }
+
+    /// Sets whether the client is sent the list of public lobbies whenever it changes.
+    pub fn watch_lobbies(&mut self, enabled: bool) {
+        self.lobbies = enabled.then(|| self.manager.subscribe_lobbies());
+        self.lobbies_pending = enabled;
+    }
+
+    /// Requests that the full game state be sent again, such as when the client has detected it is out of sync.
+    pub fn resync(&mut self) {
+        self.resync = true;
+    }
+
+    /// Leaves the game, giving up the player's seat if the game is still in the lobby.
     pub fn leave(&mut self) {
+        if let (Some(session), Some(player)) = (&self.session, &self.player) {
+            let (player, claim) = (player.clone(), self.claim);
+            session.cast(move |session| {
+                if session.check_claim(&player, claim).is_ok() {
+                    session.remove_player(&player).ok();
+                }
+            });
+        }
+        self.detach();
         self.player = None;
         self.game_id = None;
+        self.board_key = None;
+        self.view = View::Board;
         self.updates = None;
+        self.seat = None;
+        self.last_sent = None;
+        self.last_generation = None;
+        self.chat = None;
+        self.chat_backlog.clear();
         self.session = None;
     }
 
-    /// Starts a new game of Secret Hitler.
-    pub fn start_game(&self) -> Result<(), GameError> {
+    /// Starts a new game of Secret Hitler, unless the game has changed since the client last saw it.
+    /// If the game was already started, the client is sent the latest state so that it catches up.
+    pub async fn start_game(&mut self) -> Result<(), GameError> {
         let Some(session) = &self.session else {
             return Err(GameError::InvalidAction);
         };
-        let mut session = session.lock().unwrap();
-        session.start_game()
+        let generation = self.last_generation;
+        let result = session.call(move |session| session.start_game_from(generation)).await;
+        if let Err(GameError::AlreadyStarted) = result {
+            self.resync = true;
+        }
+        result
     }
 
     /// Called when the board performs an action.
-    pub fn board_action(&self, action: BoardAction) -> Result<(), GameError> {
+    pub async fn board_action(&self, action: BoardAction) -> Result<(), GameError> {
         if self.player.is_some() {
             return Err(GameError::InvalidAction);
         }
-        self.mutate_game(|game| match action {
-            BoardAction::EndVoting => game.end_voting(),
-            BoardAction::EndCardReveal => game.end_card_reveal(None),
-            BoardAction::EndExecutiveAction => game.end_executive_action(None),
-            BoardAction::EndLegislativeSession => game.end_legislative_session(),
-            BoardAction::EndAssassination => game.end_assassination(),
-            BoardAction::EndCommunistStart => game.end_communist_start(),
-            BoardAction::EndCommunistEnd => game.end_communist_end(),
-            BoardAction::StartSpecialElection => game.start_special_election(),
-        })
+        if matches!(action, BoardAction::ForceAdvance) {
+            return Err(GameError::ConfirmationRequired);
+        }
+        self.perform_action(GameAction::Board { action }).await
+    }
+
+    /// Asks to move a stuck game on with safe defaults, at the request of the board.
+    /// As this overrides the players, nothing happens until the board confirms it with [Client::confirm_force_advance].
+    pub async fn request_force_advance(&mut self) -> Result<(), GameError> {
+        let session = self.board_session()?;
+        let version = session.call(|session| session.version()).await;
+        self.force_advance = Some(version);
+        Ok(())
+    }
+
+    /// Moves a stuck game on with safe defaults, once the board has confirmed its request to.
+    /// If the game has changed since the request, it must be made again.
+    pub async fn confirm_force_advance(&mut self) -> Result<(), GameError> {
+        let version = self.force_advance.take().ok_or(GameError::ConfirmationRequired)?;
+        let session = self.board_session()?;
+        let advanced = {
+            let _span = tracing::warn_span!("perform_action", action = "ForceAdvance").entered();
+            session.call(move |session| session.force_advance_from(version))
+        };
+        advanced.await
+    }
+
+    /// Drops the board's request to move the game on, if it has one.
+    pub fn cancel_force_advance(&mut self) {
+        self.force_advance = None;
     }
 
     /// Called when a player performs an action.
-    pub fn player_action(&self, action: PlayerAction) -> Result<(), GameError> {
-        let player = self.player.as_ref().ok_or(GameError::InvalidAction)?;
-        self.mutate_game(|game| {
-            let player = game.find_player(player)?;
-            match &action {
-                PlayerAction::EndNightRound => game.end_night_round(player),
-                PlayerAction::EndCardReveal => game.end_card_reveal(Some(player)),
-                PlayerAction::EndExecutiveAction => game.end_executive_action(Some(player)),
-                PlayerAction::CastVote { vote } => game.cast_vote(player, *vote),
-                PlayerAction::ChoosePlayer { name } => {
-                    let other = game.find_player(name)?;
-                    game.choose_player(player, other)
-                }
-                PlayerAction::Discard { index } => game.discard_policy(player, *index),
-                PlayerAction::VetoAgenda => game.veto_agenda(player),
-                PlayerAction::AcceptVeto => game.veto_agenda(player),
-                PlayerAction::RejectVeto => game.reject_veto(player),
-                PlayerAction::StartAssassination => game.start_assassination(player),
-                PlayerAction::EndCongress => game.end_congress(player),
-                PlayerAction::HijackElection => game.hijack_special_election(player),
+    pub async fn player_action(&self, action: PlayerAction) -> Result<(), GameError> {
+        let name = self.player.clone().ok_or(GameError::InvalidAction)?;
+        self.perform_action(GameAction::Player { name, action }).await
+    }
+
+    /// Adds a player to a hotseat game, which only the board may do.
+    pub async fn add_hotseat_player(&self, name: &str) -> Result<(), GameError> {
+        let name = name.to_string();
+        self.board_session()?
+            .call(move |session| session.add_hotseat_player(&name))
+            .await
+    }
+
+    /// Lets the connection waiting to claim the given player's seat take it over, which only the board may do.
+    pub async fn approve_takeover(&self, name: &str) -> Result<(), GameError> {
+        let name = name.to_string();
+        self.board_session()?
+            .call(move |session| session.approve_takeover(&name))
+            .await
+    }
+
+    /// Lets a newcomer take over a player's seat in a game in progress, which only the board may do.
+    pub async fn substitute_player(&self, name: &str, new_name: &str) -> Result<(), GameError> {
+        let (name, new_name) = (name.to_string(), new_name.to_string());
+        self.board_session()?
+            .call(move |session| session.substitute_player(&name, &new_name))
+            .await
+    }
+
+    /// Gets a save code from which the game can be resumed elsewhere, which only the board may do.
+    pub async fn save_code(&self) -> Result<String, GameError> {
+        let config = self.manager.config().clone();
+        self.board_session()?
+            .call(move |session| session.save_code(&config))
+            .await
+    }
+
+    /// Removes a player from the lobby, which only the board may do.
+    pub async fn remove_player(&self, name: &str) -> Result<(), GameError> {
+        let name = name.to_string();
+        self.board_session()?
+            .call(move |session| session.remove_player(&name))
+            .await
+    }
+
+    /// Reveals the private prompt of the player holding the device, in a hotseat game.
+    pub async fn hotseat_ready(&self) -> Result<(), GameError> {
+        self.board_session()?.call(|session| session.hotseat_ready()).await
+    }
+
+    /// Performs an action for the player holding the device, in a hotseat game.
+    pub async fn hotseat_action(&self, action: PlayerAction) -> Result<(), GameError> {
+        self.board_session()?
+            .call(move |session| session.hotseat_action(action))
+            .await
+    }
+
+    /// Undoes the player's last action.
+    pub async fn undo(&self) -> Result<(), GameError> {
+        let player = self.player.clone().ok_or(GameError::InvalidAction)?;
+        let Some(session) = &self.session else {
+            return Err(GameError::InvalidAction);
+        };
+        let claim = self.claim;
+        let undone = session.call(move |session| {
+            session.check_claim(&player, claim)?;
+            session.undo(&player)
+        });
+        undone.await
+    }
+
+    /// Sends a chat message to the given scope.
+    pub async fn send_chat(&self, scope: ChatScope, text: &str) -> Result<(), GameError> {
+        let Some(session) = &self.session else {
+            return Err(GameError::InvalidAction);
+        };
+        let (player, claim, text) = (self.player.clone(), self.claim, text.to_string());
+        let sent = session.call(move |session| {
+            if let Some(player) = &player {
+                session.check_claim(player, claim)?;
             }
-        })
+            session.send_chat(player.as_deref(), scope, &text)
+        });
+        sent.await
     }
 
-    /// Keeps the game session alive.
+    /// Keeps the game session alive, and records that the player is still connected.
     pub fn heartbeat(&self) {
         let Some(session) = &self.session else {
             return;
         };
-        let mut session = session.lock().unwrap();
-        session.heartbeat();
+        let player = self.player.clone();
+        session.cast(move |session| session.heartbeat(player.as_deref()));
     }
 
     /// Ends the game.
-    pub fn end_game(&self) -> Result<(), GameError> {
+    pub async fn end_game(&self) -> Result<(), GameError> {
         let Some(session) = &self.session else {
             return Err(GameError::InvalidAction);
         };
-        let mut session = session.lock().unwrap();
-        session.end_game()
+        session.call(|session| session.end_game()).await
+    }
+
+    /// Returns a finished game to the lobby, keeping the same players and options.
+    pub async fn restart_lobby(&self) -> Result<(), GameError> {
+        let Some(session) = &self.session else {
+            return Err(GameError::InvalidAction);
+        };
+        session.call(|session| session.restart_lobby()).await
+    }
+
+    /// Detaches this client from the seat of the player it joined as, if any.
+    /// Nothing waits for it, so that a client can be detached as it is dropped.
+    fn detach(&mut self) {
+        let (Some(session), Some(player)) = (&self.session, &self.player) else {
+            return;
+        };
+        let (player, claim) = (player.clone(), self.claim);
+        session.cast(move |session| session.detach(&player, claim));
+    }
+
+    /// Sets the session's chat which this client follows, queueing up the message history visible to it.
+    fn set_chat(&mut self, (history, chat): (Vec<ChatMessage>, broadcast::Receiver<ChatMessage>)) {
+        self.chat_backlog = history.into();
+        self.chat = Some(chat);
     }
 
-    /// Performs an action on the game.
-    fn mutate_game<F>(&self, mutation: F) -> Result<(), GameError>
-    where
-        F: FnOnce(&mut GameInner) -> Result<(), GameError>,
-    {
+    /// Gets the session this client has joined as the board.
+    fn board_session(&self) -> Result<&SessionHandle, GameError> {
+        match (&self.session, &self.player) {
+            (Some(session), None) => Ok(session),
+            _ => Err(GameError::InvalidAction),
+        }
+    }
+
+    /// Performs an action on the game, provided a player client still holds its claim on the player's seat.
+    async fn perform_action(&self, action: GameAction) -> Result<(), GameError> {
         let Some(session) = &self.session else {
             return Err(GameError::InvalidAction);
         };
-        let mut session = session.lock().unwrap();
-        session.mutate_game(mutation)
+        let (player, claim) = (self.player.clone(), self.claim);
+        let performed = {
+            // The action is named in any warning that it held the session's lock for too long
+            let _span = tracing::warn_span!("perform_action", action = action.kind()).entered();
+            session.call(move |session| {
+                player
+                    .as_ref()
+                    .map_or(Ok(()), |player| session.check_claim(player, claim))
+                    .and_then(|_| session.perform_action(action))
+            })
+        };
+        performed.await
+    }
+}
+
+impl Drop for Client<'_> {
+    fn drop(&mut self) {
+        self.detach();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::action::GameAction;
+    use crate::clock::MockClock;
+    use crate::config::Config;
+    use crate::session::NOTIFY_WINDOW;
+    use crate::store::SqliteStore;
+    use std::time::Duration;
+
+    /// Waits for the game to be updated, returning what the client was told of it.
+    async fn poll(client: &mut Client<'_>) -> Option<ClientEvent> {
+        let event = tokio::time::timeout(Duration::from_secs(5), client.poll_event());
+        event.await.expect("the game was updated")
+    }
+
+    #[tokio::test]
+    async fn overlays_are_only_told_of_changes_they_show() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let clock = Arc::new(MockClock::new());
+        let manager = SessionManager::with_clock(store, Config::from_env(), clock.clone()).unwrap();
+        let handle = manager.create_game(GameOptions::default(), None).unwrap();
+        let players = ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"];
+        let game_id = {
+            let mut session = handle.lock().unwrap();
+            for name in players {
+                session.add_player(name).unwrap();
+            }
+            session.start_game().unwrap();
+            session.id().to_string()
+        };
+
+        clock.advance(NOTIFY_WINDOW);
+        let mut client = Client::new(&manager);
+        client.join_as_overlay(&game_id, View::Overlay, None).await.unwrap();
+        assert!(
+            matches!(poll(&mut client).await, Some(ClientEvent::Update(state)) if state["state"]["type"] == "overlay")
+        );
+
+        // The board shows who has finished the night round, but the overlay does not
+        let end_night_round = |name: &str| {
+            clock.advance(NOTIFY_WINDOW);
+            let action = PlayerAction::EndNightRound;
+            let mut session = handle.lock().unwrap();
+            session
+                .perform_action(GameAction::Player { name: name.into(), action })
+                .unwrap();
+        };
+        end_night_round("ALEX");
+        assert!(poll(&mut client).await.is_none());
+
+        for name in &players[1..] {
+            end_night_round(name);
+        }
+        assert!(
+            matches!(poll(&mut client).await, Some(ClientEvent::Update(state)) if state["state"]["phase"] == "Election")
+        );
+    }
+
+    #[tokio::test]
+    async fn boards_must_present_the_board_key() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let clock = Arc::new(MockClock::new());
+        let manager = SessionManager::with_clock(store, Config::from_env(), clock.clone()).unwrap();
+        let mut client = Client::new(&manager);
+        let (game_id, board_key) = client.create_game(GameOptions::default(), None).await.unwrap();
+        let board_key = board_key.expect("games have a board key");
+
+        let result = client.join_as_board(&game_id, None).await;
+        assert!(matches!(result, Err(GameError::InvalidBoardKey)));
+        let result = client.join_as_board(&game_id, Some("WRONG")).await;
+        assert!(matches!(result, Err(GameError::InvalidBoardKey)));
+        let result = client.remove_player("ALEX").await;
+        assert!(matches!(result, Err(GameError::InvalidAction)));
+
+        clock.advance(NOTIFY_WINDOW);
+        client.join_as_board(&game_id, Some(&board_key)).await.unwrap();
+        let Some(ClientEvent::Update(state)) = poll(&mut client).await else {
+            panic!("the board was not sent the game");
+        };
+        assert_eq!(state["state"]["type"], "lobby");
+        let result = client.remove_player("ALEX").await;
+        assert!(matches!(result, Err(GameError::PlayerNotFound)));
+    }
+
+    #[tokio::test]
+    async fn followers_without_the_board_key_are_not_shown_the_device() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let clock = Arc::new(MockClock::new());
+        let manager = SessionManager::with_clock(store, Config::from_env(), clock.clone()).unwrap();
+        let options = GameOptions { hotseat: true, ..GameOptions::default() };
+        let handle = manager.create_game(options, None).unwrap();
+        let (game_id, board_key) = {
+            let mut session = handle.lock().unwrap();
+            for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"] {
+                session.add_hotseat_player(name).unwrap();
+            }
+            session.start_game().unwrap();
+            session.hotseat_ready().unwrap();
+            (session.id().to_string(), session.board_key().unwrap().to_string())
+        };
+
+        clock.advance(NOTIFY_WINDOW);
+        let mut client = Client::new(&manager);
+        let result = client.join_as_overlay(&game_id, View::Board, None).await;
+        assert!(matches!(result, Err(GameError::InvalidBoardKey)));
+        let result = client.join_as_overlay(&game_id, View::Board, Some("WRONG")).await;
+        assert!(matches!(result, Err(GameError::InvalidBoardKey)));
+        client.join_as_overlay(&game_id, View::Overlay, None).await.unwrap();
+        let Some(ClientEvent::Update(state)) = poll(&mut client).await else {
+            panic!("the overlay was not sent the game");
+        };
+        assert!(state["state"].get("hotseat").is_none());
+        assert!(!state.to_string().contains("role"));
+
+        // With the key, the board is shown the prompt of whoever holds the device
+        clock.advance(NOTIFY_WINDOW);
+        client
+            .join_as_overlay(&game_id, View::Board, Some(&board_key))
+            .await
+            .unwrap();
+        let Some(ClientEvent::Update(state)) = poll(&mut client).await else {
+            panic!("the board was not sent the game");
+        };
+        assert_eq!(state["state"]["hotseat"]["type"], "Player");
     }
 }
@@ -0,0 +1,77 @@
+//! The source of the current time for sessions, which can be replaced so that time-based behaviour can be tested.
+
+use chrono::{DateTime, Utc};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A source of the current time.
+pub trait Clock: Send + Sync {
+    /// The current monotonic time, used to measure how long something has taken.
+    fn instant(&self) -> Instant;
+
+    /// The current wall-clock time, used for deadlines and timestamps.
+    fn now(&self) -> DateTime<Utc>;
+
+    /// How long it has been since `earlier`.
+    fn since(&self, earlier: Instant) -> Duration {
+        self.instant().saturating_duration_since(earlier)
+    }
+}
+
+/// The system's own clock.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock which only moves when it is told to.
+#[derive(Debug)]
+pub struct MockClock {
+    instant: Instant,
+    now: DateTime<Utc>,
+    offset: Mutex<Duration>,
+}
+
+impl MockClock {
+    /// Creates a clock which is stopped at the current time.
+    pub fn new() -> Self {
+        Self {
+            instant: Instant::now(),
+            now: Utc::now(),
+            offset: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Moves the clock forwards.
+    pub fn advance(&self, by: Duration) {
+        *self.offset.lock().unwrap() += by;
+    }
+
+    fn offset(&self) -> Duration {
+        *self.offset.lock().unwrap()
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn instant(&self) -> Instant {
+        self.instant + self.offset()
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.now + chrono::Duration::from_std(self.offset()).expect("clock advanced too far")
+    }
+}
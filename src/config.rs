@@ -0,0 +1,308 @@
+use crate::names::DEFAULT_MAX_NAME_LENGTH;
+use rand::{distributions::Alphanumeric, Rng};
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Server configuration, read from environment variables.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// How long archived games are kept before being pruned, or `None` to keep them forever.
+    pub archive_retention: Option<Duration>,
+    /// Where games are persisted to.
+    pub storage: StorageBackend,
+    /// How often websocket clients are pinged.
+    pub ping_interval: Duration,
+    /// How long a client may go without sending a message before it is considered disconnected.
+    pub connection_timeout: Duration,
+    /// How long a player may stay disconnected from a lobby before their seat is given up.
+    pub lobby_grace: Duration,
+    /// How long a game with no connected clients may go without interaction before it is purged.
+    pub idle_timeout: Duration,
+    /// How long a game may go without changing before it is purged, even if clients are still connected.
+    pub inactive_timeout: Duration,
+    /// How long an asynchronous game may go without changing before it is purged.
+    /// Asynchronous games are never purged for being idle, as their players are rarely connected.
+    pub async_inactive_timeout: Duration,
+    /// How long a player of an asynchronous game may leave the game waiting on them before they are reminded again.
+    pub async_nudge_interval: Duration,
+    /// How often expired games are purged.
+    pub purge_interval: Duration,
+    /// How this server cooperates with others sharing the same store, or `None` if it runs alone.
+    pub cluster: Option<ClusterConfig>,
+    /// The secret used to sign players' tokens.
+    /// Servers sharing a store must share the secret, so that each accepts the tokens issued by the others.
+    pub token_secret: String,
+    /// How long a player's token remains valid.
+    pub token_lifetime: Duration,
+    /// The secret from which save codes are encrypted, or `None` if save codes are disabled.
+    /// It must be configured, as codes signed with a secret made up at startup could not be resumed anywhere else.
+    pub save_secret: Option<String>,
+    /// How long a session's lock may be held, whether by an action or anything else, before a warning is logged.
+    pub slow_action_threshold: Duration,
+    /// The token an operator must present to watch the server's events, or `None` to disallow it.
+    pub admin_token: Option<String>,
+    /// The URLs which are sent a request whenever a game is created, started, finished or archived.
+    pub webhooks: Vec<String>,
+    /// The largest a persisted game may be once compressed, in bytes.
+    pub max_record_size: usize,
+    /// Whether a player must present a valid token to rejoin a game under a name they have already taken.
+    pub require_tokens: bool,
+    /// The greatest number of characters in a player's name.
+    pub max_name_length: usize,
+    /// Words which may not appear in a player's name.
+    pub name_deny_list: Vec<String>,
+    /// The most sessions the server will hold at once, or `None` for no limit.
+    pub max_sessions: Option<usize>,
+    /// The most open lobbies a single creator may have at once, or `None` for no limit.
+    /// Creators are told apart by their address.
+    pub max_lobbies_per_creator: Option<usize>,
+    /// The origins from which browsers may call the API, where `*` allows any origin.
+    pub cors_origins: Vec<String>,
+    /// Whether the server sits behind a reverse proxy, whose `X-Forwarded-For` header gives each client's address.
+    pub trust_proxy: bool,
+    /// Whether players may register to be notified when a game is waiting on them.
+    pub push_notifications: bool,
+    /// The hosts on the server's own network to which notifications may nonetheless be sent.
+    pub push_allowed_hosts: Vec<String>,
+}
+
+/// Configuration for running several servers behind a load balancer, sharing one store.
+/// Each session is owned by a single node, which must renew its lease on the session to keep it.
+#[derive(Clone, Debug)]
+pub struct ClusterConfig {
+    /// The unique ID of this node.
+    pub node_id: String,
+    /// The address clients should connect to in order to reach this node directly.
+    pub address: String,
+    /// How long a lease on a session lasts before another node may take it over.
+    pub lease: Duration,
+    /// How long a session must be idle before it is released for other nodes to claim.
+    pub idle_handoff: Duration,
+}
+
+/// The database that games are persisted to.
+#[derive(Clone, Debug)]
+pub enum StorageBackend {
+    /// A sled database in the given directory.
+    Sled { path: String },
+    /// An SQLite database in the given file.
+    Sqlite { path: String },
+    /// A temporary database which is discarded when the server stops.
+    Memory,
+}
+
+impl Config {
+    /// Reads the configuration from the environment.
+    pub fn from_env() -> Self {
+        Self {
+            archive_retention: env_parse::<u64>("ARCHIVE_RETENTION_DAYS").map(|days| Duration::from_secs(days * 86400)),
+            storage: StorageBackend::from_env(),
+            ping_interval: Duration::from_secs(env_parse("PING_INTERVAL_SECS").unwrap_or(15)),
+            connection_timeout: Duration::from_secs(env_parse("CONNECTION_TIMEOUT_SECS").unwrap_or(45)),
+            lobby_grace: Duration::from_secs(env_parse("LOBBY_GRACE_SECS").unwrap_or(120)),
+            idle_timeout: Duration::from_secs(env_parse("IDLE_TIMEOUT_SECS").unwrap_or(3600)),
+            inactive_timeout: Duration::from_secs(env_parse("INACTIVE_TIMEOUT_SECS").unwrap_or(12 * 3600)),
+            async_inactive_timeout: Duration::from_secs(env_parse::<u64>("ASYNC_INACTIVE_DAYS").unwrap_or(30) * 86400),
+            async_nudge_interval: Duration::from_secs(env_parse::<u64>("ASYNC_NUDGE_HOURS").unwrap_or(12) * 3600),
+            purge_interval: Duration::from_secs(env_parse("PURGE_INTERVAL_SECS").unwrap_or(15)),
+            cluster: ClusterConfig::from_env(),
+            token_secret: std::env::var("TOKEN_SECRET").unwrap_or_else(|_| {
+                log::warn!("TOKEN_SECRET is not set; players' tokens will not outlive this server");
+                rand::thread_rng()
+                    .sample_iter(Alphanumeric)
+                    .take(32)
+                    .map(char::from)
+                    .collect()
+            }),
+            token_lifetime: Duration::from_secs(env_parse("TOKEN_LIFETIME_SECS").unwrap_or(24 * 3600)),
+            save_secret: read_save_secret(),
+            max_record_size: env_parse::<usize>("MAX_RECORD_KB").unwrap_or(1024) * 1024,
+            require_tokens: env_parse("REQUIRE_TOKENS").unwrap_or(false),
+            max_name_length: env_parse("MAX_NAME_LENGTH").unwrap_or(DEFAULT_MAX_NAME_LENGTH),
+            name_deny_list: read_deny_list(),
+            max_sessions: env_parse("MAX_SESSIONS"),
+            max_lobbies_per_creator: env_parse("MAX_LOBBIES_PER_CREATOR"),
+            cors_origins: env_list("CORS_ORIGINS"),
+            trust_proxy: env_parse("TRUST_PROXY").unwrap_or(false),
+            push_notifications: env_parse("PUSH_NOTIFICATIONS").unwrap_or(false),
+            push_allowed_hosts: env_list("PUSH_ALLOWED_HOSTS"),
+            slow_action_threshold: Duration::from_millis(env_parse("SLOW_ACTION_MS").unwrap_or(100)),
+            admin_token: std::env::var("ADMIN_TOKEN").ok().filter(|token| !token.is_empty()),
+            webhooks: env_list("WEBHOOK_URLS"),
+        }
+    }
+}
+
+/// The configuration of a server started without any environment variables set,
+/// except that the token secret is made up afresh without warning.
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            archive_retention: None,
+            storage: StorageBackend::default(),
+            ping_interval: Duration::from_secs(15),
+            connection_timeout: Duration::from_secs(45),
+            lobby_grace: Duration::from_secs(120),
+            idle_timeout: Duration::from_secs(3600),
+            inactive_timeout: Duration::from_secs(12 * 3600),
+            async_inactive_timeout: Duration::from_secs(30 * 86400),
+            async_nudge_interval: Duration::from_secs(12 * 3600),
+            purge_interval: Duration::from_secs(15),
+            cluster: None,
+            token_secret: rand::thread_rng()
+                .sample_iter(Alphanumeric)
+                .take(32)
+                .map(char::from)
+                .collect(),
+            token_lifetime: Duration::from_secs(24 * 3600),
+            save_secret: None,
+            max_record_size: 1024 * 1024,
+            require_tokens: false,
+            max_name_length: DEFAULT_MAX_NAME_LENGTH,
+            name_deny_list: vec![],
+            max_sessions: None,
+            max_lobbies_per_creator: None,
+            cors_origins: vec![],
+            trust_proxy: false,
+            push_notifications: false,
+            push_allowed_hosts: vec![],
+            slow_action_threshold: Duration::from_millis(100),
+            admin_token: None,
+            webhooks: vec![],
+        }
+    }
+}
+
+impl Config {
+    /// Works out the address of a client from the address of its connection,
+    /// trusting the `X-Forwarded-For` header only if the server sits behind a reverse proxy.
+    /// The last address in the header is the one the proxy itself saw, so it is the only one which cannot be forged.
+    pub fn client_address(&self, peer: IpAddr, forwarded_for: Option<&str>) -> IpAddr {
+        if !self.trust_proxy {
+            return peer;
+        }
+        forwarded_for
+            .and_then(|header| header.rsplit(',').next())
+            .and_then(|address| address.trim().parse().ok())
+            .unwrap_or(peer)
+    }
+}
+
+impl ClusterConfig {
+    /// Reads the cluster configuration from the environment, if `NODE_ID` and `NODE_ADDRESS` are set.
+    fn from_env() -> Option<Self> {
+        let node_id = std::env::var("NODE_ID").ok()?;
+        let Ok(address) = std::env::var("NODE_ADDRESS") else {
+            log::error!("NODE_ADDRESS must be set when NODE_ID is set; running without a cluster");
+            return None;
+        };
+        Some(Self {
+            node_id,
+            address,
+            lease: Duration::from_secs(env_parse("SESSION_LEASE_SECS").unwrap_or(60)),
+            idle_handoff: Duration::from_secs(env_parse("IDLE_HANDOFF_SECS").unwrap_or(300)),
+        })
+    }
+}
+
+impl StorageBackend {
+    /// Reads the storage backend from the `EPHEMERAL`, `STORAGE` and `STORAGE_PATH` environment variables.
+    fn from_env() -> Self {
+        if env_parse("EPHEMERAL").unwrap_or(false) {
+            return Self::Memory;
+        }
+        let path = std::env::var("STORAGE_PATH").ok();
+        match std::env::var("STORAGE").as_deref() {
+            Ok("memory") => Self::Memory,
+            Ok("sqlite") => Self::Sqlite {
+                path: path.unwrap_or_else(|| "data.sqlite".into()),
+            },
+            Ok("sled") | Err(_) => Self::Sled { path: path.unwrap_or_else(|| "data".into()) },
+            Ok(other) => {
+                log::error!("Unknown storage backend: {}; using sled", other);
+                Self::Sled { path: path.unwrap_or_else(|| "data".into()) }
+            }
+        }
+    }
+}
+
+impl Default for StorageBackend {
+    fn default() -> Self {
+        Self::Sled { path: "data".into() }
+    }
+}
+
+/// Reads the secret from which save codes are encrypted, being `SAVE_SECRET`, or else `TOKEN_SECRET` if that is set.
+fn read_save_secret() -> Option<String> {
+    let secret = (std::env::var("SAVE_SECRET").or_else(|_| std::env::var("TOKEN_SECRET")))
+        .ok()
+        .filter(|secret| !secret.is_empty());
+    if secret.is_none() {
+        log::warn!("Neither SAVE_SECRET nor TOKEN_SECRET is set; save codes are disabled");
+    }
+    secret
+}
+
+/// Reads the words which may not appear in players' names from the file named by `NAME_DENY_LIST`,
+/// one per line, ignoring blank lines and those starting with `#`.
+fn read_deny_list() -> Vec<String> {
+    let Ok(path) = std::env::var("NAME_DENY_LIST") else {
+        return vec![];
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect(),
+        Err(err) => {
+            log::error!("Cannot read name deny list: {}: {}", path, err);
+            vec![]
+        }
+    }
+}
+
+/// Reads a comma-separated list from an environment variable, which is empty if the variable is not set.
+fn env_list(key: &str) -> Vec<String> {
+    std::env::var(key)
+        .map(|list| {
+            list.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parses the value of an environment variable, logging an error if it is present but invalid.
+fn env_parse<T: std::str::FromStr>(key: &str) -> Option<T> {
+    let value = std::env::var(key).ok()?;
+    value
+        .parse()
+        .map_err(|_| log::error!("Invalid value for {}: {}", key, value))
+        .ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn forwarded_addresses_are_trusted_only_behind_a_proxy() {
+        let mut config = Config::from_env();
+        let peer: IpAddr = "10.0.0.1".parse().unwrap();
+        let header = Some("1.1.1.1, 2.2.2.2");
+
+        config.trust_proxy = false;
+        assert_eq!(config.client_address(peer, header), peer);
+        config.trust_proxy = true;
+        assert_eq!(
+            config.client_address(peer, header),
+            "2.2.2.2".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(config.client_address(peer, Some("garbage")), peer);
+        assert_eq!(config.client_address(peer, None), peer);
+    }
+}
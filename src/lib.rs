@@ -0,0 +1,19 @@
+pub mod action;
+pub mod admin;
+pub mod api;
+pub mod auth;
+pub mod client;
+pub mod clock;
+pub mod config;
+pub mod names;
+pub mod notifications;
+pub mod patch;
+pub mod save;
+pub mod session;
+pub mod sim;
+pub mod store;
+pub mod summary;
+pub mod webhook;
+pub mod ws;
+
+pub use shxl_engine::{self as game, error};
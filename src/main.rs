@@ -1,75 +1,164 @@
-use crate::session::SessionManager;
-use crate::ws::accept_connection;
+use hitler_server_rs::api;
+use hitler_server_rs::config::{Config, StorageBackend};
+use hitler_server_rs::session::{self, SessionManager};
+use hitler_server_rs::store::{self, GameStore};
+use hitler_server_rs::ws::accept_connection;
 use std::{
     error::Error,
-    net::{Ipv4Addr, SocketAddrV4},
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    sync::Arc,
     time::Duration,
 };
 use tokio::net::TcpListener;
-
-mod api;
-mod client;
-mod error;
-mod game;
-mod session;
-mod ws;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() {
     dotenv::dotenv().ok();
-    env_logger::try_init().ok();
+    // Both tracing's events and spans and the log crate's records are written out, filtered by `RUST_LOG`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init()
+        .ok();
 
-    let Ok(Ok(port)) = std::env::var("PORT").map(|s| s.parse::<u16>()) else {
-        log::error!("port is unspecified or is invalid");
+    // The API server also accepts websocket connections, so the dedicated websocket port is optional
+    let port = std::env::var("PORT").ok().and_then(|s| s.parse::<u16>().ok());
+    let api_port = std::env::var("API_PORT").ok().and_then(|s| s.parse::<u16>().ok());
+    if port.is_none() && api_port.is_none() {
+        log::error!("neither PORT nor API_PORT is specified and valid");
         return;
-    };
-
-    // Bind to the socket
-    let addr = SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), port);
-    let listener = TcpListener::bind(addr).await.unwrap_or_else(|err| {
-        log::error!("Could not bind to address {:?}: {:?}", addr, err);
-        std::process::exit(1)
-    });
-    log::info!("Listening on: {:?}", addr);
+    }
 
-    let db = sled::open("data").unwrap_or_else(|err| {
+    let mut config = Config::from_env();
+    if std::env::args().skip(1).any(|arg| arg == "--ephemeral") {
+        config.storage = StorageBackend::Memory;
+    }
+    if let StorageBackend::Memory = config.storage {
+        log::warn!("Running with ephemeral storage; games will be lost when the server stops");
+    }
+    if let (Some(_), StorageBackend::Sled { .. }) = (&config.cluster, &config.storage) {
+        log::warn!("sled databases cannot be shared between servers; use STORAGE=sqlite in distributed mode");
+    }
+    let store = store::open(&config.storage).unwrap_or_else(|err| {
         log::error!("Could not open database: {:?}", err);
         std::process::exit(1)
     });
 
     // Create the session manager
-    let manager = create_session_manager(db.clone()).unwrap_or_else(|err| {
+    let manager = create_session_manager(store, config).unwrap_or_else(|err| {
         log::error!("Could not create session manager: {:?}", err);
         std::process::exit(1)
     });
     log::info!("Created session manager. Loaded {} games.", manager.num_games());
 
+    // Background tasks run until the server is asked to stop
+    let (stop, stopping) = watch::channel(false);
+    let mut tasks = vec![];
+
     // Spin up background task to clean up old games
-    tokio::spawn(async {
-        loop {
-            tokio::task::spawn_blocking(|| manager.purge_games());
-            tokio::time::sleep(Duration::from_secs(15)).await;
-        }
-    });
+    let purge_interval = manager.config().purge_interval;
+    tasks.push(spawn_periodic(purge_interval, stopping.clone(), move || {
+        manager.purge_games()
+    }));
+
+    // Spin up background task to renew this node's leases on its sessions
+    if let Some(cluster) = &manager.config().cluster {
+        log::info!("Running in distributed mode as node {}", cluster.node_id);
+        let interval = cluster.lease / 3;
+        tasks.push(spawn_periodic(interval, stopping.clone(), move || {
+            manager.renew_leases()
+        }));
+    }
+
+    // Spin up background task to resolve timed phases of games
+    let interval = Duration::from_secs(1);
+    tasks.push(spawn_periodic(interval, stopping.clone(), move || {
+        manager.check_timers()
+    }));
 
-    // API server
-    if let Some(port) = std::env::var("API_PORT").ok().and_then(|s| s.parse::<u16>().ok()) {
+    // Spin up background task to send game updates which were deferred to coalesce them
+    let interval = session::NOTIFY_WINDOW;
+    tasks.push(spawn_periodic(interval, stopping.clone(), move || {
+        manager.flush_updates()
+    }));
+
+    // Legacy websocket server, kept for clients which have not moved to the API server's `/ws` route
+    if let Some(port) = port {
+        let addr = SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), port);
+        let listener = TcpListener::bind(addr).await.unwrap_or_else(|err| {
+            log::error!("Could not bind to address {:?}: {:?}", addr, err);
+            std::process::exit(1)
+        });
+        log::info!("Listening on: {:?}", addr);
         tokio::spawn(async move {
-            let router = api::make_router(manager).await;
-            let listener = api::listen(port).await;
-            axum::serve(listener, router).await.unwrap_or_else(|err| {
-                log::error!("Could not start API server: {}", err);
-            });
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(accept_connection(stream, manager));
+            }
         });
     }
 
-    // Accept connections
-    while let Ok((stream, _)) = listener.accept().await {
-        tokio::spawn(accept_connection(stream, manager));
+    // API server, which also accepts websocket connections
+    match api_port {
+        Some(api_port) => {
+            let router = api::make_router(manager).await;
+            let listener = api::listen(api_port).await;
+            let service = router.into_make_service_with_connect_info::<SocketAddr>();
+            axum::serve(listener, service)
+                .with_graceful_shutdown(shutdown_signal())
+                .await
+                .unwrap_or_else(|err| {
+                    log::error!("Could not start API server: {}", err);
+                });
+        }
+        None => shutdown_signal().await,
+    }
+
+    // Let the background tasks finish what they are doing, then send any updates they left pending
+    log::info!("Shutting down");
+    stop.send(true).ok();
+    for task in tasks {
+        task.await.ok();
+    }
+    tokio::task::spawn_blocking(move || manager.flush_updates()).await.ok();
+}
+
+/// Runs `work` on a blocking thread every `period`, until told to stop.
+/// Each run is awaited before the next is started, so runs never pile up behind one which is slow.
+fn spawn_periodic(
+    period: Duration,
+    mut stopping: watch::Receiver<bool>,
+    work: impl Fn() + Copy + Send + 'static,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = stopping.changed() => break,
+            }
+            if let Err(err) = tokio::task::spawn_blocking(work).await {
+                log::error!("Background task failed: {}", err);
+            }
+        }
+    })
+}
+
+/// Resolves once the server is asked to stop.
+async fn shutdown_signal() {
+    if let Err(err) = tokio::signal::ctrl_c().await {
+        log::error!("Could not listen for the shutdown signal: {}", err);
+        std::future::pending::<()>().await;
     }
 }
 
-fn create_session_manager(db: sled::Db) -> Result<&'static SessionManager, Box<dyn Error>> {
-    let manager = SessionManager::new(db)?;
+fn create_session_manager(
+    store: Arc<dyn GameStore>,
+    config: Config,
+) -> Result<&'static SessionManager, Box<dyn Error>> {
+    let manager = SessionManager::new(store, config)?;
     Ok(Box::leak(Box::new(manager)))
 }
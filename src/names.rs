@@ -0,0 +1,75 @@
+//! The rules for the names players choose, so that every name is readable on the board
+//! and no player can pass themselves off as another.
+
+use crate::config::Config;
+use crate::error::GameError;
+
+/// The longest name a player may choose, unless the server is configured otherwise.
+pub const DEFAULT_MAX_NAME_LENGTH: usize = 20;
+
+/// The punctuation which may appear in a name, besides letters, digits and single spaces.
+const NAME_PUNCTUATION: &[char] = &['-', '_', '\'', '.'];
+
+/// Decides which names players may choose.
+#[derive(Clone, Debug)]
+pub struct NamePolicy {
+    /// The greatest number of characters in a name.
+    pub max_length: usize,
+    /// Words which may not appear in a name, already normalized.
+    deny_list: Vec<String>,
+}
+
+impl Default for NamePolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_NAME_LENGTH, &[])
+    }
+}
+
+impl NamePolicy {
+    /// Creates a policy which allows names of up to `max_length` characters, none of which contain a denied word.
+    pub fn new(max_length: usize, deny_list: &[String]) -> Self {
+        let deny_list = deny_list
+            .iter()
+            .map(|word| normalize(word))
+            .filter(|word| !word.is_empty())
+            .collect();
+        Self { max_length, deny_list }
+    }
+
+    /// Creates the policy the server is configured with.
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.max_name_length, &config.name_deny_list)
+    }
+
+    /// Checks that a name may be chosen, regardless of the names already taken.
+    pub fn validate(&self, name: &str) -> Result<(), GameError> {
+        let length = name.chars().count();
+        if length == 0 || length > self.max_length {
+            return Err(GameError::InvalidNameLength { max: self.max_length });
+        }
+        let allowed = |c: char| c.is_alphanumeric() || c == ' ' || NAME_PUNCTUATION.contains(&c);
+        if !name.chars().all(allowed) || name.trim() != name || name.contains("  ") {
+            return Err(GameError::InvalidNameCharacters);
+        }
+        let normalized = normalize(name);
+        let joined = normalized.replace(' ', "");
+        let denied = |word: &String| *word == joined || normalized.split(' ').any(|part| part == word);
+        if self.deny_list.iter().any(denied) {
+            return Err(GameError::NameNotAllowed);
+        }
+        Ok(())
+    }
+}
+
+/// Folds a name into the form in which two names count as the same, ignoring case and repeated whitespace.
+pub fn normalize(name: &str) -> String {
+    name.split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns whether two names would be mistaken for each other.
+pub fn same_name(a: &str, b: &str) -> bool {
+    normalize(a) == normalize(b)
+}
@@ -0,0 +1,221 @@
+//! Push notifications which tell players when the game is waiting on them,
+//! so that a game can be played slowly by players who are not keeping it open.
+
+use crate::error::GameError;
+use crate::game::Message;
+use chrono::{DateTime, Utc};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long a push target has to respond before the request is abandoned.
+const NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The endpoint of the Expo push service, which delivers notifications to the mobile app.
+const EXPO_PUSH_URL: &str = "https://exp.host/--/api/v2/push/send";
+
+/// Where a player's notifications are delivered.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PushTarget {
+    /// A URL which is sent each notification as JSON.
+    Webhook { url: String },
+    /// A token issued by the Expo push service for the player's device.
+    Expo { token: String },
+}
+
+impl PushTarget {
+    /// Checks that notifications could be delivered to the target,
+    /// which may only be on the server's own network if it is one of the allowed hosts.
+    pub fn validate(&self, allowed_hosts: &[String]) -> Result<(), GameError> {
+        let valid = match self {
+            PushTarget::Webhook { url } => reqwest::Url::parse(url).is_ok_and(|url| {
+                matches!(url.scheme(), "http" | "https")
+                    && url.host_str().is_some_and(|host| is_public_host(host, allowed_hosts))
+            }),
+            PushTarget::Expo { token } => token.starts_with("ExponentPushToken[") && token.ends_with(']'),
+        };
+        match valid {
+            true => Ok(()),
+            false => Err(GameError::InvalidPushTarget),
+        }
+    }
+}
+
+/// Whether notifications may be sent to the host, which must not name an address on the server's own network unless
+/// it is allowed, lest players use the server to reach services which are not exposed to the internet.
+fn is_public_host(host: &str, allowed_hosts: &[String]) -> bool {
+    if allowed_hosts.iter().any(|allowed| allowed.eq_ignore_ascii_case(host)) {
+        return true;
+    }
+    match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => is_public(ip),
+        Err(_) => !(host.eq_ignore_ascii_case("localhost") || host.to_ascii_lowercase().ends_with(".localhost")),
+    }
+}
+
+/// Whether the address is reachable from the internet, rather than being loopback, private or link-local.
+fn is_public(ip: IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => {
+            let shared = ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64;
+            let this_network = ip.octets()[0] == 0;
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || shared
+                || this_network)
+        }
+        IpAddr::V6(ip) => {
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_unique_local()
+                || ip.is_unicast_link_local()
+                || ip.is_multicast())
+        }
+    }
+}
+
+/// Resolves the hosts notifications are sent to, leaving out any addresses on the server's own network.
+/// Checking a target when it is registered is not enough, as its name may later resolve elsewhere.
+struct PublicResolver {
+    allowed_hosts: Arc<[String]>,
+}
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        let allowed = self
+            .allowed_hosts
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(&host));
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((host.as_str(), 0)).await?;
+            let addrs = addrs.filter(|addr| allowed || is_public(addr.ip())).collect::<Vec<_>>();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// The body of a notification, as sent to a webhook target.
+#[derive(Serialize, Debug)]
+pub struct NotificationPayload {
+    pub game_id: String,
+    pub player: String,
+    /// What the player is being asked to do.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<Message>,
+    pub sent: DateTime<Utc>,
+}
+
+/// Delivers notifications to players' push targets, if the server has them enabled.
+#[derive(Default)]
+pub struct Notifier {
+    enabled: bool,
+    client: reqwest::Client,
+    /// The hosts on the server's own network to which notifications may nonetheless be sent.
+    allowed_hosts: Arc<[String]>,
+}
+
+impl Notifier {
+    /// Creates a notifier, which only sends notifications to the server's own network on the allowed hosts.
+    /// Redirects are not followed, as they could lead anywhere.
+    pub fn new(enabled: bool, allowed_hosts: &[String]) -> Result<Self, reqwest::Error> {
+        let allowed_hosts: Arc<[String]> = allowed_hosts.into();
+        let resolver = PublicResolver { allowed_hosts: allowed_hosts.clone() };
+        let client = reqwest::Client::builder()
+            .timeout(NOTIFICATION_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(resolver))
+            .build()?;
+        Ok(Self { enabled, client, allowed_hosts })
+    }
+
+    /// Whether players may register to be notified.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Checks that notifications could be delivered to the target.
+    pub fn validate(&self, target: &PushTarget) -> Result<(), GameError> {
+        target.validate(&self.allowed_hosts)
+    }
+
+    /// Sends a notification to the target in the background, logging it if it fails.
+    pub fn notify(&self, target: &PushTarget, payload: NotificationPayload) {
+        if !self.enabled {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            log::warn!("Cannot send notifications outside of a runtime: {}", payload.game_id);
+            return;
+        };
+        let (url, body) = match target {
+            PushTarget::Webhook { url } => (url.as_str(), serde_json::to_vec(&payload)),
+            PushTarget::Expo { token } => {
+                let message = json!({
+                    "to": token,
+                    "title": "Secret Hitler",
+                    "body": format!("Game {} is waiting for you", payload.game_id),
+                    "data": payload,
+                });
+                (EXPO_PUSH_URL, serde_json::to_vec(&message))
+            }
+        };
+        let body = match body {
+            Ok(body) => body,
+            Err(err) => return log::error!("Cannot serialize notification: {}", err),
+        };
+        let request = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body);
+        let url = url.to_string();
+        runtime.spawn(async move {
+            match request.send().await.and_then(|res| res.error_for_status()) {
+                Ok(_) => {}
+                Err(err) => log::warn!("Notification failed: {}: {}", url, err),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn push_targets_are_validated() {
+        let webhook = |url: &str| PushTarget::Webhook { url: url.into() };
+        let expo = |token: &str| PushTarget::Expo { token: token.into() };
+        assert!(webhook("https://example.com/hook").validate(&[]).is_ok());
+        assert!(webhook("file:///etc/passwd").validate(&[]).is_err());
+        assert!(webhook("not a url").validate(&[]).is_err());
+        assert!(expo("ExponentPushToken[abc123]").validate(&[]).is_ok());
+        assert!(expo("abc123").validate(&[]).is_err());
+
+        // Webhooks may not reach the server's own network, unless the host is allowed
+        for url in [
+            "http://localhost/hook",
+            "http://127.0.0.1/hook",
+            "http://10.0.0.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]/hook",
+            "http://[::ffff:192.168.0.1]/hook",
+        ] {
+            assert!(webhook(url).validate(&[]).is_err(), "{} is refused", url);
+        }
+        assert!(webhook("http://127.0.0.1:8080/hook")
+            .validate(&["127.0.0.1".into()])
+            .is_ok());
+    }
+}
@@ -0,0 +1,71 @@
+//! Computes JSON patches (RFC 6902) between successive game states,
+//! so that clients can be sent only what has changed.
+
+use serde_json::{json, Value};
+
+/// Computes the patch which transforms `old` into `new`.
+/// Objects are compared key by key, and arrays element by element when their lengths match;
+/// anything else which differs is replaced outright.
+pub fn diff(old: &Value, new: &Value) -> Vec<Value> {
+    let mut ops = vec![];
+    diff_at(&mut String::new(), old, new, &mut ops);
+    ops
+}
+
+fn diff_at(path: &mut String, old: &Value, new: &Value, ops: &mut Vec<Value>) {
+    match (old, new) {
+        _ if old == new => {}
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                with_segment(path, key, |path| match new.get(key) {
+                    Some(new_value) => diff_at(path, old_value, new_value, ops),
+                    None => ops.push(json!({ "op": "remove", "path": path })),
+                });
+            }
+            for (key, new_value) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                with_segment(path, key, |path| {
+                    ops.push(json!({ "op": "add", "path": path, "value": new_value }));
+                });
+            }
+        }
+        (Value::Array(old), Value::Array(new)) if old.len() == new.len() => {
+            for (index, (old_value, new_value)) in old.iter().zip(new).enumerate() {
+                with_segment(path, &index.to_string(), |path| {
+                    diff_at(path, old_value, new_value, ops)
+                });
+            }
+        }
+        _ => ops.push(json!({ "op": "replace", "path": path, "value": new })),
+    }
+}
+
+/// Calls `f` with the path extended by the given segment, escaped as per RFC 6901.
+fn with_segment(path: &mut String, segment: &str, f: impl FnOnce(&mut String)) {
+    let len = path.len();
+    path.push('/');
+    path.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    f(path);
+    path.truncate(len);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn diffs_nested_values() {
+        let old = json!({ "a": 1, "b": { "c": [1, 2], "d": "x" }, "e/f": true });
+        let new = json!({ "a": 1, "b": { "c": [1, 3], "g": null }, "e/f": false, "h": [] });
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                json!({ "op": "replace", "path": "/b/c/1", "value": 3 }),
+                json!({ "op": "remove", "path": "/b/d" }),
+                json!({ "op": "add", "path": "/b/g", "value": null }),
+                json!({ "op": "replace", "path": "/e~1f", "value": false }),
+                json!({ "op": "add", "path": "/h", "value": [] }),
+            ]
+        );
+        assert_eq!(diff(&new, &new), Vec::<Value>::new());
+    }
+}
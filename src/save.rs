@@ -0,0 +1,97 @@
+//! Save codes, which capture a game in a compact, encrypted string so that it can be resumed on another server.
+//! They are encrypted rather than merely signed, as a game in progress holds every role and the order of the deck.
+
+use crate::config::Config;
+use crate::error::GameError;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::hkdf::{Salt, HKDF_SHA256};
+use std::io::{Read, Write};
+
+/// The largest snapshot a save code may expand to, so that a malicious code cannot exhaust memory.
+const MAX_SNAPSHOT_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Derives the key save codes are encrypted with from the configured secret, failing if none has been configured.
+fn key(config: &Config) -> Result<LessSafeKey, GameError> {
+    let secret = config.save_secret.as_ref().ok_or(GameError::SaveCodesDisabled)?;
+    let prk = Salt::new(HKDF_SHA256, b"save codes").extract(secret.as_bytes());
+    let okm = prk
+        .expand(&[b"chacha20-poly1305"], &CHACHA20_POLY1305)
+        .expect("the key length is valid");
+    Ok(LessSafeKey::new(UnboundKey::from(okm)))
+}
+
+/// Compresses and encrypts a snapshot of a game, producing its save code.
+pub fn encode(config: &Config, snapshot: &[u8]) -> Result<String, GameError> {
+    let key = key(config)?;
+    let mut encoder = DeflateEncoder::new(vec![], Compression::best());
+    encoder.write_all(snapshot).expect("writing to a vec cannot fail");
+    let mut data = encoder.finish().expect("writing to a vec cannot fail");
+    let nonce: [u8; NONCE_LEN] = rand::random();
+    key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+        .expect("the snapshot is small enough to encrypt");
+    Ok(URL_SAFE_NO_PAD.encode([&nonce[..], &data].concat()))
+}
+
+/// Decrypts a save code, returning the snapshot of the game it captures.
+/// Codes which were tampered with or encrypted with another secret are rejected.
+pub fn decode(config: &Config, code: &str) -> Result<Vec<u8>, GameError> {
+    let key = key(config)?;
+    let data = URL_SAFE_NO_PAD
+        .decode(code.trim())
+        .map_err(|_| GameError::InvalidSaveCode)?;
+    if data.len() < NONCE_LEN {
+        return Err(GameError::InvalidSaveCode);
+    }
+    let (nonce, mut data) = (data[..NONCE_LEN].try_into().unwrap(), data[NONCE_LEN..].to_vec());
+    let compressed = key
+        .open_in_place(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+        .map_err(|_| GameError::InvalidSaveCode)?;
+    let mut snapshot = vec![];
+    DeflateDecoder::new(&compressed[..])
+        .take(MAX_SNAPSHOT_SIZE)
+        .read_to_end(&mut snapshot)
+        .map_err(|_| GameError::InvalidSaveCode)?;
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn save_codes_round_trip() {
+        let mut config = Config::from_env();
+        config.save_secret = Some("secret".into());
+        let code = encode(&config, b"{\"Lobby\":{}}").unwrap();
+        assert_eq!(decode(&config, &code).unwrap(), b"{\"Lobby\":{}}");
+
+        config.save_secret = Some("another secret".into());
+        assert!(matches!(decode(&config, &code), Err(GameError::InvalidSaveCode)));
+
+        config.save_secret = None;
+        assert!(matches!(encode(&config, b"{}"), Err(GameError::SaveCodesDisabled)));
+        assert!(matches!(decode(&config, &code), Err(GameError::SaveCodesDisabled)));
+    }
+
+    #[test]
+    fn save_codes_do_not_reveal_the_game() {
+        let mut config = Config::from_env();
+        config.save_secret = Some("secret".into());
+        let snapshot = br#"{"roles":["Liberal","Fascist","Hitler","Liberal","Liberal"]}"#;
+        let code = encode(&config, snapshot).unwrap();
+        let data = URL_SAFE_NO_PAD.decode(&code).unwrap();
+        let mut contents = vec![];
+        DeflateDecoder::new(&data[NONCE_LEN..]).read_to_end(&mut contents).ok();
+        for text in [code.as_bytes(), &data, &contents] {
+            assert!(!text.windows(6).any(|window| window == b"Hitler"));
+        }
+
+        // A tampered code is rejected
+        let mut data = data;
+        data[NONCE_LEN] ^= 1;
+        let tampered = URL_SAFE_NO_PAD.encode(&data);
+        assert!(matches!(decode(&config, &tampered), Err(GameError::InvalidSaveCode)));
+    }
+}
@@ -1,27 +1,146 @@
-use crate::game::{BoardUpdate, GameOptions, PlayerUpdate, PublicPlayer, WinCondition};
-use crate::{error::GameError, game::Game as GameInner};
+use crate::game::{
+    BoardUpdate, ChatScope, GameOptions, HouseRules, OptionsProblem, OverlayState, Party, PlayerUpdate, PublicPlayer,
+    Role, Scenario, Substitution, TimedPhase, WinCondition, WinStatus,
+};
+use crate::store::{
+    record, schema, Fence, Fenced, GameStore, QuarantinedGame, SessionOwner, Snapshot, SnapshotWriter, WriteResult,
+};
+use crate::{
+    action::{BoardAction, GameAction, PlayerAction},
+    admin::{AdminEvent, AdminEventKind, ADMIN_EVENT_BUFFER},
+    auth::Claims,
+    clock::{Clock, SystemClock},
+    config::{Config, StorageBackend},
+    error::GameError,
+    game::Game as GameInner,
+    names::{self, NamePolicy},
+    notifications::{NotificationPayload, Notifier, PushTarget},
+    save,
+    webhook::{WebhookEvent, Webhooks},
+};
 use chrono::{DateTime, Utc};
-use dashmap::{mapref::entry::Entry, DashMap};
-use rand::{Rng, RngCore};
+use dashmap::{mapref::entry::Entry, DashMap, DashSet};
+use rand::{distributions::Alphanumeric, Rng, RngCore};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use sled::CompareAndSwapError;
+use std::collections::{HashMap, VecDeque};
 use std::error::Error;
-use std::sync::{Arc, Mutex};
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::panic::Location;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, LockResult, Mutex, MutexGuard, OnceLock, PoisonError};
 use std::time::{Duration, Instant};
-use tokio::sync::watch;
+use tokio::sync::{broadcast, oneshot, watch};
 
 /// Manages all the game sessions running on the server.
 pub struct SessionManager {
     sessions: DashMap<String, SessionHandle>,
-    db: Database,
+    store: Arc<dyn GameStore>,
+    config: Config,
+    /// The IDs of the sessions which were created as public lobbies.
+    public_ids: DashSet<String>,
+    /// The summaries of the public lobbies which are open to new players.
+    lobbies: watch::Sender<Vec<LobbySummary>>,
+    /// The webhooks which are told about each game's lifecycle.
+    webhooks: Arc<Webhooks>,
+    /// Channel for streaming server events to operators.
+    events: broadcast::Sender<AdminEvent>,
+    /// Writes snapshots of every session's game to the store.
+    writer: SnapshotWriter,
+    /// What became of the games in the store when the server started.
+    load_report: LoadReport,
+    /// The source of the current time for every session.
+    clock: Arc<dyn Clock>,
+    /// Decides which names players may choose.
+    names: Arc<NamePolicy>,
+    /// Delivers the notifications players have asked for.
+    notifier: Arc<Notifier>,
+    /// How many games have been created, and how many creations were refused.
+    creations: CreationCounters,
+    /// How many open lobbies each creator has.
+    lobby_counts: Arc<LobbyCounts>,
 }
 
-/// The databases that games are persisted to.
-#[derive(Clone)]
-struct Database {
-    game: sled::Tree,
-    archive: sled::Tree,
+/// Counts game creations as they happen, so that any thread can record one without a lock.
+#[derive(Default, Debug)]
+struct CreationCounters {
+    created: AtomicU64,
+    rejected_server_full: AtomicU64,
+    rejected_too_many_lobbies: AtomicU64,
+}
+
+/// How many games have been created since the server started, and how many creations were refused for each reason.
+#[derive(Clone, Copy, Default, Serialize, Debug)]
+pub struct CreationStats {
+    pub created: u64,
+    /// Refused because the server was holding as many sessions as it may.
+    pub rejected_server_full: u64,
+    /// Refused because the creator already had as many open lobbies as they may.
+    pub rejected_too_many_lobbies: u64,
+}
+
+/// How many open lobbies each creator has, kept up to date as lobbies are created and left behind so that the
+/// limit on them can be checked without locking every session.
+#[derive(Default, Debug)]
+struct LobbyCounts(DashMap<String, usize>);
+
+impl LobbyCounts {
+    /// Counts a new lobby against its creator, unless they already have `max` open lobbies.
+    fn take(self: &Arc<Self>, creator: &str, max: Option<usize>) -> Option<LobbyTicket> {
+        let mut count = self.0.entry(creator.to_string()).or_insert(0);
+        if max.is_some_and(|max| *count >= max) {
+            return None;
+        }
+        *count += 1;
+        Some(LobbyTicket {
+            counts: self.clone(),
+            creator: creator.to_string(),
+        })
+    }
+}
+
+/// An open lobby's place in its creator's count, which is given up when it is dropped.
+#[derive(Debug)]
+struct LobbyTicket {
+    counts: Arc<LobbyCounts>,
+    creator: String,
+}
+
+impl Drop for LobbyTicket {
+    fn drop(&mut self) {
+        if let Entry::Occupied(mut entry) = self.counts.0.entry(self.creator.clone()) {
+            *entry.get_mut() -= 1;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+    }
+}
+
+/// What became of the games in the store when the server started.
+#[derive(Clone, Copy, Default, Serialize, Debug)]
+pub struct LoadReport {
+    /// The number of games which were loaded, including those which were migrated.
+    pub loaded: usize,
+    /// The number of games which were persisted in an older format, and were rewritten in the current one.
+    pub migrated: usize,
+    /// The number of games which were persisted by a newer server, and were left in the store untouched.
+    pub skipped: usize,
+    /// The number of games which could not be loaded, and were moved into quarantine.
+    pub quarantined: usize,
+}
+
+/// A public lobby, as listed to players looking for a game to join.
+/// It deliberately leaves out the players' names and the board key.
+#[derive(Clone, PartialEq, Eq, Serialize, Debug)]
+pub struct LobbySummary {
+    pub id: String,
+    pub num_players: usize,
+    pub min_players: usize,
+    pub max_players: usize,
+    pub options: GameOptions,
+    /// When the game is due to start, if it was scheduled.
+    pub start_at: Option<DateTime<Utc>>,
 }
 
 /// A single game session.
@@ -30,24 +149,189 @@ pub struct Session {
     id: String,
     /// The game itself.
     game: Game,
-    /// Channel for sending game state updates.
+    /// Channel for sending game state updates, which carry what the board sees.
     updates: watch::Sender<GameUpdate>,
-    /// The databases.
-    db: Database,
+    /// Channels for sending each seat's player update, so that no player is sent the others' private state.
+    /// They are replaced whenever the game's lifecycle changes, as the seats may then belong to other players.
+    seat_updates: Vec<watch::Sender<Option<Arc<PlayerUpdate>>>>,
+    /// Channel for relaying chat messages.
+    chat: broadcast::Sender<ChatMessage>,
+    /// All chat messages sent during this session.
+    chat_log: VecDeque<ChatMessage>,
+    /// The store that the game is persisted to.
+    store: Arc<dyn GameStore>,
     /// Timestamp of the last time this session was interacted with.
     last_ts: Instant,
+    /// When the state of this session last changed. It is kept with the game in progress or in the lobby,
+    /// so that a restart does not give an inactive game a fresh lease of life.
+    last_change: DateTime<Utc>,
+    /// The countdown for the current phase of the game, if it is timed.
+    timer: Option<PhaseTimer>,
+    /// The time after which the last action can no longer be undone.
+    undo_deadline: Option<DateTime<Utc>>,
+    /// The lobby to return to if the players choose to play again, kept once the game has been ended.
+    /// It is not persisted, so is lost if the server restarts.
+    rematch: Option<Game>,
+    /// Writes snapshots of the game to the store.
+    writer: SnapshotWriter,
+    /// A snapshot waiting to be serialized and queued for writing once the session is unlocked.
+    pending_snapshot: Option<PendingSnapshot>,
+    /// The sequence number of the next action to be journaled.
+    journal_seq: u64,
+    /// The number of actions journaled since the last snapshot was taken.
+    journal_len: u64,
+    /// The claim under which this node writes the session to the store, when running in distributed mode.
+    fence: Option<Fence>,
+    /// Channel on which the writer reports whether each snapshot was written.
+    write_reports: mpsc::Sender<WriteResult>,
+    write_results: mpsc::Receiver<WriteResult>,
+    /// The connection status of each player who has joined the game.
+    seats: HashMap<String, Seat>,
+    /// When clients were last sent a game update.
+    last_notify: Option<Instant>,
+    /// Whether the game has changed since clients were last sent an update.
+    notify_pending: bool,
+    /// The number of game updates sent during this session.
+    version: u64,
+    /// Increases by one with every change to the lifecycle of the game, such as it starting or ending,
+    /// so that a request made against an out of date lifecycle can be refused.
+    generation: u64,
+    /// The player whose turn it is to use the shared device, in a hotseat game.
+    hotseat: Option<HotseatTurn>,
+    /// The webhooks which are told about the game's lifecycle.
+    webhooks: Arc<Webhooks>,
+    /// Channel for streaming server events to operators.
+    events: broadcast::Sender<AdminEvent>,
+    /// The source of the current time.
+    clock: Arc<dyn Clock>,
+    /// Decides which names players may choose.
+    names: Arc<NamePolicy>,
+    /// The lobby's place in its creator's count of open lobbies, which is given up once the lobby is left behind.
+    lobby_ticket: Option<LobbyTicket>,
+    /// Each player's update as last published, which is reused for the seats whose view has not changed since.
+    player_updates: Vec<Arc<PlayerUpdate>>,
+    /// Delivers the notifications players have asked for.
+    notifier: Arc<Notifier>,
+    /// Where to notify each player who wants to know when the game is waiting on them.
+    /// They are not persisted, so players must register again if the server restarts.
+    push_targets: HashMap<String, PushTarget>,
+    /// When each player was last notified that the game is waiting on them.
+    notified: HashMap<String, DateTime<Utc>>,
+    /// How long the session may be locked before a warning is logged.
+    slow_lock_threshold: Duration,
 }
 
-pub type SessionHandle = Arc<Mutex<Session>>;
+/// The player whose private prompt is shown on the shared device in a hotseat game.
+#[derive(Clone, Copy, Debug)]
+struct HotseatTurn {
+    player: usize,
+    /// Whether the player has confirmed they are holding the device.
+    revealed: bool,
+}
 
-#[allow(clippy::large_enum_variant)]
+/// What the shared device should show in a hotseat game.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum HotseatPrompt {
+    /// The device should be passed to the given player, who must confirm they have it before their prompt is shown.
+    PassDevice { player: String },
+    /// The private view of the player holding the device.
+    Player { update: Box<PlayerUpdate> },
+}
+
+/// The websocket connections attached to a player's seat in a session.
+#[derive(Clone, Copy, Debug)]
+struct Seat {
+    /// The number of connections currently attached as the player.
+    connections: usize,
+    /// When the player was last heard from.
+    last_seen: DateTime<Utc>,
+    /// Identifies the current claim on the seat; it changes whenever a new connection takes the seat over.
+    claim: u64,
+    /// Whether another connection tried to claim the seat while it was in use, and is waiting to take it over.
+    claim_pending: bool,
+    /// Whether the board has approved the waiting connection taking over the seat.
+    takeover_approved: bool,
+}
+
+/// The number of journaled actions after which the full game state is persisted.
+const SNAPSHOT_INTERVAL: u64 = 16;
+
+/// The minimum time between game updates, such that bursts of actions are coalesced into a single update.
+pub const NOTIFY_WINDOW: Duration = Duration::from_millis(50);
+
+/// An action recorded in the write-ahead journal, along with a hash of the game state it resulted in.
 #[derive(Serialize, Deserialize)]
+struct JournalEntry {
+    action: GameAction,
+    state_hash: u64,
+    /// When the action was performed, which is missing from entries journaled by older servers.
+    #[serde(default)]
+    at: Option<DateTime<Utc>>,
+}
+
+/// A shared handle to a session. Clients act on the session by sending commands to its actor with
+/// [SessionHandle::call], and await the replies rather than block on the session's mutex. The actor is a task which
+/// runs the commands one at a time on the blocking thread pool, so the journal entries and snapshots they write never
+/// hold up the threads which serve other clients. The manager's own housekeeping, which runs on its own thread,
+/// locks the session directly with [SessionHandle::lock].
+#[derive(Clone)]
+pub struct SessionHandle {
+    session: Arc<Mutex<Session>>,
+    /// The queue of commands for the session's actor, which is started by the first command sent to it
+    /// and stops once every handle to the session has been dropped.
+    actor: Arc<OnceLock<tokio::sync::mpsc::UnboundedSender<Command>>>,
+}
+
+/// A command for a session's actor, along with where it was sent from, so that a command which holds the session
+/// for too long is named in the warning.
+struct Command {
+    run: Box<dyn FnOnce(&mut Session) + Send>,
+    sent_at: &'static Location<'static>,
+    span: tracing::Span,
+}
+
+/// Exclusive access to a session. Any snapshot taken while the session is locked is serialized and
+/// queued for writing after it is unlocked, so that other clients of the game are not held up by it.
+/// A warning is logged if the session is held for too long, naming the code which locked it.
+pub struct SessionGuard<'a> {
+    session: Option<MutexGuard<'a, Session>>,
+    locked: Instant,
+    locked_at: &'static Location<'static>,
+}
+
+/// A copy of the game taken to be persisted.
+struct PendingSnapshot {
+    game: Game,
+    ticket: u64,
+    journal_seq: u64,
+    fence: Option<Fence>,
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Serialize, Deserialize)]
 enum Game {
     Lobby {
         options: GameOptions,
         players: Vec<String>,
         min_players: usize,
         max_players: usize,
+        /// The key a client must present in order to join as the board.
+        #[serde(default)]
+        board_key: Option<String>,
+        /// When the game is due to start by itself, if it was scheduled.
+        #[serde(default)]
+        start_at: Option<DateTime<Utc>>,
+        /// How many times the scheduled start has been put back for want of players.
+        #[serde(default)]
+        extensions: u32,
+        /// Who created the lobby, if it was created by a client whose address is known.
+        /// A lobby opened for a rematch has no creator, and so does not count against anyone.
+        #[serde(default)]
+        creator: Option<String>,
+        /// When the lobby last changed, if it was persisted by a server which kept track.
+        #[serde(default)]
+        last_change: Option<DateTime<Utc>>,
     },
     Playing {
         /// The game itself.
@@ -56,24 +340,72 @@ enum Game {
         started_ts: DateTime<Utc>,
         /// Whether this game has been archived.
         archived: bool,
+        /// The key a client must present in order to join as the board.
+        #[serde(default)]
+        board_key: Option<String>,
+        /// The countdown for the current phase, kept with the game so that it survives a restart.
+        #[serde(default)]
+        timer: Option<PhaseTimer>,
+        /// When the game last changed, if it was persisted by a server which kept track.
+        #[serde(default)]
+        last_change: Option<DateTime<Utc>>,
     },
     #[allow(clippy::enum_variant_names)]
     GameOver,
+    /// A scheduled game which never gathered enough players.
+    Cancelled,
 }
 
 #[derive(Default, Clone, Serialize, Deserialize, Debug)]
 pub struct GameUpdate {
+    /// The ID of the game.
+    #[serde(default)]
+    pub game_id: String,
     pub lifecycle: GameLifecycle,
+    /// The options the game is to be played with, or is being played with, so that players can see the rules
+    /// before they commit to joining.
+    #[serde(default)]
+    pub options: Option<GameOptions>,
+    /// When the game started, once it has.
+    #[serde(default)]
+    pub started_at: Option<DateTime<Utc>>,
     pub players: Vec<PublicPlayer>,
     pub board_update: Option<BoardUpdate>,
-    pub player_updates: Vec<PlayerUpdate>,
+    pub timer: Option<PhaseTimer>,
+    /// The hash of the public game state, if a game is in progress.
+    pub state_hash: Option<u64>,
+    /// Increases by one with every update sent, so that clients can tell how many they have skipped.
+    pub version: u64,
+    /// Increases by one whenever the game's lifecycle changes.
+    #[serde(default)]
+    pub generation: u64,
+    /// What the shared device should show, in a hotseat game.
+    pub hotseat: Option<HotseatPrompt>,
+    /// The optional rules in play.
+    #[serde(default)]
+    pub house_rules: HouseRules,
+    /// The reasons the game in the lobby cannot yet be started, if any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<OptionsProblem>,
+    /// What stream overlays are shown of the game.
+    #[serde(default)]
+    pub overlay: OverlayState,
+}
+
+/// A countdown for the current phase of the game, after which the phase is resolved automatically.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct PhaseTimer {
+    pub phase: TimedPhase,
+    pub deadline: DateTime<Utc>,
 }
 
 #[derive(Clone, Copy, Serialize, Deserialize, Debug)]
 pub enum GameLifecycle {
     Lobby { can_start: bool },
+    Scheduled { can_start: bool, start_at: DateTime<Utc> },
     Playing,
     Ended,
+    Cancelled,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -83,47 +415,447 @@ pub struct GameStats {
     pub started: DateTime<Utc>,
     pub finished: DateTime<Utc>,
     pub outcome: WinCondition,
+    /// Any win conditions which were met alongside the outcome, but took lower priority.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secondary_conditions: Vec<WinCondition>,
+    /// Players who were replaced partway through the game; `players` lists those who finished it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub substitutions: Vec<Substitution>,
+    /// How the game turned out for each player, in the same order as `players`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub results: Vec<WinStatus>,
+    /// The role each player was dealt, in the same order as `players`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<Role>,
+    /// The players who were executed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub executed: Vec<String>,
+    /// The number of policies of each party which were enacted.
+    #[serde(default)]
+    pub policies: PolicyCounts,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chat: Vec<ChatMessage>,
+}
+
+/// A query over the archive of past games.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ArchiveQuery {
+    /// Only include games with an ID greater than this one.
+    pub after: Option<u64>,
+    /// The maximum number of games to return.
+    pub limit: Option<usize>,
+    /// Only include games which this player took part in.
+    pub player: Option<String>,
+    /// Only include games which finished at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Only include games which finished before this time.
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// A page of archived games matching a query.
+#[derive(Debug)]
+pub struct ArchivePage {
+    pub games: Vec<(u64, GameStats)>,
+    /// The ID to fetch the next page after, or `None` if this is the last page.
+    pub next: Option<u64>,
 }
 
+const DEFAULT_ARCHIVE_PAGE: usize = 100;
+const MAX_ARCHIVE_PAGE: usize = 1000;
+
+/// A chat message sent by a player or the board.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// The name of the player who sent the message, or `None` if it was sent by the board.
+    pub sender: Option<String>,
+    pub scope: ChatScope,
+    pub text: String,
+    pub sent: DateTime<Utc>,
+    /// The players who may read the message, or `None` if it is visible to everyone.
+    #[serde(skip)]
+    recipients: Option<Vec<String>>,
+}
+
+/// The number of policies of each party enacted in a game.
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
+pub struct PolicyCounts {
+    pub liberal: usize,
+    pub fascist: usize,
+    pub communist: usize,
+}
+
+/// The outcome of attempting to take over a session from the store.
+#[allow(clippy::large_enum_variant)]
+enum Claim {
+    Claimed(Game, Fence),
+    Missing,
+    Owned(SessionOwner),
+}
+
+const MAX_CHAT_LENGTH: usize = 500;
+const MAX_CHAT_LOG: usize = 1000;
+
+/// How far a scheduled start is put back when too few players have joined.
+const SCHEDULE_EXTENSION: Duration = Duration::from_secs(10 * 60);
+/// How many times a scheduled start is put back before the game is cancelled.
+const MAX_SCHEDULE_EXTENSIONS: u32 = 2;
+
 impl SessionManager {
-    pub fn new(db: sled::Db) -> Result<Self, Box<dyn Error>> {
-        let sessions = DashMap::new();
-        let db = Database {
-            game: db.open_tree("games")?,
-            archive: db.open_tree("archive")?,
-        };
-        for entry in db.game.iter() {
-            let (id, game) = entry?;
-            let id = String::from_utf8(id.to_vec())?;
-            let Ok(game) = serde_json::from_slice(&game) else {
-                continue;
+    pub fn new(store: Arc<dyn GameStore>, config: Config) -> Result<Self, Box<dyn Error>> {
+        Self::with_clock(store, config, Arc::new(SystemClock))
+    }
+
+    /// Creates a session manager whose games are kept in a temporary store, and never written to disk.
+    pub fn ephemeral(mut config: Config) -> Result<Self, Box<dyn Error>> {
+        config.storage = StorageBackend::Memory;
+        Self::new(crate::store::open(&config.storage)?, config)
+    }
+
+    /// Creates a session manager whose sessions tell the time using `clock`.
+    pub fn with_clock(
+        store: Arc<dyn GameStore>,
+        config: Config,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut manager = Self {
+            sessions: DashMap::new(),
+            public_ids: DashSet::new(),
+            lobbies: watch::channel(vec![]).0,
+            webhooks: Arc::new(Webhooks::new(config.webhooks.clone())),
+            events: broadcast::channel(ADMIN_EVENT_BUFFER).0,
+            writer: SnapshotWriter::spawn(store.clone(), config.max_record_size),
+            load_report: LoadReport::default(),
+            clock,
+            names: Arc::new(NamePolicy::from_config(&config)),
+            notifier: Arc::new(Notifier::new(config.push_notifications, &config.push_allowed_hosts)?),
+            creations: CreationCounters::default(),
+            lobby_counts: Arc::default(),
+            store,
+            config,
+        };
+        if manager.config.cluster.is_some() {
+            // Sessions are claimed lazily in distributed mode, as other nodes may own them
+            return Ok(manager);
+        }
+        let mut report = LoadReport::default();
+        for (id, record) in manager.store.games()? {
+            let (game, outdated) = match Game::decode(&record) {
+                Ok(decoded) => decoded,
+                Err(err) if persisted_by_newer_server(err.as_ref()) => {
+                    log::warn!("Skipping game: {}: {}", id, err);
+                    report.skipped += 1;
+                    continue;
+                }
+                Err(err) => {
+                    log::error!("Quarantining game which cannot be loaded: {}: {}", id, err);
+                    match manager.store.quarantine_game(&id, &err.to_string()) {
+                        Ok(()) => report.quarantined += 1,
+                        Err(err) => log::error!("Could not quarantine game: {}: {}", id, err),
+                    }
+                    continue;
+                }
             };
-            let session = Session::hydrate(id.clone(), db.clone(), game);
-            let session = Arc::new(Mutex::new(session));
-            sessions.insert(id, session);
+            if game.options().public {
+                manager.public_ids.insert(id.clone());
+            }
+            let mut session = Session::hydrate(id.clone(), manager.store.clone(), game, None);
+            manager.adopt(&mut session);
+            if outdated {
+                session.snapshot();
+                report.migrated += session.write_snapshot().map_or(0, |_| 1);
+            }
+            report.loaded += 1;
+            let session = SessionHandle::new(session);
+            manager.sessions.insert(id, session);
         }
-        Ok(Self { sessions, db })
+        log::info!(
+            "Loaded {} games: {} migrated, {} skipped, {} quarantined",
+            report.loaded,
+            report.migrated,
+            report.skipped,
+            report.quarantined
+        );
+        manager.load_report = report;
+        Ok(manager)
     }
 
-    pub fn create_game(&self, options: GameOptions) -> Result<SessionHandle, GameError> {
-        loop {
-            let id = Self::random_id();
-            let entry = self.sessions.entry(id);
+    /// Creates a new game, on behalf of the given creator if they are known,
+    /// unless the server or the creator already has as many games as they may.
+    pub fn create_game(&self, options: GameOptions, creator: Option<&str>) -> Result<SessionHandle, GameError> {
+        let ticket = self.take_lobby_ticket(creator)?;
+        self.add_session(|id| {
+            let mut session = Session::new(id, self.store.clone(), options)?;
+            session.game.set_creator(creator);
+            session.lobby_ticket = ticket;
+            Ok(session)
+        })
+    }
+
+    /// Counts a new lobby against its creator, if they are known,
+    /// unless they already have as many open lobbies as they may.
+    fn take_lobby_ticket(&self, creator: Option<&str>) -> Result<Option<LobbyTicket>, GameError> {
+        let Some(creator) = creator else {
+            return Ok(None);
+        };
+        let max = self.config.max_lobbies_per_creator;
+        match self.lobby_counts.take(creator, max) {
+            Some(ticket) => Ok(Some(ticket)),
+            None => {
+                let max = max.unwrap_or_default();
+                self.creations.rejected_too_many_lobbies.fetch_add(1, Ordering::Relaxed);
+                log::warn!("Refusing to create game: {} already has {} open lobbies", creator, max);
+                Err(GameError::TooManyLobbies { max })
+            }
+        }
+    }
+
+    /// Gets how many games have been created since the server started, and how many creations were refused.
+    pub fn creation_stats(&self) -> CreationStats {
+        CreationStats {
+            created: self.creations.created.load(Ordering::Relaxed),
+            rejected_server_full: self.creations.rejected_server_full.load(Ordering::Relaxed),
+            rejected_too_many_lobbies: self.creations.rejected_too_many_lobbies.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Recreates a game from a save code, under a new ID, on behalf of the given creator if they are known.
+    /// A lobby counts against its creator's open lobbies just as if they had created it.
+    pub fn import_game(&self, code: &str, creator: Option<&str>) -> Result<SessionHandle, GameError> {
+        let snapshot = save::decode(&self.config, code)?;
+        let (mut game, _) = Game::from_versioned(&snapshot).map_err(|_| GameError::InvalidSaveCode)?;
+        game.set_creator(creator);
+        let ticket = match game {
+            Game::Lobby { .. } => self.take_lobby_ticket(creator)?,
+            _ => None,
+        };
+        let session = self.add_session(|id| {
+            let mut session = Session::hydrate(id, self.store.clone(), game, None);
+            session.lobby_ticket = ticket;
+            Ok(session)
+        })?;
+        session.lock().unwrap().snapshot();
+        Ok(session)
+    }
+
+    /// Adds a session under a new, unused ID, which is passed to `build` to create the session.
+    fn add_session(
+        &self,
+        build: impl FnOnce(String) -> Result<Session, GameError>,
+    ) -> Result<SessionHandle, GameError> {
+        if self.config.max_sessions.is_some_and(|max| self.sessions.len() >= max) {
+            self.creations.rejected_server_full.fetch_add(1, Ordering::Relaxed);
+            log::warn!("Refusing to create game: the server is full");
+            return Err(GameError::ServerFull);
+        }
+        let entry = loop {
+            let entry = self.sessions.entry(Self::random_id());
             if let Entry::Occupied(_) = entry {
                 continue;
             }
-            let session = Session::new(entry.key().clone(), self.db.clone(), options)?;
-            let session = Arc::new(Mutex::new(session));
-            entry.or_insert(session.clone());
-            break Ok(session);
+            let fence = match &self.config.cluster {
+                Some(_) => match self.claim_new(entry.key())? {
+                    Some(fence) => Some(fence),
+                    None => continue,
+                },
+                None => None,
+            };
+            break (entry, fence);
+        };
+        let (entry, fence) = entry;
+        let mut session = build(entry.key().clone())?;
+        self.adopt(&mut session);
+        session.fence = fence;
+        session.fire(WebhookEvent::GameCreated);
+        session.emit(AdminEventKind::SessionCreated);
+        if session.game.options().public {
+            self.public_ids.insert(entry.key().clone());
+        }
+        let session = SessionHandle::new(session);
+        entry.or_insert(session.clone());
+        self.creations.created.fetch_add(1, Ordering::Relaxed);
+        Ok(session)
+    }
+
+    /// Connects a session to the manager's webhooks, event stream and clock.
+    fn adopt(&self, session: &mut Session) {
+        session.webhooks = self.webhooks.clone();
+        session.events = self.events.clone();
+        session.writer = self.writer.clone();
+        session.clock = self.clock.clone();
+        session.names = self.names.clone();
+        session.notifier = self.notifier.clone();
+        session.slow_lock_threshold = self.config.slow_action_threshold;
+        session.last_ts = self.clock.instant();
+        session.last_change = session.game.last_change().unwrap_or_else(|| self.clock.now());
+        if let (None, Game::Lobby { creator: Some(creator), .. }) = (&session.lobby_ticket, &session.game) {
+            session.lobby_ticket = self.lobby_counts.take(creator, None);
         }
     }
 
+    /// Subscribes to the events happening across all sessions.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<AdminEvent> {
+        self.events.subscribe()
+    }
+
     pub fn find_game(&self, game_id: &str) -> Result<SessionHandle, GameError> {
-        self.sessions
-            .get(game_id)
-            .map(|session| session.clone())
-            .ok_or(GameError::GameNotFound)
+        if let Some(session) = self.sessions.get(game_id) {
+            return Ok(session.clone());
+        }
+        if self.config.cluster.is_none() {
+            return Err(GameError::GameNotFound);
+        }
+        self.claim_existing(game_id)
+    }
+
+    /// The ownership record for a session held by this node, with a freshly renewed lease.
+    fn lease(&self) -> Option<SessionOwner> {
+        let cluster = self.config.cluster.as_ref()?;
+        let lease = chrono::Duration::from_std(cluster.lease).unwrap_or_else(|_| chrono::Duration::zero());
+        Some(SessionOwner {
+            node: cluster.node_id.clone(),
+            address: cluster.address.clone(),
+            expires: self.clock.now() + lease,
+            epoch: 0,
+        })
+    }
+
+    /// Claims a newly generated game ID for this node, returning the fence to write the session under,
+    /// or `None` if the ID is already in use.
+    fn claim_new(&self, game_id: &str) -> Result<Option<Fence>, GameError> {
+        let Some(lease) = self.lease() else {
+            return Ok(None);
+        };
+        let claim = || -> Result<Option<Fence>, Box<dyn Error>> {
+            if self.store.session_owner(game_id)?.is_some() {
+                return Ok(None);
+            }
+            let owner = self.store.claim_session(game_id, &lease)?;
+            Ok((owner.node == lease.node).then(|| owner.fence()))
+        };
+        claim().map_err(|err| {
+            log::error!("Could not claim session: {}: {}", game_id, err);
+            GameError::PersistenceFailed
+        })
+    }
+
+    /// Takes over a session from the store, unless another node currently owns it.
+    fn claim_existing(&self, game_id: &str) -> Result<SessionHandle, GameError> {
+        let Some(lease) = self.lease() else {
+            return Err(GameError::GameNotFound);
+        };
+        let claim = || -> Result<Claim, Box<dyn Error>> {
+            if self.store.session_owner(game_id)?.is_none() {
+                return Ok(Claim::Missing);
+            }
+            let owner = self.store.claim_session(game_id, &lease)?;
+            if owner.node != lease.node {
+                return Ok(Claim::Owned(owner));
+            }
+            match self.store.game(game_id)? {
+                Some(record) => match Game::decode(&record) {
+                    Ok((game, _)) => Ok(Claim::Claimed(game, owner.fence())),
+                    Err(err) if persisted_by_newer_server(err.as_ref()) => Err(err),
+                    Err(err) => {
+                        log::error!("Quarantining game which cannot be loaded: {}: {}", game_id, err);
+                        self.store.quarantine_game(game_id, &err.to_string())?;
+                        Ok(Claim::Missing)
+                    }
+                },
+                None => Ok(Claim::Missing),
+            }
+        };
+        let (game, fence) = match claim() {
+            Ok(Claim::Claimed(game, fence)) => (game, fence),
+            Ok(Claim::Missing) => return Err(GameError::GameNotFound),
+            Ok(Claim::Owned(owner)) => return Err(GameError::HostedElsewhere { address: owner.address }),
+            Err(err) => {
+                log::error!("Could not claim session: {}: {}", game_id, err);
+                return Err(GameError::GameNotFound);
+            }
+        };
+
+        log::info!("Took ownership of session: {}", game_id);
+        let session = self.sessions.entry(game_id.to_string()).or_insert_with(|| {
+            let mut session = Session::hydrate(game_id.to_string(), self.store.clone(), game, Some(fence));
+            self.adopt(&mut session);
+            SessionHandle::new(session)
+        });
+        Ok(session.clone())
+    }
+
+    /// Renews this node's leases on the sessions it hosts, when running in distributed mode.
+    /// Sessions which have been idle for a while are released so that any node may take them over,
+    /// and sessions which another node has taken over are dropped.
+    pub fn renew_leases(&self) {
+        let Some(cluster) = &self.config.cluster else {
+            return;
+        };
+        let mut ids_to_drop = vec![];
+
+        for session in self.sessions.iter() {
+            let game_id = session.key();
+            let Ok(mut session) = session.lock() else {
+                continue;
+            };
+            if self.clock.since(session.last_ts) > cluster.idle_handoff && session.subscribers() == 0 {
+                // The snapshot must be written before the lease is released
+                session.snapshot();
+                if session.write_snapshot().is_err() {
+                    // Hold on to the session, as its latest state could not be saved
+                    continue;
+                }
+                match self.store.release_session(game_id, &cluster.node_id) {
+                    Ok(()) => ids_to_drop.push(game_id.clone()),
+                    Err(err) => log::error!("Could not release session: {}: {}", game_id, err),
+                }
+                continue;
+            }
+            let Some(lease) = self.lease() else {
+                continue;
+            };
+            match self.store.claim_session(game_id, &lease) {
+                Ok(owner) if session.fence.as_ref() != Some(&owner.fence()) => {
+                    // Even if this node has since won the session back, another may have written to it meanwhile
+                    log::warn!("Session was taken over by node {}: {}", owner.node, game_id);
+                    ids_to_drop.push(game_id.clone());
+                }
+                Ok(_) => {}
+                Err(err) => log::error!("Could not renew lease: {}: {}", game_id, err),
+            }
+        }
+
+        for game_id in ids_to_drop.into_iter() {
+            self.sessions.remove(&game_id);
+        }
+    }
+
+    /// Checks that the store can be read from.
+    pub fn check_store(&self) -> Result<(), Box<dyn Error>> {
+        self.store.last_archived().map(|_| ())
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Gets what became of the games in the store when the server started.
+    pub fn load_report(&self) -> LoadReport {
+        self.load_report
+    }
+
+    /// Gets the games which could not be loaded, and were set aside for inspection.
+    pub fn quarantined_games(&self) -> Vec<QuarantinedGame> {
+        self.store.quarantined().unwrap_or_else(|err| {
+            log::error!("Could not read quarantine: {}", err);
+            vec![]
+        })
+    }
+
+    /// Deletes a quarantined game for good, returning whether it was found.
+    pub fn remove_quarantined(&self, id: &str) -> Result<bool, Box<dyn Error>> {
+        let found = self.store.quarantined()?.iter().any(|game| game.id == id);
+        self.store.remove_quarantined(id)?;
+        Ok(found)
     }
 
     pub fn num_games(&self) -> usize {
@@ -131,15 +863,16 @@ impl SessionManager {
     }
 
     pub fn purge_games(&self) {
-        let max_idle = Duration::from_secs(3600);
         let mut ids_to_delete = vec![];
 
-        // Find expired sessions and delete them from sled
+        // Find expired sessions and delete them from the store
         for session in self.sessions.iter() {
             let game_id = session.key();
-            let expired = session.lock().map_or(true, |s| s.last_ts.elapsed() > max_idle);
+            let (expired, fence) = session
+                .lock()
+                .map_or((true, None), |s| (s.expired(&self.config), s.fence.clone()));
             if expired {
-                match self.db.game.remove(game_id) {
+                match self.store.remove_game(game_id, fence.as_ref()) {
                     Ok(_) => ids_to_delete.push(game_id.clone()),
                     Err(err) => log::error!("Could not remove game: {}: {}", game_id, err),
                 }
@@ -149,20 +882,170 @@ impl SessionManager {
         // Remove the deleted sessions from cache
         for game_id in ids_to_delete.into_iter() {
             self.sessions.remove(&game_id);
+            self.public_ids.remove(&game_id);
+            self.events
+                .send(AdminEvent::new(&game_id, AdminEventKind::SessionPurged))
+                .ok();
         }
+
+        self.purge_released_games(self.config.idle_timeout)
+            .unwrap_or_else(|err| {
+                log::error!("Could not purge released games: {}", err);
+            });
+
+        self.prune_archive().unwrap_or_else(|err| {
+            log::error!("Could not prune archive: {}", err);
+        });
     }
 
-    pub fn past_games(&self) -> Vec<(u64, GameStats)> {
-        self.db
-            .archive
-            .iter()
-            .flat_map(|row| {
-                let (key, value) = row.ok()?;
-                let key = u64::from_be_bytes(<[u8; 8]>::try_from(&*key).ok()?);
-                let value = serde_json::from_slice(&value).ok()?;
-                Some((key, value))
-            })
-            .collect()
+    /// Deletes games which no node has hosted for longer than `max_idle`, when running in distributed mode.
+    fn purge_released_games(&self, max_idle: Duration) -> Result<(), Box<dyn Error>> {
+        let Some(lease) = self.lease() else {
+            return Ok(());
+        };
+        let cutoff = self.clock.now() - chrono::Duration::from_std(max_idle)?;
+        for (game_id, _) in self.store.games()? {
+            if self.sessions.contains_key(&game_id) {
+                continue;
+            }
+            let released = self
+                .store
+                .session_owner(&game_id)?
+                .is_none_or(|owner| owner.expires < cutoff);
+            if !released {
+                continue;
+            }
+            let owner = self.store.claim_session(&game_id, &lease)?;
+            if owner.node == lease.node {
+                self.store.remove_game(&game_id, Some(&owner.fence()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends the deferred game updates of every session, and refreshes the list of public lobbies.
+    pub fn flush_updates(&self) {
+        for session in self.sessions.iter() {
+            if let Ok(mut session) = session.lock() {
+                session.flush_updates();
+            }
+        }
+        self.refresh_lobbies();
+    }
+
+    /// Gets the public lobbies which are open to new players.
+    /// In distributed mode, only the lobbies hosted by this node are listed.
+    pub fn public_lobbies(&self) -> Vec<LobbySummary> {
+        self.lobbies.borrow().clone()
+    }
+
+    /// Subscribes to changes to the list of public lobbies.
+    pub fn subscribe_lobbies(&self) -> watch::Receiver<Vec<LobbySummary>> {
+        self.lobbies.subscribe()
+    }
+
+    /// Rebuilds the list of public lobbies, notifying subscribers if it has changed.
+    fn refresh_lobbies(&self) {
+        let mut lobbies = vec![];
+        self.public_ids.retain(|id| {
+            let Some(session) = self.sessions.get(id) else {
+                return false;
+            };
+            let Ok(session) = session.lock() else {
+                return false;
+            };
+            lobbies.extend(session.lobby_summary());
+            true
+        });
+        lobbies.sort_by(|a, b| a.id.cmp(&b.id));
+        self.lobbies.send_if_modified(|current| {
+            let modified = *current != lobbies;
+            *current = lobbies;
+            modified
+        });
+    }
+
+    /// Resolves the current phase of any game whose timer has run out.
+    pub fn check_timers(&self) {
+        for session in self.sessions.iter() {
+            if let Ok(mut session) = session.lock() {
+                session.check_timer();
+                session.check_schedule();
+                session.prune_lobby(self.config.lobby_grace);
+                session.nudge_players(self.config.async_nudge_interval);
+            }
+        }
+    }
+
+    /// Gets a page of archived games matching the given query, in order of ID.
+    pub fn past_games(&self, query: &ArchiveQuery) -> ArchivePage {
+        let mut start = query.after.map_or(0, |id| id.saturating_add(1));
+        let limit = query.limit.unwrap_or(DEFAULT_ARCHIVE_PAGE).min(MAX_ARCHIVE_PAGE);
+        let mut games = vec![];
+
+        // One more game than fits on the page is sought, to tell whether there is another page
+        while games.len() <= limit {
+            let rows = self.store.archived(start, MAX_ARCHIVE_PAGE).unwrap_or_else(|err| {
+                log::error!("Could not read archive: {}", err);
+                vec![]
+            });
+            let Some((last, _)) = rows.last() else {
+                break;
+            };
+            start = last + 1;
+            games.extend(
+                rows.into_iter()
+                    .flat_map(|(id, value)| Some((id, serde_json::from_slice(&value).ok()?)))
+                    .filter(|(_, stats)| query.matches(stats)),
+            );
+        }
+
+        let more = games.len() > limit;
+        games.truncate(limit);
+        let next = more.then(|| games.last().map(|(id, _)| *id)).flatten();
+        ArchivePage { games, next }
+    }
+
+    /// Gets a single archived game by its ID.
+    pub fn archived_game(&self, id: u64) -> Option<GameStats> {
+        let rows = self.store.archived(id, 1).unwrap_or_else(|err| {
+            log::error!("Could not read archive: {}", err);
+            vec![]
+        });
+        let (found, value) = rows.into_iter().next()?;
+        (found == id).then(|| serde_json::from_slice(&value).ok()).flatten()
+    }
+
+    /// Deletes archived games which finished longer ago than the retention period.
+    /// The most recent game is always kept, so that archive IDs are never reused, and so is any game which cannot be
+    /// read, as there is no telling when it finished.
+    fn prune_archive(&self) -> Result<(), Box<dyn Error>> {
+        let Some(retention) = self.config.archive_retention else {
+            return Ok(());
+        };
+        let cutoff = self.clock.now() - chrono::Duration::from_std(retention)?;
+        let Some(last) = self.store.last_archived()? else {
+            return Ok(());
+        };
+
+        let mut start = 0;
+        loop {
+            let rows = self.store.archived(start, MAX_ARCHIVE_PAGE)?;
+            let Some(&(end, _)) = rows.last() else {
+                return Ok(());
+            };
+            for (id, value) in rows.iter() {
+                if *id == last {
+                    return Ok(());
+                }
+                match serde_json::from_slice::<GameStats>(value) {
+                    Ok(stats) if stats.finished >= cutoff => return Ok(()),
+                    Ok(_) => self.store.remove_archived(*id)?,
+                    Err(err) => log::error!("Keeping archived game which cannot be read: {}: {}", id, err),
+                }
+            }
+            start = end + 1;
+        }
     }
 
     fn random_id() -> String {
@@ -172,28 +1055,139 @@ impl SessionManager {
 }
 
 impl Session {
-    fn new(id: String, dbs: Database, options: GameOptions) -> Result<Self, GameError> {
+    fn new(id: String, store: Arc<dyn GameStore>, options: GameOptions) -> Result<Self, GameError> {
+        let options = options.resolve_preset();
         let game = Game::Lobby {
             options,
             players: vec![],
-            min_players: options.min_players().ok_or(GameError::InvalidGameOptions)?,
-            max_players: options.max_players().ok_or(GameError::InvalidGameOptions)?,
+            min_players: options
+                .min_players()
+                .ok_or_else(|| GameError::OptionsValidation(options.problems(0)))?,
+            max_players: options
+                .max_players()
+                .ok_or_else(|| GameError::OptionsValidation(options.problems(0)))?,
+            board_key: Some(
+                rand::thread_rng()
+                    .sample_iter(Alphanumeric)
+                    .take(16)
+                    .map(char::from)
+                    .collect(),
+            ),
+            start_at: None,
+            extensions: 0,
+            creator: None,
+            last_change: None,
         };
-        Ok(Self::hydrate(id, dbs, game))
+        Ok(Self::hydrate(id, store, game, None))
     }
 
-    fn hydrate(id: String, db: Database, game: Game) -> Self {
-        let mut player_states = vec![];
-        for _ in 0..game.num_players() {
-            player_states.push(watch::channel(Value::Null).0);
-        }
-        Self {
+    /// Restores a session for a persisted game, replaying its journal. The fence is the one the session was claimed
+    /// under, if it was, which is set before the journal is replayed so that the snapshot it writes is fenced too.
+    fn hydrate(id: String, store: Arc<dyn GameStore>, game: Game, fence: Option<Fence>) -> Self {
+        let seat_updates = (0..game.num_players()).map(|_| watch::channel(None).0).collect();
+        let timer = match &game {
+            Game::Playing { timer, .. } => *timer,
+            _ => None,
+        };
+        let last_change = game.last_change().unwrap_or_else(Utc::now);
+        let (write_reports, write_results) = mpsc::channel();
+        let mut session = Self {
             id,
             game,
             updates: watch::channel(GameUpdate::default()).0,
-            db,
+            seat_updates,
+            chat: broadcast::channel(64).0,
+            chat_log: VecDeque::new(),
+            store: store.clone(),
             last_ts: Instant::now(),
+            last_change,
+            timer,
+            undo_deadline: None,
+            rematch: None,
+            writer: SnapshotWriter::direct(store.clone()),
+            pending_snapshot: None,
+            journal_seq: 0,
+            journal_len: 0,
+            fence,
+            write_reports,
+            write_results,
+            seats: HashMap::new(),
+            last_notify: None,
+            notify_pending: false,
+            version: 0,
+            generation: 0,
+            hotseat: None,
+            webhooks: Arc::default(),
+            events: broadcast::channel(1).0,
+            clock: Arc::new(SystemClock),
+            names: Arc::default(),
+            lobby_ticket: None,
+            player_updates: vec![],
+            notifier: Arc::default(),
+            push_targets: HashMap::new(),
+            notified: HashMap::new(),
+            slow_lock_threshold: Duration::MAX,
+        };
+        session.replay_journal();
+        session
+    }
+
+    /// Replays any actions which were journaled but not persisted before the server stopped.
+    fn replay_journal(&mut self) {
+        let entries = match self.store.journal(&self.id) {
+            Ok(entries) => entries,
+            Err(err) => {
+                log::error!("Cannot read journal: {}: {}", &self.id, err);
+                return;
+            }
+        };
+        let Some(&(last_seq, _)) = entries.last() else {
+            return;
+        };
+        // Later entries must not overwrite those which are still in the journal
+        self.journal_seq = last_seq + 1;
+        let Some(game) = self.game.game_mut() else {
+            return;
+        };
+
+        let mut replayed = 0;
+        let mut changed_at = None;
+        for (_, entry) in &entries {
+            let entry = match serde_json::from_slice::<JournalEntry>(entry) {
+                Ok(entry) => entry,
+                Err(err) => {
+                    log::error!("Invalid journal entry: {}: {}", &self.id, err);
+                    break;
+                }
+            };
+            let mut next = game.clone();
+            if let Err(err) = entry.action.apply(&mut next) {
+                log::error!("Cannot replay journaled action: {}: {}", &self.id, err);
+                break;
+            }
+            if next.state_hash() != entry.state_hash && next.legacy_state_hash() != entry.state_hash {
+                log::error!("Journaled action diverged on replay: {}", &self.id);
+                break;
+            }
+            *game = next;
+            replayed += 1;
+            changed_at = entry.at.or(changed_at);
+        }
+        log::info!(
+            "Replayed {} of {} journaled actions: {}",
+            replayed,
+            entries.len(),
+            &self.id
+        );
+        if let Some(at) = changed_at {
+            self.last_change = at;
+            self.game.set_last_change(at);
         }
+
+        self.try_archive();
+        // The snapshot covers every entry, so writing it clears those which could not be replayed too
+        self.snapshot();
+        self.write_snapshot().ok();
     }
 
     /// Gets the unique game ID.
@@ -201,38 +1195,358 @@ impl Session {
         &self.id
     }
 
+    /// Summarises the game for the public lobby list, if it is a public lobby which has room for more players.
+    pub fn lobby_summary(&self) -> Option<LobbySummary> {
+        let Game::Lobby {
+            options,
+            players,
+            min_players,
+            max_players,
+            start_at,
+            ..
+        } = &self.game
+        else {
+            return None;
+        };
+        (options.public && players.len() < *max_players).then(|| LobbySummary {
+            id: self.id.clone(),
+            num_players: players.len(),
+            min_players: *min_players,
+            max_players: *max_players,
+            options: *options,
+            start_at: *start_at,
+        })
+    }
+
+    /// Captures the game in a save code, from which it can be resumed on any server sharing this one's secret.
+    pub fn save_code(&self, config: &Config) -> Result<String, GameError> {
+        let snapshot = schema::serialize(&self.game).expect("game is serializable");
+        save::encode(config, &snapshot)
+    }
+
+    /// Gets the key a client must present in order to join as the board.
+    pub fn board_key(&self) -> Option<&str> {
+        self.game.board_key()
+    }
+
+    /// Checks that a client is permitted to join as the board.
+    /// Games created before board keys were introduced may be joined without one.
+    pub fn authorize_board(&self, key: Option<&str>) -> Result<(), GameError> {
+        match self.game.board_key() {
+            Some(expected) if key != Some(expected) => Err(GameError::InvalidBoardKey),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that a client is permitted to take the given seat. A client presenting a token must present one for
+    /// this seat in this game, and if `require_token` is set, a seat which is already taken can only be reclaimed
+    /// with one.
+    pub fn authorize_player(&self, name: &str, token: Option<&Claims>, require_token: bool) -> Result<(), GameError> {
+        match token {
+            Some(claims) if claims.game_id != self.id || claims.seat != name => Err(GameError::InvalidToken),
+            None if require_token && self.has_player(name) => Err(GameError::InvalidToken),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns whether a player with the given name has joined the game.
+    fn has_player(&self, name: &str) -> bool {
+        match &self.game {
+            Game::Lobby { players, .. } => players.iter().any(|n| n == name),
+            Game::Playing { game, .. } => game.find_player(name).is_ok(),
+            Game::GameOver | Game::Cancelled => false,
+        }
+    }
+
     /// Adds the player to the game if there are not already a member,
-    /// unless the game is unable to accept any new players.
+    /// unless the game is unable to accept any new players or the name is not allowed.
+    /// A name which differs from a member's only in case or spacing is taken.
     pub fn add_player(&mut self, name: &str) -> Result<(), GameError> {
         match &mut self.game {
             Game::Lobby { players, max_players, .. } => {
                 if players.iter().any(|n| *n == name) {
                     return Ok(());
                 }
+                self.names.validate(name)?;
+                if players.iter().any(|n| names::same_name(n, name)) {
+                    return Err(GameError::NameTaken);
+                }
                 if players.len() == *max_players {
                     return Err(GameError::TooManyPlayers);
                 }
                 players.push(name.to_string());
+                self.snapshot();
                 Ok(())
             }
             Game::Playing { game, .. } => match game.find_player(name) {
                 Ok(_) => Ok(()),
                 Err(_) => Err(GameError::CannotJoinStartedGame),
             },
-            Game::GameOver => Err(GameError::GameNotFound),
+            Game::GameOver | Game::Cancelled => Err(GameError::GameNotFound),
         }
     }
 
-    /// Called by a new client to subscribe to game state updates.
-    pub fn subscribe(&mut self) -> watch::Receiver<GameUpdate> {
-        let rx = self.updates.subscribe();
-        self.notify();
-        rx
+    /// Hands a player's seat in a game in progress over to a newcomer, who keeps the player's role.
+    /// The outgoing player's connection loses its claim on the seat.
+    pub fn substitute_player(&mut self, name: &str, new_name: &str) -> Result<(), GameError> {
+        self.names.validate(new_name)?;
+        if let Game::Playing { game, .. } = &self.game {
+            if game.player_names().any(|n| n != name && names::same_name(n, new_name)) {
+                return Err(GameError::NameTaken);
+            }
+        }
+        self.perform_action(GameAction::Substitute {
+            name: name.to_string(),
+            new_name: new_name.to_string(),
+        })?;
+        self.seats.remove(name);
+        self.push_targets.remove(name);
+        Ok(())
     }
 
-    /// Starts the game.
-    pub fn start_game(&mut self) -> Result<(), GameError> {
-        // Check there isn't already a game in progress
+    /// Removes a player from the lobby, freeing up their seat.
+    pub fn remove_player(&mut self, name: &str) -> Result<(), GameError> {
+        let Game::Lobby { players, .. } = &mut self.game else {
+            return Err(GameError::InvalidAction);
+        };
+        let Some(index) = players.iter().position(|n| n == name) else {
+            return Err(GameError::PlayerNotFound);
+        };
+        players.remove(index);
+        self.seats.remove(name);
+        self.push_targets.remove(name);
+        self.notify();
+        self.snapshot();
+        Ok(())
+    }
+
+    /// Removes any player from the lobby whose connection has been gone for longer than `grace`.
+    /// Players who never connected, such as those added to a hotseat game, are left alone.
+    pub fn prune_lobby(&mut self, grace: Duration) {
+        // The players of an asynchronous game are not expected to stay connected while they wait for it to start
+        if !matches!(self.game, Game::Lobby { .. }) || self.game.options().asynchronous.is_some() {
+            return;
+        }
+        let now = self.clock.now();
+        let abandoned = self
+            .seats
+            .iter()
+            .filter(|(_, seat)| seat.connections == 0)
+            .filter(|(_, seat)| (now - seat.last_seen).to_std().is_ok_and(|idle| idle > grace))
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        for name in abandoned {
+            self.remove_player(&name).ok();
+        }
+    }
+
+    /// Changes the name of a player in the lobby, which is no longer possible once the game has started.
+    pub fn rename_player(&mut self, name: &str, new_name: &str) -> Result<(), GameError> {
+        let Game::Lobby { players, .. } = &mut self.game else {
+            return Err(GameError::CannotJoinStartedGame);
+        };
+        self.names.validate(new_name)?;
+        if players.iter().any(|n| n != name && names::same_name(n, new_name)) {
+            return Err(GameError::NameTaken);
+        }
+        let Some(player) = players.iter_mut().find(|n| *n == name) else {
+            return Err(GameError::PlayerNotFound);
+        };
+        *player = new_name.to_string();
+        if let Some(seat) = self.seats.remove(name) {
+            self.seats.insert(new_name.to_string(), seat);
+        }
+        if let Some(target) = self.push_targets.remove(name) {
+            self.push_targets.insert(new_name.to_string(), target);
+        }
+        self.notify();
+        self.snapshot();
+        Ok(())
+    }
+
+    /// Gets the number of clients subscribed to game state updates.
+    pub fn subscribers(&self) -> usize {
+        self.updates.receiver_count()
+    }
+
+    /// Whether the session should be purged, either because it has been idle with no clients connected,
+    /// or because it has gone a very long time without changing.
+    /// The game's own idle timeout, if it has one, takes precedence over the server's.
+    /// An asynchronous game is never idle, and only expires once it has gone much longer without changing.
+    fn expired(&self, config: &Config) -> bool {
+        let unchanged = (self.clock.now() - self.last_change).to_std().unwrap_or_default();
+        if self.game.options().asynchronous.is_some() {
+            return unchanged > config.async_inactive_timeout;
+        }
+        let override_timeout = self
+            .game
+            .options()
+            .idle_timeout
+            .map(|secs| Duration::from_secs(secs.into()));
+        let idle_timeout = override_timeout.unwrap_or(config.idle_timeout);
+        let inactive_timeout = override_timeout.map_or(config.inactive_timeout, |t| t.max(config.inactive_timeout));
+        let idle = self.clock.since(self.last_ts) > idle_timeout && self.subscribers() == 0;
+        idle || unchanged > inactive_timeout
+    }
+
+    /// Called by a new client to subscribe to game state updates.
+    pub fn subscribe(&mut self) -> watch::Receiver<GameUpdate> {
+        let rx = self.updates.subscribe();
+        self.notify();
+        rx
+    }
+
+    /// Called by a new client to subscribe to the updates of the player in the given seat.
+    /// The channel is closed once the game's lifecycle changes, after which the seat must be looked up again.
+    pub fn subscribe_player(&mut self, seat: usize) -> Result<watch::Receiver<Option<Arc<PlayerUpdate>>>, GameError> {
+        let game = self.game.game().ok_or(GameError::InvalidAction)?;
+        if seat >= game.num_players() {
+            return Err(GameError::InvalidPlayerIndex);
+        }
+        while self.seat_updates.len() <= seat {
+            let update = self.player_updates.get(self.seat_updates.len()).cloned();
+            self.seat_updates.push(watch::channel(update).0);
+        }
+        Ok(self.seat_updates[seat].subscribe())
+    }
+
+    /// Gets the seat of the player with the given name, once the game has started.
+    pub fn seat(&self, name: &str) -> Option<usize> {
+        self.game.game()?.find_player(name).ok()
+    }
+
+    /// Sets where the given player is notified when the game is waiting on them and they are not connected,
+    /// or stops notifying them if `target` is `None`.
+    pub fn register_push(&mut self, name: &str, target: Option<PushTarget>) -> Result<(), GameError> {
+        if !self.notifier.enabled() {
+            return Err(GameError::NotificationsDisabled);
+        }
+        if !self.has_player(name) {
+            return Err(GameError::PlayerNotFound);
+        }
+        match target {
+            Some(target) => {
+                self.notifier.validate(&target)?;
+                self.push_targets.insert(name.to_string(), target);
+            }
+            None => {
+                self.push_targets.remove(name);
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the given player as the rest of the table sees them, as last published to subscribers.
+    /// This does not count as interacting with the session, so polling it does not keep the session alive.
+    pub fn public_player(&self, name: &str) -> Result<PublicPlayer, GameError> {
+        let update = self.updates.borrow();
+        let player = update.players.iter().find(|player| player.name == name);
+        player.cloned().ok_or(GameError::PlayerNotFound)
+    }
+
+    /// Gets the given player's private view of the game, including their prompt, as last published to subscribers,
+    /// or `None` if the game has yet to start. Like [`Session::public_player`], this leaves the session's idle timer
+    /// alone.
+    pub fn player_update(&self, name: &str) -> Result<Option<Arc<PlayerUpdate>>, GameError> {
+        if self.game.game().is_none() {
+            return match self.has_player(name) {
+                true => Ok(None),
+                false => Err(GameError::PlayerNotFound),
+            };
+        }
+        let seat = self.seat(name).ok_or(GameError::PlayerNotFound)?;
+        Ok(self.player_updates.get(seat).cloned())
+    }
+
+    /// Moves the game's lifecycle on to its next generation, closing the channels of seats which may change hands.
+    fn next_generation(&mut self) {
+        self.generation += 1;
+        self.seat_updates.clear();
+        self.player_updates.clear();
+    }
+
+    /// Called by a new client to subscribe to chat messages.
+    /// Returns the messages sent so far which are visible to the given player (or the board), and the receiver.
+    pub fn subscribe_chat(&self, player: Option<&str>) -> (Vec<ChatMessage>, broadcast::Receiver<ChatMessage>) {
+        let history = self.chat_log.iter().filter(|m| m.visible_to(player)).cloned().collect();
+        (history, self.chat.subscribe())
+    }
+
+    /// Sends a chat message from the given player, or from the board if `sender` is `None`.
+    pub fn send_chat(&mut self, sender: Option<&str>, scope: ChatScope, text: &str) -> Result<(), GameError> {
+        let text = text.trim();
+        if text.is_empty() || text.chars().count() > MAX_CHAT_LENGTH {
+            return Err(GameError::InvalidChatMessage);
+        }
+
+        let recipients = match (sender, scope) {
+            (None, ChatScope::Board) => None,
+            (Some(_), ChatScope::All) => None,
+            (Some(name), ChatScope::Fascists | ChatScope::Communists) => {
+                let Game::Playing { game, .. } = &self.game else {
+                    return Err(GameError::ChatNotPermitted);
+                };
+                if !game.can_chat(game.find_player(name)?, scope) {
+                    return Err(GameError::ChatNotPermitted);
+                }
+                Some(game.chat_members(scope))
+            }
+            _ => return Err(GameError::ChatNotPermitted),
+        };
+
+        let message = ChatMessage {
+            sender: sender.map(str::to_string),
+            scope,
+            text: text.to_string(),
+            sent: self.clock.now(),
+            recipients,
+        };
+        if self.chat_log.len() == MAX_CHAT_LOG {
+            self.chat_log.pop_front();
+        }
+        self.chat_log.push_back(message.clone());
+        self.chat.send(message).ok();
+        self.last_ts = self.clock.instant();
+        Ok(())
+    }
+
+    /// Gets the generation of the game's lifecycle, which increases whenever the game starts or ends.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Gets the version of the game update last sent to clients.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Moves a stuck game on with safe defaults, provided it has not changed since the given version was sent.
+    /// This stops the board from confirming that it means to override the players once they have moved on by themselves.
+    pub fn force_advance_from(&mut self, version: u64) -> Result<(), GameError> {
+        if version != self.version || self.notify_pending {
+            return Err(GameError::ConfirmationRequired);
+        }
+        self.perform_action(GameAction::Board { action: BoardAction::ForceAdvance })
+    }
+
+    /// Starts the game, provided its lifecycle is still at the given generation, if one is given.
+    /// This stops a client which has yet to see that the game was started from starting it again.
+    pub fn start_game_from(&mut self, generation: Option<u64>) -> Result<(), GameError> {
+        if generation.is_some_and(|generation| generation != self.generation) {
+            return Err(GameError::AlreadyStarted);
+        }
+        self.start_game()
+    }
+
+    /// Starts the game.
+    #[tracing::instrument(level = "debug", skip_all, fields(game_id = %self.id))]
+    pub fn start_game(&mut self) -> Result<(), GameError> {
+        // Check there isn't already a game in progress
+        if let Game::Playing { game, .. } = &self.game {
+            if !game.game_over() {
+                return Err(GameError::AlreadyStarted);
+            }
+        }
         if !self.game.can_start() {
             return Err(GameError::InvalidAction);
         }
@@ -240,107 +1554,647 @@ impl Session {
         self.try_archive();
         let opts = self.game.options();
         let names = self.game.player_names();
-        let seed = rand::thread_rng().next_u64();
+        opts.validate(names.len())?;
+        let seed = opts
+            .scenario
+            .map_or_else(|| rand::thread_rng().next_u64(), Scenario::seed);
         self.game = Game::Playing {
             game: GameInner::new(opts, &names, seed)?,
-            started_ts: chrono::offset::Utc::now(),
+            started_ts: self.clock.now(),
             archived: false,
+            board_key: self.game.board_key().map(str::to_string),
+            timer: None,
+            last_change: None,
         };
+        self.lobby_ticket = None;
+        self.next_generation();
+        self.fire(WebhookEvent::GameStarted);
+        self.emit(AdminEventKind::GameStarted);
         self.notify();
-        self.persist_game().ok();
+        self.snapshot();
 
         Ok(())
     }
 
+    /// Schedules the game to start by itself at the given time, which must be in the future.
+    pub fn schedule_start(&mut self, at: DateTime<Utc>) -> Result<(), GameError> {
+        let Game::Lobby { start_at, .. } = &mut self.game else {
+            return Err(GameError::InvalidAction);
+        };
+        if at <= self.clock.now() {
+            return Err(GameError::InvalidGameOptions);
+        }
+        *start_at = Some(at);
+        self.notify();
+        self.snapshot();
+        Ok(())
+    }
+
+    /// Starts a scheduled game once its start time arrives. If too few players have joined by then,
+    /// the start is put back, or after several extensions, the game is cancelled.
+    #[tracing::instrument(level = "debug", skip_all, fields(game_id = %self.id))]
+    pub fn check_schedule(&mut self) {
+        let now = self.clock.now();
+        let Game::Lobby {
+            players,
+            min_players,
+            start_at: Some(start_at),
+            extensions,
+            ..
+        } = &mut self.game
+        else {
+            return;
+        };
+        if now < *start_at {
+            return;
+        }
+
+        let notice = if players.len() >= *min_players {
+            if let Err(err) = self.start_game() {
+                log::error!("Cannot start scheduled game: {}: {}", &self.id, err);
+            }
+            return;
+        } else if *extensions < MAX_SCHEDULE_EXTENSIONS {
+            *extensions += 1;
+            *start_at = now + chrono::Duration::from_std(SCHEDULE_EXTENSION).unwrap();
+            format!(
+                "Not enough players have joined, so the game will now start at {}.",
+                start_at.format("%H:%M UTC")
+            )
+        } else {
+            self.game = Game::Cancelled;
+            self.lobby_ticket = None;
+            self.next_generation();
+            "Not enough players joined, so the game has been cancelled.".to_string()
+        };
+        self.send_chat(None, ChatScope::Board, &notice).ok();
+        self.notify();
+        self.snapshot();
+    }
+
     /// Performs an action on the game.
-    pub fn mutate_game<F>(&mut self, mutation: F) -> Result<(), GameError>
-    where
-        F: FnOnce(&mut GameInner) -> Result<(), GameError>,
-    {
-        let Some(game) = self.game.game_mut() else {
+    /// The action is journaled before it takes effect, so that it survives a server crash.
+    #[tracing::instrument(skip_all, fields(game_id = %self.id, action = action.kind(), player))]
+    pub fn perform_action(&mut self, action: GameAction) -> Result<(), GameError> {
+        self.check_writes();
+        let Some(game) = self.game.game() else {
             return Err(GameError::InvalidAction);
         };
+        if let Some(player) = action.player().and_then(|name| game.find_player(name).ok()) {
+            tracing::Span::current().record("player", player);
+        }
+
+        let started = Instant::now();
+        let was_over = game.game_over();
+        let mut next = game.clone();
+        if let Err(err) = action.apply(&mut next) {
+            let error = err.to_string();
+            self.emit(AdminEventKind::ActionRejected { action, error });
+            return Err(err);
+        }
+        self.journal(action.clone(), &next).map_err(|err| {
+            log::error!("Cannot journal action: {}: {}", &self.id, err);
+            GameError::PersistenceFailed
+        })?;
+        self.emit(AdminEventKind::ActionApplied { action });
 
-        mutation(game)?;
+        self.undo_deadline = next.undo_player().and_then(|_| {
+            let secs = next.options().undo_window?;
+            Some(self.clock.now() + chrono::Duration::seconds(secs.into()))
+        });
+        let game_over = next.game_over();
+        let finished = game_over && !was_over;
+        if let Some(game) = self.game.game_mut() {
+            *game = next;
+        }
+        if finished {
+            self.fire(WebhookEvent::GameFinished);
+        }
         self.notify();
-        self.persist_game().ok();
         self.try_archive();
+        if game_over || self.journal_len >= SNAPSHOT_INTERVAL {
+            self.snapshot();
+        }
+        tracing::debug!(elapsed_us = started.elapsed().as_micros() as u64, "action performed");
 
         Ok(())
     }
 
+    /// Reverts the last action of the given player, if it is still within the undo window.
+    pub fn undo(&mut self, name: &str) -> Result<(), GameError> {
+        if self.undo_deadline.is_none_or(|deadline| self.clock.now() > deadline) {
+            return Err(GameError::UndoExpired);
+        }
+        self.perform_action(GameAction::Undo { name: name.to_string() })
+    }
+
+    /// Resolves the current phase of the game if its timer has run out.
+    #[tracing::instrument(level = "debug", skip_all, fields(game_id = %self.id))]
+    pub fn check_timer(&mut self) {
+        self.check_writes();
+        if self.undo_deadline.is_some_and(|deadline| self.clock.now() > deadline) {
+            self.undo_deadline = None;
+            self.perform_action(GameAction::ExpireUndo).unwrap_or_else(|err| {
+                log::error!("Cannot expire undo: {}: {}", &self.id, err);
+            });
+        }
+
+        let Some(timer) = self.timer else {
+            return;
+        };
+        if self.clock.now() < timer.deadline {
+            return;
+        }
+        self.timer = None;
+        self.perform_action(GameAction::Timeout).unwrap_or_else(|err| {
+            log::error!("Cannot resolve timeout: {}: {}", &self.id, err);
+        });
+    }
+
     /// Keeps the game session alive.
-    pub fn heartbeat(&mut self) {
-        self.last_ts = Instant::now();
+    pub fn heartbeat(&mut self, player: Option<&str>) {
+        self.last_ts = self.clock.instant();
+        if let Some(seat) = player.and_then(|player| self.seats.get_mut(player)) {
+            seat.last_seen = self.clock.now();
+        }
+    }
+
+    /// Attaches a websocket connection to the given player's seat, returning the claim it holds on the seat.
+    /// A seat which is in use can only be taken over once its connection has gone quiet for longer than
+    /// `stale_after`, or the board has approved the takeover; the connection it displaces loses its claim.
+    pub fn attach(&mut self, player: &str, stale_after: Duration) -> Result<u64, GameError> {
+        let now = self.clock.now();
+        let seat = self.seats.entry(player.to_string()).or_insert(Seat {
+            connections: 0,
+            last_seen: now,
+            claim: 0,
+            claim_pending: false,
+            takeover_approved: false,
+        });
+        if seat.connections > 0 {
+            let stale = (now - seat.last_seen).to_std().is_ok_and(|idle| idle > stale_after);
+            if !stale && !seat.takeover_approved {
+                if !seat.claim_pending {
+                    seat.claim_pending = true;
+                    self.notify();
+                }
+                return Err(GameError::SeatTaken);
+            }
+            seat.claim += 1;
+            seat.connections = 0;
+        }
+        seat.claim_pending = false;
+        seat.takeover_approved = false;
+        seat.connections += 1;
+        seat.last_seen = now;
+        let claim = seat.claim;
+        self.notify();
+        Ok(claim)
+    }
+
+    /// Allows the connection waiting to claim the given player's seat to take it over, which only the board may do.
+    pub fn approve_takeover(&mut self, player: &str) -> Result<(), GameError> {
+        match self.seats.get_mut(player) {
+            Some(seat) if seat.claim_pending => {
+                seat.takeover_approved = true;
+                Ok(())
+            }
+            _ => Err(GameError::InvalidAction),
+        }
+    }
+
+    /// Checks that a connection still holds the given claim on the player's seat.
+    pub fn check_claim(&self, player: &str, claim: u64) -> Result<(), GameError> {
+        match self.seats.get(player) {
+            Some(seat) if seat.claim == claim => Ok(()),
+            _ => Err(GameError::SeatTaken),
+        }
+    }
+
+    /// Detaches a websocket connection from the given player's seat, unless it has already lost its claim on it.
+    pub fn detach(&mut self, player: &str, claim: u64) {
+        let Some(seat) = self.seats.get_mut(player) else {
+            return;
+        };
+        if seat.claim != claim {
+            return;
+        }
+        seat.connections = seat.connections.saturating_sub(1);
+        seat.last_seen = self.clock.now();
+        if seat.connections == 0 {
+            self.notify();
+        }
     }
 
-    /// Ends the game.
+    /// Ends the game. Ending a game which has already ended does nothing.
+    #[tracing::instrument(level = "debug", skip_all, fields(game_id = %self.id))]
     pub fn end_game(&mut self) -> Result<(), GameError> {
+        if let Game::GameOver = self.game {
+            return Ok(());
+        }
         // Check the game is over.
         if !self.game.can_end() {
             return Err(GameError::InvalidAction);
         }
 
         self.try_archive();
+        self.rematch = self.game.rematch();
         self.game = Game::GameOver;
+        self.next_generation();
+        self.notify();
+        self.snapshot();
+
+        Ok(())
+    }
+
+    /// Returns a finished game to a fresh lobby with the same players and options,
+    /// so that the group can play again without having to share a new game code.
+    #[tracing::instrument(level = "debug", skip_all, fields(game_id = %self.id))]
+    pub fn restart_lobby(&mut self) -> Result<(), GameError> {
+        let lobby = match &self.game {
+            Game::GameOver => self.rematch.take(),
+            game => game.rematch(),
+        };
+        let Some(lobby) = lobby else {
+            return Err(GameError::InvalidAction);
+        };
+
+        self.try_archive();
+        self.game = lobby;
+        self.next_generation();
         self.notify();
-        self.persist_game().ok();
+        self.snapshot();
 
         Ok(())
     }
 
     /// Notifies all connected clients of the new game state.
+    /// If clients were sent an update very recently, the update is deferred until [Session::flush_updates] is called.
     fn notify(&mut self) {
-        let state = match &self.game {
-            Game::Lobby { players, options, .. } => Self::lobby_update(players, options),
-            Game::Playing { game, .. } => Self::game_update(game),
-            Game::GameOver => Self::game_over_update(),
+        self.update_timer();
+        self.update_hotseat();
+        self.last_ts = self.clock.instant();
+        self.last_change = self.clock.now();
+        self.game.set_last_change(self.last_change);
+        if self
+            .last_notify
+            .is_some_and(|last| self.clock.since(last) < NOTIFY_WINDOW)
+        {
+            self.notify_pending = true;
+            return;
+        }
+        self.publish();
+    }
+
+    /// Sends any deferred game update, once enough time has passed since the last one.
+    pub fn flush_updates(&mut self) {
+        if self.notify_pending
+            && self
+                .last_notify
+                .is_none_or(|last| self.clock.since(last) >= NOTIFY_WINDOW)
+        {
+            self.publish();
+        }
+    }
+
+    /// Sends the current game state to all connected clients.
+    fn publish(&mut self) {
+        self.version += 1;
+        let waiting = self
+            .player_updates
+            .iter()
+            .map(|update| !update.legal_actions.is_empty())
+            .collect::<Vec<_>>();
+        let state = match &mut self.game {
+            Game::Lobby { players, options, start_at, .. } => Self::lobby_update(players, options, *start_at),
+            Game::Playing { game, started_ts, .. } => GameUpdate {
+                started_at: Some(*started_ts),
+                ..Self::game_update(game, &mut self.player_updates)
+            },
+            Game::GameOver => Self::game_over_update(GameLifecycle::Ended),
+            Game::Cancelled => Self::game_over_update(GameLifecycle::Cancelled),
+        };
+        let mut update = GameUpdate {
+            game_id: self.id.clone(),
+            timer: self.timer,
+            version: self.version,
+            generation: self.generation,
+            hotseat: self.hotseat_prompt(),
+            ..state
+        };
+        for player in &mut update.players {
+            let seat = self.seats.get(&player.name);
+            player.connected = seat.is_some_and(|seat| seat.connections > 0);
+            player.last_seen = seat.map(|seat| seat.last_seen);
+            player.claim_pending = seat.is_some_and(|seat| seat.claim_pending);
+        }
+        for (seat, sender) in self.seat_updates.iter().enumerate() {
+            let latest = self.player_updates.get(seat).filter(|_| self.game.game().is_some());
+            sender.send_if_modified(|current| match (current.as_ref(), latest) {
+                (Some(current), Some(latest)) if Arc::ptr_eq(current, latest) => false,
+                (None, None) => false,
+                _ => {
+                    *current = latest.cloned();
+                    true
+                }
+            });
+        }
+        self.updates.send_replace(update);
+        self.last_notify = Some(self.clock.instant());
+        self.notify_pending = false;
+        self.send_notifications(&waiting);
+    }
+
+    /// Notifies the players who have just been given something to do, if they asked to be and are not connected.
+    /// `waiting` holds whether each seat already had something to do when updates were last published.
+    fn send_notifications(&mut self, waiting: &[bool]) {
+        if self.push_targets.is_empty() || self.game.game().is_none() {
+            return;
+        }
+        let due = (self.player_updates.iter().enumerate())
+            .filter(|(seat, update)| waiting.get(*seat) != Some(&true) && !update.legal_actions.is_empty())
+            .map(|(_, update)| update.clone())
+            .collect::<Vec<_>>();
+        for update in due {
+            self.notify_player(&update);
+        }
+    }
+
+    /// Reminds the players of an asynchronous game who are still keeping it waiting, once `interval` has passed since
+    /// they were last notified or seen.
+    pub fn nudge_players(&mut self, interval: Duration) {
+        let asynchronous = self.game.options().asynchronous.is_some();
+        if !asynchronous || self.push_targets.is_empty() || self.game.game().is_none() {
+            return;
+        }
+        let now = self.clock.now();
+        let overdue = |name: &str| {
+            let notified = self.notified.get(name).copied();
+            let seen = self.seats.get(name).map(|seat| seat.last_seen);
+            notified
+                .max(seen)
+                .is_none_or(|last| (now - last).to_std().is_ok_and(|idle| idle >= interval))
+        };
+        let due = (self.player_updates.iter())
+            .filter(|update| !update.legal_actions.is_empty() && overdue(&update.name))
+            .cloned()
+            .collect::<Vec<_>>();
+        for update in due {
+            self.notify_player(&update);
+        }
+    }
+
+    /// Tells a player that the game is waiting on them, if they asked to be told and are not connected.
+    fn notify_player(&mut self, update: &PlayerUpdate) {
+        let Some(target) = self.push_targets.get(&update.name) else {
+            return;
+        };
+        if self.seats.get(&update.name).is_some_and(|seat| seat.connections > 0) {
+            return;
+        }
+        let payload = NotificationPayload {
+            game_id: self.id.clone(),
+            player: update.name.clone(),
+            message: update.message.clone(),
+            sent: self.clock.now(),
+        };
+        self.notifier.notify(target, payload);
+        self.notified.insert(update.name.clone(), self.clock.now());
+    }
+
+    /// Passes the shared device to the next player who must act, in a hotseat game.
+    /// The player holding the device keeps it for as long as they have actions to perform.
+    fn update_hotseat(&mut self) {
+        let Game::Playing { game, .. } = &self.game else {
+            self.hotseat = None;
+            return;
+        };
+        if !game.options().hotseat {
+            return;
+        }
+        let num_players = game.num_players();
+        let start = self.hotseat.map_or(0, |turn| turn.player);
+        let next = (0..num_players)
+            .map(|i| (start + i) % num_players)
+            .find(|player| !game.legal_actions(*player).is_empty());
+        self.hotseat = next.map(|player| HotseatTurn {
+            player,
+            revealed: self.hotseat.is_some_and(|turn| turn.player == player && turn.revealed),
+        });
+    }
+
+    /// Gets what the shared device should show, in a hotseat game.
+    fn hotseat_prompt(&self) -> Option<HotseatPrompt> {
+        let (Some(turn), Game::Playing { game, .. }) = (self.hotseat, &self.game) else {
+            return None;
+        };
+        let update = game.get_player_update(turn.player);
+        Some(match turn.revealed {
+            true => HotseatPrompt::Player { update: Box::new(update) },
+            false => HotseatPrompt::PassDevice { player: update.name },
+        })
+    }
+
+    /// Adds a player to a hotseat game, on behalf of the board.
+    pub fn add_hotseat_player(&mut self, name: &str) -> Result<(), GameError> {
+        if !self.game.options().hotseat {
+            return Err(GameError::InvalidAction);
+        }
+        self.add_player(name)?;
+        self.notify();
+        Ok(())
+    }
+
+    /// Called once the player whose turn it is has the shared device, to reveal their prompt.
+    pub fn hotseat_ready(&mut self) -> Result<(), GameError> {
+        let Some(turn) = &mut self.hotseat else {
+            return Err(GameError::InvalidAction);
+        };
+        turn.revealed = true;
+        self.notify();
+        Ok(())
+    }
+
+    /// Performs an action on behalf of the player holding the shared device.
+    /// The device must then be passed on, unless the player has further actions to perform.
+    pub fn hotseat_action(&mut self, action: PlayerAction) -> Result<(), GameError> {
+        let (Some(turn), Some(game)) = (self.hotseat, self.game.game()) else {
+            return Err(GameError::InvalidAction);
+        };
+        if !turn.revealed {
+            return Err(GameError::InvalidAction);
+        }
+        let name = game.get_player_update(turn.player).name;
+        self.perform_action(GameAction::Player { name, action })
+    }
+
+    /// Starts the countdown for the current phase of the game, if it is timed and has just begun.
+    /// The countdown is persisted with the game straight away, so that a restart does not give the phase more time.
+    fn update_timer(&mut self) {
+        let Game::Playing { game, timer, .. } = &mut self.game else {
+            self.timer = None;
+            return;
         };
-        self.updates.send_replace(state);
-        self.last_ts = Instant::now();
+        let phase = game.timed_phase();
+        if self.timer.map(|t| t.phase) == phase {
+            return;
+        }
+        self.timer = phase.and_then(|phase| {
+            let secs = game.options().time_limit(phase)?;
+            let deadline = self.clock.now() + chrono::Duration::seconds(secs.into());
+            Some(PhaseTimer { phase, deadline })
+        });
+        if *timer != self.timer {
+            *timer = self.timer;
+            self.snapshot();
+        }
     }
 
     /// Creates a lobby game update.
-    fn lobby_update(players: &[String], opts: &GameOptions) -> GameUpdate {
+    fn lobby_update(players: &[String], opts: &GameOptions, start_at: Option<DateTime<Utc>>) -> GameUpdate {
         let make_player = |name: &String| PublicPlayer {
             name: name.clone(),
             alive: true,
             not_hitler: false,
+            confirmed_party: None,
+            investigated: false,
+            connected: false,
+            last_seen: None,
+            claim_pending: false,
+        };
+        let problems = opts.problems(players.len());
+        let can_start = problems.is_empty();
+        let lifecycle = match start_at {
+            Some(start_at) => GameLifecycle::Scheduled { can_start, start_at },
+            None => GameLifecycle::Lobby { can_start },
         };
-        let can_start = players.len() >= opts.min_players().unwrap_or(999);
         GameUpdate {
-            lifecycle: GameLifecycle::Lobby { can_start },
+            game_id: String::new(),
+            lifecycle,
+            options: Some(*opts),
+            started_at: None,
             players: players.iter().map(make_player).collect(),
             board_update: None,
-            player_updates: vec![],
+            timer: None,
+            state_hash: None,
+            version: 0,
+            generation: 0,
+            hotseat: None,
+            house_rules: opts.house_rules,
+            problems,
+            overlay: OverlayState::Lobby,
         }
     }
 
     /// Create a game update.
-    fn game_update(game: &GameInner) -> GameUpdate {
+    /// Only the updates of players whose view has changed are regenerated; the rest are reused from `cache`.
+    fn game_update(game: &mut GameInner, cache: &mut Vec<Arc<PlayerUpdate>>) -> GameUpdate {
+        match game.take_changed_seats() {
+            Some(changed) if cache.len() == game.num_players() => {
+                for (player, update) in cache.iter_mut().enumerate().filter(|(player, _)| changed[*player]) {
+                    *update = Arc::new(game.get_player_update(player));
+                }
+            }
+            _ => *cache = game.get_player_updates().into_iter().map(Arc::new).collect(),
+        }
+        let board_update = game.get_board_update();
         GameUpdate {
+            game_id: String::new(),
             lifecycle: GameLifecycle::Playing,
+            options: Some(game.options()),
+            started_at: None,
             players: game.get_public_players(),
-            board_update: Some(game.get_board_update()),
-            player_updates: (0..game.num_players()).map(|i| game.get_player_update(i)).collect(),
+            state_hash: Some(board_update.state_hash),
+            board_update: Some(board_update),
+            timer: None,
+            version: 0,
+            generation: 0,
+            hotseat: None,
+            house_rules: game.options().house_rules,
+            problems: vec![],
+            overlay: game.get_overlay_state(),
         }
     }
 
-    /// Creates a game over update.
-    fn game_over_update() -> GameUpdate {
+    /// Creates an update for a session whose game has ended or was cancelled.
+    fn game_over_update(lifecycle: GameLifecycle) -> GameUpdate {
         GameUpdate {
-            lifecycle: GameLifecycle::Ended,
+            game_id: String::new(),
+            lifecycle,
+            options: None,
+            started_at: None,
             players: vec![],
             board_update: None,
-            player_updates: vec![],
+            timer: None,
+            state_hash: None,
+            version: 0,
+            generation: 0,
+            hotseat: None,
+            house_rules: HouseRules::default(),
+            problems: vec![],
+            overlay: match lifecycle {
+                GameLifecycle::Cancelled => OverlayState::Cancelled,
+                _ => OverlayState::Ended,
+            },
         }
     }
 
-    /// Persists the game state to disk, so it can be recovered upon server restart.
-    fn persist_game(&mut self) -> Result<(), Box<dyn Error>> {
-        self.db
-            .game
-            .insert(self.id.as_bytes(), serde_json::to_string(&self.game)?.as_bytes())?;
+    /// Takes a snapshot of the game state, to be persisted once the session is unlocked.
+    fn snapshot(&mut self) {
+        self.pending_snapshot = Some(PendingSnapshot {
+            game: self.game.clone(),
+            ticket: self.writer.ticket(),
+            journal_seq: self.journal_seq,
+            fence: self.fence.clone(),
+        });
+        self.journal_len = 0;
+    }
+
+    /// Persists any pending snapshot straight away, without waiting for the session to be unlocked.
+    fn write_snapshot(&mut self) -> WriteResult {
+        let Some(pending) = self.pending_snapshot.take() else {
+            return Ok(());
+        };
+        let result = match pending.serialize(&self.id, self.write_reports.clone()) {
+            Ok(snapshot) => self.writer.write_now(snapshot),
+            Err(err) => Err(err.to_string()),
+        };
+        self.check_writes();
+        result
+    }
+
+    /// Handles the outcome of the snapshots the writer has dealt with.
+    /// If one could not be written, the next action takes a fresh one.
+    fn check_writes(&mut self) {
+        while let Ok(result) = self.write_results.try_recv() {
+            if let Err(err) = result {
+                log::error!("Cannot persist game: {}: {}", &self.id, err);
+                let message = format!("cannot persist game: {}", err);
+                self.emit(AdminEventKind::Error { message });
+                self.journal_len = SNAPSHOT_INTERVAL;
+            }
+        }
+    }
+
+    /// Durably appends an action to the journal, along with a hash of the game state it results in.
+    fn journal(&mut self, action: GameAction, game: &GameInner) -> Result<(), Box<dyn Error>> {
+        let entry = JournalEntry {
+            action,
+            state_hash: game.state_hash(),
+            at: Some(self.clock.now()),
+        };
+        let entry = serde_json::to_vec(&entry)?;
+        if let Err(err) = self
+            .store
+            .append_journal(&self.id, self.journal_seq, &entry, self.fence.as_ref())
+        {
+            if err.is::<Fenced>() {
+                log::warn!("Refusing action on session taken over by another node: {}", &self.id);
+            }
+            return Err(err);
+        }
+        self.journal_seq += 1;
+        self.journal_len += 1;
         Ok(())
     }
 
@@ -353,55 +2207,233 @@ impl Session {
 
     /// Archives the game if it is over and hasn't been archived yet.
     fn archive(&mut self) -> Result<(), Box<dyn Error>> {
-        let Game::Playing { ref game, started_ts, archived } = self.game else {
+        let Game::Playing { archived: false, .. } = self.game else {
             return Ok(());
         };
-        if archived {
-            return Ok(());
-        }
-        let Some(outcome) = game.outcome() else {
+        let Some(stats) = self.stats() else {
             return Ok(());
         };
+        self.store.archive(serde_json::to_string(&stats)?.as_bytes())?;
+
+        if let Game::Playing { archived, .. } = &mut self.game {
+            *archived = true;
+        }
+        self.webhooks.fire(WebhookEvent::GameArchived, &self.id, Some(stats));
+        Ok(())
+    }
 
-        let stats = serde_json::to_string(&GameStats {
+    /// Gets the statistics of the game, if it is over.
+    fn stats(&self) -> Option<GameStats> {
+        let Game::Playing { ref game, started_ts, .. } = self.game else {
+            return None;
+        };
+        let outcome = game.outcome()?;
+        Some(GameStats {
             id: self.id.clone(),
             started: started_ts,
-            finished: chrono::offset::Utc::now(),
+            finished: self.clock.now(),
             players: game.player_names().map(str::to_string).collect(),
             outcome,
-        })?;
-        let value = Some(stats.as_bytes());
-
-        loop {
-            let key = self.next_id()?;
-            match self.db.archive.compare_and_swap::<_, &[_], _>(key, None, value)? {
-                Ok(_) => break,
-                Err(CompareAndSwapError { .. }) => continue,
-            }
-        }
+            secondary_conditions: game.secondary_conditions().to_vec(),
+            substitutions: game.substitutions().cloned().collect(),
+            results: (0..game.num_players())
+                .filter_map(|p| game.outcome_for_player(p))
+                .collect(),
+            roles: game.roles().collect(),
+            executed: game.executed_players().map(str::to_string).collect(),
+            policies: PolicyCounts {
+                liberal: game.policies_enacted(Party::Liberal),
+                fascist: game.policies_enacted(Party::Fascist),
+                communist: game.policies_enacted(Party::Communist),
+            },
+            chat: match game.options().archive_chat {
+                true => self.chat_log.iter().cloned().collect(),
+                false => vec![],
+            },
+        })
+    }
 
-        if let Game::Playing { archived, .. } = &mut self.game {
-            *archived = true;
-        }
-        Ok(())
+    /// Publishes an event to any operators watching the server.
+    fn emit(&self, kind: AdminEventKind) {
+        self.events.send(AdminEvent::new(&self.id, kind)).ok();
     }
 
-    fn next_id(&self) -> sled::Result<[u8; 8]> {
-        let latest = self.db.archive.last()?.map_or(0, |(k, _)| {
-            let mut bytes = [0; 8];
-            bytes.copy_from_slice(&k[..k.len().min(8)]);
-            u64::from_be_bytes(bytes)
-        });
-        Ok((latest + 1).to_be_bytes())
+    /// Tells the webhooks about a change in the game's lifecycle.
+    fn fire(&self, event: WebhookEvent) {
+        self.webhooks.fire(event, &self.id, self.stats());
+    }
+}
+
+impl SessionHandle {
+    fn new(session: Session) -> Self {
+        Self {
+            session: Arc::new(Mutex::new(session)),
+            actor: Arc::default(),
+        }
+    }
+
+    /// Locks the session for exclusive access, blocking until it is available.
+    #[track_caller]
+    pub fn lock(&self) -> LockResult<SessionGuard<'_>> {
+        lock_session(&self.session, Location::caller())
+    }
+
+    /// Has the session's actor run the given command, returning a future which resolves to its result.
+    /// The command is queued straight away, so commands run in the order they are sent, whether or not their replies
+    /// are awaited.
+    #[track_caller]
+    pub fn call<R: Send + 'static>(
+        &self,
+        command: impl FnOnce(&mut Session) -> R + Send + 'static,
+    ) -> impl Future<Output = R> {
+        let (reply, result) = oneshot::channel();
+        self.send(Location::caller(), move |session| {
+            reply.send(command(session)).ok();
+        });
+        async move { result.await.expect("the session's actor ran the command") }
+    }
+
+    /// Has the session's actor run the given command, without waiting for it to finish.
+    /// Outside of a runtime, where there can be no actor, the command is run straight away instead.
+    #[track_caller]
+    pub fn cast(&self, command: impl FnOnce(&mut Session) + Send + 'static) {
+        if self.actor.get().is_none() && tokio::runtime::Handle::try_current().is_err() {
+            command(&mut self.lock().unwrap());
+            return;
+        }
+        self.send(Location::caller(), command);
+    }
+
+    fn send(&self, sent_at: &'static Location<'static>, command: impl FnOnce(&mut Session) + Send + 'static) {
+        let actor = self.actor.get_or_init(|| {
+            let (commands, queue) = tokio::sync::mpsc::unbounded_channel();
+            tokio::spawn(run_actor(self.session.clone(), queue));
+            commands
+        });
+        let command = Command {
+            run: Box::new(command),
+            sent_at,
+            span: tracing::Span::current(),
+        };
+        actor.send(command).ok();
+    }
+}
+
+/// Runs the commands sent to a session's actor until every handle to the session has been dropped.
+async fn run_actor(session: Arc<Mutex<Session>>, mut queue: tokio::sync::mpsc::UnboundedReceiver<Command>) {
+    while let Some(command) = queue.recv().await {
+        let session = session.clone();
+        let run = move || {
+            let _span = command.span.enter();
+            let mut session = lock_session(&session, command.sent_at).unwrap();
+            (command.run)(&mut session);
+        };
+        // A command which panics drops its reply, so the panic reaches whoever is waiting on it instead
+        tokio::task::spawn_blocking(run).await.ok();
+    }
+}
+
+/// Locks a session for exclusive access, on behalf of the code at the given location.
+fn lock_session<'a>(
+    session: &'a Mutex<Session>,
+    locked_at: &'static Location<'static>,
+) -> LockResult<SessionGuard<'a>> {
+    let guard = |session| SessionGuard {
+        session: Some(session),
+        locked: Instant::now(),
+        locked_at,
+    };
+    match session.lock() {
+        Ok(session) => Ok(guard(session)),
+        Err(err) => Err(PoisonError::new(guard(err.into_inner()))),
+    }
+}
+
+impl Deref for SessionGuard<'_> {
+    type Target = Session;
+
+    fn deref(&self) -> &Session {
+        self.session.as_ref().expect("session is locked")
+    }
+}
+
+impl DerefMut for SessionGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Session {
+        self.session.as_mut().expect("session is locked")
+    }
+}
+
+impl Drop for SessionGuard<'_> {
+    fn drop(&mut self) {
+        let Some(mut session) = self.session.take() else {
+            return;
+        };
+        let held = self.locked.elapsed();
+        if held > session.slow_lock_threshold {
+            tracing::warn!(
+                game_id = session.id(),
+                locked_at = %self.locked_at,
+                held_ms = held.as_millis() as u64,
+                "session lock was held for too long"
+            );
+        }
+        let Some(pending) = session.pending_snapshot.take() else {
+            return;
+        };
+        let id = session.id.clone();
+        let writer = session.writer.clone();
+        let report = session.write_reports.clone();
+        drop(session);
+
+        match pending.serialize(&id, report.clone()) {
+            Ok(snapshot) => writer.write(snapshot),
+            Err(err) => {
+                report.send(Err(err.to_string())).ok();
+            }
+        }
+    }
+}
+
+/// Whether a game failed to load because a newer server persisted it, rather than because it is corrupt.
+fn persisted_by_newer_server(err: &(dyn Error + 'static)) -> bool {
+    matches!(err.downcast_ref(), Some(schema::SchemaError::Newer(_)))
+}
+
+impl PendingSnapshot {
+    /// Serializes the snapshot, ready to be written to the store.
+    #[tracing::instrument(level = "debug", skip_all, fields(game_id = %game_id))]
+    fn serialize(self, game_id: &str, report: mpsc::Sender<WriteResult>) -> serde_json::Result<Snapshot> {
+        Ok(Snapshot {
+            game_id: game_id.to_string(),
+            game: schema::serialize(&self.game)?,
+            ticket: self.ticket,
+            journal_seq: self.journal_seq,
+            fence: self.fence,
+            report,
+        })
     }
 }
 
 impl Game {
+    /// Decodes a game from the record it was persisted as.
+    /// Also returns whether the record is in an older format, and should be rewritten.
+    fn decode(record: &[u8]) -> Result<(Self, bool), Box<dyn Error>> {
+        let (game, migrated) = Self::from_versioned(&record::decode(record)?)?;
+        Ok((game, migrated || record::is_legacy(record)))
+    }
+
+    /// Deserializes a game tagged with the version of the schema it was serialized with, migrating it if need be.
+    /// Also returns whether it was migrated.
+    fn from_versioned(json: &[u8]) -> Result<(Self, bool), Box<dyn Error>> {
+        let (game, version) = schema::upgrade(serde_json::from_slice(json)?)?;
+        Ok((serde_json::from_value(game)?, version < schema::SCHEMA_VERSION))
+    }
+
     fn num_players(&self) -> usize {
         match self {
             Game::Lobby { players, .. } => players.len(),
             Game::Playing { game, .. } => game.num_players(),
-            Game::GameOver => 0,
+            Game::GameOver | Game::Cancelled => 0,
         }
     }
 
@@ -409,7 +2441,7 @@ impl Game {
         match self {
             Game::Lobby { options, .. } => *options,
             Game::Playing { game, .. } => game.options(),
-            Game::GameOver => GameOptions::default(),
+            Game::GameOver | Game::Cancelled => GameOptions::default(),
         }
     }
 
@@ -417,7 +2449,23 @@ impl Game {
         match self {
             Game::Lobby { players, .. } => players.clone(),
             Game::Playing { game, .. } => game.player_names().map(|s| s.to_string()).collect(),
-            Game::GameOver => vec![],
+            Game::GameOver | Game::Cancelled => vec![],
+        }
+    }
+
+    fn game(&self) -> Option<&GameInner> {
+        match self {
+            Game::Lobby { .. } => None,
+            Game::Playing { game, .. } => Some(game),
+            Game::GameOver | Game::Cancelled => None,
+        }
+    }
+
+    fn board_key(&self) -> Option<&str> {
+        match self {
+            Game::Lobby { board_key, .. } => board_key.as_deref(),
+            Game::Playing { board_key, .. } => board_key.as_deref(),
+            Game::GameOver | Game::Cancelled => None,
         }
     }
 
@@ -425,7 +2473,7 @@ impl Game {
         match self {
             Game::Lobby { .. } => None,
             Game::Playing { game, .. } => Some(game),
-            Game::GameOver => None,
+            Game::GameOver | Game::Cancelled => None,
         }
     }
 
@@ -433,7 +2481,52 @@ impl Game {
         match self {
             Game::Lobby { .. } => true,
             Game::Playing { game, .. } => game.game_over(),
-            Game::GameOver => false,
+            Game::GameOver | Game::Cancelled => false,
+        }
+    }
+
+    /// Creates the lobby for another game with the same players and options, if this game is over.
+    fn rematch(&self) -> Option<Game> {
+        let Game::Playing { game, board_key, .. } = self else {
+            return None;
+        };
+        if !game.game_over() {
+            return None;
+        }
+        let options = game.options();
+        Some(Game::Lobby {
+            options,
+            players: self.player_names(),
+            min_players: options.min_players()?,
+            max_players: options.max_players()?,
+            board_key: board_key.clone(),
+            start_at: None,
+            extensions: 0,
+            creator: None,
+            last_change: None,
+        })
+    }
+
+    /// When the game last changed, if that is known.
+    fn last_change(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Game::Lobby { last_change, .. } | Game::Playing { last_change, .. } => *last_change,
+            Game::GameOver | Game::Cancelled => None,
+        }
+    }
+
+    /// Records when the game last changed, so that it is persisted with the game.
+    /// Games which are over do not track it, as they are soon purged regardless.
+    fn set_last_change(&mut self, at: DateTime<Utc>) {
+        if let Game::Lobby { last_change, .. } | Game::Playing { last_change, .. } = self {
+            *last_change = Some(at);
+        }
+    }
+
+    /// Records who created the lobby; other games do not track their creator.
+    fn set_creator(&mut self, creator: Option<&str>) {
+        if let Game::Lobby { creator: lobby_creator, .. } = self {
+            *lobby_creator = creator.map(str::to_string);
         }
     }
 
@@ -441,7 +2534,36 @@ impl Game {
         match self {
             Game::Lobby { .. } => false,
             Game::Playing { game, .. } => game.game_over(),
-            Game::GameOver => false,
+            Game::GameOver | Game::Cancelled => false,
+        }
+    }
+}
+
+impl ArchiveQuery {
+    /// Returns whether the archived game satisfies the filters of this query.
+    fn matches(&self, stats: &GameStats) -> bool {
+        if let Some(player) = &self.player {
+            if !stats.players.iter().any(|p| p.eq_ignore_ascii_case(player)) {
+                return false;
+            }
+        }
+        if self.since.is_some_and(|since| stats.finished < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| stats.finished >= until) {
+            return false;
+        }
+        true
+    }
+}
+
+impl ChatMessage {
+    /// Returns whether the message can be read by the given player, or the board if `player` is `None`.
+    pub fn visible_to(&self, player: Option<&str>) -> bool {
+        match (&self.recipients, player) {
+            (None, _) => true,
+            (Some(recipients), Some(player)) => recipients.iter().any(|r| r == player),
+            (Some(_), None) => false,
         }
     }
 }
@@ -451,3 +2573,1054 @@ impl Default for GameLifecycle {
         Self::Lobby { can_start: false }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::store::SqliteStore;
+
+    #[test]
+    fn second_claim_needs_approval() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let mut session = Session::new("ABCD".into(), store, GameOptions::default()).unwrap();
+        let stale_after = Duration::from_secs(45);
+
+        let first = session.attach("ALEX", stale_after).unwrap();
+        assert!(matches!(session.attach("ALEX", stale_after), Err(GameError::SeatTaken)));
+        session.approve_takeover("ALEX").unwrap();
+        let second = session.attach("ALEX", stale_after).unwrap();
+
+        assert!(session.check_claim("ALEX", first).is_err());
+        session.check_claim("ALEX", second).unwrap();
+        session.detach("ALEX", first);
+        assert_eq!(session.seats["ALEX"].connections, 1);
+    }
+
+    #[test]
+    fn public_lobbies_are_listed() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let manager = SessionManager::new(store, Config::from_env()).unwrap();
+        let options = GameOptions { public: true, ..Default::default() };
+        let public = manager.create_game(options, None).unwrap();
+        manager.create_game(GameOptions::default(), None).unwrap();
+        public.lock().unwrap().add_player("ALEX").unwrap();
+
+        manager.flush_updates();
+        let lobbies = manager.public_lobbies();
+        assert_eq!(lobbies.len(), 1);
+        assert_eq!(lobbies[0].id, public.lock().unwrap().id());
+        assert_eq!(lobbies[0].num_players, 1);
+    }
+
+    #[test]
+    fn game_creation_is_limited() {
+        let mut config = Config::from_env();
+        config.max_sessions = Some(3);
+        config.max_lobbies_per_creator = Some(2);
+        let manager = SessionManager::ephemeral(config).unwrap();
+
+        let first = manager.create_game(GameOptions::default(), Some("1.2.3.4")).unwrap();
+        manager.create_game(GameOptions::default(), Some("1.2.3.4")).unwrap();
+        assert!(matches!(
+            manager.create_game(GameOptions::default(), Some("1.2.3.4")),
+            Err(GameError::TooManyLobbies { max: 2 })
+        ));
+
+        // Lobbies which have started no longer count against their creator
+        {
+            let mut first = first.lock().unwrap();
+            for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"] {
+                first.add_player(name).unwrap();
+            }
+            first.start_game().unwrap();
+        }
+        manager.create_game(GameOptions::default(), Some("1.2.3.4")).unwrap();
+        assert!(matches!(
+            manager.create_game(GameOptions::default(), Some("5.6.7.8")),
+            Err(GameError::ServerFull)
+        ));
+
+        let stats = manager.creation_stats();
+        assert_eq!(stats.created, 3);
+        assert_eq!(stats.rejected_server_full, 1);
+        assert_eq!(stats.rejected_too_many_lobbies, 1);
+    }
+
+    #[test]
+    fn lobby_limits_survive_restarts_and_apply_to_imports() {
+        let mut config = Config::from_env();
+        config.max_lobbies_per_creator = Some(1);
+        config.save_secret = Some("secret".into());
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let mut session = Session::new("ABCD".into(), store.clone(), GameOptions::default()).unwrap();
+        session.game.set_creator(Some("1.2.3.4"));
+        let code = session.save_code(&config).unwrap();
+        let record = schema::serialize(&session.game).unwrap();
+        store.put_game("ABCD", &record, None, 0).unwrap();
+
+        let manager = SessionManager::new(store, config).unwrap();
+        assert!(matches!(
+            manager.create_game(GameOptions::default(), Some("1.2.3.4")),
+            Err(GameError::TooManyLobbies { max: 1 })
+        ));
+        assert!(matches!(
+            manager.import_game(&code, Some("1.2.3.4")),
+            Err(GameError::TooManyLobbies { max: 1 })
+        ));
+        manager.import_game(&code, Some("5.6.7.8")).unwrap();
+        assert!(matches!(
+            manager.create_game(GameOptions::default(), Some("5.6.7.8")),
+            Err(GameError::TooManyLobbies { max: 1 })
+        ));
+    }
+
+    #[test]
+    fn lobbies_keep_their_players_across_restarts() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let manager = SessionManager::new(store.clone(), Config::default()).unwrap();
+        let options = GameOptions {
+            asynchronous: Some(crate::game::AsyncOptions { turn_deadline_hours: None }),
+            ..Default::default()
+        };
+        let handle = manager.create_game(options, None).unwrap();
+        let id = {
+            let mut session = handle.lock().unwrap();
+            session.write_snapshot().unwrap();
+            for name in ["ALEX", "BOB", "CHARLIE"] {
+                session.add_player(name).unwrap();
+            }
+            session.rename_player("BOB", "BOBBY").unwrap();
+            session.remove_player("CHARLIE").unwrap();
+            session.write_snapshot().unwrap();
+            session.id().to_string()
+        };
+        drop(handle);
+        drop(manager);
+
+        let manager = SessionManager::new(store, Config::default()).unwrap();
+        let handle = manager.find_game(&id).unwrap();
+        assert_eq!(handle.lock().unwrap().game.player_names(), ["ALEX", "BOBBY"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn commands_run_in_the_order_they_are_sent() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let manager = SessionManager::new(store, Config::default()).unwrap();
+        let handle = manager.create_game(GameOptions::default(), None).unwrap();
+
+        // Only the last reply is awaited, but every command before it has already run
+        for name in ["ALEX", "BOB", "CHARLIE"] {
+            let name = name.to_string();
+            drop(handle.call(move |session| session.add_player(&name)));
+        }
+        handle.cast(|session| session.rename_player("BOB", "BOBBY").unwrap());
+        let names = handle.call(|session| session.game.player_names()).await;
+        assert_eq!(names, ["ALEX", "BOBBY", "CHARLIE"]);
+    }
+
+    /// Archives a game between the given players which finished at the given time, returning its archive ID.
+    fn archive_game(store: &SqliteStore, players: &[&str], finished: DateTime<Utc>) -> u64 {
+        let stats = GameStats {
+            id: "ABCD".into(),
+            players: players.iter().map(|name| name.to_string()).collect(),
+            started: finished - chrono::Duration::minutes(30),
+            finished,
+            outcome: WinCondition::LiberalPolicyTrack,
+            secondary_conditions: vec![],
+            substitutions: vec![],
+            results: vec![],
+            roles: vec![],
+            executed: vec![],
+            policies: PolicyCounts::default(),
+            chat: vec![],
+        };
+        store.archive(&serde_json::to_vec(&stats).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn past_games_are_paged_and_filtered() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let now = Utc::now();
+        let hours = |n: i64| now - chrono::Duration::hours(n);
+        let ids = [
+            archive_game(&store, &["ALEX", "BOB"], hours(5)),
+            archive_game(&store, &["BOB"], hours(4)),
+            archive_game(&store, &["ALEX"], hours(3)),
+            archive_game(&store, &["CHARLIE"], hours(2)),
+            archive_game(&store, &["ALEX"], hours(1)),
+        ];
+        let manager = SessionManager::new(store, Config::default()).unwrap();
+        let page = |query: ArchiveQuery| {
+            let page = manager.past_games(&query);
+            (page.games.into_iter().map(|(id, _)| id).collect::<Vec<_>>(), page.next)
+        };
+
+        let query = ArchiveQuery { limit: Some(2), ..Default::default() };
+        assert_eq!(page(query.clone()), (ids[0..2].to_vec(), Some(ids[1])));
+        let query = ArchiveQuery { after: Some(ids[1]), ..query };
+        assert_eq!(page(query.clone()), (ids[2..4].to_vec(), Some(ids[3])));
+        // The last page has no cursor, even when it is full
+        let query = ArchiveQuery { after: Some(ids[2]), ..query };
+        assert_eq!(page(query), (ids[3..5].to_vec(), None));
+
+        let query = ArchiveQuery {
+            player: Some("alex".into()),
+            limit: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(page(query.clone()), (vec![ids[0], ids[2]], Some(ids[2])));
+        let query = ArchiveQuery { after: Some(ids[2]), ..query };
+        assert_eq!(page(query), (vec![ids[4]], None));
+
+        let query = ArchiveQuery {
+            since: Some(hours(4)),
+            until: Some(hours(2)),
+            ..Default::default()
+        };
+        assert_eq!(page(query), (ids[1..3].to_vec(), None));
+    }
+
+    #[test]
+    fn archive_retention_keeps_recent_and_unreadable_games() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let old = Utc::now() - chrono::Duration::days(2);
+        archive_game(&store, &["ALEX"], old);
+        let unreadable = store.archive(b"not a game").unwrap();
+        archive_game(&store, &["BOB"], old);
+        let recent = archive_game(&store, &["CHARLIE"], Utc::now());
+        let config = Config {
+            archive_retention: Some(Duration::from_secs(86400)),
+            ..Default::default()
+        };
+        let manager = SessionManager::new(store.clone(), config).unwrap();
+
+        manager.prune_archive().unwrap();
+        let kept: Vec<_> = store.archived(0, 10).unwrap().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(kept, [unreadable, recent]);
+
+        // The most recent game is kept even once it has expired, so that its ID is not reused
+        let last = archive_game(&store, &["DAVID"], old);
+        store.remove_archived(recent).unwrap();
+        manager.prune_archive().unwrap();
+        let kept: Vec<_> = store.archived(0, 10).unwrap().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(kept, [unreadable, last]);
+    }
+
+    #[test]
+    fn scheduled_game_extends_then_cancels() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let mut session = Session::new("ABCD".into(), store, GameOptions::default()).unwrap();
+        assert!(session
+            .schedule_start(Utc::now() - chrono::Duration::minutes(1))
+            .is_err());
+        session
+            .schedule_start(Utc::now() + chrono::Duration::minutes(1))
+            .unwrap();
+        session.add_player("ALEX").unwrap();
+
+        let expire = |session: &mut Session| {
+            if let Game::Lobby { start_at, .. } = &mut session.game {
+                *start_at = Some(Utc::now());
+            }
+            session.check_schedule();
+        };
+        for _ in 0..MAX_SCHEDULE_EXTENSIONS {
+            expire(&mut session);
+            assert!(matches!(session.game, Game::Lobby { start_at: Some(t), .. } if t > Utc::now()));
+        }
+        expire(&mut session);
+        assert!(matches!(session.game, Game::Cancelled));
+        assert_eq!(session.chat_log.len(), MAX_SCHEDULE_EXTENSIONS as usize + 1);
+
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let mut session = Session::new("EFGH".into(), store, GameOptions::default()).unwrap();
+        session
+            .schedule_start(Utc::now() + chrono::Duration::minutes(1))
+            .unwrap();
+        for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"] {
+            session.add_player(name).unwrap();
+        }
+        expire(&mut session);
+        assert!(matches!(session.game, Game::Playing { .. }));
+    }
+
+    #[test]
+    fn players_can_rename_in_lobby() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let mut session = Session::new("ABCD".into(), store, GameOptions::default()).unwrap();
+        for name in ["ALXE", "BOB", "CHARLIE", "DAVID", "ED"] {
+            session.add_player(name).unwrap();
+        }
+        let claim = session.attach("ALXE", Duration::from_secs(45)).unwrap();
+
+        assert!(matches!(
+            session.rename_player("ALXE", "BOB"),
+            Err(GameError::NameTaken)
+        ));
+        session.rename_player("ALXE", "ALEX").unwrap();
+        assert_eq!(session.game.player_names()[0], "ALEX");
+        session.check_claim("ALEX", claim).unwrap();
+
+        session.start_game().unwrap();
+        assert!(session.rename_player("ALEX", "ALEXANDER").is_err());
+    }
+
+    #[test]
+    fn player_names_are_validated() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let mut session = Session::new("ABCD".into(), store, GameOptions::default()).unwrap();
+        session.names = Arc::new(NamePolicy::new(8, &["Rude".into()]));
+        assert!(matches!(
+            session.add_player("Alexander"),
+            Err(GameError::InvalidNameLength { max: 8 })
+        ));
+        assert!(matches!(
+            session.add_player(""),
+            Err(GameError::InvalidNameLength { .. })
+        ));
+        assert!(matches!(
+            session.add_player("A<b>"),
+            Err(GameError::InvalidNameCharacters)
+        ));
+        assert!(matches!(
+            session.add_player(" Al"),
+            Err(GameError::InvalidNameCharacters)
+        ));
+        assert!(matches!(session.add_player("Mr RUDE"), Err(GameError::NameNotAllowed)));
+        assert!(matches!(session.add_player("R u d e"), Err(GameError::NameNotAllowed)));
+        session.add_player("Rudolph").unwrap();
+
+        session.add_player("Al Bo").unwrap();
+        session.add_player("Al Bo").unwrap();
+        assert!(matches!(session.add_player("AL BO"), Err(GameError::NameTaken)));
+        assert!(matches!(
+            session.rename_player("Rudolph", "al bo"),
+            Err(GameError::NameTaken)
+        ));
+        session.rename_player("Al Bo", "AL BO").unwrap();
+    }
+
+    #[test]
+    fn players_are_shown_as_connected_while_attached() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let manager = SessionManager::with_clock(store, Config::default(), clock.clone()).unwrap();
+        let handle = manager.create_game(GameOptions::default(), None).unwrap();
+        let mut session = handle.lock().unwrap();
+        session.add_player("ALEX").unwrap();
+        let player = |session: &mut Session| {
+            session.publish();
+            session.public_player("ALEX").unwrap()
+        };
+        assert!(!player(&mut session).connected);
+
+        let claim = session.attach("ALEX", Duration::from_secs(45)).unwrap();
+        let attached = player(&mut session);
+        assert!(attached.connected);
+        assert_eq!(attached.last_seen, Some(clock.now()));
+
+        clock.advance(Duration::from_secs(10));
+        session.heartbeat(Some("ALEX"));
+        assert_eq!(player(&mut session).last_seen, Some(clock.now()));
+
+        // A connection which has lost its claim on the seat does not detach the one which holds it
+        clock.advance(Duration::from_secs(10));
+        session.detach("ALEX", claim + 1);
+        assert!(player(&mut session).connected);
+        session.detach("ALEX", claim);
+        let detached = player(&mut session);
+        assert!(!detached.connected);
+        assert_eq!(detached.last_seen, Some(clock.now()));
+
+        clock.advance(Duration::from_secs(10));
+        session.attach("ALEX", Duration::from_secs(45)).unwrap();
+        assert!(player(&mut session).connected);
+    }
+
+    #[test]
+    fn abandoned_lobby_seats_are_freed() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let mut session = Session::new("ABCD".into(), store, GameOptions::default()).unwrap();
+        for name in ["ALEX", "BOB", "CHARLIE"] {
+            session.add_player(name).unwrap();
+        }
+        let claim = session.attach("ALEX", Duration::from_secs(45)).unwrap();
+        session.attach("BOB", Duration::from_secs(45)).unwrap();
+        session.detach("ALEX", claim);
+        session.seats.get_mut("ALEX").unwrap().last_seen -= chrono::Duration::minutes(5);
+
+        session.prune_lobby(Duration::from_secs(120));
+        assert_eq!(session.game.player_names(), vec!["BOB", "CHARLIE"]);
+        session.remove_player("CHARLIE").unwrap();
+        assert_eq!(session.game.player_names(), vec!["BOB"]);
+        assert!(matches!(
+            session.remove_player("CHARLIE"),
+            Err(GameError::PlayerNotFound)
+        ));
+    }
+
+    #[test]
+    fn games_resume_from_save_codes() {
+        let mut config = Config::from_env();
+        config.save_secret = Some("secret".into());
+        let source = SessionManager::new(Arc::new(SqliteStore::open(":memory:").unwrap()), config.clone()).unwrap();
+        let session = source.create_game(GameOptions::default(), None).unwrap();
+        let code = {
+            let mut session = session.lock().unwrap();
+            for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"] {
+                session.add_player(name).unwrap();
+            }
+            session.start_game().unwrap();
+            session.save_code(&config).unwrap()
+        };
+
+        let target = SessionManager::new(Arc::new(SqliteStore::open(":memory:").unwrap()), config).unwrap();
+        let imported = target.import_game(&code, None).unwrap();
+        let imported = imported.lock().unwrap();
+        let session = session.lock().unwrap();
+        assert_eq!(
+            imported.game.game().unwrap().state_hash(),
+            session.game.game().unwrap().state_hash()
+        );
+        assert_eq!(imported.board_key(), session.board_key());
+        assert!(matches!(
+            target.import_game("garbage", None),
+            Err(GameError::InvalidSaveCode)
+        ));
+    }
+
+    #[test]
+    fn events_are_published_to_operators() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let manager = SessionManager::new(store, Config::from_env()).unwrap();
+        let mut events = manager.subscribe_events();
+        let session = manager.create_game(GameOptions::default(), None).unwrap();
+        let mut session = session.lock().unwrap();
+        for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"] {
+            session.add_player(name).unwrap();
+        }
+        session.start_game().unwrap();
+        let action = |name: &str| GameAction::Player {
+            name: name.into(),
+            action: PlayerAction::EndNightRound,
+        };
+        session.perform_action(action("ALEX")).unwrap();
+        session.perform_action(action("NOBODY")).unwrap_err();
+
+        let kinds = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|e| e.kind)
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            kinds.as_slice(),
+            [
+                AdminEventKind::SessionCreated,
+                AdminEventKind::GameStarted,
+                AdminEventKind::ActionApplied { .. },
+                AdminEventKind::ActionRejected { .. },
+            ]
+        ));
+    }
+
+    #[test]
+    fn snapshots_are_written_once_unlocked() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let manager = SessionManager::new(store.clone(), Config::from_env()).unwrap();
+        let session = manager.create_game(GameOptions::default(), None).unwrap();
+        let mut guard = session.lock().unwrap();
+        for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"] {
+            guard.add_player(name).unwrap();
+        }
+        guard.start_game().unwrap();
+        let id = guard.id().to_string();
+        assert!(store.game(&id).unwrap().is_none());
+        drop(guard);
+
+        let written = || {
+            let game = store.game(&id).unwrap()?;
+            Game::decode(&game).ok().map(|(game, _)| game)
+        };
+        while !matches!(written(), Some(Game::Playing { .. })) {
+            let result = session
+                .lock()
+                .unwrap()
+                .write_results
+                .recv_timeout(Duration::from_secs(5));
+            assert_eq!(result, Ok(Ok(())));
+        }
+        let action = GameAction::Player {
+            name: "ALEX".into(),
+            action: PlayerAction::EndNightRound,
+        };
+        session.lock().unwrap().perform_action(action).unwrap();
+        assert_eq!(store.journal(&id).unwrap().len(), 1);
+
+        // Once the journal has been replayed, later entries are numbered after it
+        let manager = SessionManager::new(store.clone(), Config::from_env()).unwrap();
+        let session = manager.find_game(&id).unwrap();
+        assert_eq!(session.lock().unwrap().journal_seq, 1);
+        assert_eq!(store.journal(&id).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn legacy_records_are_migrated_on_load() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let session = Session::new("ABCD".into(), store.clone(), GameOptions::default()).unwrap();
+        store
+            .put_game("ABCD", &serde_json::to_vec(&session.game).unwrap(), None, 0)
+            .unwrap();
+
+        let manager = SessionManager::new(store.clone(), Config::from_env()).unwrap();
+        manager.find_game("ABCD").unwrap();
+        let record = store.game("ABCD").unwrap().unwrap();
+        assert!(!record::is_legacy(&record));
+        assert!(matches!(Game::decode(&record).unwrap(), (Game::Lobby { .. }, false)));
+    }
+
+    #[test]
+    fn corrupt_games_are_quarantined() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        store.put_game("ABCD", b"{ not a game", None, 0).unwrap();
+        let newer = serde_json::json!({ "schema": schema::SCHEMA_VERSION + 1, "game": "GameOver" });
+        store.put_game("EFGH", newer.to_string().as_bytes(), None, 0).unwrap();
+
+        let manager = SessionManager::new(store.clone(), Config::from_env()).unwrap();
+        let report = manager.load_report();
+        assert_eq!((report.loaded, report.skipped, report.quarantined), (0, 1, 1));
+        assert!(store.game("ABCD").unwrap().is_none());
+        assert!(store.game("EFGH").unwrap().is_some());
+
+        let quarantined = manager.quarantined_games();
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(quarantined[0].record, b"{ not a game");
+        assert!(manager.remove_quarantined("ABCD").unwrap());
+        assert!(manager.quarantined_games().is_empty());
+    }
+
+    #[test]
+    fn games_cannot_be_started_twice() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let mut session = Session::new("ABCD".into(), store, GameOptions::default()).unwrap();
+        for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"] {
+            session.add_player(name).unwrap();
+        }
+        let seen = Some(session.generation());
+
+        session.start_game_from(seen).unwrap();
+        assert!(matches!(session.start_game_from(seen), Err(GameError::AlreadyStarted)));
+        assert!(matches!(session.start_game(), Err(GameError::AlreadyStarted)));
+        assert_eq!(session.generation(), 1);
+    }
+
+    #[test]
+    fn forcing_the_game_on_is_refused_once_it_has_moved_on() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let mut session = Session::new("ABCD".into(), store, GameOptions::default()).unwrap();
+        for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"] {
+            session.add_player(name).unwrap();
+        }
+        session.start_game().unwrap();
+        let seen = session.version();
+
+        // A change which has yet to be sent counts as the game moving on too
+        let action = GameAction::Player {
+            name: "ALEX".into(),
+            action: PlayerAction::EndNightRound,
+        };
+        session.perform_action(action).unwrap();
+        assert!(matches!(
+            session.force_advance_from(seen),
+            Err(GameError::ConfirmationRequired)
+        ));
+        session.publish();
+        assert!(matches!(
+            session.force_advance_from(seen),
+            Err(GameError::ConfirmationRequired)
+        ));
+
+        session.force_advance_from(session.version()).unwrap();
+    }
+
+    #[test]
+    fn finished_games_return_to_the_lobby() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let mut session = Session::new("ABCD".into(), store, GameOptions::default()).unwrap();
+        let players = ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"];
+        for name in players {
+            session.add_player(name).unwrap();
+        }
+        assert!(session.restart_lobby().is_err());
+        session.start_game().unwrap();
+        assert!(session.restart_lobby().is_err());
+
+        session
+            .game
+            .game_mut()
+            .unwrap()
+            .force_outcome(WinCondition::LiberalPolicyTrack);
+        session.end_game().unwrap();
+        session.restart_lobby().unwrap();
+        assert!(matches!(session.game, Game::Lobby { .. }));
+        assert_eq!(session.game.player_names(), players);
+        assert!(session.restart_lobby().is_err());
+    }
+
+    #[test]
+    fn idle_sessions_are_purged_as_the_clock_advances() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let config = Config::from_env();
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let manager = SessionManager::with_clock(store, config.clone(), clock.clone()).unwrap();
+        manager.create_game(GameOptions::default(), None).unwrap();
+
+        clock.advance(config.idle_timeout / 2);
+        manager.purge_games();
+        assert_eq!(manager.num_games(), 1);
+
+        clock.advance(config.idle_timeout);
+        manager.purge_games();
+        assert_eq!(manager.num_games(), 0);
+    }
+
+    #[test]
+    fn subscribed_sessions_are_not_purged_for_being_idle() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let config = Config::default();
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let manager = SessionManager::with_clock(store, config.clone(), clock.clone()).unwrap();
+        let handle = manager.create_game(GameOptions::default(), None).unwrap();
+        let updates = handle.lock().unwrap().subscribe();
+
+        clock.advance(config.idle_timeout * 2);
+        manager.purge_games();
+        assert_eq!(manager.num_games(), 1);
+
+        // Once the last client goes, the session has been idle for long enough
+        drop(updates);
+        manager.purge_games();
+        assert_eq!(manager.num_games(), 0);
+    }
+
+    #[test]
+    fn games_may_override_the_idle_timeout() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let config = Config::default();
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let manager = SessionManager::with_clock(store, config.clone(), clock.clone()).unwrap();
+        let create = |idle_timeout: Option<Duration>| {
+            let options = GameOptions {
+                idle_timeout: idle_timeout.map(|timeout| timeout.as_secs() as u32),
+                ..Default::default()
+            };
+            let handle = manager.create_game(options, None).unwrap();
+            let id = handle.lock().unwrap().id().to_string();
+            id
+        };
+        let shorter = create(Some(config.idle_timeout / 4));
+        let server = create(None);
+        let longer = create(Some(config.idle_timeout * 4));
+
+        clock.advance(config.idle_timeout / 2);
+        manager.purge_games();
+        assert!(manager.find_game(&shorter).is_err());
+        assert!(manager.find_game(&server).is_ok());
+
+        clock.advance(config.idle_timeout);
+        manager.purge_games();
+        assert!(manager.find_game(&server).is_err());
+        assert!(manager.find_game(&longer).is_ok());
+
+        clock.advance(config.idle_timeout * 4);
+        manager.purge_games();
+        assert_eq!(manager.num_games(), 0);
+    }
+
+    #[test]
+    fn asynchronous_games_wait_for_their_players() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let config = Config {
+            lobby_grace: Duration::from_secs(120),
+            idle_timeout: Duration::from_secs(3600),
+            async_inactive_timeout: Duration::from_secs(7 * 86400),
+            async_nudge_interval: Duration::from_secs(12 * 3600),
+            ..Default::default()
+        };
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let manager = SessionManager::with_clock(store, config.clone(), clock.clone()).unwrap();
+        let asynchronous = crate::game::AsyncOptions { turn_deadline_hours: Some(24) };
+        let options = GameOptions {
+            asynchronous: Some(asynchronous),
+            ..Default::default()
+        };
+        let handle = manager.create_game(options, None).unwrap();
+        {
+            let mut session = handle.lock().unwrap();
+            session.notifier = Arc::new(Notifier::new(true, &["127.0.0.1".into()]).unwrap());
+            for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"] {
+                session.add_player(name).unwrap();
+            }
+            let claim = session.attach("ALEX", config.connection_timeout).unwrap();
+            session.detach("ALEX", claim);
+            let target = PushTarget::Webhook { url: "http://127.0.0.1:9/push".into() };
+            session.register_push("ALEX", Some(target)).unwrap();
+        }
+
+        // Players who leave the lobby keep their seats, and the game outlives the idle timeout
+        clock.advance(config.lobby_grace.max(config.idle_timeout) * 2);
+        manager.check_timers();
+        manager.purge_games();
+        assert_eq!(manager.num_games(), 1);
+        assert_eq!(handle.lock().unwrap().game.player_names().len(), 5);
+
+        handle.lock().unwrap().start_game().unwrap();
+        handle.lock().unwrap().publish();
+        let notified = handle.lock().unwrap().notified["ALEX"];
+        clock.advance(config.async_nudge_interval / 2);
+        manager.check_timers();
+        assert_eq!(handle.lock().unwrap().notified["ALEX"], notified);
+        clock.advance(config.async_nudge_interval);
+        manager.check_timers();
+        assert!(handle.lock().unwrap().notified["ALEX"] > notified);
+
+        clock.advance(config.async_inactive_timeout);
+        manager.purge_games();
+        assert_eq!(manager.num_games(), 0);
+    }
+
+    #[test]
+    fn inactivity_is_measured_across_restarts() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let config = Config {
+            async_inactive_timeout: Duration::from_secs(7 * 86400),
+            ..Default::default()
+        };
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let manager = SessionManager::with_clock(store.clone(), config.clone(), clock.clone()).unwrap();
+        let options = GameOptions {
+            asynchronous: Some(crate::game::AsyncOptions { turn_deadline_hours: None }),
+            ..Default::default()
+        };
+        let handle = manager.create_game(options, None).unwrap();
+        let mut session = handle.lock().unwrap();
+        let players = ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"];
+        for name in players {
+            session.add_player(name).unwrap();
+        }
+        session.start_game().unwrap();
+        session.write_snapshot().unwrap();
+        let id = session.id().to_string();
+        drop(session);
+
+        // The last change is only in the journal, which the restarted server replays
+        let half = config.async_inactive_timeout / 2;
+        clock.advance(half);
+        let action = PlayerAction::EndNightRound;
+        handle
+            .lock()
+            .unwrap()
+            .perform_action(GameAction::Player { name: "ALEX".into(), action })
+            .unwrap();
+        drop(handle);
+        drop(manager);
+
+        clock.advance(half);
+        let manager = SessionManager::with_clock(store, config.clone(), clock.clone()).unwrap();
+        manager.find_game(&id).unwrap();
+        clock.advance(Duration::from_secs(1));
+        manager.purge_games();
+        assert_eq!(manager.num_games(), 1);
+
+        // Restarting does not give the game a fresh lease of life
+        clock.advance(half);
+        manager.purge_games();
+        assert_eq!(manager.num_games(), 0);
+    }
+
+    #[test]
+    fn phase_timers_survive_a_restart() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let manager = SessionManager::with_clock(store.clone(), Config::from_env(), clock.clone()).unwrap();
+        let mut options = GameOptions::default();
+        options.timers.nominate = Some(60);
+        let handle = manager.create_game(options, None).unwrap();
+        let mut session = handle.lock().unwrap();
+        let players = ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"];
+        for name in players {
+            session.add_player(name).unwrap();
+        }
+        session.start_game().unwrap();
+        for name in players {
+            let action = PlayerAction::EndNightRound;
+            session
+                .perform_action(GameAction::Player { name: name.into(), action })
+                .unwrap();
+        }
+        let timer = session.timer.expect("nomination is timed");
+        session.write_snapshot().unwrap();
+        let id = session.id().to_string();
+        drop(session);
+
+        // The restarted server keeps the deadline, rather than giving the president a fresh minute
+        clock.advance(Duration::from_secs(30));
+        let manager = SessionManager::with_clock(store, Config::from_env(), clock.clone()).unwrap();
+        let handle = manager.find_game(&id).unwrap();
+        handle.lock().unwrap().notify();
+        assert_eq!(handle.lock().unwrap().timer, Some(timer));
+
+        clock.advance(Duration::from_secs(31));
+        manager.check_timers();
+        let session = handle.lock().unwrap();
+        assert_eq!(session.game.game().unwrap().get_board_update().election_tracker, 1);
+    }
+
+    #[test]
+    fn journals_are_not_replayed_over_a_session_which_changed_hands() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let mut session = Session::new("ABCD".into(), store.clone(), GameOptions::default()).unwrap();
+        for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"] {
+            session.add_player(name).unwrap();
+        }
+        session.start_game().unwrap();
+        session.write_snapshot().unwrap();
+        let action = PlayerAction::EndNightRound;
+        session
+            .perform_action(GameAction::Player { name: "ALEX".into(), action })
+            .unwrap();
+        drop(session);
+        let record = store.game("ABCD").unwrap().unwrap();
+        assert_eq!(store.journal("ABCD").unwrap().len(), 1);
+
+        // This node claims the session, but another takes it over before the journal is replayed
+        let owner = |node: &str, lease: i64| SessionOwner {
+            node: node.into(),
+            address: format!("ws://{}", node),
+            expires: Utc::now() + chrono::Duration::seconds(lease),
+            epoch: 0,
+        };
+        let stale = store.claim_session("ABCD", &owner("a", -1)).unwrap();
+        store.claim_session("ABCD", &owner("b", 60)).unwrap();
+        let (game, _) = Game::decode(&record).unwrap();
+        Session::hydrate("ABCD".into(), store.clone(), game, Some(stale.fence()));
+        assert_eq!(store.game("ABCD").unwrap(), Some(record));
+        assert_eq!(store.journal("ABCD").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn ephemeral_managers_keep_games_in_memory() {
+        let manager = SessionManager::ephemeral(Config::from_env()).unwrap();
+        assert!(matches!(manager.config().storage, StorageBackend::Memory));
+        let session = manager.create_game(GameOptions::default(), None).unwrap();
+        let id = session.lock().unwrap().id().to_string();
+        assert!(manager.find_game(&id).is_ok());
+    }
+
+    #[test]
+    fn players_can_be_polled_without_keeping_the_session_alive() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let mut session = Session::new("ABCD".into(), store, GameOptions::default()).unwrap();
+        for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"] {
+            session.add_player(name).unwrap();
+        }
+        session.publish();
+        assert_eq!(session.public_player("BOB").unwrap().name, "BOB");
+        assert!(session.player_update("BOB").unwrap().is_none());
+        assert!(matches!(session.public_player("FRED"), Err(GameError::PlayerNotFound)));
+
+        session.start_game().unwrap();
+        session.publish();
+        let last_ts = session.last_ts;
+        let update = session.player_update("BOB").unwrap().unwrap();
+        assert_eq!(update.name, "BOB");
+        assert!(matches!(update.prompt, Some(crate::game::PlayerPrompt::Night { .. })));
+        assert!(session.public_player("BOB").unwrap().alive);
+        assert!(matches!(session.player_update("FRED"), Err(GameError::PlayerNotFound)));
+        assert_eq!(session.last_ts, last_ts);
+    }
+
+    #[tokio::test]
+    async fn disconnected_players_are_notified_of_their_turn() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/push", listener.local_addr().unwrap());
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let mut session = Session::new("ABCD".into(), store, GameOptions::default()).unwrap();
+        for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"] {
+            session.add_player(name).unwrap();
+        }
+        let target = PushTarget::Webhook { url };
+        assert!(matches!(
+            session.register_push("ALEX", Some(target.clone())),
+            Err(GameError::NotificationsDisabled)
+        ));
+
+        session.notifier = Arc::new(Notifier::new(true, &["127.0.0.1".into()]).unwrap());
+        let token = PushTarget::Expo { token: "abc".into() };
+        assert!(matches!(
+            session.register_push("ALEX", Some(token)),
+            Err(GameError::InvalidPushTarget)
+        ));
+        assert!(matches!(
+            session.register_push("FRED", Some(target.clone())),
+            Err(GameError::PlayerNotFound)
+        ));
+        session.register_push("ALEX", Some(target)).unwrap();
+        session.start_game().unwrap();
+        session.publish();
+
+        let accept = tokio::time::timeout(Duration::from_secs(5), listener.accept());
+        let (mut socket, _) = accept.await.expect("the player is notified").unwrap();
+        let mut request = vec![];
+        while !String::from_utf8_lossy(&request).contains("\"game_id\":\"ABCD\"") {
+            let mut buf = [0; 1024];
+            let n = socket.read(&mut buf).await.unwrap();
+            assert!(n > 0);
+            request.extend_from_slice(&buf[..n]);
+        }
+        socket.write_all(b"HTTP/1.1 204 No Content\r\n\r\n").await.unwrap();
+        assert!(String::from_utf8(request).unwrap().contains("\"player\":\"ALEX\""));
+    }
+
+    #[test]
+    fn seats_are_sent_only_their_own_updates() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let mut session = Session::new("ABCD".into(), store, GameOptions::default()).unwrap();
+        for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"] {
+            session.add_player(name).unwrap();
+        }
+        assert!(matches!(session.subscribe_player(0), Err(GameError::InvalidAction)));
+        session.start_game().unwrap();
+
+        let seat = session.seat("BOB").unwrap();
+        let mut bob = session.subscribe_player(seat).unwrap();
+        let mut alex = session.subscribe_player(session.seat("ALEX").unwrap()).unwrap();
+        assert_eq!(bob.borrow_and_update().as_ref().unwrap().name, "BOB");
+        alex.borrow_and_update();
+
+        // Only the seat whose view changed is sent an update
+        session
+            .perform_action(GameAction::Player {
+                name: "BOB".into(),
+                action: PlayerAction::EndNightRound,
+            })
+            .unwrap();
+        session.publish();
+        assert!(bob.has_changed().unwrap());
+        assert!(bob.borrow_and_update().as_ref().unwrap().prompt.is_none());
+        assert!(!alex.has_changed().unwrap());
+
+        // The seats may change hands once the game ends, so their channels are closed
+        session
+            .game
+            .game_mut()
+            .unwrap()
+            .force_outcome(WinCondition::LiberalPolicyTrack);
+        session.end_game().unwrap();
+        assert!(bob.has_changed().is_err());
+    }
+
+    /// Creates a hotseat game with five players, all added by the board, and starts it.
+    fn hotseat_session() -> Session {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let options = GameOptions { hotseat: true, ..GameOptions::default() };
+        let mut session = Session::new("ABCD".into(), store, options).unwrap();
+        for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED"] {
+            session.add_hotseat_player(name).unwrap();
+        }
+        session.start_game().unwrap();
+        session
+    }
+
+    #[test]
+    fn hotseat_device_is_passed_between_players() {
+        let mut session = hotseat_session();
+        let mut board = session.subscribe();
+        let shown = |board: &mut watch::Receiver<GameUpdate>| board.borrow_and_update().hotseat.clone();
+
+        session.publish();
+        assert!(matches!(shown(&mut board), Some(HotseatPrompt::PassDevice { player }) if player == "ALEX"));
+        let end_night = || PlayerAction::EndNightRound;
+        assert!(matches!(
+            session.hotseat_action(end_night()),
+            Err(GameError::InvalidAction)
+        ));
+
+        // The player keeps the device while they have something left to do
+        session.hotseat_ready().unwrap();
+        session.hotseat_action(PlayerAction::ViewRole).unwrap();
+        session.publish();
+        assert!(matches!(shown(&mut board), Some(HotseatPrompt::Player { update }) if update.name == "ALEX"));
+
+        session.hotseat_action(end_night()).unwrap();
+        session.publish();
+        assert!(matches!(shown(&mut board), Some(HotseatPrompt::PassDevice { player }) if player == "BOB"));
+        for _ in 0..4 {
+            session.hotseat_ready().unwrap();
+            session.hotseat_action(end_night()).unwrap();
+        }
+        session.publish();
+        // If ED is the president, they keep the device to nominate a chancellor
+        let president = match shown(&mut board) {
+            Some(HotseatPrompt::PassDevice { player }) => player,
+            Some(HotseatPrompt::Player { update }) if update.name == "ED" => update.name,
+            other => panic!("Expected the device to be passed to the president, got {:?}", other),
+        };
+        let game = session.game.game().unwrap();
+        assert!(!game.legal_actions(game.find_player(&president).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn hotseat_prompts_are_only_shown_to_the_player_holding_the_device() {
+        let mut session = hotseat_session();
+        let mut board = session.subscribe();
+        session.publish();
+
+        // Until the player confirms they have the device, nothing private is shown
+        let hidden = serde_json::to_value(&board.borrow_and_update().hotseat).unwrap();
+        assert_eq!(hidden, serde_json::json!({ "type": "PassDevice", "player": "ALEX" }));
+
+        session.hotseat_ready().unwrap();
+        session.publish();
+        let Some(HotseatPrompt::Player { update }) = board.borrow_and_update().hotseat.clone() else {
+            panic!("Expected ALEX's prompt to be shown");
+        };
+        let alex = session.game.game().unwrap().get_player_update(0);
+        assert_eq!(
+            serde_json::to_value(&update).unwrap(),
+            serde_json::to_value(&alex).unwrap()
+        );
+
+        // Once they are done, the next player must confirm before anything of theirs is shown
+        session.hotseat_action(PlayerAction::EndNightRound).unwrap();
+        session.publish();
+        let hidden = serde_json::to_value(&board.borrow_and_update().hotseat).unwrap();
+        assert_eq!(hidden, serde_json::json!({ "type": "PassDevice", "player": "BOB" }));
+        assert!(matches!(
+            session.hotseat_action(PlayerAction::EndNightRound),
+            Err(GameError::InvalidAction)
+        ));
+    }
+
+    #[test]
+    fn updates_describe_the_game() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let options = GameOptions { communists: true, ..GameOptions::default() };
+        let mut session = Session::new("ABCD".into(), store, options).unwrap();
+        let updates = session.subscribe();
+        {
+            let update = updates.borrow();
+            assert_eq!(update.game_id, "ABCD");
+            assert!(update.options.is_some_and(|options| options.communists));
+            assert!(update.started_at.is_none());
+        }
+
+        for name in ["ALEX", "BOB", "CHARLIE", "DAVID", "ED", "FRED"] {
+            session.add_player(name).unwrap();
+        }
+        session.start_game().unwrap();
+        session.publish();
+        let update = updates.borrow();
+        assert!(update.options.is_some_and(|options| options.communists));
+        assert!(update.started_at.is_some());
+    }
+}
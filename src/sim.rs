@@ -0,0 +1,107 @@
+//! Automated games between bots, for testing the balance of the game engine.
+
+pub use self::bots::{Bot, PartisanBot, RandomBot};
+pub use self::stats::Stats;
+use crate::action::{BoardAction, GameAction, PlayerAction};
+use crate::error::GameError;
+use crate::game::{Game, GameOptions, PlayerPrompt};
+use rand::RngCore;
+
+mod bots;
+mod stats;
+
+/// The order in which the board attempts to advance the game when no player has anything to do.
+pub const BOARD_ACTIONS: [BoardAction; 9] = [
+    BoardAction::EndVoting,
+    BoardAction::EndElectionFailed,
+    BoardAction::EndCardReveal,
+    BoardAction::EndExecutiveAction,
+    BoardAction::EndLegislativeSession,
+    BoardAction::EndAssassination,
+    BoardAction::EndCommunistStart,
+    BoardAction::EndCommunistEnd,
+    BoardAction::StartSpecialElection,
+];
+
+/// The number of steps after which a game is assumed to be stuck.
+const MAX_STEPS: usize = 10_000;
+
+/// Plays a game to completion with the given bot controlling every player, returning the finished game.
+pub fn play_game(
+    opts: GameOptions,
+    num_players: usize,
+    seed: u64,
+    bot: &dyn Bot,
+    rng: &mut dyn RngCore,
+) -> Result<Game, GameError> {
+    let names = (0..num_players).map(|i| format!("BOT{}", i + 1)).collect::<Vec<_>>();
+    let mut game = Game::new(opts, &names, seed)?;
+
+    for _ in 0..MAX_STEPS {
+        if game.game_over() {
+            return Ok(game);
+        }
+        if !step(&mut game, bot, rng)? {
+            break;
+        }
+    }
+    Err(GameError::InvalidAction)
+}
+
+/// Performs the next action for every player who has one, or else advances the game as the board.
+/// Returns `false` if nothing could be done.
+fn step(game: &mut Game, bot: &dyn Bot, rng: &mut dyn RngCore) -> Result<bool, GameError> {
+    let mut acted = false;
+    let names = game.player_names().map(str::to_string).collect::<Vec<_>>();
+    for player in 0..game.num_players() {
+        let update = game.get_player_update(player);
+        let Some(action) = bot.act(&update, &names, rng) else {
+            continue;
+        };
+        // The prompt may be stale if another player's action has already moved the game on
+        let action = GameAction::Player { name: update.name, action };
+        if action.apply(game).is_ok() {
+            acted = true;
+        }
+    }
+    if acted {
+        return Ok(true);
+    }
+
+    Ok(BOARD_ACTIONS.iter().any(|action| action.apply(game).is_ok()))
+}
+
+/// Maps a prompt to the action a bot takes when it has no meaningful decision to make.
+fn acknowledge(prompt: &PlayerPrompt) -> Option<PlayerAction> {
+    match prompt {
+        PlayerPrompt::Night { .. } => Some(PlayerAction::EndNightRound),
+        PlayerPrompt::StartElection { .. } => Some(PlayerAction::EndCardReveal),
+        PlayerPrompt::InvestigatePlayer { .. }
+        | PlayerPrompt::Radicalisation { .. }
+        | PlayerPrompt::Confession { .. } => Some(PlayerAction::EndExecutiveAction),
+        PlayerPrompt::PolicyPeak { .. } => Some(PlayerAction::AcknowledgePolicyPeak),
+        PlayerPrompt::EndCongress { .. } => Some(PlayerAction::EndCongress),
+        PlayerPrompt::OfferBribe { .. } => Some(PlayerAction::DeclineBribe),
+        PlayerPrompt::ConsiderBribe { .. } => Some(PlayerAction::RejectBribe),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn bots_finish_games() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut stats = Stats::default();
+        for seed in 0..50 {
+            let bot: &dyn Bot = if seed % 2 == 0 { &RandomBot } else { &PartisanBot };
+            stats.record(&play_game(GameOptions::default(), 7, seed, bot, &mut rng).unwrap());
+        }
+        assert_eq!(stats.games, 50);
+        assert_eq!(stats.roles["Hitler"].played, 50);
+    }
+}
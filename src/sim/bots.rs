@@ -0,0 +1,111 @@
+use super::acknowledge;
+use crate::action::PlayerAction;
+use crate::game::{ChoosePlayerKind, InvestigationResult, Party, PlayerPrompt, PlayerUpdate};
+use rand::{seq::SliceRandom, Rng, RngCore};
+
+/// A policy for deciding how a player responds to their prompts.
+/// Bots only see what the player themselves would see.
+pub trait Bot {
+    /// Decides how to respond to the player's current prompt, or returns `None` to do nothing.
+    /// `names` are the names of all players, in seating order.
+    fn act(&self, player: &PlayerUpdate, names: &[String], rng: &mut dyn RngCore) -> Option<PlayerAction>;
+}
+
+/// A bot which makes every decision at random.
+pub struct RandomBot;
+
+/// A bot which plays in the interests of its own party, using whatever it has learned about the other players.
+pub struct PartisanBot;
+
+impl Bot for RandomBot {
+    fn act(&self, player: &PlayerUpdate, _names: &[String], rng: &mut dyn RngCore) -> Option<PlayerAction> {
+        let prompt = player.prompt.as_ref()?;
+        match prompt {
+            PlayerPrompt::ChoosePlayer { options, .. } => {
+                let name = options.choose(rng)?.clone();
+                Some(PlayerAction::ChoosePlayer { name })
+            }
+            PlayerPrompt::Vote { .. } => Some(PlayerAction::CastVote { vote: rng.gen_bool(0.6) }),
+            PlayerPrompt::HijackElection => rng.gen_bool(0.5).then_some(PlayerAction::HijackElection),
+            PlayerPrompt::PresidentDiscard { .. } => Some(PlayerAction::Discard { index: rng.gen_range(0..3) }),
+            PlayerPrompt::ChancellorDiscard { can_veto, .. } => match *can_veto && rng.gen_bool(0.2) {
+                true => Some(PlayerAction::VetoAgenda),
+                false => Some(PlayerAction::Discard { index: rng.gen_range(0..2) }),
+            },
+            PlayerPrompt::ApproveVeto => match rng.gen_bool(0.5) {
+                true => Some(PlayerAction::AcceptVeto),
+                false => Some(PlayerAction::RejectVeto),
+            },
+            PlayerPrompt::StartElection { can_assassinate: true } if rng.gen_bool(0.1) => {
+                Some(PlayerAction::StartAssassination)
+            }
+            prompt => acknowledge(prompt),
+        }
+    }
+}
+
+impl Bot for PartisanBot {
+    fn act(&self, player: &PlayerUpdate, names: &[String], rng: &mut dyn RngCore) -> Option<PlayerAction> {
+        let prompt = player.prompt.as_ref()?;
+        let party = player.role.party();
+        // What the player knows about each of the others' parties
+        let known = |name: &str| {
+            let idx = names.iter().position(|n| n == name)?;
+            match player.others.get(idx)? {
+                InvestigationResult::Unknown => None,
+                InvestigationResult::Party(party) => Some(*party),
+                InvestigationResult::Role(role) => Some(role.party()),
+            }
+        };
+
+        match prompt {
+            PlayerPrompt::ChoosePlayer { kind, options, .. } => {
+                let hostile = matches!(
+                    kind,
+                    ChoosePlayerKind::Execute | ChoosePlayerKind::Investigate | ChoosePlayerKind::Radicalise
+                );
+                let preferred = options
+                    .iter()
+                    .filter(|name| **name != player.name)
+                    .filter(|name| match hostile {
+                        true => known(name).is_none_or(|p| p != party),
+                        false => known(name).is_some_and(|p| p == party),
+                    })
+                    .collect::<Vec<_>>();
+                let name = match preferred.choose(rng) {
+                    Some(name) => (*name).clone(),
+                    None => options.choose(rng)?.clone(),
+                };
+                Some(PlayerAction::ChoosePlayer { name })
+            }
+            PlayerPrompt::Vote { .. } => {
+                let vote = match party {
+                    Party::Fascist => rng.gen_bool(0.8),
+                    _ => rng.gen_bool(0.6),
+                };
+                Some(PlayerAction::CastVote { vote })
+            }
+            PlayerPrompt::HijackElection => Some(PlayerAction::HijackElection),
+            PlayerPrompt::PresidentDiscard { cards } => {
+                Some(PlayerAction::Discard { index: discard_index(cards, party) })
+            }
+            PlayerPrompt::ChancellorDiscard { cards, can_veto } => {
+                if *can_veto && cards.iter().all(|card| *card != party) {
+                    return Some(PlayerAction::VetoAgenda);
+                }
+                Some(PlayerAction::Discard { index: discard_index(cards, party) })
+            }
+            PlayerPrompt::ApproveVeto => match party {
+                Party::Fascist => Some(PlayerAction::RejectVeto),
+                _ => Some(PlayerAction::AcceptVeto),
+            },
+            PlayerPrompt::StartElection { can_assassinate: true } => Some(PlayerAction::StartAssassination),
+            prompt => acknowledge(prompt),
+        }
+    }
+}
+
+/// Chooses a card to discard, preferring one which doesn't belong to the given party.
+fn discard_index(cards: &[Party], party: Party) -> usize {
+    cards.iter().position(|card| *card != party).unwrap_or(0)
+}
@@ -0,0 +1,81 @@
+use crate::game::Game;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Aggregated results from a number of simulated games.
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    /// The number of games which finished.
+    pub games: usize,
+    /// The number of games which could not be played to completion.
+    pub failures: usize,
+    /// The number of games which ended by each win condition.
+    pub outcomes: BTreeMap<String, usize>,
+    /// The number of times each party was played, and the number of those times it won.
+    pub parties: BTreeMap<String, Record>,
+    /// The number of times each role was played, and the number of those times it won.
+    pub roles: BTreeMap<String, Record>,
+}
+
+/// The number of times something was played and won.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Record {
+    pub played: usize,
+    pub won: usize,
+}
+
+impl Stats {
+    /// Records the result of a finished game.
+    pub fn record(&mut self, game: &Game) {
+        let Some(outcome) = game.outcome() else {
+            self.failures += 1;
+            return;
+        };
+        self.games += 1;
+        *self.outcomes.entry(outcome.to_string()).or_default() += 1;
+
+        for (player, role) in game.roles().enumerate() {
+            let won = game.player_has_won(player) as usize;
+            for (map, key) in [
+                (&mut self.parties, role.party().to_string()),
+                (&mut self.roles, role.to_string()),
+            ] {
+                let record = map.entry(key).or_default();
+                record.played += 1;
+                record.won += won;
+            }
+        }
+    }
+
+    /// Records a game which could not be played to completion.
+    pub fn record_failure(&mut self) {
+        self.failures += 1;
+    }
+}
+
+impl Record {
+    /// The proportion of times this was won.
+    pub fn win_rate(&self) -> f64 {
+        self.won as f64 / self.played.max(1) as f64
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Games: {} ({} failed)", self.games, self.failures)?;
+        writeln!(f, "Outcomes:")?;
+        for (outcome, count) in &self.outcomes {
+            let rate = *count as f64 / self.games.max(1) as f64;
+            writeln!(f, "  {:<24}{:>7} {:>6.1}%", outcome, count, rate * 100.0)?;
+        }
+        writeln!(f, "Win rate by party:")?;
+        for (party, record) in &self.parties {
+            writeln!(f, "  {:<24}{:>6.1}%", party, record.win_rate() * 100.0)?;
+        }
+        writeln!(f, "Win rate by role:")?;
+        for (role, record) in &self.roles {
+            writeln!(f, "  {:<24}{:>6.1}%", role, record.win_rate() * 100.0)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+pub use self::sled_store::SledStore;
+pub use self::sqlite_store::SqliteStore;
+pub use self::writer::{Snapshot, SnapshotWriter, WriteResult};
+use crate::config::StorageBackend;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::sync::Arc;
+
+pub mod record;
+pub mod schema;
+mod sled_store;
+mod sqlite_store;
+mod writer;
+
+pub type StoreResult<T> = Result<T, Box<dyn Error>>;
+
+/// A persistent store for games in progress, and the archive of finished games.
+/// Games and archive entries are stored as opaque serialized blobs.
+pub trait GameStore: Send + Sync {
+    /// Gets every game in progress, along with its ID.
+    fn games(&self) -> StoreResult<Vec<(String, Vec<u8>)>>;
+
+    /// Gets a single game in progress.
+    fn game(&self, id: &str) -> StoreResult<Option<Vec<u8>>>;
+
+    /// Inserts or updates a game in progress, as encoded by [record::encode], and clears the entries in its
+    /// journal with a sequence number below `journal_before` in the same transaction.
+    /// If a fence is given, the write fails with [Fenced] unless it still matches the session's owner.
+    fn put_game(&self, id: &str, game: &[u8], fence: Option<&Fence>, journal_before: u64) -> StoreResult<()>;
+
+    /// Removes a game in progress, along with its journal and owner.
+    /// If a fence is given, the game is only removed if it still matches the session's owner.
+    fn remove_game(&self, id: &str, fence: Option<&Fence>) -> StoreResult<()>;
+
+    /// Moves a game in progress into quarantine, recording why, so that it is kept for inspection but never loaded.
+    fn quarantine_game(&self, id: &str, reason: &str) -> StoreResult<()>;
+
+    /// Gets every quarantined game.
+    fn quarantined(&self) -> StoreResult<Vec<QuarantinedGame>>;
+
+    /// Deletes a quarantined game for good.
+    fn remove_quarantined(&self, id: &str) -> StoreResult<()>;
+
+    /// Appends an entry to the journal of actions performed on a game since it was last saved.
+    /// The entry must be durably written before this returns.
+    /// If a fence is given, the write fails with [Fenced] unless it still matches the session's owner.
+    fn append_journal(&self, id: &str, seq: u64, entry: &[u8], fence: Option<&Fence>) -> StoreResult<()>;
+
+    /// Gets the journal entries for a game, in order, along with their sequence numbers.
+    fn journal(&self, id: &str) -> StoreResult<Vec<(u64, Vec<u8>)>>;
+
+    /// Gets the node which owns a session, if it has ever been claimed.
+    fn session_owner(&self, id: &str) -> StoreResult<Option<SessionOwner>>;
+
+    /// Atomically claims a session for the given owner, unless another node holds a lease which has not yet expired.
+    /// Returns the owner of the session after the claim, whose epoch is one more than the last owner's
+    /// if the session changed hands. The epoch of the given owner is ignored.
+    fn claim_session(&self, id: &str, owner: &SessionOwner) -> StoreResult<SessionOwner>;
+
+    /// Expires the lease on a session if it is held by the given node, so that any node may claim it.
+    fn release_session(&self, id: &str, node: &str) -> StoreResult<()>;
+
+    /// Adds an entry to the archive, returning its newly assigned ID.
+    /// IDs are assigned in increasing order, and are never reused.
+    fn archive(&self, entry: &[u8]) -> StoreResult<u64>;
+
+    /// Gets up to `limit` archive entries with an ID of at least `start`, in order of ID.
+    fn archived(&self, start: u64, limit: usize) -> StoreResult<Vec<(u64, Vec<u8>)>>;
+
+    /// Gets the ID of the most recent archive entry.
+    fn last_archived(&self) -> StoreResult<Option<u64>>;
+
+    /// Removes an entry from the archive.
+    fn remove_archived(&self, id: u64) -> StoreResult<()>;
+}
+
+/// A persisted game which could not be loaded, set aside for an operator to inspect.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct QuarantinedGame {
+    pub id: String,
+    /// Why the game could not be loaded.
+    pub reason: String,
+    pub quarantined: DateTime<Utc>,
+    /// The record the game was persisted as.
+    pub record: Vec<u8>,
+}
+
+/// The node which hosts a session when running in distributed mode.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct SessionOwner {
+    /// The unique ID of the node.
+    pub node: String,
+    /// The address clients should connect to in order to reach the node.
+    pub address: String,
+    /// The time at which the node's lease on the session expires, unless renewed.
+    pub expires: DateTime<Utc>,
+    /// Increases by one every time the session changes hands.
+    #[serde(default)]
+    pub epoch: u64,
+}
+
+impl SessionOwner {
+    /// Whether the given node may claim a session held by this owner.
+    pub fn can_claim(&self, node: &str) -> bool {
+        self.node == node || self.expires <= Utc::now()
+    }
+
+    /// The owner of a session after the given node claims it from this owner.
+    pub fn claimed_by(&self, owner: &SessionOwner) -> SessionOwner {
+        let epoch = match self.node == owner.node {
+            true => self.epoch,
+            false => self.epoch + 1,
+        };
+        SessionOwner { epoch, ..owner.clone() }
+    }
+
+    /// The fence with which this owner writes to the session.
+    pub fn fence(&self) -> Fence {
+        Fence { node: self.node.clone(), epoch: self.epoch }
+    }
+}
+
+/// Identifies a node's claim on a session in distributed mode. Writes made under a fence are refused once
+/// the session has changed hands, so a node which has lost a session can no longer overwrite it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Fence {
+    pub node: String,
+    pub epoch: u64,
+}
+
+impl Fence {
+    /// Whether the session is still held under this fence by the given owner.
+    pub fn holds(&self, owner: Option<&SessionOwner>) -> bool {
+        owner.is_some_and(|owner| owner.node == self.node && owner.epoch == self.epoch)
+    }
+}
+
+/// The error returned when writing to a session under a fence which no longer matches its owner.
+#[derive(thiserror::Error, Debug)]
+#[error("session is owned by another node")]
+pub struct Fenced;
+
+/// Opens the store for the configured storage backend.
+pub fn open(backend: &StorageBackend) -> StoreResult<Arc<dyn GameStore>> {
+    Ok(match backend {
+        StorageBackend::Sled { path } => Arc::new(SledStore::new(sled::open(path)?)?),
+        StorageBackend::Sqlite { path } => Arc::new(SqliteStore::open(path)?),
+        StorageBackend::Memory => Arc::new(SledStore::new(sled::Config::new().temporary(true).open()?)?),
+    })
+}
@@ -0,0 +1,71 @@
+//! The format games in progress are persisted in: a short header naming the format's version,
+//! followed by the game serialized as JSON and compressed with zstd.
+//! Games persisted before the header was introduced are stored as plain JSON.
+
+use std::borrow::Cow;
+use thiserror::Error;
+
+/// Marks the start of a versioned record, and can never begin a plain JSON one.
+const MAGIC: &[u8; 3] = b"SHG";
+
+/// The version of the record format which is written.
+const VERSION: u8 = 1;
+
+const COMPRESSION_LEVEL: i32 = 3;
+
+#[derive(Error, Debug)]
+pub enum RecordError {
+    #[error("record is {size} bytes, over the limit of {limit} bytes")]
+    TooLarge { size: usize, limit: usize },
+    #[error("record has unknown format version {0}")]
+    UnknownVersion(u8),
+    #[error("record is corrupt: {0}")]
+    Corrupt(#[from] std::io::Error),
+}
+
+/// Encodes a serialized game as a record, provided the record is no larger than `limit` bytes.
+pub fn encode(game: &[u8], limit: usize) -> Result<Vec<u8>, RecordError> {
+    let mut record = MAGIC.to_vec();
+    record.push(VERSION);
+    record.extend(zstd::encode_all(game, COMPRESSION_LEVEL)?);
+    match record.len() {
+        size if size > limit => Err(RecordError::TooLarge { size, limit }),
+        _ => Ok(record),
+    }
+}
+
+/// Decodes a record, returning the serialized game.
+pub fn decode(record: &[u8]) -> Result<Cow<'_, [u8]>, RecordError> {
+    let Some(rest) = record.strip_prefix(MAGIC) else {
+        return Ok(Cow::Borrowed(record));
+    };
+    match rest.split_first() {
+        Some((&VERSION, compressed)) => Ok(Cow::Owned(zstd::decode_all(compressed)?)),
+        Some((&version, _)) => Err(RecordError::UnknownVersion(version)),
+        None => Err(RecordError::UnknownVersion(0)),
+    }
+}
+
+/// Whether a record was persisted as plain JSON, before records were versioned, and should be rewritten.
+pub fn is_legacy(record: &[u8]) -> bool {
+    !record.starts_with(MAGIC)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn records_are_compressed_and_legacy_records_still_read() {
+        let game = serde_json::to_vec(&vec!["LIBERAL"; 100]).unwrap();
+        let record = encode(&game, 1024).unwrap();
+        assert!(record.len() < game.len());
+        assert_eq!(decode(&record).unwrap(), game);
+        assert!(!is_legacy(&record));
+
+        assert_eq!(decode(&game).unwrap(), game);
+        assert!(is_legacy(&game));
+        assert!(matches!(encode(&game, 8), Err(RecordError::TooLarge { limit: 8, .. })));
+        assert!(matches!(decode(b"SHG\x09"), Err(RecordError::UnknownVersion(9))));
+    }
+}
@@ -0,0 +1,81 @@
+//! Versioning of the schema games are persisted with, so that a game persisted by an older server
+//! is brought up to date when it is loaded rather than being dropped.
+
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+/// The version of the schema games are persisted with. It must be bumped, and a migration added,
+/// whenever the game state changes in a way which would stop games persisted with the previous version loading.
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// Brings a game persisted with one version of the schema up to the next.
+type Migration = fn(Value) -> Result<Value, SchemaError>;
+
+/// The migrations, indexed by the version they upgrade from.
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [from_unversioned];
+
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    #[error("game was persisted with schema version {0}, which is newer than this server understands")]
+    Newer(u64),
+    #[error("game cannot be migrated from schema version {version}: {reason}")]
+    Migration { version: u64, reason: String },
+}
+
+/// A game, tagged with the version of the schema it was persisted with.
+#[derive(Serialize)]
+struct Versioned<'a, T> {
+    schema: u64,
+    game: &'a T,
+}
+
+/// Serializes a game, tagged with the current schema version.
+pub fn serialize<T: Serialize>(game: &T) -> serde_json::Result<Vec<u8>> {
+    serde_json::to_vec(&Versioned { schema: SCHEMA_VERSION, game })
+}
+
+/// Reads a persisted game, migrating it to the current schema version.
+/// Returns the game, along with the version of the schema it was persisted with.
+pub fn upgrade(persisted: Value) -> Result<(Value, u64), SchemaError> {
+    let (persisted_version, mut game) = match persisted {
+        Value::Object(mut versioned) if versioned.contains_key("schema") => {
+            let version = versioned["schema"].as_u64().ok_or_else(|| SchemaError::Migration {
+                version: 0,
+                reason: "schema version is not a number".into(),
+            })?;
+            (version, versioned.remove("game").unwrap_or_default())
+        }
+        game => (0, game),
+    };
+    if persisted_version > SCHEMA_VERSION {
+        return Err(SchemaError::Newer(persisted_version));
+    }
+    for migrate in &MIGRATIONS[persisted_version as usize..] {
+        game = migrate(game)?;
+    }
+    Ok((game, persisted_version))
+}
+
+/// Games persisted before the schema was versioned are the game itself.
+fn from_unversioned(game: Value) -> Result<Value, SchemaError> {
+    Ok(game)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn games_are_upgraded_to_the_current_schema() {
+        let game = json!({ "Lobby": { "players": ["ALEX"] } });
+        assert_eq!(upgrade(game.clone()).unwrap(), (game.clone(), 0));
+
+        let current = serde_json::from_slice(&serialize(&game).unwrap()).unwrap();
+        assert_eq!(upgrade(current).unwrap(), (game, SCHEMA_VERSION));
+
+        let newer = json!({ "schema": SCHEMA_VERSION + 1, "game": "GameOver" });
+        assert!(matches!(upgrade(newer), Err(SchemaError::Newer(_))));
+    }
+}
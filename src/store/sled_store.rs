@@ -0,0 +1,248 @@
+use super::{Fence, Fenced, GameStore, QuarantinedGame, SessionOwner, StoreResult};
+use sled::transaction::{ConflictableTransactionError, TransactionError, TransactionalTree};
+use sled::{CompareAndSwapError, Transactional};
+
+/// Stores games in a sled database.
+pub struct SledStore {
+    game: sled::Tree,
+    journal: sled::Tree,
+    owner: sled::Tree,
+    archive: sled::Tree,
+    quarantine: sled::Tree,
+}
+
+impl SledStore {
+    pub fn new(db: sled::Db) -> StoreResult<Self> {
+        Ok(Self {
+            game: db.open_tree("games")?,
+            journal: db.open_tree("journal")?,
+            owner: db.open_tree("owners")?,
+            archive: db.open_tree("archive")?,
+            quarantine: db.open_tree("quarantine")?,
+        })
+    }
+}
+
+impl SledStore {
+    /// Gets the keys of the entries in the journal for a game with a sequence number below `before`.
+    fn journal_keys(&self, id: &str, before: u64) -> StoreResult<Vec<sled::IVec>> {
+        let prefix = journal_prefix(id);
+        let mut keys = vec![];
+        for row in self.journal.scan_prefix(&prefix) {
+            let (key, _) = row?;
+            let seq = key[prefix.len()..].try_into().map(u64::from_be_bytes)?;
+            if seq >= before {
+                break;
+            }
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+}
+
+impl GameStore for SledStore {
+    fn games(&self) -> StoreResult<Vec<(String, Vec<u8>)>> {
+        let mut games = vec![];
+        for entry in self.game.iter() {
+            let (id, game) = entry?;
+            games.push((String::from_utf8(id.to_vec())?, game.to_vec()));
+        }
+        Ok(games)
+    }
+
+    fn game(&self, id: &str) -> StoreResult<Option<Vec<u8>>> {
+        Ok(self.game.get(id.as_bytes())?.map(|game| game.to_vec()))
+    }
+
+    fn put_game(&self, id: &str, game: &[u8], fence: Option<&Fence>, journal_before: u64) -> StoreResult<()> {
+        // Sled transactions cannot scan, so the covered entries are found first
+        let covered = self.journal_keys(id, journal_before)?;
+        let result = (&self.game, &self.owner, &self.journal).transaction(|(games, owners, journal)| {
+            check_fence(owners, id, fence)?;
+            games.insert(id.as_bytes(), game)?;
+            for key in &covered {
+                journal.remove(key)?;
+            }
+            Ok(())
+        });
+        fenced_result(result)
+    }
+
+    fn remove_game(&self, id: &str, fence: Option<&Fence>) -> StoreResult<()> {
+        let result = (&self.game, &self.owner).transaction(|(games, owners)| {
+            check_fence(owners, id, fence)?;
+            games.remove(id.as_bytes())?;
+            owners.remove(id.as_bytes())?;
+            Ok(())
+        });
+        fenced_result(result)?;
+        for key in self.journal_keys(id, u64::MAX)? {
+            self.journal.remove(key)?;
+        }
+        Ok(())
+    }
+
+    fn quarantine_game(&self, id: &str, reason: &str) -> StoreResult<()> {
+        let Some(record) = self.game(id)? else {
+            return Ok(());
+        };
+        let entry = QuarantinedGame {
+            id: id.to_string(),
+            reason: reason.to_string(),
+            quarantined: chrono::offset::Utc::now(),
+            record,
+        };
+        self.quarantine.insert(id.as_bytes(), serde_json::to_vec(&entry)?)?;
+        self.remove_game(id, None)
+    }
+
+    fn quarantined(&self) -> StoreResult<Vec<QuarantinedGame>> {
+        let mut games = vec![];
+        for row in self.quarantine.iter() {
+            let (_, entry) = row?;
+            games.push(serde_json::from_slice(&entry)?);
+        }
+        Ok(games)
+    }
+
+    fn remove_quarantined(&self, id: &str) -> StoreResult<()> {
+        self.quarantine.remove(id.as_bytes())?;
+        Ok(())
+    }
+
+    fn append_journal(&self, id: &str, seq: u64, entry: &[u8], fence: Option<&Fence>) -> StoreResult<()> {
+        let mut key = journal_prefix(id);
+        key.extend_from_slice(&seq.to_be_bytes());
+        let result = (&self.journal, &self.owner).transaction(|(journal, owners)| {
+            check_fence(owners, id, fence)?;
+            journal.insert(key.as_slice(), entry)?;
+            Ok(())
+        });
+        fenced_result(result)?;
+        self.journal.flush()?;
+        Ok(())
+    }
+
+    fn journal(&self, id: &str) -> StoreResult<Vec<(u64, Vec<u8>)>> {
+        let prefix = journal_prefix(id);
+        let mut entries = vec![];
+        for row in self.journal.scan_prefix(&prefix) {
+            let (key, entry) = row?;
+            let seq = key[prefix.len()..].try_into().map(u64::from_be_bytes)?;
+            entries.push((seq, entry.to_vec()));
+        }
+        Ok(entries)
+    }
+
+    fn session_owner(&self, id: &str) -> StoreResult<Option<SessionOwner>> {
+        let Some(owner) = self.owner.get(id.as_bytes())? else {
+            return Ok(None);
+        };
+        Ok(Some(serde_json::from_slice(&owner)?))
+    }
+
+    fn claim_session(&self, id: &str, owner: &SessionOwner) -> StoreResult<SessionOwner> {
+        loop {
+            let current = self.owner.get(id.as_bytes())?;
+            let claimed = match &current {
+                Some(current) => {
+                    let current: SessionOwner = serde_json::from_slice(current)?;
+                    if !current.can_claim(&owner.node) {
+                        break Ok(current);
+                    }
+                    current.claimed_by(owner)
+                }
+                None => SessionOwner { epoch: 0, ..owner.clone() },
+            };
+            let new = serde_json::to_vec(&claimed)?;
+            match self
+                .owner
+                .compare_and_swap(id.as_bytes(), current, Some(new.as_slice()))?
+            {
+                Ok(_) => break Ok(claimed),
+                Err(CompareAndSwapError { .. }) => continue,
+            }
+        }
+    }
+
+    fn release_session(&self, id: &str, node: &str) -> StoreResult<()> {
+        let Some(mut owner) = self.session_owner(id)? else {
+            return Ok(());
+        };
+        if owner.node == node {
+            owner.expires = chrono::offset::Utc::now();
+            self.owner.insert(id.as_bytes(), serde_json::to_vec(&owner)?)?;
+        }
+        Ok(())
+    }
+
+    fn archive(&self, entry: &[u8]) -> StoreResult<u64> {
+        loop {
+            let id = self.last_archived()?.unwrap_or(0) + 1;
+            match self
+                .archive
+                .compare_and_swap::<_, &[_], _>(id.to_be_bytes(), None, Some(entry))?
+            {
+                Ok(_) => break Ok(id),
+                Err(CompareAndSwapError { .. }) => continue,
+            }
+        }
+    }
+
+    fn archived(&self, start: u64, limit: usize) -> StoreResult<Vec<(u64, Vec<u8>)>> {
+        let mut entries = vec![];
+        for row in self.archive.range(start.to_be_bytes()..).take(limit) {
+            let (key, value) = row?;
+            entries.push((decode_key(&key), value.to_vec()));
+        }
+        Ok(entries)
+    }
+
+    fn last_archived(&self) -> StoreResult<Option<u64>> {
+        Ok(self.archive.last()?.map(|(key, _)| decode_key(&key)))
+    }
+
+    fn remove_archived(&self, id: u64) -> StoreResult<()> {
+        self.archive.remove(id.to_be_bytes())?;
+        Ok(())
+    }
+}
+
+/// Aborts a transaction with [Fenced] if the session is not held under the given fence.
+fn check_fence(
+    owners: &TransactionalTree,
+    id: &str,
+    fence: Option<&Fence>,
+) -> Result<(), ConflictableTransactionError<Fenced>> {
+    let Some(fence) = fence else {
+        return Ok(());
+    };
+    let owner = owners.get(id.as_bytes())?;
+    let owner = owner.and_then(|owner| serde_json::from_slice::<SessionOwner>(&owner).ok());
+    match fence.holds(owner.as_ref()) {
+        true => Ok(()),
+        false => Err(ConflictableTransactionError::Abort(Fenced)),
+    }
+}
+
+/// Converts the outcome of a fenced transaction, so that a [Fenced] error can be told apart from others.
+fn fenced_result(result: Result<(), TransactionError<Fenced>>) -> StoreResult<()> {
+    match result {
+        Ok(()) => Ok(()),
+        Err(TransactionError::Abort(fenced)) => Err(fenced.into()),
+        Err(TransactionError::Storage(err)) => Err(err.into()),
+    }
+}
+
+/// The prefix of the keys of a game's journal entries, which are followed by the big-endian sequence number.
+fn journal_prefix(id: &str) -> Vec<u8> {
+    let mut prefix = id.as_bytes().to_vec();
+    prefix.push(0);
+    prefix
+}
+
+fn decode_key(key: &[u8]) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&key[..key.len().min(8)]);
+    u64::from_be_bytes(bytes)
+}
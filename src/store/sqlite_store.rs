@@ -0,0 +1,346 @@
+use super::{Fence, Fenced, GameStore, QuarantinedGame, SessionOwner, StoreResult};
+use chrono::{TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Stores games in an SQLite database.
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    pub fn open(path: &str) -> StoreResult<Self> {
+        let conn = Connection::open(path)?;
+        // The database may be shared by several servers in distributed mode
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS games (
+                id TEXT PRIMARY KEY,
+                game BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS journal (
+                game_id TEXT NOT NULL,
+                seq INTEGER NOT NULL,
+                entry BLOB NOT NULL,
+                PRIMARY KEY (game_id, seq)
+            );
+            CREATE TABLE IF NOT EXISTS owners (
+                game_id TEXT PRIMARY KEY,
+                node TEXT NOT NULL,
+                address TEXT NOT NULL,
+                expires INTEGER NOT NULL,
+                epoch INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE TABLE IF NOT EXISTS archive (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                entry BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS quarantine (
+                game_id TEXT PRIMARY KEY,
+                reason TEXT NOT NULL,
+                quarantined INTEGER NOT NULL,
+                record BLOB NOT NULL
+            );",
+        )?;
+        // Databases created before sessions were fenced lack the epoch column
+        let fenced: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('owners') WHERE name = 'epoch'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !fenced {
+            conn.execute("ALTER TABLE owners ADD COLUMN epoch INTEGER NOT NULL DEFAULT 0", [])?;
+        }
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl GameStore for SqliteStore {
+    fn games(&self) -> StoreResult<Vec<(String, Vec<u8>)>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT id, game FROM games")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn game(&self, id: &str) -> StoreResult<Option<Vec<u8>>> {
+        let game = self
+            .conn()
+            .query_row("SELECT game FROM games WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()?;
+        Ok(game)
+    }
+
+    fn put_game(&self, id: &str, game: &[u8], fence: Option<&Fence>, journal_before: u64) -> StoreResult<()> {
+        let journal_before = i64::try_from(journal_before).unwrap_or(i64::MAX);
+        let mut conn = self.conn();
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        check_fence(&tx, id, fence)?;
+        tx.execute(
+            "INSERT INTO games (id, game) VALUES (?1, ?2) ON CONFLICT (id) DO UPDATE SET game = excluded.game",
+            params![id, game],
+        )?;
+        tx.execute(
+            "DELETE FROM journal WHERE game_id = ?1 AND seq < ?2",
+            params![id, journal_before],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn remove_game(&self, id: &str, fence: Option<&Fence>) -> StoreResult<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        check_fence(&tx, id, fence)?;
+        tx.execute("DELETE FROM games WHERE id = ?1", params![id])?;
+        tx.execute("DELETE FROM owners WHERE game_id = ?1", params![id])?;
+        tx.execute("DELETE FROM journal WHERE game_id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn quarantine_game(&self, id: &str, reason: &str) -> StoreResult<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO quarantine (game_id, reason, quarantined, record)
+            SELECT id, ?2, ?3, game FROM games WHERE id = ?1",
+            params![id, reason, Utc::now().timestamp_millis()],
+        )?;
+        tx.execute("DELETE FROM games WHERE id = ?1", params![id])?;
+        tx.execute("DELETE FROM owners WHERE game_id = ?1", params![id])?;
+        tx.execute("DELETE FROM journal WHERE game_id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn quarantined(&self) -> StoreResult<Vec<QuarantinedGame>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT game_id, reason, quarantined, record FROM quarantine ORDER BY game_id")?;
+        let rows = stmt.query_map([], |row| {
+            let quarantined = row.get(2)?;
+            Ok(QuarantinedGame {
+                id: row.get(0)?,
+                reason: row.get(1)?,
+                quarantined: Utc
+                    .timestamp_millis_opt(quarantined)
+                    .single()
+                    .ok_or(rusqlite::Error::IntegralValueOutOfRange(2, quarantined))?,
+                record: row.get(3)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn remove_quarantined(&self, id: &str) -> StoreResult<()> {
+        self.conn()
+            .execute("DELETE FROM quarantine WHERE game_id = ?1", params![id])?;
+        Ok(())
+    }
+
+    fn append_journal(&self, id: &str, seq: u64, entry: &[u8], fence: Option<&Fence>) -> StoreResult<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        check_fence(&tx, id, fence)?;
+        tx.execute(
+            "INSERT OR REPLACE INTO journal (game_id, seq, entry) VALUES (?1, ?2, ?3)",
+            params![id, seq as i64, entry],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn journal(&self, id: &str) -> StoreResult<Vec<(u64, Vec<u8>)>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT seq, entry FROM journal WHERE game_id = ?1 ORDER BY seq")?;
+        let rows = stmt.query_map(params![id], |row| Ok((row.get::<_, i64>(0)? as u64, row.get(1)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn session_owner(&self, id: &str) -> StoreResult<Option<SessionOwner>> {
+        Ok(read_owner(&self.conn(), id)?)
+    }
+
+    fn claim_session(&self, id: &str, owner: &SessionOwner) -> StoreResult<SessionOwner> {
+        let mut conn = self.conn();
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let owner = match read_owner(&tx, id)? {
+            Some(current) if !current.can_claim(&owner.node) => return Ok(current),
+            Some(current) => current.claimed_by(owner),
+            None => SessionOwner { epoch: 0, ..owner.clone() },
+        };
+        tx.execute(
+            "INSERT OR REPLACE INTO owners (game_id, node, address, expires, epoch) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                id,
+                owner.node,
+                owner.address,
+                owner.expires.timestamp_millis(),
+                owner.epoch as i64
+            ],
+        )?;
+        tx.commit()?;
+        Ok(owner)
+    }
+
+    fn release_session(&self, id: &str, node: &str) -> StoreResult<()> {
+        self.conn().execute(
+            "UPDATE owners SET expires = ?3 WHERE game_id = ?1 AND node = ?2",
+            params![id, node, Utc::now().timestamp_millis()],
+        )?;
+        Ok(())
+    }
+
+    fn archive(&self, entry: &[u8]) -> StoreResult<u64> {
+        let conn = self.conn();
+        conn.execute("INSERT INTO archive (entry) VALUES (?1)", params![entry])?;
+        Ok(conn.last_insert_rowid() as u64)
+    }
+
+    fn archived(&self, start: u64, limit: usize) -> StoreResult<Vec<(u64, Vec<u8>)>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT id, entry FROM archive WHERE id >= ?1 ORDER BY id LIMIT ?2")?;
+        let start = i64::try_from(start).unwrap_or(i64::MAX);
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let rows = stmt.query_map(params![start, limit], |row| {
+            Ok((row.get::<_, i64>(0)? as u64, row.get(1)?))
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn last_archived(&self) -> StoreResult<Option<u64>> {
+        let id = self
+            .conn()
+            .query_row("SELECT MAX(id) FROM archive", [], |row| row.get::<_, Option<i64>>(0))
+            .optional()?;
+        Ok(id.flatten().map(|id| id as u64))
+    }
+
+    fn remove_archived(&self, id: u64) -> StoreResult<()> {
+        self.conn()
+            .execute("DELETE FROM archive WHERE id = ?1", params![id as i64])?;
+        Ok(())
+    }
+}
+
+/// Fails with [Fenced] if the session is not held under the given fence.
+fn check_fence(conn: &Connection, id: &str, fence: Option<&Fence>) -> StoreResult<()> {
+    match fence {
+        Some(fence) if !fence.holds(read_owner(conn, id)?.as_ref()) => Err(Fenced.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Reads the owner of a session.
+fn read_owner(conn: &Connection, id: &str) -> rusqlite::Result<Option<SessionOwner>> {
+    conn.query_row(
+        "SELECT node, address, expires, epoch FROM owners WHERE game_id = ?1",
+        params![id],
+        |row| {
+            let expires = row.get(2)?;
+            Ok(SessionOwner {
+                node: row.get(0)?,
+                address: row.get(1)?,
+                expires: Utc
+                    .timestamp_millis_opt(expires)
+                    .single()
+                    .ok_or(rusqlite::Error::IntegralValueOutOfRange(2, expires))?,
+                epoch: row.get::<_, i64>(3)? as u64,
+            })
+        },
+    )
+    .optional()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn archive_ids_are_not_reused() {
+        let store = SqliteStore::open(":memory:").unwrap();
+        assert_eq!(store.archive(b"a").unwrap(), 1);
+        assert_eq!(store.archive(b"b").unwrap(), 2);
+        store.remove_archived(2).unwrap();
+        assert_eq!(store.archive(b"c").unwrap(), 3);
+
+        let archived = store.archived(2, 10).unwrap();
+        assert_eq!(archived, vec![(3, b"c".to_vec())]);
+        assert_eq!(store.last_archived().unwrap(), Some(3));
+    }
+
+    #[test]
+    fn journal_is_ordered_and_cleared_with_game() {
+        let store = SqliteStore::open(":memory:").unwrap();
+        store.put_game("ABCD", b"{}", None, 0).unwrap();
+        store.append_journal("ABCD", 1, b"second", None).unwrap();
+        store.append_journal("ABCD", 0, b"first", None).unwrap();
+        store.append_journal("EFGH", 0, b"other", None).unwrap();
+        assert_eq!(
+            store.journal("ABCD").unwrap(),
+            vec![(0, b"first".to_vec()), (1, b"second".to_vec())]
+        );
+        store.put_game("ABCD", b"{}", None, 1).unwrap();
+        assert_eq!(store.journal("ABCD").unwrap(), vec![(1, b"second".to_vec())]);
+
+        store.remove_game("ABCD", None).unwrap();
+        assert_eq!(store.journal("ABCD").unwrap(), vec![]);
+        assert_eq!(store.journal("EFGH").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn sessions_can_only_be_claimed_once_lease_expires() {
+        let store = SqliteStore::open(":memory:").unwrap();
+        let owner = |node: &str| SessionOwner {
+            node: node.into(),
+            address: format!("ws://{}", node),
+            expires: Utc::now() + chrono::Duration::seconds(60),
+            epoch: 0,
+        };
+        let claim = |node: &str| store.claim_session("ABCD", &owner(node)).unwrap().node;
+
+        assert_eq!(claim("a"), "a");
+        assert_eq!(claim("b"), "a");
+
+        store.release_session("ABCD", "b").unwrap();
+        assert_eq!(claim("b"), "a");
+
+        store.release_session("ABCD", "a").unwrap();
+        assert_eq!(claim("b"), "b");
+    }
+
+    #[test]
+    fn writes_are_fenced_once_a_session_changes_hands() {
+        let store = SqliteStore::open(":memory:").unwrap();
+        let owner = |node: &str, lease: i64| SessionOwner {
+            node: node.into(),
+            address: format!("ws://{}", node),
+            expires: Utc::now() + chrono::Duration::seconds(lease),
+            epoch: 0,
+        };
+
+        // The first node's lease has already lapsed, so the second can take the session over
+        let a = store.claim_session("ABCD", &owner("a", -1)).unwrap();
+        store.put_game("ABCD", b"a", Some(&a.fence()), 0).unwrap();
+        let b = store.claim_session("ABCD", &owner("b", 60)).unwrap();
+        assert_eq!(b.epoch, a.epoch + 1);
+        store.append_journal("ABCD", 0, b"b", Some(&b.fence())).unwrap();
+
+        // A fenced write leaves the journal alone, as it is cleared in the same transaction
+        let fenced = |result: StoreResult<()>| result.unwrap_err().is::<Fenced>();
+        assert!(fenced(store.put_game("ABCD", b"stale", Some(&a.fence()), u64::MAX)));
+        assert!(fenced(store.append_journal("ABCD", 1, b"stale", Some(&a.fence()))));
+        assert!(fenced(store.remove_game("ABCD", Some(&a.fence()))));
+        assert_eq!(store.journal("ABCD").unwrap(), vec![(0, b"b".to_vec())]);
+
+        store.put_game("ABCD", b"b", Some(&b.fence()), 1).unwrap();
+        assert_eq!(store.journal("ABCD").unwrap(), vec![]);
+        assert_eq!(store.game("ABCD").unwrap(), Some(b"b".to_vec()));
+        assert_eq!(store.claim_session("ABCD", &owner("b", 60)).unwrap().epoch, b.epoch);
+    }
+}
@@ -0,0 +1,183 @@
+use super::{record, Fence, GameStore};
+use std::collections::hash_map::{Entry, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+/// Writes snapshots of games to the store on a dedicated thread, so that sessions never wait on the store.
+/// When several snapshots of the same game are waiting to be written, only the latest is written.
+#[derive(Clone)]
+pub struct SnapshotWriter {
+    store: Arc<dyn GameStore>,
+    /// The queue of snapshots waiting to be written, or `None` if snapshots are written on the calling thread.
+    queue: Option<mpsc::Sender<Snapshot>>,
+    /// Hands out the position of each snapshot, in the order they were taken.
+    tickets: Arc<AtomicU64>,
+    /// The largest a record may be, in bytes.
+    max_record_size: usize,
+}
+
+/// A serialized game, to be written to the store.
+pub struct Snapshot {
+    pub game_id: String,
+    pub game: Vec<u8>,
+    /// The position of the snapshot, as reserved with [SnapshotWriter::ticket].
+    pub ticket: u64,
+    /// The sequence number of the first journal entry the snapshot does not cover.
+    /// Entries before it are cleared in the same transaction as the snapshot is written.
+    pub journal_seq: u64,
+    /// The claim under which the game is written, when running in distributed mode.
+    pub fence: Option<Fence>,
+    /// Where to report whether the snapshot was written.
+    pub report: mpsc::Sender<WriteResult>,
+}
+
+/// The outcome of writing a snapshot, with the error described as a message if it failed.
+pub type WriteResult = Result<(), String>;
+
+/// How long the writer remembers the last snapshot it wrote of a game, so it can discard any older one
+/// which reaches it late. Snapshots are only ever reordered by a few moments.
+const REORDER_WINDOW: Duration = Duration::from_secs(60);
+
+impl SnapshotWriter {
+    /// Creates a writer which writes snapshots on the calling thread, whatever their size.
+    pub fn direct(store: Arc<dyn GameStore>) -> Self {
+        Self {
+            store,
+            queue: None,
+            tickets: Arc::default(),
+            max_record_size: usize::MAX,
+        }
+    }
+
+    /// Creates a writer with its own thread, which runs for as long as the writer or any of its clones are alive.
+    /// Snapshots which are larger than `max_record_size` bytes once encoded are not written.
+    pub fn spawn(store: Arc<dyn GameStore>, max_record_size: usize) -> Self {
+        let (queue, snapshots) = mpsc::channel();
+        let thread_store = store.clone();
+        std::thread::Builder::new()
+            .name("snapshot-writer".into())
+            .spawn(move || run(thread_store.as_ref(), snapshots, max_record_size))
+            .expect("writer thread can be spawned");
+        Self {
+            store,
+            queue: Some(queue),
+            tickets: Arc::default(),
+            max_record_size,
+        }
+    }
+
+    /// Reserves the position of a snapshot which is about to be taken.
+    /// A snapshot is never written over one with a later ticket.
+    pub fn ticket(&self) -> u64 {
+        self.tickets.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Queues a snapshot to be written, reporting the outcome once it has been.
+    pub fn write(&self, snapshot: Snapshot) {
+        let snapshot = match &self.queue {
+            Some(queue) => match queue.send(snapshot) {
+                Ok(()) => return,
+                Err(mpsc::SendError(snapshot)) => snapshot,
+            },
+            None => snapshot,
+        };
+        let result = write(self.store.as_ref(), &snapshot, self.max_record_size);
+        snapshot.report.send(result).ok();
+    }
+
+    /// Writes a snapshot, waiting until it has been written.
+    pub fn write_now(&self, mut snapshot: Snapshot) -> WriteResult {
+        let (report, outcome) = mpsc::channel();
+        let caller = std::mem::replace(&mut snapshot.report, report);
+        self.write(snapshot);
+        let result = outcome.recv().unwrap_or_else(|_| Err("snapshot writer stopped".into()));
+        caller.send(result.clone()).ok();
+        result
+    }
+}
+
+/// Writes snapshots as they arrive, coalescing those which queued up while the last batch was being written.
+fn run(store: &dyn GameStore, snapshots: mpsc::Receiver<Snapshot>, max_record_size: usize) {
+    let mut written = HashMap::<String, (u64, Instant)>::new();
+    while let Ok(first) = snapshots.recv() {
+        let mut batch = HashMap::<String, (Snapshot, Vec<mpsc::Sender<WriteResult>>)>::new();
+        for snapshot in std::iter::once(first).chain(snapshots.try_iter()) {
+            match batch.entry(snapshot.game_id.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert((snapshot, vec![]));
+                }
+                Entry::Occupied(mut entry) => {
+                    let (latest, coalesced) = entry.get_mut();
+                    let older = match snapshot.ticket > latest.ticket {
+                        true => std::mem::replace(latest, snapshot),
+                        false => snapshot,
+                    };
+                    coalesced.push(older.report);
+                }
+            }
+        }
+
+        written.retain(|_, (_, at)| at.elapsed() < REORDER_WINDOW);
+        for (game_id, (snapshot, coalesced)) in batch {
+            let stale = written
+                .get(&game_id)
+                .is_some_and(|(ticket, _)| *ticket > snapshot.ticket);
+            let result = match stale {
+                true => Ok(()),
+                false => write(store, &snapshot, max_record_size),
+            };
+            if result.is_ok() && !stale {
+                written.insert(game_id, (snapshot.ticket, Instant::now()));
+            }
+            for report in coalesced.into_iter().chain([snapshot.report]) {
+                report.send(result.clone()).ok();
+            }
+        }
+    }
+}
+
+/// Encodes and writes a snapshot, and clears the journal entries it covers.
+fn write(store: &dyn GameStore, snapshot: &Snapshot, max_record_size: usize) -> WriteResult {
+    let record = record::encode(&snapshot.game, max_record_size).map_err(|err| err.to_string())?;
+    store
+        .put_game(
+            &snapshot.game_id,
+            &record,
+            snapshot.fence.as_ref(),
+            snapshot.journal_seq,
+        )
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::store::SqliteStore;
+
+    #[test]
+    fn older_snapshots_never_overwrite_newer_ones() {
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        store.append_journal("ABCD", 0, b"covered", None).unwrap();
+        store.append_journal("ABCD", 1, b"pending", None).unwrap();
+        let writer = SnapshotWriter::spawn(store.clone(), 1024);
+        let (report, results) = mpsc::channel();
+        let snapshot = |game: &[u8], ticket| Snapshot {
+            game_id: "ABCD".into(),
+            game: game.to_vec(),
+            ticket,
+            journal_seq: 1,
+            fence: None,
+            report: report.clone(),
+        };
+
+        let (older, newer) = (writer.ticket(), writer.ticket());
+        writer.write(snapshot(b"newer", newer));
+        writer.write(snapshot(b"older", older));
+        assert_eq!(results.recv().unwrap(), Ok(()));
+        assert_eq!(results.recv().unwrap(), Ok(()));
+        let game = store.game("ABCD").unwrap().unwrap();
+        assert_eq!(record::decode(&game).unwrap(), b"newer".as_slice());
+        assert_eq!(store.journal("ABCD").unwrap(), vec![(1, b"pending".to_vec())]);
+    }
+}
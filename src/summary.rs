@@ -0,0 +1,150 @@
+//! Renders archived games as compact summaries, laid out to suit the embeds of chat services such as Discord.
+
+use crate::game::{Party, WinCondition, WinStatus};
+use crate::session::GameStats;
+use serde::Serialize;
+
+/// A summary of a finished game.
+#[derive(Clone, Serialize, Debug)]
+pub struct GameSummary {
+    /// A stable ID for the archived game, which can be used to link back to it.
+    pub permalink: String,
+    pub title: String,
+    /// Which side won the game.
+    pub winner: String,
+    /// How the game was won.
+    pub description: String,
+    /// The colour of the winning party, as an RGB value.
+    pub color: u32,
+    /// How long the game took, in minutes.
+    pub duration_mins: i64,
+    pub fields: Vec<SummaryField>,
+}
+
+/// A titled section of a summary.
+#[derive(Clone, Serialize, Debug)]
+pub struct SummaryField {
+    pub name: String,
+    pub value: String,
+    /// Whether the field is short enough to be shown alongside others.
+    pub inline: bool,
+}
+
+/// Summarises the archived game with the given archive ID.
+pub fn summarise(archive_id: u64, stats: &GameStats) -> GameSummary {
+    let winner = winning_party(stats.outcome);
+    let duration_mins = (stats.finished - stats.started).num_minutes();
+
+    let mut fields = vec![
+        field("Players", stats.players.len().to_string(), true),
+        field("Duration", format!("{} min", duration_mins), true),
+        field(
+            "Policies",
+            format!(
+                "{} liberal, {} fascist, {} communist",
+                stats.policies.liberal, stats.policies.fascist, stats.policies.communist
+            ),
+            true,
+        ),
+    ];
+    if !stats.roles.is_empty() {
+        let roles = stats
+            .players
+            .iter()
+            .zip(&stats.roles)
+            .enumerate()
+            .map(|(i, (name, role))| match stats.results.get(i) {
+                Some(WinStatus::Won) => format!("{} ({}, won)", name, role),
+                _ => format!("{} ({})", name, role),
+            });
+        fields.push(field("Roles", roles.collect::<Vec<_>>().join("\n"), false));
+    }
+    if !stats.executed.is_empty() {
+        fields.push(field("Executed", stats.executed.join(", "), false));
+    }
+    if !stats.substitutions.is_empty() {
+        let subs = stats
+            .substitutions
+            .iter()
+            .map(|sub| format!("{} replaced {}", sub.name, sub.previous_name));
+        fields.push(field("Substitutions", subs.collect::<Vec<_>>().join("\n"), false));
+    }
+
+    GameSummary {
+        permalink: format!("{}-{}", stats.id, archive_id),
+        title: format!("Game {}", stats.id),
+        winner: match stats.outcome {
+            WinCondition::HitlerExecuted => "Liberals and communists win".to_string(),
+            WinCondition::AnarchistChaos => "The anarchist wins".to_string(),
+            _ => format!("{}s win", winner),
+        },
+        description: describe(stats.outcome).to_string(),
+        color: match winner {
+            Party::Liberal => 0x3b82c4,
+            Party::Fascist => 0xc0392b,
+            Party::Communist => 0x7b1e1e,
+        },
+        duration_mins,
+        fields,
+    }
+}
+
+fn field(name: &str, value: String, inline: bool) -> SummaryField {
+    SummaryField { name: name.to_string(), value, inline }
+}
+
+/// Gets the party which won through the given condition.
+fn winning_party(outcome: WinCondition) -> Party {
+    match outcome {
+        WinCondition::LiberalPolicyTrack | WinCondition::HitlerExecuted => Party::Liberal,
+        WinCondition::FascistPolicyTrack | WinCondition::HitlerChancellor => Party::Fascist,
+        WinCondition::CommunistPolicyTrack | WinCondition::CapitalistExecuted | WinCondition::AnarchistChaos => {
+            Party::Communist
+        }
+    }
+}
+
+fn describe(outcome: WinCondition) -> &'static str {
+    match outcome {
+        WinCondition::LiberalPolicyTrack => "The liberal policy track was completed.",
+        WinCondition::FascistPolicyTrack => "The fascist policy track was completed.",
+        WinCondition::CommunistPolicyTrack => "The communist policy track was completed.",
+        WinCondition::HitlerChancellor => "Hitler was elected chancellor.",
+        WinCondition::HitlerExecuted => "Hitler was executed.",
+        WinCondition::CapitalistExecuted => "The capitalist was executed.",
+        WinCondition::AnarchistChaos => "The anarchist thrived on chaos.",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::Role;
+    use crate::session::PolicyCounts;
+
+    #[test]
+    fn summarises_archived_game() {
+        let started = chrono::offset::Utc::now();
+        let stats = GameStats {
+            id: "ABCD".into(),
+            players: vec!["ALEX".into(), "BOB".into()],
+            started,
+            finished: started + chrono::Duration::minutes(42),
+            outcome: WinCondition::HitlerChancellor,
+            secondary_conditions: vec![],
+            substitutions: vec![],
+            results: vec![WinStatus::Won, WinStatus::Lost],
+            roles: vec![Role::Hitler, Role::Liberal],
+            executed: vec!["BOB".into()],
+            policies: PolicyCounts { liberal: 2, fascist: 4, communist: 0 },
+            chat: vec![],
+        };
+        let summary = summarise(7, &stats);
+        assert_eq!(summary.permalink, "ABCD-7");
+        assert_eq!(summary.winner, "Fascists win");
+        assert_eq!(summary.duration_mins, 42);
+        let roles = summary.fields.iter().find(|f| f.name == "Roles").unwrap();
+        assert_eq!(roles.value, "ALEX (Hitler, won)\nBOB (Liberal)");
+        assert!(summary.fields.iter().any(|f| f.name == "Executed" && f.value == "BOB"));
+    }
+}
@@ -0,0 +1,108 @@
+//! Outbound webhooks, which announce changes in games' lifecycles to other services such as chat bots.
+
+use crate::session::GameStats;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+
+/// How long a webhook has to respond before the request is abandoned.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A change in a game's lifecycle, as announced to webhooks.
+#[derive(Clone, Copy, Serialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    GameCreated,
+    GameStarted,
+    GameFinished,
+    GameArchived,
+}
+
+/// The body of a webhook request.
+#[derive(Serialize, Debug)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub game_id: String,
+    pub sent: DateTime<Utc>,
+    /// The statistics of the game, once it has finished.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<GameStats>,
+}
+
+/// The webhooks which are notified of every game's lifecycle events.
+#[derive(Default)]
+pub struct Webhooks {
+    urls: Vec<String>,
+    client: reqwest::Client,
+}
+
+impl Webhooks {
+    pub fn new(urls: Vec<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { urls, client }
+    }
+
+    /// Sends the event to every webhook in the background, logging any which fail.
+    pub fn fire(&self, event: WebhookEvent, game_id: &str, stats: Option<GameStats>) {
+        if self.urls.is_empty() {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            log::warn!("Cannot fire webhooks outside of a runtime: {}", game_id);
+            return;
+        };
+        let payload = WebhookPayload {
+            event,
+            game_id: game_id.to_string(),
+            sent: chrono::offset::Utc::now(),
+            stats,
+        };
+        let payload = match serde_json::to_vec(&payload) {
+            Ok(payload) => payload,
+            Err(err) => return log::error!("Cannot serialize webhook payload: {}", err),
+        };
+        for url in &self.urls {
+            let request = self
+                .client
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(payload.clone());
+            let url = url.clone();
+            runtime.spawn(async move {
+                match request.send().await.and_then(|res| res.error_for_status()) {
+                    Ok(_) => {}
+                    Err(err) => log::error!("Webhook failed: {}: {}", url, err),
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn events_are_posted() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        Webhooks::new(vec![url]).fire(WebhookEvent::GameStarted, "ABCD", None);
+
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![];
+        while !String::from_utf8_lossy(&request).contains("\"game_id\":\"ABCD\"") {
+            let mut buf = [0; 1024];
+            let n = socket.read(&mut buf).await.unwrap();
+            assert!(n > 0);
+            request.extend_from_slice(&buf[..n]);
+        }
+        socket.write_all(b"HTTP/1.1 204 No Content\r\n\r\n").await.unwrap();
+        let request = String::from_utf8(request).unwrap();
+        assert!(request.starts_with("POST /hook"));
+        assert!(request.contains("\"event\":\"game_started\""));
+    }
+}
@@ -1,54 +1,201 @@
 use crate::{
-    client::{BoardAction, Client, PlayerAction},
+    action::{BoardAction, PlayerAction},
+    admin,
+    client::{Client, ClientEvent},
     error::GameError,
-    game::GameOptions,
+    game::{ChatScope, GameOptions, View},
     session::SessionManager,
 };
-use futures_util::{select, FutureExt, SinkExt, StreamExt, TryStreamExt};
+use chrono::{DateTime, Utc};
+use futures_util::{select, FutureExt, Sink, SinkExt, Stream, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
+use std::net::IpAddr;
+use std::time::Instant;
 use tokio::net::TcpStream;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::header::{HeaderValue, AUTHORIZATION, SEC_WEBSOCKET_PROTOCOL};
+use tokio_tungstenite::tungstenite::http::StatusCode;
 use tokio_tungstenite::tungstenite::Message;
 
+/// The header in which a reverse proxy gives the address of the client it is forwarding.
+pub const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// The format in which messages are exchanged with a client.
+/// JSON is the default, and a client may ask for MessagePack by offering the "msgpack" websocket subprotocol.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum WireFormat {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl WireFormat {
+    /// Chooses the format to use, given the subprotocols offered by the client.
+    pub fn negotiate(protocols: Option<&str>) -> Self {
+        let mut offered = protocols.into_iter().flat_map(|p| p.split(',')).map(str::trim);
+        match offered.any(|p| p == "msgpack") {
+            true => WireFormat::MessagePack,
+            false => WireFormat::Json,
+        }
+    }
+
+    /// The subprotocol the server accepts for this format, if any.
+    pub fn protocol(self) -> Option<&'static str> {
+        match self {
+            WireFormat::Json => None,
+            WireFormat::MessagePack => Some("msgpack"),
+        }
+    }
+
+    /// Encodes a message to be sent to the client.
+    pub fn encode(self, value: &Value) -> Message {
+        match self {
+            WireFormat::Json => Message::Text(value.to_string()),
+            WireFormat::MessagePack => {
+                Message::Binary(rmp_serde::to_vec_named(value).expect("JSON values can be encoded as MessagePack"))
+            }
+        }
+    }
+
+    /// Decodes a message received from the client, returning `None` if it is not valid in this format.
+    pub fn decode<T: DeserializeOwned>(self, msg: &Message) -> Option<T> {
+        let value = match (self, msg) {
+            (WireFormat::Json, Message::Text(text)) => serde_json::from_str::<Value>(text).ok()?,
+            (WireFormat::MessagePack, Message::Binary(bytes)) => rmp_serde::from_slice::<Value>(bytes).ok()?,
+            _ => return None,
+        };
+        serde_json::from_value(value).ok()
+    }
+}
+
+/// Accepts a connection on the legacy websocket port, which serves nothing but websockets.
+/// The API server accepts the same connections under `/ws`, so that both can share one port.
 pub async fn accept_connection(stream: TcpStream, manager: &SessionManager) {
     log::info!("Accepted new connection");
+    let peer = stream.peer_addr().ok().map(|addr| addr.ip());
 
-    let Ok(stream) = tokio_tungstenite::accept_async(stream).await else {
+    let mut format = WireFormat::Json;
+    let mut watch_events = false;
+    let mut forwarded_for = None;
+    // The callback's signature is dictated by tungstenite, error response and all
+    #[allow(clippy::result_large_err)]
+    let negotiate = |req: &Request, mut res: Response| -> Result<Response, ErrorResponse> {
+        if req.uri().path() == ADMIN_EVENTS_PATH {
+            if !admin::is_authorized(manager.config(), admin_token(req)) {
+                let mut res = ErrorResponse::new(None);
+                *res.status_mut() = StatusCode::UNAUTHORIZED;
+                return Err(res);
+            }
+            watch_events = true;
+        }
+        forwarded_for = (req.headers().get(X_FORWARDED_FOR))
+            .and_then(|h| h.to_str().ok())
+            .map(str::to_string);
+        let offered = req.headers().get(SEC_WEBSOCKET_PROTOCOL).and_then(|h| h.to_str().ok());
+        format = WireFormat::negotiate(offered);
+        if let Some(protocol) = format.protocol() {
+            res.headers_mut()
+                .insert(SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static(protocol));
+        }
+        Ok(res)
+    };
+    let Ok(stream) = tokio_tungstenite::accept_hdr_async(stream, negotiate).await else {
         log::error!("Error occured during websocket handshake");
         return;
     };
+    if watch_events {
+        return stream_events(stream, manager).await;
+    }
+    let address = peer.map(|peer| manager.config().client_address(peer, forwarded_for.as_deref()));
+    serve_client(stream, format, address, None, manager).await
+}
+
+/// Exchanges messages with a game client, which connected from the given address if it is known,
+/// until it disconnects. A client which connected to a particular game follows it as an overlay until it joins it.
+pub async fn serve_client<S, E>(
+    stream: S,
+    format: WireFormat,
+    address: Option<IpAddr>,
+    game_id: Option<String>,
+    manager: &SessionManager,
+) where
+    S: Stream<Item = Result<Message, E>> + Sink<Message> + Unpin,
+{
     let (mut write, read) = stream.split();
     let mut read = read.fuse();
 
     let mut client = Client::new(manager);
+    if let Some(address) = address {
+        client.set_address(address);
+    }
+    if let Some(game_id) = game_id {
+        if let Some(reply) = reply(
+            client
+                .join_as_overlay(&game_id, View::Overlay, None)
+                .await
+                .map(|_| None),
+        ) {
+            write.send(format.encode(&reply)).await.ok();
+        }
+    }
+    let config = manager.config();
+    let mut ping = tokio::time::interval(config.ping_interval);
+    let mut last_seen = Instant::now();
 
     loop {
         select! {
             msg = read.try_next() => {
-                let Ok(Some(Message::Text(msg))) = msg else {
+                let Ok(Some(msg)) = msg else {
                     break;
                 };
-                let Ok(msg) = serde_json::from_str::<WsRequest>(&msg) else {
+                if msg.is_close() {
+                    break;
+                }
+                let Some(msg) = format.decode::<WsRequest>(&msg) else {
                     log::error!("Cannot parse message: {}", &msg);
                     break;
                 };
-                match process_request(msg, &mut client) {
-                    Ok(()) => {},
-                    Err(err) => {
-                        let reply = json!({
-                            "type": "error",
-                            "error": err.to_string()
-                        });
-                        write.send(Message::Text(reply.to_string())).await.ok();
-                    }
+                last_seen = Instant::now();
+                let result = process_request(msg, &mut client).await;
+                client.heartbeat();
+                if let Some(reply) = reply(result) {
+                    write.send(format.encode(&reply)).await.ok();
                 }
             },
-            state = client.next_state().fuse() => {
-                let msg = json!({
-                    "type": "update",
-                    "state": state
-                });
-                if write.send(Message::Text(msg.to_string())).await.is_err() {
+            event = client.next_event().fuse() => {
+                let msg = match event {
+                    ClientEvent::Update(state) => json!({
+                        "type": "update",
+                        "state": state
+                    }),
+                    ClientEvent::Patch(patch) => json!({
+                        "type": "patch",
+                        "patch": patch
+                    }),
+                    ClientEvent::Chat(message) => json!({
+                        "type": "chat",
+                        "message": message
+                    }),
+                    ClientEvent::Lobbies(lobbies) => json!({
+                        "type": "lobbies",
+                        "lobbies": lobbies
+                    }),
+                };
+                if write.send(format.encode(&msg)).await.is_err() {
+                    log::error!("Could not send websockets message");
+                    break;
+                }
+            },
+            _ = ping.tick().fuse() => {
+                if last_seen.elapsed() > config.connection_timeout {
+                    log::info!("Closing stale connection");
+                    break;
+                }
+                let msg = json!({ "type": "ping" });
+                if write.send(format.encode(&msg)).await.is_err() {
                     log::error!("Could not send websockets message");
                     break;
                 }
@@ -57,42 +204,310 @@ pub async fn accept_connection(stream: TcpStream, manager: &SessionManager) {
     }
 }
 
+/// Gets the reply to send to the client for the outcome of its request, if there is one.
+fn reply(result: Result<Option<Value>, GameError>) -> Option<Value> {
+    match result {
+        Ok(reply) => reply,
+        Err(GameError::HostedElsewhere { address }) => Some(json!({
+            "type": "redirect",
+            "address": address
+        })),
+        Err(err) => {
+            let mut reply = json!({
+                "type": "error",
+                "error": err.to_string(),
+                "code": err.code()
+            });
+            if let GameError::WrongPhase { expected, actual } = &err {
+                reply["expected"] = json!(expected);
+                reply["actual"] = json!(actual);
+            }
+            Some(reply)
+        }
+    }
+}
+
+/// The path at which operators connect to watch the server's events.
+const ADMIN_EVENTS_PATH: &str = "/admin/logs";
+
+/// Gets the admin token presented with a request, either as a bearer token or in the `token` query parameter,
+/// as browsers cannot set headers on websocket requests.
+fn admin_token(req: &Request) -> Option<&str> {
+    let header = req.headers().get(AUTHORIZATION).and_then(|h| h.to_str().ok());
+    find_admin_token(header, req.uri().query())
+}
+
+/// Gets the admin token from a request's `Authorization` header or its query string.
+pub fn find_admin_token<'a>(authorization: Option<&'a str>, query: Option<&'a str>) -> Option<&'a str> {
+    let query = query.into_iter().flat_map(|q| q.split('&'));
+    authorization
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .or_else(|| query.filter_map(|pair| pair.strip_prefix("token=")).next())
+}
+
+/// Streams the server's events to an operator until they disconnect.
+pub async fn stream_events<S, E>(stream: S, manager: &SessionManager)
+where
+    S: Stream<Item = Result<Message, E>> + Sink<Message> + Unpin,
+{
+    log::info!("Operator is watching server events");
+    let (mut write, read) = stream.split();
+    let mut read = read.fuse();
+    let mut events = manager.subscribe_events();
+
+    loop {
+        let msg = select! {
+            msg = read.try_next() => match msg {
+                Ok(Some(msg)) if !msg.is_close() => continue,
+                _ => break,
+            },
+            event = events.recv().fuse() => match event {
+                Ok(event) => json!(event),
+                Err(RecvError::Lagged(skipped)) => json!({ "type": "lagged", "skipped": skipped }),
+                Err(RecvError::Closed) => break,
+            },
+        };
+        if write.send(Message::Text(msg.to_string())).await.is_err() {
+            break;
+        }
+    }
+}
+
 /// A message sent by a game client to the server.
 #[derive(Serialize, Deserialize)]
 enum WsRequest {
-    CreateGame { options: GameOptions },
-    JoinAsBoard { game_id: String },
-    JoinAsPlayer { game_id: String, name: String },
+    CreateGame {
+        options: GameOptions,
+        /// When the game should start by itself, if it is to be scheduled.
+        #[serde(default)]
+        start_at: Option<DateTime<Utc>>,
+    },
+    /// Follows a game without taking part in it. By default the connection is a stream overlay,
+    /// which is sent only a minimal summary of the game, but it may instead ask to be shown the board,
+    /// for which it needs the board key.
+    JoinAsOverlay {
+        game_id: String,
+        #[serde(default = "overlay_view")]
+        view: View,
+        #[serde(default)]
+        board_key: Option<String>,
+    },
+    JoinAsBoard {
+        game_id: String,
+        #[serde(default)]
+        board_key: Option<String>,
+    },
+    JoinAsPlayer {
+        game_id: String,
+        name: String,
+        /// The token issued when the player first joined, if they are reclaiming their seat.
+        #[serde(default)]
+        token: Option<String>,
+    },
+    /// Changes the player's name, while the game is still in the lobby.
+    RenamePlayer {
+        name: String,
+    },
     LeaveGame,
+    /// Removes a player from the lobby, at the request of the board.
+    RemovePlayer {
+        name: String,
+    },
     StartGame,
+    /// Hands a player's seat over to a newcomer in a game in progress, at the request of the board.
+    SubstitutePlayer {
+        name: String,
+        new_name: String,
+    },
     BoardAction(BoardAction),
+    /// Asks to move a stuck game on with safe defaults, which the board must then confirm.
+    ForceAdvance,
+    /// Confirms the board's request to move the game on, provided the game has not changed since.
+    ConfirmForceAdvance,
+    /// Drops the board's request to move the game on.
+    CancelForceAdvance,
     PlayerAction(PlayerAction),
+    Undo,
+    SendChat {
+        scope: ChatScope,
+        text: String,
+    },
     Heartbeat,
+    /// The reply to a ping from the server.
+    Pong,
+    /// Asks the server to resend the full game state.
+    Resync,
+    /// Adds a player to a hotseat game.
+    AddHotseatPlayer {
+        name: String,
+    },
+    /// Confirms the player whose turn it is has the shared device, in a hotseat game.
+    HotseatReady,
+    /// An action performed by the player holding the shared device, in a hotseat game.
+    HotseatAction(PlayerAction),
+    /// Chooses whether the list of public lobbies is sent whenever it changes.
+    WatchLobbies {
+        enabled: bool,
+    },
+    /// Lets the connection waiting to claim the given player's seat take it over.
+    ApproveTakeover {
+        name: String,
+    },
+    /// Chooses whether updates are sent as patches against the previous state.
+    SetDiffs {
+        enabled: bool,
+    },
     EndGame,
+    /// Returns a finished game to the lobby with the same players, so they can play again.
+    RestartLobby,
+    /// Asks for a save code from which the game can be resumed on another server.
+    ExportGame,
+}
+
+/// The view of a connection following a game, unless it asks for another.
+fn overlay_view() -> View {
+    View::Overlay
 }
 
-/// Processes a request from the client.
-fn process_request(req: WsRequest, client: &mut Client) -> Result<(), GameError> {
+/// Processes a request from the client, returning the reply to send to it, if there is one.
+async fn process_request(req: WsRequest, client: &mut Client<'_>) -> Result<Option<Value>, GameError> {
     match req {
-        WsRequest::CreateGame { options } => {
-            let game_id = client.create_game(options)?;
-            client.join_as_board(&game_id)?;
+        WsRequest::CreateGame { options, start_at } => {
+            let (game_id, board_key) = client.create_game(options, start_at).await?;
+            client.join_as_board(&game_id, board_key.as_deref()).await?;
+        }
+        WsRequest::JoinAsOverlay { game_id, view, board_key } => {
+            client.join_as_overlay(&game_id, view, board_key.as_deref()).await?;
         }
-        WsRequest::JoinAsBoard { game_id } => {
-            client.join_as_board(&game_id)?;
+        WsRequest::JoinAsBoard { game_id, board_key } => {
+            client.join_as_board(&game_id, board_key.as_deref()).await?;
         }
-        WsRequest::JoinAsPlayer { game_id, name } => {
-            client.join_as_player(&game_id, &name)?;
+        WsRequest::JoinAsPlayer { game_id, name, token } => {
+            let token = client.join_as_player(&game_id, &name, token.as_deref()).await?;
+            return Ok(Some(json!({ "type": "token", "token": token })));
+        }
+        WsRequest::RenamePlayer { name } => {
+            let token = client.rename(&name).await?;
+            return Ok(Some(json!({ "type": "token", "token": token })));
         }
         WsRequest::LeaveGame => client.leave(),
-        WsRequest::StartGame => client.start_game()?,
+        WsRequest::RemovePlayer { name } => client.remove_player(&name).await?,
+        WsRequest::StartGame => client.start_game().await?,
+        WsRequest::SubstitutePlayer { name, new_name } => client.substitute_player(&name, &new_name).await?,
         WsRequest::BoardAction(action) => {
             // Explicitely ignore errors as they will occur when there is more than one game board.
-            client.board_action(action).ok();
+            client.board_action(action).await.ok();
+        }
+        WsRequest::ForceAdvance => {
+            client.request_force_advance().await?;
+            return Ok(Some(json!({ "type": "confirm_force_advance" })));
+        }
+        WsRequest::ConfirmForceAdvance => client.confirm_force_advance().await?,
+        WsRequest::CancelForceAdvance => client.cancel_force_advance(),
+        WsRequest::PlayerAction(action) => client.player_action(action).await?,
+        WsRequest::Undo => client.undo().await?,
+        WsRequest::SendChat { scope, text } => client.send_chat(scope, &text).await?,
+        WsRequest::EndGame => client.end_game().await?,
+        WsRequest::RestartLobby => client.restart_lobby().await?,
+        WsRequest::ExportGame => {
+            let code = client.save_code().await?;
+            return Ok(Some(json!({ "type": "save_code", "code": code })));
         }
-        WsRequest::PlayerAction(action) => client.player_action(action)?,
-        WsRequest::EndGame => client.end_game()?,
-        WsRequest::Heartbeat => client.heartbeat(),
+        WsRequest::Resync => client.resync(),
+        WsRequest::AddHotseatPlayer { name } => client.add_hotseat_player(&name).await?,
+        WsRequest::HotseatReady => client.hotseat_ready().await?,
+        WsRequest::HotseatAction(action) => client.hotseat_action(action).await?,
+        WsRequest::SetDiffs { enabled } => client.set_diffs(enabled),
+        WsRequest::ApproveTakeover { name } => client.approve_takeover(&name).await?,
+        WsRequest::WatchLobbies { enabled } => client.watch_lobbies(enabled),
+        // Every message counts as a heartbeat, so there is nothing more to do.
+        WsRequest::Heartbeat | WsRequest::Pong => {}
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn stale_connections_are_closed() {
+        use crate::clock::MockClock;
+        use crate::config::Config;
+        use crate::session::NOTIFY_WINDOW;
+        use crate::store::SqliteStore;
+        use std::sync::Arc;
+        use std::time::Duration;
+        use tokio_tungstenite::tungstenite::protocol::Role;
+        use tokio_tungstenite::WebSocketStream;
+
+        let store = Arc::new(SqliteStore::open(":memory:").unwrap());
+        let clock = Arc::new(MockClock::new());
+        let config = Config {
+            ping_interval: Duration::from_millis(50),
+            connection_timeout: Duration::from_millis(200),
+            ..Default::default()
+        };
+        let manager = SessionManager::with_clock(store, config.clone(), clock.clone()).unwrap();
+        let manager: &'static SessionManager = Box::leak(Box::new(manager));
+        let handle = manager.create_game(GameOptions::default(), None).unwrap();
+        let game_id = handle.lock().unwrap().id().to_string();
+
+        let (server, client) = tokio::io::duplex(64 * 1024);
+        let server = WebSocketStream::from_raw_socket(server, Role::Server, None).await;
+        tokio::spawn(serve_client(server, WireFormat::Json, None, None, manager));
+        let mut client = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+        let join = json!({ "JoinAsPlayer": { "game_id": game_id, "name": "ALEX" } });
+        client.send(Message::Text(join.to_string())).await.unwrap();
+
+        // The client is pinged while it stays quiet, until it has been quiet for too long
+        let started = Instant::now();
+        let mut pings = 0;
+        while let Some(Ok(msg)) = client.next().await {
+            let msg = WireFormat::Json.decode::<Value>(&msg);
+            if msg.is_some_and(|msg| msg["type"] == "ping") {
+                pings += 1;
+            }
+        }
+        assert!(started.elapsed() > config.connection_timeout);
+        assert!(pings >= config.connection_timeout.as_millis() / config.ping_interval.as_millis());
+
+        // Closing the connection frees the player's seat
+        clock.advance(NOTIFY_WINDOW);
+        let player = handle.call(|session| {
+            session.flush_updates();
+            session.public_player("ALEX")
+        });
+        assert!(!player.await.unwrap().connected);
+    }
+
+    #[test]
+    fn formats_are_interchangeable() {
+        assert_eq!(WireFormat::negotiate(None), WireFormat::Json);
+        assert_eq!(WireFormat::negotiate(Some("chat, msgpack")), WireFormat::MessagePack);
+
+        let request = json!({ "JoinAsPlayer": { "game_id": "ABCD", "name": "ALEX" } });
+        for format in [WireFormat::Json, WireFormat::MessagePack] {
+            let msg = format.encode(&request);
+            assert_eq!(format.decode::<Value>(&msg), Some(request.clone()));
+            assert!(matches!(
+                format.decode::<WsRequest>(&msg),
+                Some(WsRequest::JoinAsPlayer { name, .. }) if name == "ALEX"
+            ));
+        }
+        let json = WireFormat::Json.encode(&request);
+        assert_eq!(WireFormat::MessagePack.decode::<Value>(&json), None);
+    }
+
+    #[test]
+    fn admin_token_is_read_from_header_or_query() {
+        let req = Request::builder().uri("/admin/logs?token=abc").body(()).unwrap();
+        assert_eq!(admin_token(&req), Some("abc"));
+        let req = Request::builder()
+            .uri("/admin/logs")
+            .header(AUTHORIZATION, "Bearer xyz")
+            .body(())
+            .unwrap();
+        assert_eq!(admin_token(&req), Some("xyz"));
     }
-    Ok(())
 }